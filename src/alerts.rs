@@ -0,0 +1,204 @@
+use anyhow::{bail, Context, Result};
+use minijinja::{Environment, Value};
+use std::sync::OnceLock;
+use tracing::warn;
+
+use crate::config::AlertsConfig;
+use crate::money::dollars;
+
+pub use minijinja::context;
+
+/// Built-in message template per alert type. Any of these can be replaced under
+/// `[alerts.templates]`; money fields are in cents, formatted with the `dollars` filter.
+const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "risk_limit",
+        "*RISK LIMIT: {{ reason }}*\nEvent: `{{ event }}`\n{{ roi }}\nOpen arbs: {{ open_arbs }}/{{ max_open_arbs }}\nDaily P&L: {{ daily_pnl_cents | dollars }}\nDaily orders: {{ daily_orders }}/{{ max_daily_orders }}",
+    ),
+    (
+        "brackets_changed",
+        "*BRACKETS CHANGED*\nEvent: `{{ event }}`\nDirection: {{ direction }}\n{{ change }}\nExecution aborted",
+    ),
+    (
+        "total_failure",
+        "*TOTAL FAILURE*\nEvent: `{{ event }}`\nDirection: {{ direction }}\nBrackets: {{ brackets }} ({{ bracket_names | join(', ') }})\nAll {{ failed }} orders failed{% if reasons %}\n{{ reasons | join(', ') }}{% endif %}",
+    ),
+    (
+        "partial_fill",
+        "*PARTIAL FILL*\nEvent: `{{ event }}`\nDirection: {{ direction }}\nBrackets: {{ brackets }}\nFilled: {{ filled }}{% if filled_legs %}: {{ filled_legs | join(', ') }}{% endif %}\nResting: {{ resting }}{% if resting_legs %}: {{ resting_legs | join(', ') }}{% endif %} (cancelled)\nFailed: {{ failed }}\nExpected profit: {{ net_profit_cents | dollars }}\n{{ roi }}{% if unwind %}\nUnwind: {{ unwind }}{% endif %}{% if unquoted_legs %}\nLeft open, no quote: {{ unquoted_legs | join(', ') }}{% endif %}",
+    ),
+    (
+        "opportunities",
+        "*OPPORTUNITIES* {{ count }} found{% for o in opportunities %}\n\n`{{ o.event }}` {{ o.direction }} x{{ o.size }} {{ o.title }}\nNet {{ o.net_profit_cents | dollars }} ({{ o.net_roi }}){% for leg in o.legs %}\n{{ leg.name }} {{ leg.price_cents }}c x{{ leg.depth }}{% endfor %}\n{{ o.url }}{% endfor %}{% if more %}\n\n...and {{ more }} more{% endif %}",
+    ),
+    (
+        "execution_receipt",
+        "*FILLED*\nEvent: `{{ event }}`\nDirection: {{ direction }} x{{ size }}\n{% for leg in legs %}`{{ leg.ticker }}`{% if leg.label %} {{ leg.label }}{% endif %} {{ leg.price_cents }}c (quoted {{ leg.expected_cents }}c)\n{% endfor %}{{ 'Cost' if direction == 'LONG' else 'Premium' }}: {{ total_cents | dollars }}\nFees: {{ fees_cents | dollars }}\nLocked profit: {{ net_cents | dollars }} (expected {{ expected_net_cents | dollars }})\nCapital tied up: {{ capital_cents | dollars }}\nSettles: {{ settles or 'unknown' }}",
+    ),
+    (
+        "market_lifecycle",
+        "*MARKET {{ phase }}*\nEvent: `{{ event }}`\nMarket: `{{ market }}`{% if label %} {{ label }}{% endif %} ({{ status }}{% if result %}, result {{ result }}{% endif %}{% if previous %}, was {{ previous }}{% endif %}){% if held %}\nHolding an open arb leg in this market{% endif %}\nResting orders cancelled: {{ cancelled }}{% if failed %}, {{ failed }} could not be{% endif %}",
+    ),
+    (
+        "insufficient_balance",
+        "*INSUFFICIENT BALANCE*\nEvent: `{{ event }}`\nDirection: {{ direction }} x{{ size }}\nNeeds {{ required_cents | dollars }}, balance {{ balance_cents | dollars }}",
+    ),
+    (
+        "cross_market_arb",
+        "*CROSS-MARKET ARB* {{ relation }}\nDirection: {{ direction }}\nMarket: `{{ market }}` at {{ whole_cents }}c\nParts: {{ parts | join(', ') }} at {{ parts_cents }}c total\nSize: {{ size }}\nFees: {{ fees_cents | dollars }}\nNet: {{ net_profit_cents | dollars }}",
+    ),
+    (
+        "series_safe_mode",
+        "*SERIES SAFE MODE*\nSeries refresh failing for over {{ staleness_secs }}s: {{ error }}\nScanning {{ series }} whitelisted series only",
+    ),
+    ("series_safe_mode_cleared", "*SERIES SAFE MODE CLEARED*\nSeries refresh recovered"),
+    (
+        "daily_report",
+        "*DAILY REPORT* {{ trading_day }}\nP&L: {{ daily_pnl_cents | dollars }}\nOrders: {{ daily_orders }} entries, {{ daily_cancels }} cancels, {{ daily_unwinds }} unwinds\nOpen arbs: {{ open_arbs }}\nResting orders: {{ resting_orders }}\nFills: edge {{ edge_cents | dollars }}, slippage {{ slippage_cents | dollars }}, fees {{ fee_cents | dollars }}, unfilled {{ unfilled_cents | dollars }}",
+    ),
+    (
+        "roi_floor",
+        "*ROI FLOOR {{ 'RAISED' if floor > previous else 'LOWERED' }}*\nmin_roi: {{ previous }}% -> {{ floor }}% (configured {{ base }}%)\nSlippage took {{ share_pct }}% of expected profit over the last {{ samples }} arbs",
+    ),
+    (
+        "orphaned_orders",
+        "*ORPHANED ORDERS*\nResting legs a previous run never recorded\nAdopted: {{ adopted }}\nCancelled: {{ cancelled }}{% if failed %}\nFailed to cancel: {{ failed }}{% endif %}",
+    ),
+    (
+        "shutdown",
+        "*SHUTDOWN* ({{ reason }}){% if dry_run %} dry run{% endif %}\nUp {{ uptime_secs // 3600 }}h {{ uptime_secs % 3600 // 60 }}m, {{ cycles }} cycles\nOpportunities: {{ opportunities }}\nExecutions: {{ executions }}\nDaily P&L: {{ daily_pnl_cents | dollars }}\nOpen arbs: {{ open_arbs }}\nResting orders left: {{ resting_orders }}",
+    ),
+    (
+        "kill_switch",
+        "*KILL SWITCH*\nFills the bot can't account for:\n{{ anomalies | join('\\n') }}\nNew executions stopped until restart\nResting orders cancelled: {{ cancelled }}{% if failed %}, {{ failed }} could not be{% endif %}",
+    ),
+    (
+        "unhedged_loss",
+        "*UNHEDGED LOSS*\nEvent: `{{ event }}`\nMarket: `{{ market }}` {{ 'long' if contracts > 0 else 'short' }} {{ contracts | abs }} YES from {{ entry_cents }}c\nMarket now: {{ market_cents }}c{% if exit_cents %}, exiting at {{ exit_cents }}c{% endif %}\nLoss at the market: {{ loss_cents | dollars }}",
+    ),
+    ("backup_failed", "*BACKUP FAILED*\n{{ error }}"),
+    ("config_rejected", "*CONFIG REJECTED*\n`{{ path }}`: {{ error }}\nStill running the previous config"),
+    ("data_integrity", "*DATA INTEGRITY*\n{{ problems | join('\\n') }}"),
+    ("endpoint_switch", "*ENDPOINT SWITCH*\n{{ notice }}"),
+    ("circuit_breaker", "*CIRCUIT BREAKER*\n{{ notice }}"),
+    ("position_mismatch", "*POSITION MISMATCH*\n{{ mismatches | join('\\n') }}"),
+    ("api_budget", "*API BUDGET*\n{{ warnings | join('\\n') }}"),
+    ("mirror_failure", "*MIRROR FAILURE*\nEvent: `{{ event }}`\n{{ error }}"),
+    (
+        "mirror_divergence",
+        "*MIRROR DIVERGENCE*\nEvent: `{{ event }}`\nDirection: {{ direction }}\nRejected on mirror only: {{ rejected | join(', ') }}",
+    ),
+];
+
+static TEMPLATES: OnceLock<AlertTemplates> = OnceLock::new();
+
+/// Compiled alert templates: the built-ins with any user overrides applied.
+pub struct AlertTemplates {
+    env: Environment<'static>,
+}
+
+impl AlertTemplates {
+    /// Compile the templates. Overrides for unknown alert types and templates that
+    /// don't parse are errors, so a typo is caught at startup rather than mid-incident.
+    pub fn new(config: &AlertsConfig) -> Result<Self> {
+        let mut env = Environment::new();
+        env.add_filter("dollars", dollars);
+        for (kind, source) in DEFAULT_TEMPLATES {
+            env.add_template(kind, source)
+                .with_context(|| format!("Built-in alert template {} is invalid", kind))?;
+        }
+        for (kind, source) in &config.templates {
+            if !DEFAULT_TEMPLATES.iter().any(|(k, _)| k == kind) {
+                let known: Vec<&str> = DEFAULT_TEMPLATES.iter().map(|(k, _)| *k).collect();
+                bail!("Unknown alert type {:?} in [alerts.templates] (known: {})", kind, known.join(", "));
+            }
+            env.add_template_owned(kind.clone(), source.clone())
+                .with_context(|| format!("Invalid template for alert type {}", kind))?;
+        }
+        Ok(Self { env })
+    }
+
+    /// Render one alert. A template that fails at render time (e.g. a filter applied to
+    /// a missing field) falls back to the built-in, so the alert is never lost.
+    pub fn render(&self, kind: &str, ctx: Value) -> String {
+        let rendered = self.env.get_template(kind).and_then(|t| t.render(&ctx));
+        match rendered {
+            Ok(msg) => msg,
+            Err(e) => {
+                warn!(kind = kind, error = %e, "Alert template failed, using built-in");
+                let builtin = DEFAULT_TEMPLATES.iter().find(|(k, _)| *k == kind).map(|(_, s)| *s);
+                builtin
+                    .and_then(|s| self.env.render_str(s, &ctx).ok())
+                    .unwrap_or_else(|| format!("*{}*", kind.to_uppercase()))
+            }
+        }
+    }
+}
+
+/// Install the configured templates for `render`. First call wins.
+pub fn init(config: &AlertsConfig) -> Result<()> {
+    let templates = AlertTemplates::new(config)?;
+    TEMPLATES.get_or_init(|| templates);
+    Ok(())
+}
+
+/// Render an alert message of type `kind` (see `DEFAULT_TEMPLATES`) from structured data.
+pub fn render(kind: &str, ctx: Value) -> String {
+    TEMPLATES
+        .get_or_init(|| AlertTemplates::new(&AlertsConfig::default()).expect("built-in alert templates compile"))
+        .render(kind, ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_builtin_matches_previous_format_and_overrides_apply() {
+        let builtin = AlertTemplates::new(&AlertsConfig::default()).unwrap();
+        let ctx = || {
+            context! {
+                event => "EV-1",
+                direction => "LONG",
+                brackets => 3,
+                filled => 2,
+                resting => 1,
+                failed => 0,
+                net_profit_cents => -125,
+                roi => "ROI: gross 5.0% / net 2.5% / after $0.00 slippage 2.5%",
+            }
+        };
+        assert_eq!(
+            builtin.render("partial_fill", ctx()),
+            "*PARTIAL FILL*\nEvent: `EV-1`\nDirection: LONG\nBrackets: 3\nFilled: 2\nResting: 1 (cancelled)\nFailed: 0\nExpected profit: -$1.25\nROI: gross 5.0% / net 2.5% / after $0.00 slippage 2.5%"
+        );
+
+        let config = AlertsConfig {
+            templates: HashMap::from([(
+                "partial_fill".to_string(),
+                "{{ event }}: {{ filled }}/{{ brackets }} filled, {{ net_profit_cents | dollars }}".to_string(),
+            )]),
+            ..Default::default()
+        };
+        let custom = AlertTemplates::new(&config).unwrap();
+        assert_eq!(custom.render("partial_fill", ctx()), "EV-1: 2/3 filled, -$1.25");
+    }
+
+    #[test]
+    fn test_rejects_unknown_types_and_bad_syntax() {
+        let config = |kind: &str, source: &str| AlertsConfig {
+            templates: HashMap::from([(kind.to_string(), source.to_string())]),
+            ..Default::default()
+        };
+        assert!(AlertTemplates::new(&config("partial_fil", "x")).is_err());
+        assert!(AlertTemplates::new(&config("partial_fill", "{{ event ")).is_err());
+
+        // Render-time failure falls back to the built-in
+        let broken = AlertTemplates::new(&config("endpoint_switch", "{{ notice | dollars }}")).unwrap();
+        assert_eq!(
+            broken.render("endpoint_switch", context! { notice => "switched" }),
+            "*ENDPOINT SWITCH*\nswitched"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::book::{self, Side};
+use crate::detector::{evaluate_arb, quote_with_levels, DetectParams};
+use crate::fees::taker_fee_cents;
+use crate::kalshi::types::{ArbDirection, ArbOpportunity, Market, Orderbook};
+use crate::price::{self, PAYOUT_CENTS};
+
+/// One event's full set of bracket books at one moment — a line of a snapshot file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub time: DateTime<Utc>,
+    pub event_ticker: String,
+    pub title: String,
+    pub brackets: Vec<SnapshotBracket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotBracket {
+    pub ticker: String,
+    pub title: String,
+    pub orderbook: Orderbook,
+}
+
+/// Append an event's books to `<dir>/<UTC date>.jsonl`.
+pub fn record_snapshot(
+    dir: &Path,
+    event_ticker: &str,
+    title: &str,
+    markets: &[&Market],
+    books: &[Orderbook],
+) -> Result<()> {
+    let now = Utc::now();
+    let snapshot = Snapshot {
+        time: now,
+        event_ticker: event_ticker.to_string(),
+        title: title.to_string(),
+        brackets: markets
+            .iter()
+            .zip(books)
+            .map(|(m, ob)| SnapshotBracket { ticker: m.ticker.clone(), title: m.title.clone(), orderbook: ob.clone() })
+            .collect(),
+    };
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{}.jsonl", now.format("%Y-%m-%d")));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&snapshot)?).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Every snapshot in the `.jsonl` files of `dir`, oldest first. Lines that don't parse
+/// (e.g. a torn final write) are skipped with a warning.
+pub fn load_snapshots(dir: &Path) -> Result<Vec<Snapshot>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "jsonl"))
+        .collect();
+    files.sort();
+    let mut snapshots = Vec::new();
+    for path in files {
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        for (i, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            match serde_json::from_str::<Snapshot>(line) {
+                Ok(s) => snapshots.push(s),
+                Err(e) => warn!(file = %path.display(), line = i + 1, error = %e, "Skipping unparseable snapshot"),
+            }
+        }
+    }
+    snapshots.sort_by_key(|s| s.time);
+    Ok(snapshots)
+}
+
+/// Fill a taker limit order for one bracket against the recorded book: every level at
+/// the limit or better is taken, best first, each at its own price. Returns contracts
+/// filled, their total YES price, and fees (charged per level).
+fn simulate_leg(
+    ticker: &str,
+    orderbook: &Orderbook,
+    direction: ArbDirection,
+    limit_cents: i64,
+    size: i64,
+) -> (i64, i64, i64) {
+    // LONG buys YES, matching NO bids at 100 - limit or higher; SHORT sells YES into YES bids.
+    let (levels, side): (Vec<(i64, i64)>, Side) = match direction {
+        ArbDirection::Long => {
+            (book::aggregate(&orderbook.no, 0).into_iter().map(|(p, q)| (price::complement(p), q)).collect(), Side::Ask)
+        }
+        ArbDirection::Short => (book::aggregate(&orderbook.yes, 0), Side::Bid),
+    };
+    let crossing: Vec<(i64, i64)> =
+        levels.into_iter().take_while(|&(p, _)| side.at_or_better(p, limit_cents)).collect();
+    let (mut filled, mut total, mut fees) = (0, 0, 0);
+    for (price, take) in book::take(&crossing, size) {
+        filled += take;
+        total += take * price;
+        fees += taker_fee_cents(ticker, take as u32, price);
+    }
+    (filled, total, fees)
+}
+
+/// Totals over a replay.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BacktestStats {
+    pub snapshots: usize,
+    pub events: usize,
+    /// Snapshots with every bracket quotable.
+    pub quoted: usize,
+    /// Accepted by the detector and traded.
+    pub opportunities: usize,
+    /// Accepted again on books unchanged since the event was last traded; not re-traded.
+    pub repeats: usize,
+    pub long: usize,
+    pub short: usize,
+    /// Opportunities where some leg filled short of the size.
+    pub partial: usize,
+    /// Contracts filled beyond the least-filled leg across partial fills.
+    pub unhedged_contracts: i64,
+    /// Detector's net across every opportunity, partial fills included.
+    pub expected_net_cents: i64,
+    /// Net at the simulated fill prices, fully filled arbs only.
+    pub simulated_net_cents: i64,
+    pub fees_cents: i64,
+    /// Premium paid (LONG) or collateral posted (SHORT) across fully filled arbs.
+    pub capital_cents: i64,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+}
+
+impl BacktestStats {
+    /// Simulated net as a percentage of the capital it tied up.
+    pub fn roi_pct(&self) -> Option<f64> {
+        (self.capital_cents > 0).then(|| self.simulated_net_cents as f64 / self.capital_cents as f64 * 100.0)
+    }
+
+    fn record(&mut self, opp: &ArbOpportunity, snapshot: &Snapshot) {
+        self.opportunities += 1;
+        match opp.direction {
+            ArbDirection::Long => self.long += 1,
+            ArbDirection::Short => self.short += 1,
+        }
+        self.expected_net_cents += opp.net_profit_cents;
+
+        let size = opp.position_size as i64;
+        let legs: Vec<(i64, i64, i64)> = opp
+            .brackets
+            .iter()
+            .zip(&snapshot.brackets)
+            .map(|(quote, bracket)| {
+                let limit = match opp.direction {
+                    ArbDirection::Long => quote.yes_ask_cents,
+                    ArbDirection::Short => quote.yes_bid_cents,
+                };
+                simulate_leg(&quote.ticker, &bracket.orderbook, opp.direction, limit, size)
+            })
+            .collect();
+        let fees: i64 = legs.iter().map(|l| l.2).sum();
+        self.fees_cents += fees;
+        if legs.iter().any(|l| l.0 < size) {
+            self.partial += 1;
+            let matched = legs.iter().map(|l| l.0).min().unwrap_or(0);
+            self.unhedged_contracts += legs.iter().map(|l| l.0 - matched).sum::<i64>();
+            return;
+        }
+        let total: i64 = legs.iter().map(|l| l.1).sum();
+        let (net, capital) = match opp.direction {
+            ArbDirection::Long => (PAYOUT_CENTS * size - total - fees, total + fees),
+            ArbDirection::Short => {
+                (total - PAYOUT_CENTS * size - fees, PAYOUT_CENTS * size * legs.len() as i64 - total + fees)
+            }
+        };
+        self.simulated_net_cents += net;
+        self.capital_cents += capital;
+    }
+}
+
+/// Replay snapshots (oldest first) through the detector and the fill model. An event is
+/// traded at most once per distinct set of books: a recorder that doesn't trade keeps
+/// seeing the same arb until the books move, but live it would have been taken once.
+pub fn run(
+    snapshots: &[Snapshot],
+    params: &DetectParams,
+    min_level_quantity: i64,
+    sweep_levels: usize,
+) -> BacktestStats {
+    let mut stats = BacktestStats::default();
+    let mut last_traded: HashMap<&str, String> = HashMap::new();
+    let mut events: HashSet<&str> = HashSet::new();
+    for snapshot in snapshots {
+        stats.snapshots += 1;
+        stats.first.get_or_insert(snapshot.time);
+        stats.last = Some(snapshot.time);
+        events.insert(&snapshot.event_ticker);
+
+        let Some(quotes) = snapshot
+            .brackets
+            .iter()
+            .map(|b| quote_with_levels(&b.ticker, &b.title, &b.orderbook, min_level_quantity, sweep_levels))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        stats.quoted += 1;
+
+        let accepted: Vec<_> = evaluate_arb(&snapshot.event_ticker, &snapshot.title, &quotes, params)
+            .into_iter()
+            .filter(|e| e.rejected.is_none())
+            .collect();
+        if accepted.is_empty() {
+            continue;
+        }
+        let books = serde_json::to_string(&snapshot.brackets).unwrap_or_default();
+        if last_traded.get(snapshot.event_ticker.as_str()) == Some(&books) {
+            stats.repeats += accepted.len();
+            continue;
+        }
+        last_traded.insert(&snapshot.event_ticker, books);
+        for eval in &accepted {
+            stats.record(&eval.opportunity, snapshot);
+        }
+    }
+    stats.events = events.len();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kalshi::types::PriceLevel;
+
+    fn snapshot(minute: u32, no_bids: [(i64, i64); 3]) -> Snapshot {
+        let brackets = no_bids
+            .iter()
+            .enumerate()
+            .map(|(i, &(price, quantity))| SnapshotBracket {
+                ticker: format!("EV-B{}", i),
+                title: format!("Bracket {}", i),
+                orderbook: Orderbook { yes: vec![], no: vec![PriceLevel { price, quantity }] },
+            })
+            .collect();
+        Snapshot {
+            time: format!("2026-10-01T12:{:02}:00Z", minute).parse().unwrap(),
+            event_ticker: "EV".into(),
+            title: "Event".into(),
+            brackets,
+        }
+    }
+
+    #[test]
+    fn test_replay_trades_each_book_once() {
+        let params = DetectParams {
+            position_size: 10,
+            min_net_profit_cents: 1,
+            min_roi_pct: 0.0,
+            expected_slippage_cents: 0,
+            min_partial_size: None,
+            sizing: None,
+            balance_cents: None,
+            min_annualized_roi_pct: None,
+            max_quote_age_ms: None,
+        };
+        // YES asks 30 + 30 + 30 = 90: a LONG worth 100 - 90 per contract before fees
+        let arb = snapshot(0, [(70, 50), (70, 50), (70, 50)]);
+        let thin = snapshot(2, [(70, 50), (70, 50), (70, 4)]);
+        let snapshots = vec![arb.clone(), snapshot(1, [(70, 50), (70, 50), (70, 50)]), thin];
+        let stats = run(&snapshots, &params, 1, 1);
+
+        assert_eq!((stats.snapshots, stats.events, stats.quoted), (3, 1, 3));
+        // The unchanged second snapshot isn't traded again; the thin one fails the depth gate
+        assert_eq!((stats.opportunities, stats.repeats, stats.long), (1, 1, 1));
+        // 10 contracts x 3 legs at 30, fee 15 each
+        assert_eq!(stats.fees_cents, 45);
+        assert_eq!(stats.simulated_net_cents, 1000 - 900 - 45);
+        assert_eq!(stats.capital_cents, 945);
+        assert_eq!(stats.simulated_net_cents, stats.expected_net_cents);
+
+        // Round-trips through the recorded format
+        let dir = std::env::temp_dir().join(format!("arb-backtest-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        let line = serde_json::to_string(&arb).unwrap();
+        std::fs::write(dir.join("2026-10-01.jsonl"), format!("{}\n{{\"time\":", line)).unwrap();
+        assert_eq!(load_snapshots(&dir).unwrap().len(), 1);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::config::BackupConfig;
+
+pub const DATA_DIR: &str = "data";
+/// Prefix lengths and hashes of the append-only files, as of the last clean check or backup.
+const MANIFEST_FILE: &str = "integrity.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileMark {
+    len: u64,
+    sha256: String,
+}
+
+/// Append-only logs: history only grows, so a shorter file or a changed prefix is damage.
+fn is_append_only(path: &Path) -> bool {
+    matches!(path.extension().and_then(|e| e.to_str()), Some("md" | "jsonl"))
+}
+
+fn data_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && path.file_name().is_some_and(|n| n != MANIFEST_FILE) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn prefix_hash(content: &[u8]) -> String {
+    Sha256::digest(content).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Problems with the files in `dir`: torn or unparseable lines, JSON that doesn't parse,
+/// and append-only files that shrank or whose earlier contents changed since the manifest
+/// was last written. Empty means the directory looks intact.
+pub fn check_integrity(dir: &Path) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+    let manifest: BTreeMap<String, FileMark> = match std::fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            problems.push(format!("{}: unreadable ({})", MANIFEST_FILE, e));
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    };
+
+    for path in data_files(dir)? {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let content = std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let text = String::from_utf8_lossy(&content);
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("md") => {
+                if let Some(i) = text.lines().position(|l| {
+                    let l = l.trim();
+                    let complete = l.starts_with('|') && l.ends_with('|');
+                    !l.is_empty() && !complete
+                }) {
+                    problems.push(format!("{}: line {} is not a complete table row", name, i + 1));
+                }
+            }
+            Some("jsonl") => {
+                if let Some(i) = text.lines().position(|l| {
+                    !l.trim().is_empty() && serde_json::from_str::<serde_json::Value>(l).is_err()
+                }) {
+                    problems.push(format!("{}: line {} is not valid JSON", name, i + 1));
+                }
+            }
+            Some("json") if serde_json::from_slice::<serde_json::Value>(&content).is_err() => {
+                problems.push(format!("{}: not valid JSON", name));
+            }
+            _ => {}
+        }
+        if is_append_only(&path) && !content.is_empty() && !content.ends_with(b"\n") {
+            problems.push(format!("{}: last line is truncated", name));
+        }
+
+        if let Some(mark) = manifest.get(&name) {
+            if (content.len() as u64) < mark.len {
+                problems.push(format!("{}: shrank from {} to {} bytes", name, mark.len, content.len()));
+            } else if prefix_hash(&content[..mark.len as usize]) != mark.sha256 {
+                problems.push(format!("{}: first {} bytes changed since last check", name, mark.len));
+            }
+        }
+    }
+    for name in manifest.keys() {
+        if !dir.join(name).exists() {
+            problems.push(format!("{}: missing", name));
+        }
+    }
+    Ok(problems)
+}
+
+/// Record the current length and hash of every append-only file in `dir`.
+pub fn write_manifest(dir: &Path) -> Result<()> {
+    let mut manifest = BTreeMap::new();
+    for path in data_files(dir)?.into_iter().filter(|p| is_append_only(p)) {
+        let content = std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        manifest.insert(name, FileMark { len: content.len() as u64, sha256: prefix_hash(&content) });
+    }
+    let json = serde_json::to_string_pretty(&manifest).context("Failed to serialize integrity manifest")?;
+    std::fs::write(dir.join(MANIFEST_FILE), json).context("Failed to write integrity manifest")
+}
+
+/// Write `dir` as a gzipped tarball at `dest`.
+fn archive(dir: &Path, dest: &Path) -> Result<()> {
+    let file = std::fs::File::create(dest).with_context(|| format!("Failed to create {}", dest.display()))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for path in data_files(dir)? {
+        let name = Path::new(DATA_DIR).join(path.file_name().unwrap_or_default());
+        tar.append_path_with_name(&path, name)
+            .with_context(|| format!("Failed to archive {}", path.display()))?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Delete all but the newest `keep` archives for this instance in a local target.
+fn prune(target: &Path, prefix: &str, keep: usize) -> Result<usize> {
+    let mut archives: Vec<PathBuf> = std::fs::read_dir(target)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(prefix) && n.ends_with(".tar.gz"))
+        })
+        .collect();
+    // Timestamped names sort chronologically
+    archives.sort();
+    let excess = archives.len().saturating_sub(keep);
+    for path in &archives[..excess] {
+        std::fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+    Ok(excess)
+}
+
+/// Archive the data directory to the configured target: a local directory, or an
+/// `s3://bucket/prefix` URL (uploaded with the `aws` CLI). Returns where it went.
+pub async fn run(config: &BackupConfig, instance: &str) -> Result<String> {
+    let prefix = format!("{}-data-", instance);
+    let name = format!("{}{}.tar.gz", prefix, Utc::now().format("%Y%m%dT%H%M%SZ"));
+
+    let dest = if config.target.starts_with("s3://") {
+        let staged = std::env::temp_dir().join(&name);
+        let staged_path = staged.clone();
+        tokio::task::spawn_blocking(move || archive(Path::new(DATA_DIR), &staged_path)).await??;
+        let url = format!("{}/{}", config.target.trim_end_matches('/'), name);
+        let output = tokio::process::Command::new("aws")
+            .args(["s3", "cp", "--only-show-errors"])
+            .arg(&staged)
+            .arg(&url)
+            .output()
+            .await
+            .context("Failed to run the aws CLI")?;
+        std::fs::remove_file(&staged).ok();
+        if !output.status.success() {
+            bail!("aws s3 cp failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        url
+    } else {
+        let target = PathBuf::from(&config.target);
+        std::fs::create_dir_all(&target).with_context(|| format!("Failed to create {}", target.display()))?;
+        let partial = target.join(format!("{}.partial", name));
+        let partial_path = partial.clone();
+        tokio::task::spawn_blocking(move || archive(Path::new(DATA_DIR), &partial_path)).await??;
+        let dest = target.join(&name);
+        std::fs::rename(&partial, &dest).context("Failed to finalize backup archive")?;
+        let pruned = prune(&target, &prefix, config.keep.max(1))?;
+        if pruned > 0 {
+            info!(pruned, "Pruned old backups");
+        }
+        dest.display().to_string()
+    };
+
+    write_manifest(Path::new(DATA_DIR))?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("arb-backup-{}-{}", tag, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_integrity_detects_truncation_and_rewrites() {
+        let dir = temp_dir("integrity");
+        std::fs::write(dir.join("trades.md"), "| a | 1 |\n| b | 2 |\n").unwrap();
+        std::fs::write(dir.join("executions.jsonl"), "{\"kind\":\"x\"}\n").unwrap();
+        std::fs::write(dir.join("skip_memo.json"), "{}").unwrap();
+        assert!(check_integrity(&dir).unwrap().is_empty());
+        write_manifest(&dir).unwrap();
+
+        // Appending is fine
+        std::fs::write(dir.join("trades.md"), "| a | 1 |\n| b | 2 |\n| c | 3 |\n").unwrap();
+        assert!(check_integrity(&dir).unwrap().is_empty());
+
+        // Torn write, rewritten history, shrunk log, broken JSON
+        std::fs::write(dir.join("trades.md"), "| a | 9 |\n| b | 2 |\n| c | 3 |\n| d").unwrap();
+        std::fs::write(dir.join("executions.jsonl"), "").unwrap();
+        std::fs::write(dir.join("skip_memo.json"), "{\"entr").unwrap();
+        let problems = check_integrity(&dir).unwrap();
+        assert_eq!(problems.len(), 5, "{:?}", problems);
+        assert!(problems.iter().any(|p| p.contains("trades.md: line 4")));
+        assert!(problems.iter().any(|p| p.contains("trades.md: last line is truncated")));
+        assert!(problems.iter().any(|p| p.contains("trades.md: first")));
+        assert!(problems.iter().any(|p| p.contains("executions.jsonl: shrank")));
+        assert!(problems.iter().any(|p| p.contains("skip_memo.json")));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_archive_and_prune() {
+        let data = temp_dir("data");
+        std::fs::write(data.join("scans.md"), "| row |\n").unwrap();
+        let target = temp_dir("target");
+        for ts in ["20260101T000000Z", "20260102T000000Z", "20260103T000000Z"] {
+            archive(&data, &target.join(format!("prod-data-{}.tar.gz", ts))).unwrap();
+        }
+        std::fs::write(target.join("other-data-20250101T000000Z.tar.gz"), "").unwrap();
+
+        assert_eq!(prune(&target, "prod-data-", 2).unwrap(), 1);
+        assert!(!target.join("prod-data-20260101T000000Z.tar.gz").exists());
+        assert!(target.join("prod-data-20260103T000000Z.tar.gz").exists());
+        assert!(target.join("other-data-20250101T000000Z.tar.gz").exists());
+
+        let file = std::fs::File::open(target.join("prod-data-20260103T000000Z.tar.gz")).unwrap();
+        let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        let names: Vec<String> = tar
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().display().to_string())
+            .collect();
+        assert_eq!(names, vec!["data/scans.md"]);
+        std::fs::remove_dir_all(&data).ok();
+        std::fs::remove_dir_all(&target).ok();
+    }
+}
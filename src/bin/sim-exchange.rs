@@ -0,0 +1,35 @@
+//! A local stand-in for the Kalshi REST API, for running the whole bot end to end.
+//! Point `[kalshi] base_url` at `http://<listen>/trade-api/v2`.
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::path::PathBuf;
+use tokio::net::TcpListener;
+
+use bracket_arb::sim_exchange::{Scenario, SimExchange};
+
+#[derive(Debug, Parser)]
+#[command(name = "sim-exchange", about = "Simulated Kalshi exchange driven by a scenario file")]
+struct Args {
+    /// Scenario TOML (series, events, books, and scripted fills / 429s / halts).
+    scenario: PathBuf,
+    #[arg(long, default_value = "127.0.0.1:8900")]
+    listen: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "bracket_arb=info".parse().unwrap()),
+        )
+        .init();
+
+    let args = Args::parse();
+    let scenario = Scenario::load(&args.scenario)?;
+    let listener = TcpListener::bind(&args.listen)
+        .await
+        .with_context(|| format!("Failed to bind {}", args.listen))?;
+    SimExchange::new(scenario).serve(listener).await
+}
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use crate::kalshi::types::PriceLevel;
+
+/// Which way "better" runs on a list of levels: lower prices for asks, higher for bids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Ask,
+    Bid,
+}
+
+impl Side {
+    /// `price` is at `limit` or better for someone taking this side.
+    pub fn at_or_better(self, price: i64, limit: i64) -> bool {
+        match self {
+            Side::Ask => price <= limit,
+            Side::Bid => price >= limit,
+        }
+    }
+}
+
+/// Bid levels as `(price, quantity)`, best (highest) first, with duplicate prices summed and
+/// levels below `min_quantity` dropped as dust. Kalshi books list bids only; asks on one
+/// side are the complements of the other side's bids.
+pub fn aggregate(levels: &[PriceLevel], min_quantity: i64) -> Vec<(i64, i64)> {
+    let mut by_price: BTreeMap<i64, i64> = BTreeMap::new();
+    for level in levels {
+        *by_price.entry(level.price).or_default() += level.quantity;
+    }
+    by_price.into_iter().rev().filter(|&(_, qty)| qty >= min_quantity).collect()
+}
+
+/// The best non-dust bid and the total quantity at it.
+pub fn best_level(levels: &[PriceLevel], min_quantity: i64) -> Option<(i64, i64)> {
+    aggregate(levels, min_quantity).into_iter().next()
+}
+
+/// Contracts on best-first `levels` at `limit` or better.
+pub fn depth_to_price(levels: &[(i64, i64)], side: Side, limit: i64) -> i64 {
+    levels.iter().take_while(|&&(price, _)| side.at_or_better(price, limit)).map(|&(_, qty)| qty).sum()
+}
+
+/// Contracts taken per level when filling `quantity` from best-first `levels`, stopping
+/// short when the levels run out.
+pub fn take(levels: &[(i64, i64)], quantity: i64) -> Vec<(i64, i64)> {
+    let mut remaining = quantity.max(0);
+    let mut taken = Vec::new();
+    for &(price, available) in levels {
+        if remaining == 0 {
+            break;
+        }
+        let n = available.min(remaining);
+        taken.push((price, n));
+        remaining -= n;
+    }
+    taken
+}
+
+/// Volume-weighted price in cents of the first `quantity` contracts on best-first `levels`,
+/// or None when the levels hold fewer.
+pub fn vwap_to_quantity(levels: &[(i64, i64)], quantity: i64) -> Option<f64> {
+    let taken = take(levels, quantity);
+    let filled: i64 = taken.iter().map(|&(_, n)| n).sum();
+    if quantity <= 0 || filled < quantity {
+        return None;
+    }
+    let cost: i64 = taken.iter().map(|&(price, n)| price * n).sum();
+    Some(cost as f64 / filled as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(raw: &[(i64, i64)]) -> Vec<PriceLevel> {
+        raw.iter().map(|&(price, quantity)| PriceLevel { price, quantity }).collect()
+    }
+
+    #[test]
+    fn test_aggregate_sums_duplicates_and_drops_dust() {
+        let book = levels(&[(40, 3), (45, 1), (42, 10), (40, 4), (45, 1)]);
+        assert_eq!(aggregate(&book, 0), vec![(45, 2), (42, 10), (40, 7)]);
+        // 45c holds only 2 in total: dust at a floor of 5
+        assert_eq!(aggregate(&book, 5), vec![(42, 10), (40, 7)]);
+        assert_eq!(best_level(&book, 5), Some((42, 10)));
+        assert_eq!(best_level(&book, 50), None);
+        assert_eq!(best_level(&[], 0), None);
+    }
+
+    #[test]
+    fn test_depth_to_price_counts_levels_at_or_better() {
+        let asks = [(30, 5), (31, 10), (33, 20)];
+        assert_eq!(depth_to_price(&asks, Side::Ask, 29), 0);
+        assert_eq!(depth_to_price(&asks, Side::Ask, 31), 15);
+        assert_eq!(depth_to_price(&asks, Side::Ask, 99), 35);
+        let bids = [(60, 4), (58, 6)];
+        assert_eq!(depth_to_price(&bids, Side::Bid, 59), 4);
+        assert_eq!(depth_to_price(&bids, Side::Bid, 58), 10);
+    }
+
+    #[test]
+    fn test_take_and_vwap_walk_levels_best_first() {
+        let asks = [(30, 5), (31, 10), (33, 20)];
+        assert_eq!(take(&asks, 12), vec![(30, 5), (31, 7)]);
+        assert_eq!(take(&asks, 100), vec![(30, 5), (31, 10), (33, 20)]);
+        assert!(take(&asks, 0).is_empty());
+        assert_eq!(vwap_to_quantity(&asks, 5), Some(30.0));
+        // (5 x 30 + 5 x 31) / 10
+        assert_eq!(vwap_to_quantity(&asks, 10), Some(30.5));
+        assert_eq!(vwap_to_quantity(&asks, 36), None);
+        assert_eq!(vwap_to_quantity(&asks, 0), None);
+    }
+}
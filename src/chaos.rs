@@ -0,0 +1,131 @@
+// Fault injection for exercising recovery paths, compiled only with `--features chaos`.
+// Faults come from the TOML file named by `CHAOS_SCENARIO`; without it nothing fires.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+/// Fires on every `every`th matching call (1 = every call).
+#[derive(Debug, Deserialize)]
+pub struct EveryN {
+    #[serde(default = "default_every")]
+    pub every: u64,
+    #[serde(skip)]
+    seen: AtomicU64,
+}
+
+fn default_every() -> u64 { 1 }
+
+impl EveryN {
+    fn fires(&self) -> bool {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) + 1;
+        self.every > 0 && n.is_multiple_of(self.every)
+    }
+}
+
+/// Hold requests before they are sent.
+#[derive(Debug, Deserialize)]
+pub struct DelayFault {
+    pub ms: u64,
+    /// Only for this method (e.g. "POST"); unset = any.
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Only for paths starting with this (e.g. "/portfolio/orders"); unset = any.
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+    #[serde(flatten)]
+    pub when: EveryN,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ChaosScenario {
+    #[serde(default)]
+    pub delays: Vec<DelayFault>,
+    /// Report cancels as confirmed without sending them, leaving the order resting.
+    #[serde(default)]
+    pub drop_cancels: Option<EveryN>,
+    /// Hand the executor each filled order twice, as a duplicated fill report would.
+    #[serde(default)]
+    pub duplicate_fills: Option<EveryN>,
+}
+
+impl ChaosScenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+static CHAOS: OnceLock<ChaosScenario> = OnceLock::new();
+
+/// Load the scenario named by `CHAOS_SCENARIO`, if set. First call wins.
+pub fn init() -> Result<()> {
+    let Ok(path) = std::env::var("CHAOS_SCENARIO") else {
+        return Ok(());
+    };
+    let scenario = ChaosScenario::load(Path::new(&path))?;
+    warn!(path = %path, "Chaos faults enabled");
+    CHAOS.get_or_init(|| scenario);
+    Ok(())
+}
+
+fn scenario() -> &'static ChaosScenario {
+    CHAOS.get_or_init(ChaosScenario::default)
+}
+
+/// Sleep if a delay fault matches this request.
+pub async fn delay(method: &str, path: &str) {
+    for fault in &scenario().delays {
+        let matches = fault.method.as_deref().is_none_or(|m| m.eq_ignore_ascii_case(method))
+            && fault.path_prefix.as_deref().is_none_or(|p| path.starts_with(p));
+        if matches && fault.when.fires() {
+            warn!(method = method, path = path, ms = fault.ms, "Chaos: delaying request");
+            tokio::time::sleep(Duration::from_millis(fault.ms)).await;
+        }
+    }
+}
+
+/// Whether to silently drop this cancel.
+pub fn drop_cancel(order_id: &str) -> bool {
+    let drop = scenario().drop_cancels.as_ref().is_some_and(EveryN::fires);
+    if drop {
+        warn!(order_id = order_id, "Chaos: dropping cancel");
+    }
+    drop
+}
+
+/// Whether to report this fill twice.
+pub fn duplicate_fill(ticker: &str) -> bool {
+    let duplicate = scenario().duplicate_fills.as_ref().is_some_and(EveryN::fires);
+    if duplicate {
+        warn!(ticker = ticker, "Chaos: duplicating fill");
+    }
+    duplicate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faults_fire_on_their_cadence() {
+        let scenario: ChaosScenario = toml::from_str(
+            r#"
+            drop_cancels = { every = 2 }
+
+            [[delays]]
+            ms = 10
+            method = "POST"
+            "#,
+        )
+        .unwrap();
+        let drops = scenario.drop_cancels.as_ref().unwrap();
+        assert_eq!((0..4).map(|_| drops.fires()).collect::<Vec<_>>(), [false, true, false, true]);
+        assert_eq!(scenario.delays[0].when.every, 1);
+        assert!(scenario.duplicate_fills.is_none());
+    }
+}
@@ -0,0 +1,826 @@
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::alerts::AlertTemplates;
+use crate::backtest;
+use crate::book;
+use crate::clock::ExchangeClock;
+use crate::config::Config;
+use crate::config_check;
+use crate::replay;
+use crate::report::{self, InstanceReport, Period};
+use crate::detector::{
+    arb_candidate_markets, evaluate_arb, expected_settlement, gate_settlement, DetectParams,
+};
+use crate::fees::{taker_fee_cents, Fees};
+use crate::kalshi::client::KalshiClient;
+use crate::kalshi::types::*;
+use crate::money::dollars;
+use crate::positions::PositionTracker;
+use crate::status::StatusSnapshot;
+use crate::storage::{self, exchange_avg_cents, position_check, ExecutionStore, LocalPosition};
+use crate::venue::{self, Venue};
+
+#[derive(Debug, Parser)]
+#[command(name = "bracket-arb", about = "Kalshi bracket arbitrage scanner and executor")]
+pub struct Cli {
+    /// Read-only: the API client refuses every order, cancel, and other write, and runs
+    /// without KALSHI_API_KEY_ID (public market data only) when it isn't set.
+    #[arg(long, global = true)]
+    pub observer: bool,
+    /// Omit to run the scan loop (same as `run`).
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the scan loop and scheduled jobs until shut down.
+    Run,
+    /// Run one scan cycle (trading unless DRY_RUN), cancel anything it left resting, and exit.
+    ScanOnce,
+    /// Fetch an event's current books and print what the detector would do, without trading.
+    Preview {
+        event_ticker: String,
+    },
+    /// Run pre-flight checks on one event, show the planned legs, and execute after confirmation.
+    Execute {
+        event_ticker: String,
+        #[arg(long, value_enum)]
+        direction: DirectionArg,
+        /// Contracts per bracket (overrides `[risk] position_size`).
+        #[arg(long)]
+        size: u32,
+    },
+    /// Print positions derived from the local execution store.
+    Positions {
+        /// Compare against exchange-reported positions and flag discrepancies.
+        #[arg(long)]
+        diff: bool,
+    },
+    /// List orders resting on the exchange, matched to the opportunities that placed them.
+    Orders,
+    /// Cancel every order resting on the exchange, after confirmation.
+    CancelAll,
+    /// Consolidated P&L and risk report across instances' data directories. Needs no credentials.
+    AggregateReport {
+        /// Data directories, one per instance (e.g. `weather/data econ/data`).
+        #[arg(required = true)]
+        dirs: Vec<PathBuf>,
+        /// First trading day to include (YYYY-MM-DD).
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Last trading day to include (YYYY-MM-DD).
+        #[arg(long)]
+        until: Option<NaiveDate>,
+        /// Exchange timezone whose days the dates are (IANA name), as `[schedule] timezone`.
+        #[arg(long, default_value = "America/New_York")]
+        timezone: Tz,
+    },
+    /// This instance's P&L and risk report for one trading day. Needs no credentials.
+    Report {
+        /// Trading day to report (YYYY-MM-DD); defaults to today.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// Exchange timezone whose days the dates are (IANA name), as `[schedule] timezone`.
+        #[arg(long, default_value = "America/New_York")]
+        timezone: Tz,
+        /// Data directory to read.
+        #[arg(long, default_value = "data")]
+        dir: PathBuf,
+    },
+    /// Export the opportunity, trade, and reconciliation logs as CSV and Parquet with
+    /// typed columns, for tax reporting and analysis. Needs no credentials.
+    Export {
+        /// Data directory to read.
+        #[arg(long, default_value = "data")]
+        dir: PathBuf,
+        /// First trading day to include (YYYY-MM-DD).
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Last trading day to include (YYYY-MM-DD).
+        #[arg(long)]
+        until: Option<NaiveDate>,
+        /// Exchange timezone whose days the dates are (IANA name), as `[schedule] timezone`.
+        #[arg(long, default_value = "America/New_York")]
+        timezone: Tz,
+        /// Directory the files are written to.
+        #[arg(long, default_value = "export")]
+        out: PathBuf,
+    },
+    /// Replay recorded orderbook snapshots (`[scanner] snapshot_dir`) through the detector
+    /// and a simulated fill model. Needs no credentials.
+    Backtest {
+        dir: PathBuf,
+        /// Override `[risk] min_roi_pct`.
+        #[arg(long)]
+        min_roi_pct: Option<f64>,
+        /// Override `[risk] position_size`.
+        #[arg(long)]
+        size: Option<u32>,
+    },
+    /// Re-run a recorded execution against the simulated exchange, seeded with the books
+    /// recorded when it was detected, under each routing policy (plus sequential legs),
+    /// and report which would have done best. Needs no credentials.
+    ReplayExec {
+        opportunity_id: String,
+        /// Snapshot directory; defaults to `[scanner] snapshot_dir`.
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+    /// Run the bracket detector once over a venue's events and print what it finds,
+    /// without trading. Polymarket needs no credentials.
+    VenueScan {
+        #[arg(value_enum)]
+        venue: VenueArg,
+        /// Events to scan: a series ticker on Kalshi, a tag slug on Polymarket (e.g. "elections").
+        group: String,
+    },
+    /// Validate a config file: TOML syntax, unknown keys, types, alert templates, and fee
+    /// schedules. Needs no credentials.
+    ValidateConfig {
+        #[arg(default_value = "config.toml")]
+        path: PathBuf,
+    },
+    /// Inspect or validate configuration files. Needs no credentials.
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Validate a candidate config file, report unknown keys, and diff it against the current one.
+    Check {
+        path: PathBuf,
+        /// Config to compare against.
+        #[arg(long, default_value = "config.toml")]
+        against: PathBuf,
+    },
+    /// Print the JSON schema for config files.
+    Schema,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum VenueArg {
+    Kalshi,
+    Polymarket,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DirectionArg {
+    Long,
+    Short,
+}
+
+impl From<DirectionArg> for ArbDirection {
+    fn from(d: DirectionArg) -> Self {
+        match d {
+            DirectionArg::Long => ArbDirection::Long,
+            DirectionArg::Short => ArbDirection::Short,
+        }
+    }
+}
+
+/// Print the full economics of an event under the current config.
+pub async fn preview(client: &KalshiClient, config: &Config, event_ticker: &str) -> Result<()> {
+    let event = client.get_event(event_ticker).await?;
+    println!("{} — {}", event.event_ticker, event.title);
+
+    if !event.mutually_exclusive {
+        println!("NOTE: event is not mutually exclusive; the scanner would skip it");
+    }
+    let Some(markets) = arb_candidate_markets(&event.markets, Utc::now()) else {
+        bail!("Event has a decided or closed bracket; the scanner would skip it");
+    };
+    if markets.len() < config.scanner.min_brackets || markets.len() > config.scanner.max_brackets {
+        println!(
+            "NOTE: {} active brackets is outside [{}, {}]; the scanner would skip it",
+            markets.len(),
+            config.scanner.min_brackets,
+            config.scanner.max_brackets
+        );
+    }
+
+    let concurrency = config.scanner.quote_concurrency;
+    let Some(quotes) = crate::fetch_quotes(client, None, &markets, &config.risk, None, None, concurrency).await else {
+        bail!("Could not quote every bracket (see log above)");
+    };
+    let mut params = DetectParams::from_config(&config.risk);
+    if params.sizing.is_some() {
+        params.balance_cents = Some(client.get_balance().await.context("Failed to fetch balance for sizing")?);
+    }
+    let size = params.position_size;
+
+    println!();
+    println!(
+        "{:<32} {:<24} {:>7} {:>7} {:>9} {:>9} {:>7} {:>7}",
+        "Ticker", "Bracket", "YES ask", "YES bid", "Depth ask", "Depth bid", "Fee L", "Fee S"
+    );
+    for q in &quotes {
+        println!(
+            "{:<32} {:<24} {:>6}c {:>6}c {:>9} {:>9} {:>6}c {:>6}c",
+            q.ticker,
+            truncate(q.label.as_deref().unwrap_or(&q.title), 24),
+            q.yes_ask_cents,
+            q.yes_bid_cents,
+            q.depth_at_no,
+            q.depth_at_yes,
+            taker_fee_cents(&q.ticker, size, q.yes_ask_cents),
+            taker_fee_cents(&q.ticker, size, q.yes_bid_cents),
+        );
+    }
+
+    for eval in evaluate_arb(&event.event_ticker, &event.title, &quotes, &params) {
+        let opp = &eval.opportunity;
+        let (action, min_depth) = match opp.direction {
+            ArbDirection::Long => ("buy", quotes.iter().map(|q| q.depth_at_no).min()),
+            ArbDirection::Short => ("sell", quotes.iter().map(|q| q.depth_at_yes).min()),
+        };
+        println!();
+        println!(
+            "{} ({} YES x{} on {} brackets)",
+            opp.direction,
+            action,
+            opp.position_size,
+            opp.brackets.len()
+        );
+        println!("  Sum:        {}", dollars(opp.sum_cents));
+        println!("  Gross:      {}", dollars(opp.gross_profit_cents));
+        println!("  Fees:       {}", dollars(opp.total_fees_cents));
+        println!("  Net:        {}", dollars(opp.net_profit_cents));
+        println!("  {}", crate::roi_tiers(opp));
+        println!("  Min depth:  {}", min_depth.unwrap_or(0));
+        if config.risk.sweep_levels > 1 {
+            // Average price each leg would pay across the levels it sweeps
+            let vwaps: Vec<String> = quotes
+                .iter()
+                .map(|q| {
+                    let levels = match opp.direction {
+                        ArbDirection::Long => &q.ask_levels,
+                        ArbDirection::Short => &q.bid_levels,
+                    };
+                    book::vwap_to_quantity(levels, opp.position_size as i64)
+                        .map_or("-".to_string(), |v| format!("{:.1}c", v))
+                })
+                .collect();
+            println!("  Leg VWAP:   {}", vwaps.join(" / "));
+        }
+        match eval.rejected {
+            None => println!("  Result:     PASS — would execute"),
+            Some(gate) => println!("  Result:     rejected by {}", gate),
+        }
+    }
+    Ok(())
+}
+
+/// Print the detector's verdict on every quoted event in `group` on `venue`.
+pub async fn venue_scan<V: Venue>(venue: &V, config: &Config, group: &str) -> Result<()> {
+    let evaluations = venue::detect(venue, group, &config.scanner, &config.risk).await?;
+    println!("{} {}: {} evaluations", venue.name(), group, evaluations.len());
+    println!();
+    println!("{:<40} {:<6} {:>5} {:>8} {:>10}  Result", "Event", "Dir", "Size", "Sum", "Net");
+    for eval in &evaluations {
+        let opp = &eval.opportunity;
+        println!(
+            "{:<40} {:<6} {:>5} {:>7}c {:>10}  {}",
+            truncate(&opp.event_ticker, 40),
+            opp.direction.to_string(),
+            opp.position_size,
+            opp.sum_cents,
+            dollars(opp.net_profit_cents),
+            eval.rejected.unwrap_or("PASS"),
+        );
+    }
+    Ok(())
+}
+
+/// Manually execute one direction of an event through the standard pre-flight checks
+/// and executor path (risk limits, persistence, mixed-state cancels, alerts).
+pub async fn execute(
+    client: &KalshiClient,
+    config: &Config,
+    dry_run: bool,
+    event_ticker: &str,
+    direction: ArbDirection,
+    size: u32,
+) -> Result<()> {
+    if dry_run {
+        bail!("DRY_RUN is set; refusing to place orders");
+    }
+    if size == 0 {
+        bail!("--size must be at least 1");
+    }
+    // Not alongside a live bot in this directory: its counters and cooldowns are its own
+    let _lock = crate::instance_lock::acquire(Path::new(crate::instance_lock::LOCK_PATH), &config.instance.name)?;
+
+    // --- Pre-flight: the same structural gates and detector gates the scanner applies ---
+    let event = client.get_event(event_ticker).await?;
+    if !event.mutually_exclusive {
+        bail!("Event {} is not mutually exclusive", event.event_ticker);
+    }
+    let Some(markets) = arb_candidate_markets(&event.markets, Utc::now()) else {
+        bail!("Event has a decided or closed bracket");
+    };
+    if markets.len() < config.scanner.min_brackets || markets.len() > config.scanner.max_brackets {
+        bail!(
+            "{} active brackets is outside [{}, {}]",
+            markets.len(),
+            config.scanner.min_brackets,
+            config.scanner.max_brackets
+        );
+    }
+    let concurrency = config.scanner.quote_concurrency;
+    let Some(quotes) = crate::fetch_quotes(client, None, &markets, &config.risk, None, None, concurrency).await else {
+        bail!("Could not quote every bracket (see log above)");
+    };
+    // An explicit size replaces `[risk.sizing]`
+    let params = DetectParams {
+        position_size: size,
+        sizing: None,
+        ..DetectParams::from_config(&config.risk)
+    };
+    let mut evals = evaluate_arb(&event.event_ticker, &event.title, &quotes, &params);
+    gate_settlement(&mut evals, expected_settlement(&markets).or(event.strike_date), &params, Utc::now());
+    let eval = evals
+        .into_iter()
+        .find(|e| e.opportunity.direction == direction)
+        .context("Detector returned no evaluation for this direction")?;
+    if let Some(gate) = eval.rejected {
+        bail!("Pre-flight check failed: {} rejected by {}", direction, gate);
+    }
+    let opp = eval.opportunity;
+    // Sized down when `min_partial_size` lets a thin book through
+    let size = opp.position_size;
+
+    // --- Planned legs ---
+    println!("{} — {} ({})", opp.event_ticker, opp.event_title, opp.direction);
+    let router = crate::routing::router_for(&opp.event_ticker);
+    let time_in_force = crate::routing::time_in_force();
+    for bracket in &opp.brackets {
+        let req = router.route(bracket, direction, size, time_in_force, Utc::now());
+        // Fees are symmetric in YES and NO price
+        let price = req.yes_price.or(req.no_price).unwrap_or(0);
+        println!(
+            "  {} {} x{} {:<32} @ {:>2}c  fee {}c  ({})",
+            req.action.to_uppercase(),
+            req.side.to_uppercase(),
+            req.count,
+            req.ticker,
+            price,
+            taker_fee_cents(&req.ticker, size, price),
+            truncate(bracket.label.as_deref().unwrap_or(&bracket.title), 24),
+        );
+    }
+    println!(
+        "  Net {} after {} fees — {}",
+        dollars(opp.net_profit_cents),
+        dollars(opp.total_fees_cents),
+        crate::roi_tiers(&opp)
+    );
+
+    if !confirm(&format!("Place {} orders?", opp.brackets.len()))? {
+        println!("Aborted");
+        return Ok(());
+    }
+
+    let mirror_client = crate::mirror::client(config).context("Failed to set up execution mirror")?;
+    let mut limits = crate::RiskLimits::new(ExchangeClock::from_config(&config.schedule), &config.risk);
+    let mut exec_store = ExecutionStore::load().context("Failed to load execution store")?;
+    // The same limits the run loop enforces, against the same open arbs and day so far
+    let mut positions = PositionTracker::open(Path::new(crate::positions::POSITIONS_PATH))?;
+    positions.sync(&exec_store).context("Failed to sync position tracker")?;
+    limits.restore(&exec_store, &positions).context("Failed to restore risk counters")?;
+    let (status_tx, _status_rx) = tokio::sync::watch::channel(StatusSnapshot::new(&config.instance.name, false, 1));
+    let filled = crate::execute_opportunity(
+        client,
+        mirror_client.as_ref(),
+        &opp,
+        size,
+        &mut limits,
+        &mut exec_store,
+        &status_tx,
+    )
+    .await;
+
+    match exec_store.get(&opp.id) {
+        Some(rec) => println!(
+            "Filled {} / resting {} (cancelled) / other {} / failed {} of {} legs",
+            filled,
+            rec.result.resting.len(),
+            rec.result.other.len(),
+            rec.result.api_failures.len(),
+            opp.brackets.len()
+        ),
+        None => println!("No execution recorded (risk limit hit or executor error; see log above)"),
+    }
+    Ok(())
+}
+
+/// Print locally tracked positions, optionally side by side with the exchange's.
+pub async fn positions(client: &KalshiClient, diff: bool) -> Result<()> {
+    let exec_store = ExecutionStore::load().context("Failed to load execution store")?;
+    let local = exec_store.positions();
+
+    if !diff {
+        println!("{:<32} {:>8} {:>9}", "Ticker", "Position", "Avg price");
+        for p in &local {
+            println!("{:<32} {:>8} {:>9}", p.ticker, p.position, fmt_avg(p.avg_price_cents()));
+        }
+        println!("{} positions", local.len());
+        return Ok(());
+    }
+
+    let exchange = client.get_positions().await?;
+    let mut tickers: Vec<&str> = local
+        .iter()
+        .map(|p| p.ticker.as_str())
+        .chain(exchange.iter().filter(|p| p.position != 0).map(|p| p.ticker.as_str()))
+        .collect();
+    tickers.sort_unstable();
+    tickers.dedup();
+
+    println!(
+        "{:<32} {:>9} {:>9} {:>9} {:>9}  Check",
+        "Ticker", "Local qty", "Local avg", "Exch qty", "Exch avg"
+    );
+    let mut discrepancies = 0;
+    for ticker in tickers {
+        let l = local.iter().find(|p| p.ticker == ticker);
+        let x = exchange.iter().find(|p| p.ticker == ticker);
+        let local_qty = l.map_or(0, |p| p.position);
+        let exch_qty = x.map_or(0, |p| p.position);
+        let local_avg = l.and_then(LocalPosition::avg_price_cents);
+        let exch_avg = x.and_then(exchange_avg_cents);
+
+        let check = position_check(l, x);
+        if check != "ok" {
+            discrepancies += 1;
+        }
+        println!(
+            "{:<32} {:>9} {:>9} {:>9} {:>9}  {}",
+            ticker,
+            local_qty,
+            fmt_avg(local_avg),
+            exch_qty,
+            fmt_avg(exch_avg),
+            check
+        );
+    }
+    println!("{} discrepancies", discrepancies);
+    Ok(())
+}
+
+/// Print resting exchange orders next to local execution-store state.
+pub async fn orders(client: &KalshiClient) -> Result<()> {
+    let exec_store = ExecutionStore::load().context("Failed to load execution store")?;
+    let resting = client.get_orders(Some("resting")).await?;
+    let entries = crate::status::open_order_entries(&resting, &exec_store);
+
+    println!(
+        "{:<38} {:<32} {:<10} {:>5} {:>9}  Opportunity",
+        "Order", "Ticker", "Action", "Price", "Remaining"
+    );
+    for e in &entries {
+        println!(
+            "{:<38} {:<32} {:<10} {:>4}c {:>9}  {}",
+            e.order_id,
+            e.ticker,
+            format!("{} {}", e.action, e.side),
+            e.yes_price.unwrap_or(0),
+            e.remaining.map_or("-".to_string(), |r| r.to_string()),
+            e.opportunity_id.as_deref().unwrap_or("- (not placed by this store)"),
+        );
+    }
+    println!("{} resting on exchange", entries.len());
+
+    // Orders the store still believes are resting but the exchange no longer lists
+    let stale: Vec<_> = exec_store
+        .open_orders()
+        .into_iter()
+        .filter(|o| !resting.iter().any(|r| r.order_id == o.order_id))
+        .collect();
+    if !stale.is_empty() {
+        println!();
+        println!("Locally resting but not on exchange (filled or cancelled since last update):");
+        for o in stale {
+            println!("  {} {} ({}, since {})", o.order_id, o.ticker, o.opportunity_id, o.updated_at);
+        }
+    }
+    Ok(())
+}
+
+/// Cancel every resting order on the account, paced to the write rate limit.
+pub async fn cancel_all(client: &KalshiClient, dry_run: bool) -> Result<()> {
+    if dry_run {
+        bail!("DRY_RUN is set; refusing to cancel orders");
+    }
+    let resting = client.get_orders(Some("resting")).await?;
+    if resting.is_empty() {
+        println!("No resting orders");
+        return Ok(());
+    }
+    for o in &resting {
+        println!("  {} {} {} {} @ {}c", o.order_id, o.ticker, o.action, o.side, o.yes_price.unwrap_or(0));
+    }
+    if !confirm(&format!("Cancel {} orders?", resting.len()))? {
+        println!("Aborted");
+        return Ok(());
+    }
+
+    let mut exec_store = ExecutionStore::load().context("Failed to load execution store")?;
+    let ids: Vec<String> = resting.iter().map(|o| o.order_id.clone()).collect();
+    let outcomes = crate::cancel_and_record(client, &mut exec_store, &ids).await;
+    for (order_id, e) in &outcomes.failed {
+        println!("  FAILED {}: {:#}", order_id, e);
+    }
+    println!("Cancelled {} of {} orders", outcomes.canceled.len(), ids.len());
+    if !outcomes.failed.is_empty() {
+        bail!("{} orders could not be cancelled", outcomes.failed.len());
+    }
+    Ok(())
+}
+
+/// Print per-instance and combined totals from several instances' storage outputs.
+/// Days run midnight to midnight in `timezone`, the exchange's.
+pub fn aggregate_report(
+    dirs: &[PathBuf],
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    timezone: Tz,
+) -> Result<()> {
+    let (reports, total) = report::aggregate(dirs, Period { since, until, clock: ExchangeClock::new(timezone) })?;
+    println!(
+        "Period: {} to {} ({})",
+        since.map_or("start".to_string(), |d| d.to_string()),
+        until.map_or("now".to_string(), |d| d.to_string()),
+        timezone
+    );
+    println!(
+        "{:<28} {:>7} {:>6} {:>6} {:>6} {:>5} {:>10} {:>10} {:>6} {:>6} {:>8}",
+        "Source", "Cycles", "Opps", "Execs", "Recon", "Incpl", "Expected", "Actual", "Fails", "Pos", "Resting"
+    );
+    for r in reports.iter().chain(std::iter::once(&total)) {
+        print_report_row(r);
+    }
+
+    println!();
+    println!("Actual net by source (reconciled arbs with an attribution)");
+    println!("{:<28} {:>10} {:>10} {:>10} {:>10} {:>10}", "Source", "Edge", "Slippage", "Fees", "Unfilled", "Net");
+    for r in reports.iter().chain(std::iter::once(&total)) {
+        let a = &r.attribution;
+        println!(
+            "{:<28} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            truncate(&r.source, 28),
+            dollars(a.edge_cents),
+            dollars(a.slippage_cents),
+            dollars(a.fee_cents),
+            dollars(a.unfilled_cents),
+            dollars(a.net_cents()),
+        );
+    }
+
+    if !total.fills.is_empty() {
+        println!();
+        println!("Fill outcomes by series (all sources)");
+        println!(
+            "{:<20} {:>5} {:>12} {:>12} {:>12} {:>12} {:>9}",
+            "Series", "Legs", "Immediate", "Improved", "Rest>fill", "Rest>cxl", "Avg impr"
+        );
+        for (series, f) in &total.fills {
+            let share = |n: usize| format!("{} ({:.0}%)", n, 100.0 * n as f64 / f.legs().max(1) as f64);
+            println!(
+                "{:<20} {:>5} {:>12} {:>12} {:>12} {:>12} {:>9}",
+                truncate(series, 20),
+                f.legs(),
+                share(f.immediate),
+                share(f.improved),
+                share(f.rested_filled),
+                share(f.rested_cancelled),
+                f.avg_improvement_cents().map_or("-".to_string(), |c| format!("{:.1}c", c)),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `aggregate-report` for one instance and one trading day.
+pub fn report(dir: &Path, date: Option<NaiveDate>, timezone: Tz) -> Result<()> {
+    let date = date.unwrap_or_else(|| ExchangeClock::new(timezone).trading_day(Utc::now()));
+    aggregate_report(&[dir.to_path_buf()], Some(date), Some(date), timezone)
+}
+
+/// Write `{table}.csv` and `{table}.parquet` under `out` and print each table's row count.
+pub fn export(dir: &Path, since: Option<NaiveDate>, until: Option<NaiveDate>, timezone: Tz, out: &Path) -> Result<()> {
+    for table in storage::export(dir, Period { since, until, clock: ExchangeClock::new(timezone) }, out)? {
+        println!(
+            "{:<16} {:>7} rows  {}",
+            table.name,
+            table.rows,
+            out.join(format!("{}.{{csv,parquet}}", table.name)).display()
+        );
+    }
+    Ok(())
+}
+
+/// Replay a snapshot directory under the current `[risk]` parameters (with overrides)
+/// and print what the detector would have traded and what the fills would have made.
+pub fn backtest(config: &Config, dir: &Path, min_roi_pct: Option<f64>, size: Option<u32>) -> Result<()> {
+    let snapshots = backtest::load_snapshots(dir)?;
+    let base = DetectParams::from_config(&config.risk);
+    let params = DetectParams {
+        position_size: size.unwrap_or(base.position_size),
+        min_roi_pct: min_roi_pct.unwrap_or(base.min_roi_pct),
+        // Recorded books carry no balance; replay at a fixed size
+        sizing: None,
+        ..base
+    };
+    let risk = &config.risk;
+    let stats = backtest::run(&snapshots, &params, risk.min_level_quantity as i64, risk.sweep_levels as usize);
+
+    match (stats.first, stats.last) {
+        (Some(first), Some(last)) => println!("Snapshots: {} from {} to {}", stats.snapshots, first, last),
+        _ => bail!("No snapshots in {}", dir.display()),
+    }
+    println!(
+        "Params: size {}, min_roi {:.1}%, min_profit {}c, min_level_quantity {}",
+        params.position_size, params.min_roi_pct, params.min_net_profit_cents, config.risk.min_level_quantity
+    );
+    println!("Events: {} ({} snapshots fully quotable)", stats.events, stats.quoted);
+    println!(
+        "Opportunities: {} ({} LONG / {} SHORT), {} repeats on unchanged books skipped",
+        stats.opportunities, stats.long, stats.short, stats.repeats
+    );
+    println!("Partial fills: {} ({} unhedged contracts)", stats.partial, stats.unhedged_contracts);
+    println!("Expected net: {}", dollars(stats.expected_net_cents));
+    println!("Simulated net: {} after {} fees", dollars(stats.simulated_net_cents), dollars(stats.fees_cents));
+    println!(
+        "Capital tied up: {} (ROI {})",
+        dollars(stats.capital_cents),
+        stats.roi_pct().map_or("-".to_string(), |r| format!("{:.2}%", r))
+    );
+    Ok(())
+}
+
+pub async fn replay_exec(config: &Config, opportunity_id: &str, dir: Option<&Path>) -> Result<()> {
+    let store = ExecutionStore::load()?;
+    let record = store.get(opportunity_id).with_context(|| format!("No recorded execution of {}", opportunity_id))?;
+    let dir = dir
+        .or(config.scanner.snapshot_dir.as_deref())
+        .context("No snapshot directory: pass --dir or set [scanner] snapshot_dir")?;
+    let snapshots = backtest::load_snapshots(dir)?;
+    let replay = replay::run(record, &snapshots, &config.risk).await?;
+
+    let opp = &replay.opportunity;
+    println!(
+        "{} {} x{}: detected {}, books from {}",
+        opp.event_ticker,
+        opp.direction,
+        opp.position_size,
+        replay.detected_at.format("%Y-%m-%d %H:%M:%S%.3f"),
+        replay.books_at.format("%Y-%m-%d %H:%M:%S%.3f")
+    );
+    println!(
+        "Recorded: {} filled, {} resting, {} other, {} failed",
+        record.result.filled.len(),
+        record.result.resting.len(),
+        record.result.other.len(),
+        record.result.api_failures.len()
+    );
+    println!();
+    println!("{:<14} {:>6} {:>9} {:>10}", "Policy", "Sets", "Unhedged", "Net");
+    for outcome in &replay.outcomes {
+        println!(
+            "{:<14} {:>6} {:>9} {:>10}",
+            outcome.policy.to_string(),
+            outcome.complete_sets,
+            outcome.unhedged,
+            dollars(outcome.net_cents)
+        );
+    }
+    if let Some(best) = replay.best() {
+        println!();
+        println!("Best: {}", best.policy);
+    }
+    Ok(())
+}
+
+fn print_report_row(r: &InstanceReport) {
+    let label = if r.instances.is_empty() {
+        r.source.clone()
+    } else {
+        format!("{} ({})", r.source, r.instances.join(","))
+    };
+    println!(
+        "{:<28} {:>7} {:>6} {:>6} {:>6} {:>5} {:>10} {:>10} {:>6} {:>6} {:>8}",
+        truncate(&label, 28),
+        r.cycles,
+        r.opportunities,
+        r.executions,
+        r.reconciled,
+        r.incomplete,
+        dollars(r.expected_net_cents),
+        dollars(r.actual_net_cents),
+        r.failed_legs,
+        r.open_positions,
+        r.resting_orders,
+    );
+}
+
+/// Run a `config` subcommand.
+pub fn config(action: &ConfigCommand) -> Result<()> {
+    match action {
+        ConfigCommand::Schema => {
+            println!("{}", serde_json::to_string_pretty(&config_check::schema())?);
+            Ok(())
+        }
+        ConfigCommand::Check { path, against } => config_check(path, against),
+    }
+}
+
+/// Check `path` on its own, without diffing it against the running config.
+pub fn validate_config(path: &Path) -> Result<()> {
+    let (_, unknown) = load_candidate(path)?;
+    if !unknown.is_empty() {
+        bail!("{} unknown key(s) in {}", unknown.len(), path.display());
+    }
+    Ok(())
+}
+
+/// Parse and validate `path`, printing its unknown keys (returned too, since they're only
+/// fatal to the caller).
+fn load_candidate(path: &Path) -> Result<(Config, Vec<String>)> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let value: toml::Value = toml::from_str(&content)
+        .with_context(|| format!("{} is not valid TOML", path.display()))?;
+
+    let unknown = config_check::unknown_keys(&config_check::schema(), &value);
+    for key in &unknown {
+        println!("UNKNOWN KEY  {} (ignored; the default applies)", key);
+    }
+    let candidate: Config = toml::from_str(&content)
+        .with_context(|| format!("{} does not match the config schema", path.display()))?;
+    AlertTemplates::new(&candidate.alerts)
+        .with_context(|| format!("{} has invalid alert templates", path.display()))?;
+    Fees::new(&candidate.fees).with_context(|| format!("{} has an invalid fee schedule", path.display()))?;
+    println!("{} parses as a valid config", path.display());
+    Ok((candidate, unknown))
+}
+
+fn config_check(path: &Path, against: &Path) -> Result<()> {
+    let (candidate, unknown) = load_candidate(path)?;
+
+    match Config::from_file(against) {
+        Ok(running) => {
+            let changes = config_check::diff(&running, &candidate);
+            if changes.is_empty() {
+                println!("No effective changes vs {}", against.display());
+            } else {
+                println!("\nEffective changes vs {} (defaults filled in):", against.display());
+                for c in &changes {
+                    println!(
+                        "  {}: {} -> {}",
+                        c.key,
+                        c.old.as_deref().unwrap_or("(unset)"),
+                        c.new.as_deref().unwrap_or("(unset)")
+                    );
+                }
+            }
+        }
+        Err(e) => println!("Not diffing: {:#}", e),
+    }
+
+    if !unknown.is_empty() {
+        bail!("{} unknown key(s) in {}", unknown.len(), path.display());
+    }
+    Ok(())
+}
+
+fn fmt_avg(cents: Option<f64>) -> String {
+    cents.map_or("-".to_string(), |c| format!("{:.1}c", c))
+}
+
+/// Ask a yes/no question on stdin; anything but an explicit yes is a no.
+fn confirm(prompt: &str) -> Result<bool> {
+    print!("{} [y/N] ", prompt);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(matches!(line.trim(), "y" | "Y" | "yes" | "YES"))
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let mut out: String = s.chars().take(max - 1).collect();
+        out.push('…');
+        out
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::config::ScheduleConfig;
+
+/// Date math in the exchange's local timezone. Trading days, report windows, and the
+/// daily risk reset fall on local midnight, so a day is 23 or 25 hours long across DST
+/// transitions. Deriving days from UTC dates would shift every boundary by the offset.
+#[derive(Debug, Clone, Copy)]
+pub struct ExchangeClock {
+    tz: Tz,
+}
+
+impl ExchangeClock {
+    pub fn new(tz: Tz) -> Self {
+        Self { tz }
+    }
+
+    pub fn from_config(config: &ScheduleConfig) -> Self {
+        Self::new(config.timezone)
+    }
+
+    /// The exchange-local date containing `now`.
+    pub fn trading_day(&self, now: DateTime<Utc>) -> NaiveDate {
+        now.with_timezone(&self.tz).date_naive()
+    }
+
+    /// `[start, end)` of a trading day in UTC: local midnight to the next local midnight.
+    pub fn day_bounds(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let next = date.succ_opt().unwrap_or(date);
+        (self.start_of(date), self.start_of(next))
+    }
+
+    /// First instant after `after` at which the local wall clock reads `time`. On a day
+    /// the time is skipped by DST, the first instant past the gap.
+    pub fn next_local_time(&self, after: DateTime<Utc>, time: NaiveTime) -> DateTime<Utc> {
+        let mut date = self.trading_day(after);
+        loop {
+            let at = self.resolve_local(date.and_time(time));
+            if at > after {
+                return at;
+            }
+            date = match date.succ_opt() {
+                Some(next) => next,
+                None => return at,
+            };
+        }
+    }
+
+    fn start_of(&self, date: NaiveDate) -> DateTime<Utc> {
+        self.resolve_local(date.and_time(NaiveTime::MIN))
+    }
+
+    /// Resolve a local wall time. Ambiguous times (fall back) take the earlier instant;
+    /// times skipped by a spring-forward gap move to the first valid instant after it.
+    fn resolve_local(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let mut probe = local;
+        // Gaps are at most a couple of hours in practice; bound the search regardless.
+        for _ in 0..(24 * 4) {
+            match self.tz.from_local_datetime(&probe) {
+                LocalResult::Single(t) => return t.with_timezone(&Utc),
+                LocalResult::Ambiguous(earliest, _) => return earliest.with_timezone(&Utc),
+                LocalResult::None => probe += Duration::minutes(15),
+            }
+        }
+        local.and_utc()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ny() -> ExchangeClock {
+        ExchangeClock::new(chrono_tz::America::New_York)
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_trading_day_uses_exchange_midnight() {
+        // 23:30 EDT on June 30 is already July 1 in UTC
+        assert_eq!(ny().trading_day(utc("2026-07-01T03:30:00Z")), date(2026, 6, 30));
+        assert_eq!(ny().trading_day(utc("2026-07-01T04:00:00Z")), date(2026, 7, 1));
+        // Winter offset is -5
+        assert_eq!(ny().trading_day(utc("2026-01-15T04:30:00Z")), date(2026, 1, 14));
+    }
+
+    #[test]
+    fn test_day_length_across_dst() {
+        let hours = |d| {
+            let (start, end) = ny().day_bounds(d);
+            (end - start).num_hours()
+        };
+        assert_eq!(hours(date(2026, 3, 8)), 23); // spring forward
+        assert_eq!(hours(date(2026, 11, 1)), 25); // fall back
+        assert_eq!(hours(date(2026, 6, 15)), 24);
+
+        let (start, end) = ny().day_bounds(date(2026, 3, 8));
+        assert_eq!(start, utc("2026-03-08T05:00:00Z"));
+        assert_eq!(end, utc("2026-03-09T04:00:00Z"));
+    }
+
+    #[test]
+    fn test_next_local_time_follows_the_wall_clock() {
+        let five_pm = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+        // Before 17:00 EDT → today; exactly at it → tomorrow
+        assert_eq!(ny().next_local_time(utc("2026-07-01T20:00:00Z"), five_pm), utc("2026-07-01T21:00:00Z"));
+        assert_eq!(ny().next_local_time(utc("2026-07-01T21:00:00Z"), five_pm), utc("2026-07-02T21:00:00Z"));
+        // Across fall back, 17:00 local moves from 21:00Z to 22:00Z
+        assert_eq!(ny().next_local_time(utc("2026-10-31T21:00:00Z"), five_pm), utc("2026-11-01T22:00:00Z"));
+    }
+
+    #[test]
+    fn test_skipped_local_time_moves_past_gap() {
+        // 02:30 on spring-forward day does not exist in New York; 03:00 EDT is next
+        let local = date(2026, 3, 8).and_hms_opt(2, 30, 0).unwrap();
+        assert_eq!(ny().resolve_local(local), utc("2026-03-08T07:00:00Z"));
+    }
+}
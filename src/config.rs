@@ -1,59 +1,561 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::path::PathBuf;
+use chrono_tz::Tz;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-#[derive(Debug, Deserialize)]
+use crate::digest::OpportunityAlertsConfig;
+use crate::exits::ExitConfig;
+use crate::fees::FeesConfig;
+use crate::kalshi::circuit::CircuitConfig;
+use crate::kalshi::rate_limit::RateLimitConfig;
+use crate::kalshi::retry::RetryConfig;
+use crate::kalshi::usage::UsageConfig;
+use crate::polymarket::PolymarketConfig;
+use crate::priority::PriorityConfig;
+use crate::recovery::RecoveryConfig;
+use crate::relations::Relation;
+use crate::routing::RoutingConfig;
+use crate::scheduler::{Job, Schedule};
+use crate::storage::StorageConfig;
+use crate::telegram::TelegramConfig;
+use crate::telemetry::TelemetryConfig;
+
+/// Where the bot reads its config; `[scanner]` and `[risk]` are re-read when it changes.
+pub const CONFIG_PATH: &str = "config.toml";
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Config {
+    #[serde(default)]
+    pub instance: InstanceConfig,
     pub scanner: ScannerConfig,
     pub risk: RiskConfig,
     pub kalshi: KalshiConfig,
+    #[serde(default)]
+    pub status: StatusConfig,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    /// Secondary detection parameters evaluated (never traded) alongside the primary config.
+    #[serde(default)]
+    pub shadow: Option<ShadowConfig>,
+    /// Second environment that every live execution is replayed against.
+    #[serde(default)]
+    pub mirror: Option<MirrorConfig>,
+    /// Live trading on a few series at a tiny size, paper trading everywhere else.
+    #[serde(default)]
+    pub canary: Option<CanaryConfig>,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    /// Scheduled archives of `data/`.
+    #[serde(default)]
+    pub backup: Option<BackupConfig>,
+    /// Rolling over `data/` logs once they grow too large.
+    #[serde(default)]
+    pub rotation: Option<RotationConfig>,
+    /// Working off the unhedged legs of arbs that never completed.
+    #[serde(default)]
+    pub exits: Option<ExitConfig>,
+    /// Fee schedules, for when Kalshi's rates change or differ by series.
+    #[serde(default)]
+    pub fees: FeesConfig,
+    /// Order routing policy, by default and per series.
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    /// Markets declared equivalent to the union of other markets, checked by the `relations` job.
+    #[serde(default)]
+    pub relations: Vec<Relation>,
+    /// Public Polymarket endpoints for `venue-scan polymarket`.
+    #[serde(default)]
+    pub polymarket: PolymarketConfig,
+    /// What startup does with resting orders a previous run placed but never recorded.
+    #[serde(default)]
+    pub recovery: RecoveryConfig,
+    #[serde(default)]
+    pub telegram: TelegramConfig,
+    /// Sampling of high-volume debug logs.
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    /// Markdown and/or JSON logs of opportunities, trades, and scans.
+    #[serde(default)]
+    pub storage: StorageConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct InstanceConfig {
+    /// Identifies this bot (e.g. "demo", "paper", "prod") in alerts, logs, and records.
+    #[serde(default = "default_instance_name")]
+    pub name: String,
 }
 
-#[derive(Debug, Deserialize)]
+fn default_instance_name() -> String { "default".to_string() }
+
+impl Default for InstanceConfig {
+    fn default() -> Self {
+        Self { name: default_instance_name() }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ScannerConfig {
     pub interval_secs: u64,
     #[serde(default)]
     pub series_filter: Vec<String>,
     #[serde(default = "default_scan_delay_ms")]
     pub scan_delay_ms: u64,
+    /// Orderbooks of one event fetched at once. `scan_delay_ms` still spaces the requests.
+    #[serde(default = "default_quote_concurrency")]
+    pub quote_concurrency: usize,
     #[serde(default = "default_min_brackets")]
     pub min_brackets: usize,
     #[serde(default = "default_max_brackets")]
     pub max_brackets: usize,
+    /// Skip events settling sooner than this many hours from now (None = no floor).
+    #[serde(default)]
+    pub min_hours_to_close: Option<f64>,
+    /// Skip events settling further out than this many days (None = no ceiling).
+    #[serde(default)]
+    pub max_days_to_close: Option<f64>,
     #[serde(default = "default_series_cache_secs")]
     pub series_cache_secs: u64,
+    /// How long series refreshes may keep failing before the stale cache is dropped
+    /// in favour of `safe_mode_series`.
+    #[serde(default = "default_max_series_staleness_secs")]
+    pub max_series_staleness_secs: u64,
+    /// Series scanned while the series list can't be refreshed (empty = scan nothing).
+    #[serde(default)]
+    pub safe_mode_series: Vec<String>,
+    /// Series allowlist and series/event blocklists, re-read every cycle (missing = none).
+    #[serde(default = "default_watchlist_path")]
+    pub watchlist_path: PathBuf,
+    /// Record every fully fetched event's books here for `backtest` (None = off).
+    #[serde(default)]
+    pub snapshot_dir: Option<PathBuf>,
+    /// Skip events with a bracket whose YES bid-ask spread is wider than this (None = off).
+    #[serde(default)]
+    pub max_spread_cents: Option<i64>,
+    /// Skip events with a bracket showing fewer contracts than this on both sides of its
+    /// book together, dust excluded (None = off).
+    #[serde(default)]
+    pub min_book_depth: Option<i64>,
+    /// Reject opportunities with a bracket that traded fewer contracts than this over the
+    /// last `volume_window_secs` (None = off). Trades are only fetched for events with an
+    /// opportunity.
+    #[serde(default)]
+    pub min_recent_volume: Option<i64>,
+    #[serde(default = "default_volume_window_secs")]
+    pub volume_window_secs: u64,
+    /// Cycles run as dry runs after startup before orders are placed (0 = trade at once).
+    #[serde(default = "default_warmup_cycles")]
+    pub warmup_cycles: u32,
+    /// `[scanner.priority]` — scan series more or less often by their hit history
+    /// (None = every series every cycle).
+    #[serde(default)]
+    pub priority: Option<PriorityConfig>,
 }
 
 fn default_scan_delay_ms() -> u64 { 150 }
+fn default_quote_concurrency() -> usize { 4 }
 fn default_min_brackets() -> usize { 2 }
 fn default_max_brackets() -> usize { 15 }
 fn default_series_cache_secs() -> u64 { 300 }
+fn default_max_series_staleness_secs() -> u64 { 3_600 }
+fn default_watchlist_path() -> PathBuf { PathBuf::from("watchlist.toml") }
+fn default_warmup_cycles() -> u32 { 1 }
+fn default_volume_window_secs() -> u64 { 3_600 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct RiskConfig {
     pub min_net_profit_cents: u32,
     pub min_roi_pct: f64,
     pub position_size: u32,
     pub max_open_positions: u32,
+    /// Expected slippage per contract per leg, in cents. Used for reporting the
+    /// slippage-adjusted ROI tier; does not gate execution.
+    #[serde(default)]
+    pub expected_slippage_cents: u32,
+    /// Price levels with fewer contracts than this are ignored when quoting either side
+    /// of a book (0 = use the top level regardless of size).
+    #[serde(default)]
+    pub min_level_quantity: u32,
+    /// Price levels per side a leg may sweep, priced at their volume-weighted cost
+    /// (1 = best level only).
+    #[serde(default = "default_sweep_levels")]
+    pub sweep_levels: u32,
+    /// After placing an arb, how long to keep re-checking legs that came back resting
+    /// before treating the execution as mixed (0 = decide on the placement status).
+    #[serde(default = "default_fill_wait_ms")]
+    pub fill_wait_ms: u64,
+    /// Wall-clock limit on placing an arb's legs. Legs not acknowledged by then are looked
+    /// up on the exchange rather than awaited, and the arb is judged on what's found.
+    #[serde(default = "default_execution_budget_ms")]
+    pub execution_budget_ms: u64,
+    /// Abort an execution still placing legs after this long, replies and lookups included:
+    /// resting legs are cancelled at once instead of given `fill_wait_ms` (0 = off).
+    #[serde(default)]
+    pub max_execution_ms: u64,
+    /// When the thinnest leg has fewer than `position_size` contracts, trade that many
+    /// instead, as long as it's at least this many and every gate still passes.
+    /// Unset = skip the opportunity.
+    #[serde(default)]
+    pub min_partial_size: Option<u32>,
+    /// Right before placing an arb, re-fetch every leg's book and re-price it; abort if net
+    /// profit fell more than this many cents below what was detected, or the depth no
+    /// longer covers the size. Unset = place on the detection quotes.
+    #[serde(default)]
+    pub requote_tolerance_cents: Option<u32>,
+    /// Skip opportunities whose net ROI, annualized over the time left to settlement, is
+    /// below this. Opportunities with no settlement time aren't gated. Unset = off.
+    #[serde(default)]
+    pub min_annualized_roi_pct: Option<f64>,
+    /// Skip opportunities whose books were fetched further apart than this, first to last,
+    /// so every leg is priced off roughly the same moment. Unset = off.
+    #[serde(default)]
+    pub max_quote_age_ms: Option<u64>,
+    /// After an attempt that failed or left the arb incomplete, skip the event in that
+    /// direction for this long. The same arb at the same prices failing again doubles it,
+    /// up to 8x. 0 = off.
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// `[risk.adaptive_roi]` — raise `min_roi_pct` while realized slippage runs high.
+    #[serde(default)]
+    pub adaptive_roi: Option<AdaptiveRoiConfig>,
+    /// `[risk.sizing]` — size each opportunity from depth and bankroll instead of
+    /// trading a fixed `position_size`.
+    #[serde(default)]
+    pub sizing: Option<SizingConfig>,
+    /// While this file exists no new orders are placed; scanning and cleanup go on.
+    /// `/halt` creates it, deleting it resumes trading.
+    #[serde(default = "default_halt_file")]
+    pub halt_file: PathBuf,
 }
 
-#[derive(Debug, Deserialize)]
+/// Per-opportunity size: the smallest of the thinnest leg's depth, what
+/// `bankroll_fraction` of the live balance can fund, and `max_size`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SizingConfig {
+    /// Most contracts per bracket for any one opportunity.
+    pub max_size: u32,
+    /// Share of the account balance (0.1 = 10%) one opportunity may tie up.
+    #[serde(default = "default_bankroll_fraction")]
+    pub bankroll_fraction: f64,
+}
+
+fn default_bankroll_fraction() -> f64 { 0.1 }
+
+/// Scales the effective ROI floor by recent realized slippage from reconciliation.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct AdaptiveRoiConfig {
+    /// Most recent fully-filled arbs considered.
+    #[serde(default = "default_adaptive_window")]
+    pub window: usize,
+    /// Arbs needed in the window before the floor moves.
+    #[serde(default = "default_adaptive_min_samples")]
+    pub min_samples: usize,
+    /// Share of expected profit slippage may take (0.25 = 25%) before the floor rises.
+    #[serde(default = "default_max_slippage_share")]
+    pub max_slippage_share: f64,
+    /// Ceiling for the raised floor, in percent.
+    #[serde(default = "default_max_min_roi_pct")]
+    pub max_min_roi_pct: f64,
+}
+
+fn default_sweep_levels() -> u32 { 1 }
+fn default_fill_wait_ms() -> u64 { 2_000 }
+fn default_execution_budget_ms() -> u64 { 3_000 }
+fn default_cooldown_secs() -> u64 { 900 }
+fn default_halt_file() -> PathBuf { PathBuf::from("STOP") }
+fn default_adaptive_window() -> usize { 20 }
+fn default_adaptive_min_samples() -> usize { 5 }
+fn default_max_slippage_share() -> f64 { 0.25 }
+fn default_max_min_roi_pct() -> f64 { 5.0 }
+
+/// `[shadow]` — overrides applied on top of `[risk]` for shadow evaluation.
+/// Unset fields inherit the primary value.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ShadowConfig {
+    pub position_size: Option<u32>,
+    pub min_net_profit_cents: Option<u32>,
+    pub min_roi_pct: Option<f64>,
+    pub expected_slippage_cents: Option<u32>,
+}
+
+/// `[canary]` — a live run that only trades `series`, at no more than `position_size`
+/// contracts. Opportunities in every other series are paper traded as in a dry run.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CanaryConfig {
+    pub series: Vec<String>,
+    #[serde(default = "default_canary_position_size")]
+    pub position_size: u32,
+}
+
+fn default_canary_position_size() -> u32 { 1 }
+
+impl CanaryConfig {
+    /// Size to trade an opportunity in `series` at, or None to paper trade it.
+    pub fn size_for(&self, series: &str, position_size: u32) -> Option<u32> {
+        self.series.iter().any(|s| s == series).then(|| position_size.min(self.position_size))
+    }
+}
+
+/// `[mirror]` — replays each live execution as the same order set on another
+/// environment (normally demo) to catch order-path breakage there first.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct MirrorConfig {
+    /// Connection settings for the mirror environment (`[mirror.kalshi]`).
+    pub kalshi: KalshiConfig,
+    /// Environment variable holding the mirror environment's API key ID.
+    #[serde(default = "default_mirror_api_key_env")]
+    pub api_key_id_env: String,
+}
+
+fn default_mirror_api_key_env() -> String { "KALSHI_DEMO_API_KEY_ID".to_string() }
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct KalshiConfig {
     pub base_url: String,
     pub rsa_key_path: PathBuf,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub circuit: CircuitConfig,
+    #[serde(default)]
+    pub usage: UsageConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Alternate base URL used after repeated connect-level failures on `base_url`.
+    #[serde(default)]
+    pub fallback_base_url: Option<String>,
+    /// Consecutive connect failures (DNS, refused, connect timeout) before failing over.
+    #[serde(default = "default_failover_after")]
+    pub failover_after: u32,
+    /// Seconds on the fallback before retrying the primary.
+    #[serde(default = "default_failback_secs")]
+    pub failback_secs: u64,
+    /// Connect timeout; keeps DNS/connect failures from waiting out the full request timeout.
+    #[serde(default = "default_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+    /// Pin hostnames to fixed addresses, bypassing DNS (e.g. "api.elections.kalshi.com" = "1.2.3.4:443").
+    #[serde(default)]
+    pub resolve: HashMap<String, SocketAddr>,
+    /// WebSocket endpoint for live orderbooks (e.g. "wss://api.elections.kalshi.com/trade-api/ws/v2").
+    /// When set, the scanner reads books from the feed and only falls back to REST per
+    /// market while the feed is down or hasn't sent that market's snapshot yet.
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    /// Overall limit on one paginated series-list fetch. Pages fetched before the limit (or
+    /// a failure) are kept and the next fetch resumes from the last cursor.
+    #[serde(default = "default_pagination_deadline_secs")]
+    pub pagination_deadline_secs: u64,
+}
+
+fn default_failover_after() -> u32 { 3 }
+fn default_failback_secs() -> u64 { 900 }
+fn default_connect_timeout_ms() -> u64 { 3_000 }
+fn default_pagination_deadline_secs() -> u64 { 120 }
+
+impl KalshiConfig {
+    /// `base_url` with every other setting at its default (no fallback, pinning or feed).
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            rsa_key_path: PathBuf::new(),
+            retry: RetryConfig::default(),
+            circuit: CircuitConfig::default(),
+            usage: UsageConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            fallback_base_url: None,
+            failover_after: default_failover_after(),
+            failback_secs: default_failback_secs(),
+            connect_timeout_ms: default_connect_timeout_ms(),
+            resolve: HashMap::new(),
+            ws_url: None,
+            pagination_deadline_secs: default_pagination_deadline_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct StatusConfig {
+    /// Address for the status HTTP listener (e.g. "127.0.0.1:8080"). Unset = disabled.
+    #[serde(default)]
+    pub listen_addr: Option<String>,
+    /// Entries kept per recent-activity buffer (opportunities, executions, risk events).
+    #[serde(default = "default_recent_capacity")]
+    pub recent_capacity: usize,
+    /// `/healthz` fails once an unpaused job is this far past its next run.
+    #[serde(default = "default_stall_secs")]
+    pub stall_secs: u64,
+}
+
+fn default_recent_capacity() -> usize { 50 }
+fn default_stall_secs() -> u64 { 300 }
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: None,
+            recent_capacity: default_recent_capacity(),
+            stall_secs: default_stall_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ScheduleConfig {
+    /// Exchange timezone (IANA name). Trading days and the daily risk reset use its midnight.
+    #[serde(default = "default_timezone")]
+    #[schemars(with = "String")]
+    pub timezone: Tz,
+}
+
+fn default_timezone() -> Tz { chrono_tz::America::New_York }
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self { timezone: default_timezone() }
+    }
+}
+
+/// `[scheduler]` — schedules for the periodic jobs besides the scan (which runs every
+/// `scanner.interval_secs`), and jobs to start paused.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SchedulerConfig {
+    #[serde(default = "default_heartbeat")]
+    #[schemars(with = "String")]
+    pub heartbeat: Schedule,
+    /// Exchange-local time (`[schedule] timezone`).
+    #[serde(default = "default_daily_report")]
+    #[schemars(with = "String")]
+    pub daily_report: Schedule,
+    #[serde(default = "default_settlement")]
+    #[schemars(with = "String")]
+    pub settlement: Schedule,
+    #[serde(default = "default_lifecycle")]
+    #[schemars(with = "String")]
+    pub lifecycle: Schedule,
+    /// Only scheduled with `[[relations]]` declared.
+    #[serde(default = "default_relations")]
+    #[schemars(with = "String")]
+    pub relations: Schedule,
+    /// Also run between series during a scan cycle when it comes due (`every` schedules only).
+    #[serde(default = "default_watch")]
+    #[schemars(with = "String")]
+    pub watch: Schedule,
+    #[serde(default = "default_reconcile")]
+    #[schemars(with = "String")]
+    pub reconcile: Schedule,
+    /// Exchange-local time (`[schedule] timezone`).
+    #[serde(default = "default_balance_snapshot")]
+    #[schemars(with = "String")]
+    pub balance_snapshot: Schedule,
+    #[serde(default)]
+    pub paused: Vec<Job>,
+}
+
+fn default_heartbeat() -> Schedule { Schedule::Every(std::time::Duration::from_secs(300)) }
+fn default_settlement() -> Schedule { Schedule::Every(std::time::Duration::from_secs(600)) }
+fn default_lifecycle() -> Schedule { Schedule::Every(std::time::Duration::from_secs(30)) }
+fn default_relations() -> Schedule { Schedule::Every(std::time::Duration::from_secs(60)) }
+fn default_watch() -> Schedule { Schedule::Every(std::time::Duration::from_secs(5)) }
+fn default_reconcile() -> Schedule { Schedule::Every(std::time::Duration::from_secs(3_600)) }
+fn default_daily_report() -> Schedule {
+    Schedule::DailyAt(chrono::NaiveTime::from_hms_opt(23, 55, 0).unwrap_or_default())
+}
+fn default_balance_snapshot() -> Schedule {
+    Schedule::DailyAt(chrono::NaiveTime::from_hms_opt(23, 50, 0).unwrap_or_default())
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat: default_heartbeat(),
+            daily_report: default_daily_report(),
+            settlement: default_settlement(),
+            lifecycle: default_lifecycle(),
+            relations: default_relations(),
+            watch: default_watch(),
+            reconcile: default_reconcile(),
+            balance_snapshot: default_balance_snapshot(),
+            paused: Vec::new(),
+        }
+    }
+}
+
+/// `[backup]` — compressed copies of the data directory.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct BackupConfig {
+    /// Local directory, or `s3://bucket/prefix` (uploaded with the `aws` CLI).
+    pub target: String,
+    #[serde(default = "default_backup_schedule")]
+    #[schemars(with = "String")]
+    pub schedule: Schedule,
+    /// Archives kept per instance in a local target (S3 retention is left to bucket lifecycle rules).
+    #[serde(default = "default_backup_keep")]
+    pub keep: usize,
+}
+
+fn default_backup_schedule() -> Schedule {
+    Schedule::DailyAt(chrono::NaiveTime::from_hms_opt(0, 30, 0).unwrap_or_default())
+}
+fn default_backup_keep() -> usize { 14 }
+
+/// `[rotation]` — logs in `data/` past `max_mb` are renamed aside by the `rotate_logs` job.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RotationConfig {
+    #[serde(default = "default_rotation_schedule")]
+    #[schemars(with = "String")]
+    pub schedule: Schedule,
+    #[serde(default = "default_rotation_max_mb")]
+    pub max_mb: u64,
+}
+
+fn default_rotation_schedule() -> Schedule {
+    Schedule::DailyAt(chrono::NaiveTime::from_hms_opt(0, 15, 0).unwrap_or_default())
+}
+fn default_rotation_max_mb() -> u64 { 100 }
+
+/// `[alerts]` — message templates overriding the built-in ones, keyed by alert type
+/// (`risk_limit`, `partial_fill`, ...). Rendered with minijinja.
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct AlertsConfig {
+    #[serde(default)]
+    pub templates: HashMap<String, String>,
+    /// Digest of every detected opportunity; unset = none are sent.
+    #[serde(default)]
+    pub opportunities: Option<OpportunityAlertsConfig>,
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
         dotenvy::dotenv().ok();
-        let content = std::fs::read_to_string("config.toml")
-            .context("Failed to read config.toml")?;
+        Self::from_file(Path::new(CONFIG_PATH))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
         let config: Config = toml::from_str(&content)
-            .context("Failed to parse config.toml")?;
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
         Ok(config)
     }
 }
 
+static INSTANCE_NAME: OnceLock<String> = OnceLock::new();
+
+/// Record the configured instance name for alerts and storage records. First call wins.
+pub fn set_instance_name(name: &str) {
+    INSTANCE_NAME.get_or_init(|| name.to_string());
+}
+
+pub fn instance_name() -> &'static str {
+    INSTANCE_NAME.get().map(String::as_str).unwrap_or("default")
+}
+
 pub fn api_key_id() -> Result<String> {
     std::env::var("KALSHI_API_KEY_ID")
         .context("KALSHI_API_KEY_ID not set in environment or .env")
@@ -0,0 +1,202 @@
+use schemars::schema::{RootSchema, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+
+/// JSON schema for `config.toml`, generated from the `Config` types.
+pub fn schema() -> RootSchema {
+    schemars::schema_for!(Config)
+}
+
+/// Dotted paths of keys in `candidate` that the schema doesn't know about. Serde
+/// ignores these, so a typo silently falls back to the field's default.
+pub fn unknown_keys(schema: &RootSchema, candidate: &toml::Value) -> Vec<String> {
+    let candidate = serde_json::to_value(candidate).unwrap_or(Value::Null);
+    let mut out = Vec::new();
+    walk(schema, &schema.schema, &candidate, "", &mut out);
+    out
+}
+
+fn walk(root: &RootSchema, schema: &SchemaObject, value: &Value, path: &str, out: &mut Vec<String>) {
+    let variants = resolve(root, schema);
+    match value {
+        Value::Object(map) => {
+            let mut properties: BTreeMap<&str, &SchemaObject> = BTreeMap::new();
+            let mut additional: Option<&SchemaObject> = None;
+            let mut open = false;
+            for s in &variants {
+                let Some(object) = &s.object else { continue };
+                for (name, prop) in &object.properties {
+                    if let Schema::Object(prop) = prop {
+                        properties.insert(name, prop);
+                    }
+                }
+                match object.additional_properties.as_deref() {
+                    Some(Schema::Object(a)) => additional = Some(a),
+                    Some(Schema::Bool(false)) => {}
+                    // Absent or `true`: any key is allowed
+                    _ => open = object.properties.is_empty(),
+                }
+            }
+            for (key, child) in map {
+                let child_path = join(path, key);
+                if let Some(prop) = properties.get(key.as_str()) {
+                    walk(root, prop, child, &child_path, out);
+                } else if let Some(additional) = additional {
+                    walk(root, additional, child, &child_path, out);
+                } else if !open {
+                    out.push(child_path);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for s in &variants {
+                let Some(SingleOrVec::Single(item)) = s.array.as_ref().and_then(|a| a.items.as_ref()) else {
+                    continue;
+                };
+                if let Schema::Object(item) = item.as_ref() {
+                    for (i, child) in items.iter().enumerate() {
+                        walk(root, item, child, &format!("{}[{}]", path, i), out);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The schema itself plus everything reachable through `$ref` and `anyOf`/`allOf`/`oneOf`
+/// (how schemars encodes `Option<T>` and nested structs).
+fn resolve<'a>(root: &'a RootSchema, schema: &'a SchemaObject) -> Vec<&'a SchemaObject> {
+    let mut out = Vec::new();
+    let mut pending = vec![schema];
+    while let Some(s) = pending.pop() {
+        if let Some(name) = s.reference.as_deref().and_then(|r| r.strip_prefix("#/definitions/")) {
+            if let Some(Schema::Object(def)) = root.definitions.get(name) {
+                pending.push(def);
+            }
+        }
+        if let Some(sub) = &s.subschemas {
+            for list in [&sub.all_of, &sub.any_of, &sub.one_of].into_iter().flatten() {
+                pending.extend(list.iter().filter_map(|s| match s {
+                    Schema::Object(o) => Some(o),
+                    Schema::Bool(_) => None,
+                }));
+            }
+        }
+        out.push(s);
+    }
+    out
+}
+
+/// One effective setting that differs between two configs (`None` = unset).
+#[derive(Debug, PartialEq)]
+pub struct ConfigChange {
+    pub key: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Effective settings (defaults filled in) that differ between `running` and `candidate`.
+pub fn diff(running: &Config, candidate: &Config) -> Vec<ConfigChange> {
+    let old = flatten(running);
+    let new = flatten(candidate);
+    let keys: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|k| old.get(*k) != new.get(*k))
+        .map(|k| ConfigChange {
+            key: k.clone(),
+            old: old.get(k).cloned(),
+            new: new.get(k).cloned(),
+        })
+        .collect()
+}
+
+fn flatten(config: &Config) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    flatten_value(&serde_json::to_value(config).unwrap_or(Value::Null), "", &mut out);
+    out
+}
+
+fn flatten_value(value: &Value, path: &str, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                flatten_value(child, &join(path, key), out);
+            }
+        }
+        Value::Null => {}
+        other => {
+            out.insert(path.to_string(), other.to_string());
+        }
+    }
+}
+
+/// Dotted path, quoting keys that themselves contain dots (e.g. hostnames under `resolve`).
+fn join(path: &str, key: &str) -> String {
+    let key = if key.contains('.') { format!("\"{}\"", key) } else { key.to_string() };
+    if path.is_empty() { key } else { format!("{}.{}", path, key) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"
+[scanner]
+interval_secs = 30
+
+[risk]
+min_net_profit_cents = 10
+min_roi_pct = 1.0
+position_size = 5
+max_open_positions = 5
+
+[kalshi]
+base_url = "https://example"
+rsa_key_path = "secrets/kalshi_rsa.pem"
+"#;
+
+    #[test]
+    fn test_unknown_keys_reported_with_paths() {
+        let text = format!(
+            "{}fallback_base_ulr = \"https://backup\"\n\n[kalshi.retry.get]\nmax_retries = 1\nbackoff_base_ms = 1\nbackoff_cap_ms = 1\njiter_ms = 5\n\n[kalshi.resolve]\n\"api.example.com\" = \"1.2.3.4:443\"\n\n[shadow]\nposition_size = 10\nposition_sise = 20\n\n[typo_section]\nx = 1\n",
+            BASE
+        );
+        let value: toml::Value = toml::from_str(&text).unwrap();
+        let mut unknown = unknown_keys(&schema(), &value);
+        unknown.sort();
+        assert_eq!(
+            unknown,
+            vec![
+                "kalshi.fallback_base_ulr",
+                "kalshi.retry.get.jiter_ms",
+                "shadow.position_sise",
+                "typo_section",
+            ]
+        );
+
+        let clean: toml::Value = toml::from_str(BASE).unwrap();
+        assert!(unknown_keys(&schema(), &clean).is_empty());
+    }
+
+    #[test]
+    fn test_diff_compares_effective_values() {
+        let running: Config = toml::from_str(BASE).unwrap();
+        // Spelling out a default is not a change; a new value and a new section are
+        let text = BASE
+            .replace("interval_secs = 30", "interval_secs = 30\nscan_delay_ms = 150")
+            .replace("min_roi_pct = 1.0", "min_roi_pct = 0.5")
+            + "\n[shadow]\nposition_size = 10\n";
+        let candidate: Config = toml::from_str(&text).unwrap();
+        let changes = diff(&running, &candidate);
+        assert_eq!(
+            changes,
+            vec![
+                ConfigChange { key: "risk.min_roi_pct".into(), old: Some("1.0".into()), new: Some("0.5".into()) },
+                ConfigChange { key: "shadow.position_size".into(), old: None, new: Some("10".into()) },
+            ]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+// Per-event cooldown after an arb attempt that failed or left the arb incomplete. A
+// mispricing on an illiquid book is detected again every cycle; without this each cycle
+// would place the same doomed orders and spend the daily order budget on them.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+use crate::kalshi::types::{ArbDirection, ArbOpportunity};
+
+/// A repeat of the same arb doubles the cooldown at most this many times (8x the base).
+const MAX_DOUBLINGS: u32 = 3;
+
+#[derive(Debug)]
+struct Entry {
+    until: DateTime<Utc>,
+    /// Leg prices of the attempt, to tell a repeat of the same arb from a new one.
+    prices: Vec<i64>,
+    doublings: u32,
+}
+
+/// Events cooling down, per direction.
+#[derive(Debug, Default)]
+pub struct Cooldown {
+    base: Duration,
+    entries: HashMap<(String, ArbDirection), Entry>,
+}
+
+fn key(opp: &ArbOpportunity) -> (String, ArbDirection) {
+    (opp.event_ticker.clone(), opp.direction)
+}
+
+fn prices(opp: &ArbOpportunity) -> Vec<i64> {
+    opp.brackets
+        .iter()
+        .map(|b| match opp.direction {
+            ArbDirection::Long => b.yes_ask_cents,
+            ArbDirection::Short => b.yes_bid_cents,
+        })
+        .collect()
+}
+
+impl Cooldown {
+    pub fn new(secs: u64) -> Self {
+        Self { base: Duration::seconds(secs as i64), entries: HashMap::new() }
+    }
+
+    pub fn set_base(&mut self, secs: u64) {
+        self.base = Duration::seconds(secs as i64);
+    }
+
+    /// When `opp`'s event may be attempted again in its direction, if not yet.
+    pub fn until(&self, opp: &ArbOpportunity, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.entries.get(&key(opp)).map(|e| e.until).filter(|&until| until > now)
+    }
+
+    /// Start the cooldown after a failed attempt. The same arb at the same prices failing
+    /// again doubles it, up to `MAX_DOUBLINGS` times; a zero base turns it off.
+    pub fn failed(&mut self, opp: &ArbOpportunity, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.base <= Duration::zero() {
+            return None;
+        }
+        let prices = prices(opp);
+        let doublings = match self.entries.get(&key(opp)) {
+            Some(e) if e.prices == prices => (e.doublings + 1).min(MAX_DOUBLINGS),
+            _ => 0,
+        };
+        let until = now + self.base * (1 << doublings);
+        self.entries.insert(key(opp), Entry { until, prices, doublings });
+        Some(until)
+    }
+
+    /// A fully filled attempt clears the event's history.
+    pub fn succeeded(&mut self, opp: &ArbOpportunity) {
+        self.entries.remove(&key(opp));
+    }
+
+    /// Drop entries that ran out long enough ago that a repeat wouldn't back off further.
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let keep = self.base * (1 << MAX_DOUBLINGS);
+        self.entries.retain(|_, e| now - e.until < keep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kalshi::types::BracketQuote;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn opp(ask: i64) -> ArbOpportunity {
+        let quote = |ticker: &str, ask| BracketQuote {
+            ticker: ticker.into(),
+            title: ticker.into(),
+            yes_ask_cents: ask,
+            yes_bid_cents: ask - 2,
+            ..Default::default()
+        };
+        ArbOpportunity {
+            id: "EV-LONG-1".into(),
+            uuid: Uuid::nil(),
+            event_ticker: "EV".into(),
+            event_title: "Event".into(),
+            direction: ArbDirection::Long,
+            brackets: vec![quote("EV-A", ask), quote("EV-B", 40)],
+            position_size: 10,
+            sum_cents: ask + 40,
+            total_fees_cents: 0,
+            gross_profit_cents: 0,
+            net_profit_cents: 0,
+            expected_slippage_cents: 0,
+            gross_roi_pct: Decimal::ZERO,
+            net_roi_pct: Decimal::ZERO,
+            slippage_adj_roi_pct: Decimal::ZERO,
+            settles_at: None,
+        }
+    }
+
+    #[test]
+    fn test_repeat_failures_back_off() {
+        let now = Utc::now();
+        let mut cooldown = Cooldown::new(600);
+        assert_eq!(cooldown.until(&opp(50), now), None);
+
+        assert_eq!(cooldown.failed(&opp(50), now), Some(now + Duration::minutes(10)));
+        assert!(cooldown.until(&opp(55), now + Duration::minutes(9)).is_some());
+        assert_eq!(cooldown.until(&opp(50), now + Duration::minutes(10)), None);
+
+        // Same prices again: 20 minutes, then 40, capped at 80
+        let later = now + Duration::minutes(10);
+        assert_eq!(cooldown.failed(&opp(50), later), Some(later + Duration::minutes(20)));
+        cooldown.failed(&opp(50), later);
+        cooldown.failed(&opp(50), later);
+        assert_eq!(cooldown.failed(&opp(50), later), Some(later + Duration::minutes(80)));
+        // A different arb on the event starts over
+        assert_eq!(cooldown.failed(&opp(51), later), Some(later + Duration::minutes(10)));
+
+        cooldown.succeeded(&opp(51));
+        assert_eq!(cooldown.until(&opp(51), later), None);
+        assert_eq!(Cooldown::new(0).failed(&opp(50), now), None);
+    }
+}
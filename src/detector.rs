@@ -1,190 +1,796 @@
+use crate::book::{self, Side};
+use crate::config::{RiskConfig, ScannerConfig, ShadowConfig, SizingConfig};
+use crate::fees::{self, taker_fee_cents};
+use crate::kalshi::client::KalshiClient;
 use crate::kalshi::types::*;
+use crate::money::{Cents, Contracts};
+use crate::price::{self, MAX_PRICE_CENTS, PAYOUT_CENTS};
+use crate::telemetry::Sampled;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use tracing::debug;
+use tracing::{debug, warn};
+use uuid::Uuid;
 
-/// Kalshi taker fee rate: 7 basis points of notional (0.07 = 7%).
-/// Source: https://kalshi.com/docs/kalshi-fee-schedule.pdf
-pub const FEE_BPS: i64 = 7;
+/// Select the markets of an event that are eligible for arb consideration.
+/// Markets already determined NO are dropped: their outcome is excluded, so the remaining
+/// brackets still cover every possible result. Returns None if the event is unsafe to
+/// trade: a bracket has resolved YES (the event is decided), or a bracket stopped trading
+/// without a known result (the remaining set may no longer be exhaustive).
+pub fn arb_candidate_markets(markets: &[Market], now: DateTime<Utc>) -> Option<Vec<&Market>> {
+    let mut candidates = Vec::new();
+    for market in markets {
+        if market.is_determined(now) {
+            match market.settled_result() {
+                Some("no") => {
+                    debug!(market = %market.ticker, "Dropping bracket already determined NO");
+                    continue;
+                }
+                result => {
+                    debug!(
+                        market = %market.ticker,
+                        status = %market.status,
+                        result = ?result,
+                        "Event has a decided or closed bracket, skipping"
+                    );
+                    return None;
+                }
+            }
+        }
+        if market.is_open_status() {
+            candidates.push(market);
+        }
+    }
+    Some(candidates)
+}
 
-/// Calculate Kalshi taker fee in cents for a given number of contracts at a price in cents.
-/// Formula: ceil(0.07 * C * P * (1-P) * 100) / 100, where P is in dollars.
-/// In cents: fee_cents = ceil(FEE_BPS * C * price_cents * (100 - price_cents) / 10_000)
-pub fn taker_fee_cents(contracts: u32, price_cents: i64) -> i64 {
-    if price_cents <= 0 || price_cents >= 100 {
-        return 0;
+/// Why an event's markets may not span every outcome, or None when they do as far as can
+/// be told. Buying every bracket is only an arb if one of them must resolve YES: a bracket
+/// that is paused or not yet open drops out of `arb_candidate_markets` while its outcome
+/// stays possible, and numeric strikes must run from a lower tail to an upper tail with no
+/// gap wider than the narrowest bracket (temperature brackets like 60–61 and 62–63 leave a
+/// gap of one whole degree). Markets without numeric strikes are judged on status alone.
+pub fn coverage_gap(markets: &[Market], now: DateTime<Utc>) -> Option<String> {
+    if let Some(m) = markets.iter().find(|m| !m.is_open_status() && !m.is_determined(now)) {
+        return Some(format!("{} is {}", m.ticker, m.status));
+    }
+    let mut ranges = Vec::new();
+    for m in markets {
+        let range = match (m.strike_type.as_deref()?, m.floor_strike, m.cap_strike) {
+            ("between", Some(floor), Some(cap)) => (floor, cap),
+            ("greater" | "greater_or_equal", Some(floor), _) => (floor, f64::INFINITY),
+            ("less" | "less_or_equal", _, Some(cap)) => (f64::NEG_INFINITY, cap),
+            _ => return None,
+        };
+        ranges.push(range);
+    }
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (first, last) = (ranges.first()?, ranges.last()?);
+    if first.0.is_finite() {
+        return Some(format!("no bracket below {}", first.0));
     }
-    let numerator = FEE_BPS * contracts as i64 * price_cents * (100 - price_cents);
-    // Ceiling division: (a + b - 1) / b
-    (numerator + 9_999) / 10_000
+    if last.1.is_finite() {
+        return Some(format!("no bracket above {}", last.1));
+    }
+    let narrowest =
+        ranges.iter().map(|(floor, cap)| cap - floor).filter(|w| w.is_finite()).fold(f64::INFINITY, f64::min);
+    let allowed = if narrowest.is_finite() { narrowest } else { 0.0 };
+    ranges
+        .windows(2)
+        .find(|pair| pair[1].0 - pair[0].1 > allowed)
+        .map(|pair| format!("no bracket between {} and {}", pair[0].1, pair[1].0))
 }
 
-/// Extract a BracketQuote from an orderbook.
-/// YES ask = 100 - best NO bid (buying YES means taking the other side of NO).
-/// YES bid = best YES bid (selling YES means hitting the YES bid).
-pub fn quote_from_orderbook(
-    ticker: &str,
-    title: &str,
-    orderbook: &Orderbook,
-) -> Option<BracketQuote> {
-    // Best NO bid = highest price in no[] (sort-safe)
-    let best_no_price = orderbook.no.iter().map(|l| l.price).max()?;
-    if orderbook.no.first().map(|f| f.price) != Some(best_no_price) {
-        debug!(
-            "NO orderbook not sorted descending: first={}, max={}",
-            orderbook.no[0].price, best_no_price
-        );
+/// When the last of these markets is expected to settle, if any of them say: its expiration
+/// plus its settlement timer, when the payout lands.
+pub fn expected_settlement(markets: &[&Market]) -> Option<DateTime<Utc>> {
+    markets
+        .iter()
+        .filter_map(|m| {
+            let expires = m.expected_expiration_time.or(m.expiration_time)?;
+            Some(expires + chrono::Duration::seconds(m.settlement_timer_seconds.unwrap_or(0)))
+        })
+        .max()
+}
+
+/// Why an event settling at `settles_at` is outside the `[scanner]` settlement window,
+/// or None if it's inside (or the time is unknown).
+pub fn outside_settlement_window(
+    settles_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    scanner: &ScannerConfig,
+) -> Option<&'static str> {
+    let hours = (settles_at? - now).num_minutes() as f64 / 60.0;
+    if scanner.min_hours_to_close.is_some_and(|min| hours < min) {
+        return Some("settles_too_soon");
+    }
+    if scanner.max_days_to_close.is_some_and(|max| hours > max * 24.0) {
+        return Some("settles_too_late");
+    }
+    None
+}
+
+/// How an event's tradeable bracket set now differs from the tickers an opportunity was
+/// priced on, or None if it is unchanged. Checked right before execution: a bracket listed
+/// or deactivated since detection means the priced set no longer covers every outcome
+/// exactly once, so the arb is not guaranteed.
+pub fn bracket_set_change(priced: &[&str], event: &Event, now: DateTime<Utc>) -> Option<String> {
+    if !event.mutually_exclusive {
+        return Some("event is no longer mutually exclusive".to_string());
+    }
+    let Some(current) = arb_candidate_markets(&event.markets, now) else {
+        return Some("a bracket has been decided or closed".to_string());
+    };
+    let added: Vec<&str> = current
+        .iter()
+        .map(|m| m.ticker.as_str())
+        .filter(|t| !priced.contains(t))
+        .collect();
+    let removed: Vec<&str> = priced
+        .iter()
+        .copied()
+        .filter(|t| !current.iter().any(|m| m.ticker == *t))
+        .collect();
+    if added.is_empty() && removed.is_empty() {
+        let gap = coverage_gap(&event.markets, now)?;
+        return Some(format!("brackets no longer cover every outcome: {}", gap));
+    }
+    let mut parts = Vec::new();
+    if !added.is_empty() {
+        parts.push(format!("added {}", added.join(", ")));
     }
+    if !removed.is_empty() {
+        parts.push(format!("removed {}", removed.join(", ")));
+    }
+    Some(parts.join("; "))
+}
 
-    // Best YES bid = highest price in yes[] (sort-safe)
-    let best_yes_price = orderbook.yes.iter().map(|l| l.price).max();
-    if let Some(best) = best_yes_price {
-        if orderbook.yes.first().map(|f| f.price) != Some(best) {
+/// Best price on one side of the book and the total quantity resting there.
+/// Levels whose total quantity (duplicates summed) is below `min_quantity` are dust
+/// and are skipped when choosing the best price. LONG (NO side) and SHORT (YES side)
+/// both go through here so the two depth gates apply identical rules.
+fn best_level(side: &str, levels: &[PriceLevel], min_quantity: i64) -> Option<(i64, i64)> {
+    let top = levels.iter().map(|l| l.price).max()?;
+    static UNSORTED: Sampled = Sampled::new();
+    static DUST: Sampled = Sampled::new();
+    if levels.first().map(|f| f.price) != Some(top) {
+        if let Some(dropped) = UNSORTED.sample() {
             debug!(
-                "YES orderbook not sorted descending: first={}, max={}",
-                orderbook.yes[0].price, best
+                dropped,
+                "{} orderbook not sorted descending: first={}, max={}",
+                side, levels[0].price, top
             );
         }
     }
 
-    let yes_ask_cents = 100 - best_no_price;
-    let yes_bid_cents = best_yes_price.unwrap_or(0);
-    // Sum quantities at the best price (handles duplicate price levels)
-    let depth_at_no: i64 = orderbook.no.iter()
-        .filter(|l| l.price == best_no_price)
-        .map(|l| l.quantity)
+    let best = book::best_level(levels, min_quantity);
+    if let Some((price, _)) = best {
+        if price != top {
+            if let Some(dropped) = DUST.sample() {
+                debug!(side, top, best = price, dropped, "Skipped dust levels above best price");
+            }
+        }
+    }
+    best
+}
+
+/// Extract a BracketQuote from an orderbook, ignoring levels smaller than `min_level_quantity`.
+/// YES ask = 100 - best NO bid (buying YES means taking the other side of NO).
+/// YES bid = best YES bid (selling YES means hitting the YES bid).
+pub fn quote_from_orderbook(
+    ticker: &str,
+    title: &str,
+    orderbook: &Orderbook,
+    min_level_quantity: i64,
+) -> Option<BracketQuote> {
+    let (best_no_price, depth_at_no) = best_level("NO", &orderbook.no, min_level_quantity)?;
+    let (yes_bid_cents, depth_at_yes) =
+        best_level("YES", &orderbook.yes, min_level_quantity).unwrap_or((0, 0));
+    let yes_ask_cents = price::complement(best_no_price);
+    let no_ask_cents = if yes_bid_cents > 0 { price::complement(yes_bid_cents) } else { 0 };
+    let book_depth = [&orderbook.yes, &orderbook.no]
+        .into_iter()
+        .flat_map(|side| book::aggregate(side, min_level_quantity))
+        .map(|(_, qty)| qty)
         .sum();
-    let depth_at_yes: i64 = best_yes_price
-        .map(|p| orderbook.yes.iter()
-            .filter(|l| l.price == p)
-            .map(|l| l.quantity)
-            .sum())
-        .unwrap_or(0);
+    if !price::is_valid(yes_ask_cents) {
+        debug!(ticker, best_no_price, "NO bid implies an off-tick YES ask, cannot quote bracket");
+        return None;
+    }
 
     Some(BracketQuote {
         ticker: ticker.to_string(),
         title: title.to_string(),
+        label: None,
         yes_ask_cents,
         yes_bid_cents,
         depth_at_no,
         depth_at_yes,
+        no_ask_cents,
+        no_bid_cents: best_no_price,
+        book_depth,
+        ask_levels: Vec::new(),
+        bid_levels: Vec::new(),
+        fetched_at: None,
     })
 }
 
+/// Why a bracket's book is too thin to trust its quote, per the `[scanner]` floors: a YES
+/// bid-ask spread wider than `max_spread_cents` ("spread"), or fewer than `min_book_depth`
+/// contracts on the whole book ("book_depth"). Phantom quotes on such books vanish on contact.
+pub fn thin_bracket(q: &BracketQuote, scanner: &ScannerConfig) -> Option<&'static str> {
+    if scanner.max_spread_cents.is_some_and(|max| q.yes_ask_cents - q.yes_bid_cents > max) {
+        Some("spread")
+    } else if scanner.min_book_depth.is_some_and(|min| q.book_depth < min) {
+        Some("book_depth")
+    } else {
+        None
+    }
+}
+
+/// `quote_from_orderbook`, plus the top `levels` non-dust levels of each side for sweeping
+/// (nothing extra when `levels` <= 1).
+pub fn quote_with_levels(
+    ticker: &str,
+    title: &str,
+    orderbook: &Orderbook,
+    min_level_quantity: i64,
+    levels: usize,
+) -> Option<BracketQuote> {
+    let mut quote = quote_from_orderbook(ticker, title, orderbook, min_level_quantity)?;
+    if levels > 1 {
+        quote.ask_levels = book::aggregate(&orderbook.no, min_level_quantity)
+            .into_iter()
+            .take(levels)
+            .map(|(p, q)| (price::complement(p), q))
+            .take_while(|&(p, _)| price::is_valid(p))
+            .collect();
+        quote.bid_levels = book::aggregate(&orderbook.yes, min_level_quantity).into_iter().take(levels).collect();
+    }
+    Some(quote)
+}
+
+/// Thresholds and sizing inputs for `detect_arb`, built from `[risk]` config.
+#[derive(Debug, Clone)]
+pub struct DetectParams {
+    pub position_size: u32,
+    pub min_net_profit_cents: u32,
+    pub min_roi_pct: f64,
+    /// Expected slippage per contract per leg, in cents (reporting only, not a gate).
+    pub expected_slippage_cents: u32,
+    /// Smallest size to trade at when depth is below `position_size` (None = skip instead).
+    pub min_partial_size: Option<u32>,
+    /// Size each opportunity from depth and bankroll instead of `position_size`.
+    pub sizing: Option<SizingConfig>,
+    /// Live account balance the bankroll cap is taken from (None = unknown, nothing is sized).
+    pub balance_cents: Option<i64>,
+    /// Floor on net ROI annualized over the time to settlement (see `gate_settlement`).
+    pub min_annualized_roi_pct: Option<f64>,
+    /// Most time between an event's first and last timed book fetch (see `quote_age`).
+    pub max_quote_age_ms: Option<u64>,
+}
+
+impl DetectParams {
+    pub fn from_config(risk: &RiskConfig) -> Self {
+        Self {
+            position_size: risk.position_size,
+            min_net_profit_cents: risk.min_net_profit_cents,
+            min_roi_pct: risk.min_roi_pct,
+            expected_slippage_cents: risk.expected_slippage_cents,
+            min_partial_size: risk.min_partial_size,
+            sizing: risk.sizing.clone(),
+            balance_cents: None,
+            min_annualized_roi_pct: risk.min_annualized_roi_pct,
+            max_quote_age_ms: risk.max_quote_age_ms,
+        }
+    }
+
+    /// Primary parameters with the shadow overrides applied.
+    pub fn from_shadow(risk: &RiskConfig, shadow: &ShadowConfig) -> Self {
+        let base = Self::from_config(risk);
+        Self {
+            position_size: shadow.position_size.unwrap_or(base.position_size),
+            min_net_profit_cents: shadow.min_net_profit_cents.unwrap_or(base.min_net_profit_cents),
+            min_roi_pct: shadow.min_roi_pct.unwrap_or(base.min_roi_pct),
+            expected_slippage_cents: shadow
+                .expected_slippage_cents
+                .unwrap_or(base.expected_slippage_cents),
+            min_partial_size: base.min_partial_size,
+            sizing: base.sizing,
+            balance_cents: None,
+            min_annualized_roi_pct: base.min_annualized_roi_pct,
+            max_quote_age_ms: base.max_quote_age_ms,
+        }
+    }
+
+    /// Largest size an opportunity can be evaluated at.
+    pub fn max_size(&self) -> u32 {
+        self.sizing.as_ref().map_or(self.position_size, |s| s.max_size)
+    }
+}
+
+/// Percentage return of `profit` on `cost`; zero when there is no cost basis.
+fn roi_pct(profit_cents: i64, cost_cents: i64) -> Decimal {
+    if cost_cents > 0 {
+        Decimal::from(profit_cents * 100) / Decimal::from(cost_cents)
+    } else {
+        dec!(0)
+    }
+}
+
+/// Fee-inclusive limits on the bracket price sums that can still pass the gates,
+/// for one bracket count and position size. Lets the scanner abandon an event
+/// part-way through fetching its orderbooks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SumBounds {
+    /// Largest YES-ask sum a LONG can have and still clear min profit and min ROI.
+    pub max_long_sum: i64,
+    /// Smallest YES-bid sum a SHORT needs to clear min profit and min ROI.
+    pub min_short_sum: i64,
+}
+
+impl SumBounds {
+    /// Bounds use the cheapest possible fees (1c per LONG leg, since every ask is in
+    /// 1..=99, or nothing where a schedule charges no taker fee; nothing for SHORT) at the
+    /// largest size, so they never rule out an event `evaluate_arb` would pass.
+    pub fn new(brackets: usize, params: &DetectParams) -> Self {
+        let ps = params.max_size() as i64;
+        let n = brackets as i64;
+        let min_fees = n * fees::min_leg_fee_cents();
+        let min_net = params.min_net_profit_cents as i64;
+        let min_roi = Decimal::try_from(params.min_roi_pct).unwrap_or(dec!(1));
+
+        // Net and ROI improve monotonically as the LONG sum falls / SHORT sum rises
+        let max_long_sum = (0..=PAYOUT_CENTS)
+            .rev()
+            .find(|&sum| {
+                let net = (PAYOUT_CENTS - sum) * ps - min_fees;
+                net >= min_net && roi_pct(net, sum * ps + min_fees) >= min_roi
+            })
+            .unwrap_or(-1);
+        let min_short_sum = (PAYOUT_CENTS..=MAX_PRICE_CENTS * n)
+            .find(|&sum| {
+                let net = (sum - PAYOUT_CENTS) * ps;
+                net >= min_net && roi_pct(net, PAYOUT_CENTS * ps) >= min_roi
+            })
+            .unwrap_or(i64::MAX);
+
+        Self { max_long_sum, min_short_sum }
+    }
+
+    /// Widest bounds of the two, so neither parameter set loses opportunities.
+    pub fn loosest(self, other: Self) -> Self {
+        Self {
+            max_long_sum: self.max_long_sum.max(other.max_long_sum),
+            min_short_sum: self.min_short_sum.min(other.min_short_sum),
+        }
+    }
+
+    /// True if no direction can pass given partial sums over the quoted brackets and
+    /// `remaining` unquoted ones (each ask at least 1c, each bid at most 99c).
+    pub fn rules_out(&self, ask_sum: i64, bid_sum: i64, remaining: usize) -> bool {
+        let remaining = remaining as i64;
+        ask_sum + remaining > self.max_long_sum && bid_sum + 99 * remaining < self.min_short_sum
+    }
+}
+
+/// `SumBounds` precomputed for every bracket count up to `max_brackets`.
+#[derive(Debug, Clone)]
+pub struct SumBoundsTable {
+    by_count: Vec<SumBounds>,
+}
+
+impl SumBoundsTable {
+    /// Bounds loose enough for every parameter set in `params` (e.g. primary and shadow).
+    pub fn precompute(params: &[&DetectParams], max_brackets: usize) -> Self {
+        let by_count = (0..=max_brackets)
+            .map(|n| {
+                params
+                    .iter()
+                    .map(|p| SumBounds::new(n, p))
+                    .reduce(SumBounds::loosest)
+                    .unwrap_or(SumBounds { max_long_sum: i64::MAX, min_short_sum: i64::MIN })
+            })
+            .collect();
+        Self { by_count }
+    }
+
+    pub fn get(&self, brackets: usize) -> Option<SumBounds> {
+        self.by_count.get(brackets).copied()
+    }
+}
+
+/// Build the unique ID for an opportunity detected at `now`.
+pub fn opportunity_id(event_ticker: &str, direction: ArbDirection, now: DateTime<Utc>) -> String {
+    format!("{}-{}-{}", event_ticker, direction, now.timestamp_millis())
+}
+
+/// Result of evaluating one direction of an event against the gates.
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub opportunity: ArbOpportunity,
+    /// First gate that failed, or None if the opportunity passed every gate.
+    pub rejected: Option<&'static str>,
+}
+
+impl Evaluation {
+    /// Rejected by a gate despite a positive price edge.
+    pub fn is_near_miss(&self) -> bool {
+        self.rejected.is_some() && self.opportunity.gross_profit_cents > 0
+    }
+}
+
+/// How much older the stalest timed quote is than the freshest. Books are fetched one
+/// after another, so by detection the first of a many-bracket event can be seconds old.
+/// None when fewer than two quotes are timed.
+pub fn quote_age(quotes: &[BracketQuote]) -> Option<chrono::Duration> {
+    let times = || quotes.iter().filter_map(|q| q.fetched_at);
+    times().nth(1)?;
+    Some(times().max()? - times().min()?)
+}
+
+/// Name of the first gate an evaluated direction fails, if any.
+fn gate_rejection(
+    net_profit: i64,
+    roi: Decimal,
+    min_roi: Decimal,
+    min_depth: i64,
+    size: u32,
+    params: &DetectParams,
+) -> Option<&'static str> {
+    if net_profit < params.min_net_profit_cents as i64 {
+        Some("min_net_profit")
+    } else if roi < min_roi {
+        Some("min_roi")
+    } else if min_depth < size as i64 {
+        Some("depth")
+    } else {
+        None
+    }
+}
+
+/// The first bracket that traded fewer than `[scanner] min_recent_volume` contracts over the
+/// last `volume_window_secs`, with its volume. A failed fetch counts as nothing traded.
+pub async fn low_volume_bracket(
+    client: &KalshiClient,
+    quotes: &[BracketQuote],
+    scanner: &ScannerConfig,
+) -> Option<(String, i64)> {
+    let min = scanner.min_recent_volume?;
+    let since = Utc::now() - chrono::Duration::seconds(scanner.volume_window_secs as i64);
+    for quote in quotes {
+        let volume = match client.recent_volume(&quote.ticker, since, min).await {
+            Ok(volume) => volume,
+            Err(e) => {
+                warn!(market = %quote.ticker, error = %e, "Failed to fetch trades");
+                0
+            }
+        };
+        if volume < min {
+            return Some((quote.ticker.clone(), volume));
+        }
+    }
+    None
+}
+
+/// Stamp evaluations with their event's settlement time (the detector only sees quotes) and
+/// reject those that pass every other gate but whose annualized ROI is below
+/// `min_annualized_roi_pct`, as "annualized_roi". Without a settlement time nothing is rejected.
+pub fn gate_settlement(
+    evals: &mut [Evaluation],
+    settles_at: Option<DateTime<Utc>>,
+    params: &DetectParams,
+    now: DateTime<Utc>,
+) {
+    let floor = params.min_annualized_roi_pct.and_then(|f| Decimal::try_from(f).ok());
+    for eval in evals {
+        eval.opportunity.settles_at = settles_at;
+        if eval.rejected.is_some() {
+            continue;
+        }
+        if let (Some(floor), Some(annualized)) = (floor, eval.opportunity.annualized_roi_pct(now)) {
+            if annualized < floor {
+                eval.rejected = Some("annualized_roi");
+            }
+        }
+    }
+}
+
+/// Re-evaluate `opp` at a fixed `size` (a `[canary]` cap) on the quotes it was found on,
+/// with the settlement gate as in `gate_settlement`. Fees round up per leg, so a smaller
+/// size isn't the same arb scaled down and can fail a gate the full size passed. The
+/// result keeps `opp`'s ID; None if `quotes` have no evaluation in its direction.
+pub fn evaluate_at_size(
+    opp: &ArbOpportunity,
+    quotes: &[BracketQuote],
+    params: &DetectParams,
+    size: u32,
+    now: DateTime<Utc>,
+) -> Option<Evaluation> {
+    let params = DetectParams { position_size: size, min_partial_size: None, sizing: None, ..params.clone() };
+    let mut evals = evaluate_arb(&opp.event_ticker, &opp.event_title, quotes, &params);
+    gate_settlement(&mut evals, opp.settles_at, &params, now);
+    let mut eval = evals.into_iter().find(|e| e.opportunity.direction == opp.direction)?;
+    eval.opportunity.id = opp.id.clone();
+    eval.opportunity.uuid = opp.uuid;
+    Some(eval)
+}
+
+/// Levels a direction takes on one leg, best first: the swept levels when quoted, else the best.
+fn leg_levels(q: &BracketQuote, direction: ArbDirection) -> Vec<(i64, i64)> {
+    let (levels, best) = match direction {
+        ArbDirection::Long => (&q.ask_levels, (q.yes_ask_cents, q.depth_at_no)),
+        ArbDirection::Short => (&q.bid_levels, (q.yes_bid_cents, q.depth_at_yes)),
+    };
+    if levels.is_empty() {
+        vec![best]
+    } else {
+        levels.clone()
+    }
+}
+
+/// Contracts available on every leg of a direction, down to the worst level quoted.
+fn min_depth(quotes: &[BracketQuote], direction: ArbDirection) -> i64 {
+    let side = match direction {
+        ArbDirection::Long => Side::Ask,
+        ArbDirection::Short => Side::Bid,
+    };
+    let depth = |q: &BracketQuote| {
+        let levels = leg_levels(q, direction);
+        levels.last().map_or(0, |&(worst, _)| book::depth_to_price(&levels, side, worst))
+    };
+    quotes.iter().map(depth).min().unwrap_or(0)
+}
+
+/// Taking `size` contracts on one leg, best level first.
+struct LegFill {
+    cost: Cents,
+    fees: Cents,
+    /// Worst price touched: the leg's limit.
+    limit_cents: i64,
+}
+
+/// Walk `levels` for `size` contracts. Whatever the levels can't supply is priced at the
+/// deepest one; the depth gate rejects such a size anyway. None if the cost overflows.
+fn sweep_leg(ticker: &str, levels: &[(i64, i64)], size: Contracts) -> Option<LegFill> {
+    let size = size.get() as i64;
+    let mut taken = book::take(levels, size);
+    // Short only when every level was taken, so the last one taken is the deepest
+    let short = size - taken.iter().map(|&(_, n)| n).sum::<i64>();
+    if let Some(last) = taken.last_mut() {
+        last.1 += short;
+    }
+    let mut fill = LegFill { cost: Cents::ZERO, fees: Cents::ZERO, limit_cents: 0 };
+    for (price, take) in taken {
+        let take = Contracts::from_i64(take)?;
+        fill.cost = fill.cost.checked_add(Cents(price).checked_mul(take)?)?;
+        fill.fees = fill.fees.checked_add(Cents(taker_fee_cents(ticker, take.get(), price)))?;
+        fill.limit_cents = price;
+    }
+    Some(fill)
+}
+
+/// Notional, fees, gross and net profit of an arb's legs. The notional is the premium paid
+/// (Long) or received (Short), volume-weighted across levels. None if it overflows.
+fn leg_totals(legs: &[LegFill], direction: ArbDirection, size: Contracts) -> Option<(i64, i64, i64, i64)> {
+    let notional = Cents::checked_sum(legs.iter().map(|l| l.cost))?;
+    let fees = Cents::checked_sum(legs.iter().map(|l| l.fees))?;
+    let payout = Cents(PAYOUT_CENTS).checked_mul(size)?;
+    let gross = match direction {
+        ArbDirection::Long => payout.checked_sub(notional)?,
+        ArbDirection::Short => notional.checked_sub(payout)?,
+    };
+    Some((notional.0, fees.0, gross.0, gross.checked_sub(fees)?.0))
+}
+
+/// Net profit of `size` contracts on each of `opp`'s legs priced from `quotes` (matched by
+/// ticker) the way detection prices them. None if a leg has no quote or less than `size`
+/// available, or the arithmetic overflows.
+pub fn repriced_net_cents(opp: &ArbOpportunity, quotes: &[BracketQuote], size: Contracts) -> Option<i64> {
+    let quotes: Vec<BracketQuote> =
+        opp.brackets.iter().map(|b| quotes.iter().find(|q| q.ticker == b.ticker).cloned()).collect::<Option<_>>()?;
+    if min_depth(&quotes, opp.direction) < size.get() as i64 {
+        return None;
+    }
+    let legs: Vec<LegFill> =
+        quotes.iter().map(|q| sweep_leg(&q.ticker, &leg_levels(q, opp.direction), size)).collect::<Option<_>>()?;
+    leg_totals(&legs, opp.direction, size).map(|(_, _, _, net)| net)
+}
+
+/// Cash `size` contracts per leg tie up, fees included: the premium for LONG, collateral
+/// (100 less the premium received) for SHORT. None if it overflows.
+pub fn required_capital_cents(quotes: &[BracketQuote], direction: ArbDirection, size: Contracts) -> Option<Cents> {
+    let leg = |q: &BracketQuote| {
+        let (price, per_contract) = match direction {
+            ArbDirection::Long => (q.yes_ask_cents, q.yes_ask_cents),
+            ArbDirection::Short => (q.yes_bid_cents, price::complement(q.yes_bid_cents)),
+        };
+        Cents(per_contract).checked_mul(size)?.checked_add(Cents(taker_fee_cents(&q.ticker, size.get(), price)))
+    };
+    quotes.iter().map(leg).try_fold(Cents::ZERO, |total, leg| total.checked_add(leg?))
+}
+
+/// Contracts `bankroll_fraction` of the balance can fund in a direction, at the capital one
+/// contract per leg ties up: premium plus fees for LONG, collateral plus fees for SHORT.
+fn bankroll_cap(
+    quotes: &[BracketQuote],
+    direction: ArbDirection,
+    sizing: &SizingConfig,
+    balance: Option<i64>,
+) -> i64 {
+    let Some(balance) = balance else {
+        return 0;
+    };
+    let Some(Cents(per_contract)) = required_capital_cents(quotes, direction, Contracts(1)) else {
+        return 0;
+    };
+    if per_contract <= 0 {
+        return 0;
+    }
+    (balance.max(0) as f64 * sizing.bankroll_fraction / per_contract as f64).floor() as i64
+}
+
 /// Detect Dutch book arbitrage across a set of bracket quotes.
-/// Returns opportunities for both Long and Short directions if they pass the gates.
-pub fn detect_arb(
+/// Evaluates both Long and Short directions and returns each with its gate outcome;
+/// an evaluation with `rejected == None` is a tradeable opportunity.
+/// The ROI gate applies to net-of-fee ROI; gross and slippage-adjusted ROI are reported alongside.
+/// With `min_partial_size` set, a direction that only fails the depth gate is re-evaluated
+/// at the available depth, and kept if that size passes every gate.
+/// With `sizing` set, each direction is instead evaluated at the smallest of its depth, its
+/// bankroll cap and `max_size` (never below `min_partial_size`), and rejected as "bankroll"
+/// when the balance can't fund that floor.
+/// With `max_quote_age_ms` set, a direction that passes everything else is rejected as
+/// "quote_age" when the timed quotes were fetched further apart than that.
+pub fn evaluate_arb(
     event_ticker: &str,
     event_title: &str,
     quotes: &[BracketQuote],
-    position_size: u32,
-    min_net_profit_cents: u32,
-    min_roi_pct: f64,
-) -> Vec<ArbOpportunity> {
-    let mut opps = Vec::new();
-
-    // --- Direction 1: Long (buy YES on every bracket) ---
-    {
-        let sum_cents: i64 = quotes.iter().map(|q| q.yes_ask_cents).sum();
-        let total_fees: i64 = quotes
-            .iter()
-            .map(|q| taker_fee_cents(position_size, q.yes_ask_cents))
-            .sum();
-        let gross_per_contract = 100 - sum_cents;
-        let gross_profit = gross_per_contract * position_size as i64;
-        let net_profit = gross_profit - total_fees;
-        let total_cost = sum_cents * position_size as i64 + total_fees;
-
-        let min_depth = quotes.iter().map(|q| q.depth_at_no).min().unwrap_or(0);
-
-        let roi = if total_cost > 0 {
-            Decimal::from(net_profit * 100) / Decimal::from(total_cost)
-        } else {
-            dec!(0)
-        };
+    params: &DetectParams,
+) -> Vec<Evaluation> {
+    let now = Utc::now();
+    let quote_age_ms = quote_age(quotes).map_or(0, |age| age.num_milliseconds());
+    let stale = params.max_quote_age_ms.is_some_and(|max| quote_age_ms > max as i64);
+    [ArbDirection::Long, ArbDirection::Short]
+        .into_iter()
+        .map(|direction| {
+            let evaluate = |size: u32| evaluate_direction(event_ticker, event_title, quotes, direction, size, params, now);
+            let depth = min_depth(quotes, direction);
+            if let Some(sizing) = &params.sizing {
+                let cap = bankroll_cap(quotes, direction, sizing, params.balance_cents);
+                let floor = params.min_partial_size.unwrap_or(1).max(1);
+                let size = depth.min(cap).min(sizing.max_size as i64).max(floor as i64) as u32;
+                debug!(event = event_ticker, direction = %direction, depth, cap, size, "Sized opportunity");
+                let mut eval = evaluate(size);
+                if eval.rejected.is_none() && cap < floor as i64 {
+                    eval.rejected = Some("bankroll");
+                }
+                return eval;
+            }
+            let full = evaluate(params.position_size);
+            match params.min_partial_size {
+                Some(floor) if full.rejected == Some("depth") && depth >= floor.max(1) as i64 => {
+                    let partial = evaluate(depth as u32);
+                    if partial.rejected.is_none() {
+                        debug!(event = event_ticker, direction = %direction, size = depth, "Sized down to available depth");
+                        partial
+                    } else {
+                        full
+                    }
+                }
+                _ => full,
+            }
+        })
+        .map(|mut eval| {
+            if eval.rejected.is_none() && stale {
+                let direction = eval.opportunity.direction;
+                debug!(event = event_ticker, direction = %direction, quote_age_ms, "Quotes fetched too far apart");
+                eval.rejected = Some("quote_age");
+            }
+            eval
+        })
+        .collect()
+}
 
-        debug!(
-            event = event_ticker,
-            direction = "LONG",
-            brackets = quotes.len(),
-            sum_cents,
-            total_fees,
-            net_profit,
-            roi = %roi,
-            min_depth,
-            "Evaluated long arb"
-        );
+fn evaluate_direction(
+    event_ticker: &str,
+    event_title: &str,
+    quotes: &[BracketQuote],
+    direction: ArbDirection,
+    position_size: u32,
+    params: &DetectParams,
+    now: DateTime<Utc>,
+) -> Evaluation {
+    let min_roi = Decimal::try_from(params.min_roi_pct).unwrap_or(dec!(1));
+    let expected_slippage =
+        params.expected_slippage_cents as i64 * position_size as i64 * quotes.len() as i64;
+    let ps = position_size as i64;
 
-        if net_profit >= min_net_profit_cents as i64
-            && roi >= Decimal::try_from(min_roi_pct).unwrap_or(dec!(1))
-            && min_depth >= position_size as i64
-        {
-            opps.push(ArbOpportunity {
-                event_ticker: event_ticker.to_string(),
-                event_title: event_title.to_string(),
-                direction: ArbDirection::Long,
-                brackets: quotes.to_vec(),
-                sum_cents,
-                total_fees_cents: total_fees,
-                gross_profit_cents: gross_profit,
-                net_profit_cents: net_profit,
-                roi_pct: roi,
-            });
-        }
+    // Long buys YES on every bracket at the ask; Short sells YES on every bracket at the bid,
+    // sweeping down the quoted levels when there are several
+    let legs: Option<Vec<LegFill>> =
+        quotes.iter().map(|q| sweep_leg(&q.ticker, &leg_levels(q, direction), Contracts(position_size))).collect();
+    let sum_cents: i64 = legs.iter().flatten().map(|l| l.limit_cents).sum();
+    let totals = legs.as_deref().and_then(|legs| leg_totals(legs, direction, Contracts(position_size)));
+    if totals.is_none() {
+        warn!(event = event_ticker, direction = %direction, position_size, "Arb arithmetic overflowed, rejecting");
     }
+    let (notional, total_fees, gross_profit, net_profit) = totals.unwrap_or_default();
+    let min_depth = min_depth(quotes, direction);
 
-    // --- Direction 2: Short (sell YES on every bracket) ---
-    {
-        let sum_cents: i64 = quotes.iter().map(|q| q.yes_bid_cents).sum();
-        let total_fees: i64 = quotes
-            .iter()
-            .map(|q| taker_fee_cents(position_size, q.yes_bid_cents))
-            .sum();
-        let gross_per_contract = sum_cents - 100;
-        let gross_profit = gross_per_contract * position_size as i64;
-        let net_profit = gross_profit - total_fees;
-        // For short, "cost" is the liability = 100 cents per contract
-        let total_cost = 100 * position_size as i64;
-
-        let min_depth = quotes.iter().map(|q| q.depth_at_yes).min().unwrap_or(0);
-
-        let roi = if total_cost > 0 {
-            Decimal::from(net_profit * 100) / Decimal::from(total_cost)
-        } else {
-            dec!(0)
-        };
+    let (gross_roi, roi, slippage_adj_roi) = match direction {
+        ArbDirection::Long => {
+            let total_cost = notional + total_fees;
+            (
+                roi_pct(gross_profit, notional),
+                roi_pct(net_profit, total_cost),
+                roi_pct(net_profit - expected_slippage, total_cost + expected_slippage),
+            )
+        }
+        ArbDirection::Short => {
+            // For short, "cost" is the liability = the payout per contract
+            let total_cost = PAYOUT_CENTS * ps;
+            (
+                roi_pct(gross_profit, total_cost),
+                roi_pct(net_profit, total_cost),
+                roi_pct(net_profit - expected_slippage, total_cost),
+            )
+        }
+    };
 
+    static EVALUATED: Sampled = Sampled::new();
+    if let Some(dropped) = EVALUATED.sample() {
         debug!(
             event = event_ticker,
-            direction = "SHORT",
+            direction = %direction,
             brackets = quotes.len(),
+            position_size,
             sum_cents,
             total_fees,
             net_profit,
+            gross_roi = %gross_roi,
             roi = %roi,
+            slippage_adj_roi = %slippage_adj_roi,
             min_depth,
-            "Evaluated short arb"
+            dropped,
+            "Evaluated arb"
         );
-
-        if net_profit >= min_net_profit_cents as i64
-            && roi >= Decimal::try_from(min_roi_pct).unwrap_or(dec!(1))
-            && min_depth >= position_size as i64
-        {
-            opps.push(ArbOpportunity {
-                event_ticker: event_ticker.to_string(),
-                event_title: event_title.to_string(),
-                direction: ArbDirection::Short,
-                brackets: quotes.to_vec(),
-                sum_cents,
-                total_fees_cents: total_fees,
-                gross_profit_cents: gross_profit,
-                net_profit_cents: net_profit,
-                roi_pct: roi,
-            });
-        }
     }
 
-    opps
+    Evaluation {
+        opportunity: ArbOpportunity {
+            id: opportunity_id(event_ticker, direction, now),
+            uuid: Uuid::new_v4(),
+            event_ticker: event_ticker.to_string(),
+            event_title: event_title.to_string(),
+            direction,
+            // Each leg's order goes out at the worst level it sweeps to
+            brackets: match &legs {
+                Some(legs) => quotes
+                    .iter()
+                    .zip(legs)
+                    .map(|(q, leg)| match direction {
+                        ArbDirection::Long => BracketQuote { yes_ask_cents: leg.limit_cents, ..q.clone() },
+                        ArbDirection::Short => BracketQuote { yes_bid_cents: leg.limit_cents, ..q.clone() },
+                    })
+                    .collect(),
+                None => quotes.to_vec(),
+            },
+            position_size,
+            sum_cents,
+            total_fees_cents: total_fees,
+            gross_profit_cents: gross_profit,
+            net_profit_cents: net_profit,
+            expected_slippage_cents: expected_slippage,
+            gross_roi_pct: gross_roi,
+            net_roi_pct: roi,
+            slippage_adj_roi_pct: slippage_adj_roi,
+            settles_at: None,
+        },
+        rejected: match totals {
+            Some(_) => gate_rejection(net_profit, roi, min_roi, min_depth, position_size, params),
+            None => Some("overflow"),
+        },
+    }
 }
 
 #[cfg(test)]
@@ -192,6 +798,20 @@ mod tests {
     use super::*;
     use proptest::prelude::*;
 
+    /// Opportunities that pass every gate.
+    fn detect_arb(
+        event_ticker: &str,
+        event_title: &str,
+        quotes: &[BracketQuote],
+        params: &DetectParams,
+    ) -> Vec<ArbOpportunity> {
+        evaluate_arb(event_ticker, event_title, quotes, params)
+            .into_iter()
+            .filter(|e| e.rejected.is_none())
+            .map(|e| e.opportunity)
+            .collect()
+    }
+
     /// Verify the fundamental accounting identity on every ArbOpportunity:
     ///   payout - cost = gross_profit
     ///   net_profit = gross_profit - fees
@@ -221,49 +841,211 @@ mod tests {
             opp.net_profit_cents, opp.gross_profit_cents, opp.total_fees_cents);
     }
 
+    fn params(position_size: u32, min_net_profit_cents: u32, min_roi_pct: f64) -> DetectParams {
+        DetectParams {
+            position_size,
+            min_net_profit_cents,
+            min_roi_pct,
+            expected_slippage_cents: 0,
+            min_partial_size: None,
+            sizing: None,
+            balance_cents: None,
+            min_annualized_roi_pct: None,
+            max_quote_age_ms: None,
+        }
+    }
+
+    fn make_market(ticker: &str, status: &str, result: &str) -> Market {
+        Market {
+            ticker: ticker.to_string(),
+            title: ticker.to_string(),
+            subtitle: None,
+            status: status.to_string(),
+            result: Some(result.to_string()),
+            close_time: None,
+            expected_expiration_time: None,
+            expiration_time: None,
+            settlement_timer_seconds: None,
+            yes_sub_title: None,
+            strike_type: None,
+            floor_strike: None,
+            cap_strike: None,
+        }
+    }
+
+    #[test]
+    fn test_candidate_markets_drops_determined_no() {
+        let markets = vec![
+            make_market("A", "active", ""),
+            make_market("B", "active", "no"),
+            make_market("C", "active", ""),
+        ];
+        let picked = arb_candidate_markets(&markets, Utc::now()).unwrap();
+        let tickers: Vec<_> = picked.iter().map(|m| m.ticker.as_str()).collect();
+        assert_eq!(tickers, vec!["A", "C"]);
+    }
+
     #[test]
-    fn test_taker_fee_at_2_contracts() {
-        assert_eq!(taker_fee_cents(2, 5), 1);   // $0.01
-        assert_eq!(taker_fee_cents(2, 10), 2);  // $0.02
-        assert_eq!(taker_fee_cents(2, 50), 4);  // $0.04
+    fn test_candidate_markets_skips_event_decided_yes() {
+        // Status lags at "active" but the result is already in
+        let markets = vec![
+            make_market("A", "active", ""),
+            make_market("B", "active", "yes"),
+        ];
+        assert!(arb_candidate_markets(&markets, Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_candidate_markets_skips_event_past_close() {
+        let now = Utc::now();
+        let mut closed = make_market("B", "active", "");
+        closed.close_time = Some(now - chrono::Duration::minutes(5));
+        let markets = vec![make_market("A", "active", ""), closed];
+        assert!(arb_candidate_markets(&markets, now).is_none());
+
+        // Future close time is fine
+        let mut open = make_market("B", "active", "");
+        open.close_time = Some(now + chrono::Duration::hours(5));
+        let markets = vec![make_market("A", "active", ""), open];
+        assert_eq!(arb_candidate_markets(&markets, now).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_coverage_gap_finds_missing_brackets() {
+        let now = Utc::now();
+        let strikes = |ticker: &str, kind: &str, floor: Option<f64>, cap: Option<f64>| Market {
+            strike_type: Some(kind.to_string()),
+            floor_strike: floor,
+            cap_strike: cap,
+            ..make_market(ticker, "active", "")
+        };
+        let mut markets = vec![
+            strikes("T60", "less", None, Some(60.0)),
+            strikes("B60.5", "between", Some(60.0), Some(61.0)),
+            strikes("B62.5", "between", Some(62.0), Some(63.0)),
+            strikes("T63", "greater", Some(63.0), None),
+        ];
+        assert_eq!(coverage_gap(&markets, now), None);
+
+        // A paused bracket's outcome is still possible; one determined NO is not
+        markets[1].status = "paused".to_string();
+        assert_eq!(coverage_gap(&markets, now), Some("B60.5 is paused".to_string()));
+        markets[1].status = "finalized".to_string();
+        markets[1].result = Some("no".to_string());
+        assert_eq!(coverage_gap(&markets, now), None);
+
+        let unlisted = vec![markets[0].clone(), markets[3].clone()];
+        assert_eq!(coverage_gap(&unlisted, now), Some("no bracket between 60 and 63".to_string()));
+        assert_eq!(coverage_gap(&markets[..3], now), Some("no bracket above 63".to_string()));
+        assert_eq!(coverage_gap(&markets[1..], now), Some("no bracket below 60".to_string()));
+        // Without numeric strikes only status is checked
+        assert_eq!(coverage_gap(&[make_market("A", "active", ""), make_market("B", "active", "")], now), None);
+    }
+
+    #[test]
+    fn test_settlement_window_gates_both_ends() {
+        let now: DateTime<Utc> = "2026-10-17T12:00:00Z".parse().unwrap();
+        let scanner: ScannerConfig =
+            toml::from_str("interval_secs = 60\nmin_hours_to_close = 2\nmax_days_to_close = 30").unwrap();
+        let gate = |hours: i64| outside_settlement_window(Some(now + chrono::Duration::hours(hours)), now, &scanner);
+        assert_eq!(gate(1), Some("settles_too_soon"));
+        assert_eq!(gate(2), None);
+        assert_eq!(gate(30 * 24), None);
+        assert_eq!(gate(30 * 24 + 1), Some("settles_too_late"));
+        // Unknown settlement isn't gated
+        assert_eq!(outside_settlement_window(None, now, &scanner), None);
+
+        // The latest market decides, with its settlement timer on top
+        let (mut a, mut b) = (make_market("A", "active", ""), make_market("B", "active", ""));
+        a.expected_expiration_time = Some(now + chrono::Duration::hours(3));
+        b.expiration_time = Some(now + chrono::Duration::hours(2));
+        b.settlement_timer_seconds = Some(2 * 3600);
+        assert_eq!(expected_settlement(&[&a, &b]), Some(now + chrono::Duration::hours(4)));
+        assert_eq!(expected_settlement(&[&make_market("C", "active", "")]), None);
+    }
+
+    #[test]
+    fn test_annualized_roi_gate_rejects_slow_settling_arbs() {
+        let now = Utc::now();
+        let quotes = vec![
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+        ];
+        let p = DetectParams { min_annualized_roi_pct: Some(50.0), ..params(5, 10, 1.0) };
+        let long = |settles_at: Option<DateTime<Utc>>| {
+            let mut evals = evaluate_arb("TEST", "Test", &quotes, &p);
+            gate_settlement(&mut evals, settles_at, &p, now);
+            evals.into_iter().find(|e| e.opportunity.direction == ArbDirection::Long).unwrap()
+        };
+        // ~12% net: fine over a week, not over nine months
+        let soon = long(Some(now + chrono::Duration::days(7)));
+        assert_eq!(soon.rejected, None);
+        assert_eq!(soon.opportunity.settles_at, Some(now + chrono::Duration::days(7)));
+        assert_eq!(long(Some(now + chrono::Duration::days(270))).rejected, Some("annualized_roi"));
+        assert_eq!(long(None).rejected, None);
     }
 
     #[test]
-    fn test_taker_fee_at_5_contracts() {
-        // 5 contracts at 5c:  7*5*5*95   = 16625,  ceil(16625/10000) = 2
-        assert_eq!(taker_fee_cents(5, 5), 2);
-        // 5 contracts at 10c: 7*5*10*90  = 31500,  ceil(31500/10000) = 4
-        assert_eq!(taker_fee_cents(5, 10), 4);
-        // 5 contracts at 20c: 7*5*20*80  = 56000,  ceil(56000/10000) = 6
-        assert_eq!(taker_fee_cents(5, 20), 6);
-        // 5 contracts at 25c: 7*5*25*75  = 65625,  ceil(65625/10000) = 7
-        assert_eq!(taker_fee_cents(5, 25), 7);
-        // 5 contracts at 33c: 7*5*33*67  = 77490,  ceil(77490/10000) = 8
-        assert_eq!(taker_fee_cents(5, 33), 8);
-        // 5 contracts at 50c: 7*5*50*50  = 87500,  ceil(87500/10000) = 9
-        assert_eq!(taker_fee_cents(5, 50), 9);
+    fn test_quote_age_gate_rejects_spread_out_fetches() {
+        let t0 = Utc::now();
+        let quote = |ticker: &str, ask: i64, ms: i64| BracketQuote {
+            ticker: ticker.into(),
+            yes_ask_cents: ask,
+            depth_at_no: 10,
+            fetched_at: Some(t0 + chrono::Duration::milliseconds(ms)),
+            ..Default::default()
+        };
+        let quotes = vec![quote("A", 20, 0), quote("B", 25, 2_500), quote("C", 40, 900)];
+        assert_eq!(quote_age(&quotes), Some(chrono::Duration::milliseconds(2_500)));
+        assert_eq!(quote_age(&quotes[..1]), None);
+
+        let long = |max: Option<u64>| {
+            let p = DetectParams { max_quote_age_ms: max, ..params(5, 10, 1.0) };
+            evaluate_arb("TEST", "Test", &quotes, &p).remove(0).rejected
+        };
+        assert_eq!(long(Some(2_000)), Some("quote_age"));
+        assert_eq!(long(Some(3_000)), None);
+        assert_eq!(long(None), None);
     }
 
     #[test]
-    fn test_taker_fee_edge_cases() {
-        assert_eq!(taker_fee_cents(5, 0), 0);
-        assert_eq!(taker_fee_cents(5, 100), 0);
-        assert_eq!(taker_fee_cents(0, 50), 0);
+    fn test_bracket_set_change_detects_listed_and_deactivated_brackets() {
+        let now = Utc::now();
+        let mut event = Event {
+            event_ticker: "EV".to_string(),
+            title: "EV".to_string(),
+            mutually_exclusive: true,
+            strike_date: None,
+            markets: vec![make_market("A", "active", ""), make_market("B", "active", "")],
+        };
+        assert_eq!(bracket_set_change(&["A", "B"], &event, now), None);
+
+        // Newly listed and deactivated brackets are both reported
+        event.markets.push(make_market("C", "active", ""));
+        event.markets[1] = make_market("B", "inactive", "");
+        assert_eq!(
+            bracket_set_change(&["A", "B"], &event, now).as_deref(),
+            Some("added C; removed B")
+        );
+
+        event.markets[1] = make_market("B", "active", "yes");
+        assert!(bracket_set_change(&["A", "B", "C"], &event, now).unwrap().contains("decided"));
     }
 
     #[test]
     fn test_long_arb_worked_example() {
         // 4 brackets: A=10c, B=25c, C=40c, D=20c (sum=95c)
         let quotes = vec![
-            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 10, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0 },
-            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0 },
-            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0 },
-            BracketQuote { ticker: "D".into(), title: "D".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0 },
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 10, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "D".into(), title: "D".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
         ];
         // Sum=95. Gross/contract=5c. Gross for 5=25c.
         // Fees at 5 contracts: fee(5,10)=4 + fee(5,25)=7 + fee(5,40)=9 + fee(5,20)=6 = 26c.
         // Net = 25 - 26 = -1c. Not profitable.
-        let opps = detect_arb("TEST", "Test Event", &quotes, 5, 10, 1.0);
+        let opps = detect_arb("TEST", "Test Event", &quotes, &params(5, 10, 1.0));
         assert!(opps.is_empty(), "Should not find arb when sum=95c after fees");
     }
 
@@ -271,19 +1053,283 @@ mod tests {
     fn test_long_arb_profitable() {
         // 3 brackets: sum = 85c. Gross/contract = 15c. Gross for 5 = 75c.
         let quotes = vec![
-            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0 },
-            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0 },
-            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0 },
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
         ];
         // Fees at 5: fee(5,20)=6 + fee(5,25)=7 + fee(5,40)=9 = 22c.
         // Net = 75 - 22 = 53c. ROI = 53/(425+22) = 11.9%.
-        let opps = detect_arb("TEST", "Test", &quotes, 5, 10, 1.0);
+        let opps = detect_arb("TEST", "Test", &quotes, &params(5, 10, 1.0));
         assert_eq!(opps.len(), 1);
         assert_eq!(opps[0].direction, ArbDirection::Long);
         assert_eq!(opps[0].net_profit_cents, 53);
         assert_arb_identity(&opps[0], 5);
     }
 
+    #[test]
+    fn test_thin_book_sized_down_to_depth() {
+        let quotes = vec![
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 12, depth_at_yes: 0, ..Default::default() },
+        ];
+        let mut p = params(25, 10, 1.0);
+        assert!(detect_arb("TEST", "Test", &quotes, &p).is_empty(), "All-or-nothing by default");
+
+        // 10 contracts on the thinnest leg: gross 150c, fees 12 + 14 + 17 = 43c
+        p.min_partial_size = Some(5);
+        let opps = detect_arb("TEST", "Test", &quotes, &p);
+        assert_eq!(opps.len(), 1);
+        assert_eq!((opps[0].position_size, opps[0].net_profit_cents), (10, 107));
+        assert_arb_identity(&opps[0], 10);
+
+        p.min_partial_size = Some(11);
+        assert!(detect_arb("TEST", "Test", &quotes, &p).is_empty(), "Depth below the floor");
+    }
+
+    #[test]
+    fn test_canary_size_is_gated_again() {
+        let quotes = vec![
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+        ];
+        let p = params(5, 10, 1.0);
+        let opp = &detect_arb("TEST", "Test", &quotes, &p)[0];
+
+        // 1 contract: 15c gross, but every leg's fee rounds up to 2c, leaving 9c
+        let one = evaluate_at_size(opp, &quotes, &p, 1, Utc::now()).unwrap();
+        assert_eq!((one.opportunity.net_profit_cents, one.rejected), (9, Some("min_net_profit")));
+
+        // 2 contracts: 30c gross less 3 + 3 + 4 in fees clears the floor, under the same ID
+        let two = evaluate_at_size(opp, &quotes, &p, 2, Utc::now()).unwrap();
+        assert_eq!((two.opportunity.position_size, two.opportunity.net_profit_cents), (2, 20));
+        assert_eq!((two.opportunity.id.as_str(), two.rejected), (opp.id.as_str(), None));
+        assert_eq!(two.opportunity.uuid, opp.uuid);
+        assert_arb_identity(&two.opportunity, 2);
+    }
+
+    #[test]
+    fn test_sweep_prices_levels_at_their_volume_weighted_cost() {
+        // YES asks 30 x5 then 32 x10 (NO bids 70 and 68), plus a dust level at 29
+        let ob = Orderbook {
+            yes: vec![PriceLevel { price: 25, quantity: 8 }],
+            no: vec![
+                PriceLevel { price: 71, quantity: 1 },
+                PriceLevel { price: 70, quantity: 5 },
+                PriceLevel { price: 68, quantity: 10 },
+            ],
+        };
+        let q = quote_with_levels("A", "A", &ob, 2, 2).unwrap();
+        assert_eq!((q.yes_ask_cents, q.depth_at_no), (30, 5));
+        assert_eq!(q.ask_levels, vec![(30, 5), (32, 10)]);
+        assert_eq!(q.bid_levels, vec![(25, 8)]);
+        let best_only = quote_with_levels("A", "A", &ob, 2, 1).unwrap();
+        assert!(best_only.ask_levels.is_empty());
+
+        let quotes = |q: &BracketQuote| {
+            ["A", "B", "C"].map(|t| BracketQuote { ticker: t.into(), ..q.clone() }).to_vec()
+        };
+        let p = params(10, 10, 1.0);
+        assert!(detect_arb("E", "E", &quotes(&best_only), &p).is_empty(), "5 at the best level is too thin");
+
+        // Per leg 5 x 30 + 5 x 32 = 310c, fees 8 + 8; net 1000 - 930 - 48 = 22c
+        let opps = detect_arb("E", "E", &quotes(&q), &p);
+        assert_eq!(opps.len(), 1);
+        assert_eq!((opps[0].net_profit_cents, opps[0].total_fees_cents), (22, 48));
+        assert!(opps[0].brackets.iter().all(|b| b.yes_ask_cents == 32), "Limit at the worst level swept");
+    }
+
+    #[test]
+    fn test_sizing_takes_smallest_of_depth_bankroll_and_max() {
+        let quotes = vec![
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 40, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 40, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 40, depth_at_yes: 0, ..Default::default() },
+        ];
+        let mut p = params(5, 1, 1.0);
+        p.sizing = Some(SizingConfig { max_size: 30, bankroll_fraction: 0.1 });
+        let size = |p: &DetectParams| detect_arb("TEST", "Test", &quotes, p).first().map(|o| o.position_size);
+
+        // One contract per leg ties up 22 + 27 + 42 = 91c; 10% of $100 funds 10
+        p.balance_cents = Some(10_000);
+        assert_eq!(size(&p), Some(10));
+        p.balance_cents = Some(1_000_000);
+        assert_eq!(size(&p), Some(30), "Capped at max_size");
+        p.sizing = Some(SizingConfig { max_size: 100, bankroll_fraction: 0.1 });
+        assert_eq!(size(&p), Some(40), "Capped at depth");
+
+        // Without a balance nothing can be funded, even where one contract clears every gate
+        p.balance_cents = None;
+        let evals = evaluate_arb("TEST", "Test", &quotes, &p);
+        assert_eq!(evals[0].rejected, Some("bankroll"));
+    }
+
+    #[test]
+    fn test_repriced_net_on_fresh_quotes() {
+        let quotes = vec![
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+        ];
+        let opp = &detect_arb("TEST", "Test", &quotes, &params(5, 10, 1.0))[0];
+        // Repricing on the detection quotes gives back the detected net
+        assert_eq!(repriced_net_cents(opp, &opp.brackets, Contracts(5)), Some(53));
+
+        // C's ask moved up 3c: 15c less gross, fee(5,43) still 9
+        let mut fresh = quotes.clone();
+        fresh[2].yes_ask_cents = 43;
+        assert_eq!(repriced_net_cents(opp, &fresh, Contracts(5)), Some(38));
+
+        // A leg thinned below the size, or went missing
+        fresh[0].depth_at_no = 4;
+        assert_eq!(repriced_net_cents(opp, &fresh, Contracts(5)), None);
+        assert_eq!(repriced_net_cents(opp, &quotes[..2], Contracts(5)), None);
+    }
+
+    #[test]
+    fn test_required_capital_covers_premium_or_collateral_plus_fees() {
+        let quotes = vec![
+            BracketQuote { ticker: "A".into(), yes_ask_cents: 20, yes_bid_cents: 18, ..Default::default() },
+            BracketQuote { ticker: "B".into(), yes_ask_cents: 75, yes_bid_cents: 90, ..Default::default() },
+        ];
+        // 10 x (20 + 75) premium, fees 12 + 14
+        assert_eq!(required_capital_cents(&quotes, ArbDirection::Long, Contracts(10)), Some(Cents(976)));
+        // 10 x (82 + 10) collateral, fees 11 + 7
+        assert_eq!(required_capital_cents(&quotes, ArbDirection::Short, Contracts(10)), Some(Cents(938)));
+    }
+
+    #[test]
+    fn test_roi_tiers_long() {
+        let quotes = vec![
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+        ];
+        let mut p = params(5, 10, 1.0);
+        p.expected_slippage_cents = 1;
+        let opps = detect_arb("TEST", "Test", &quotes, &p);
+        assert_eq!(opps.len(), 1);
+        let opp = &opps[0];
+        // Gross: 75 on 425 cost. Net: 53 on 447. Slippage: 1c * 5 * 3 legs = 15 → 38 on 462.
+        assert_eq!(opp.expected_slippage_cents, 15);
+        assert_eq!(opp.gross_roi_pct, roi_pct(75, 425));
+        assert_eq!(opp.net_roi_pct, roi_pct(53, 447));
+        assert_eq!(opp.slippage_adj_roi_pct, roi_pct(38, 462));
+        assert!(opp.gross_roi_pct > opp.net_roi_pct);
+        assert!(opp.net_roi_pct > opp.slippage_adj_roi_pct);
+    }
+
+    #[test]
+    fn test_near_miss_reports_failed_gate() {
+        // Sum = 95c: positive edge, but fees eat it (see worked example)
+        let quotes = vec![
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 10, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "D".into(), title: "D".into(), yes_ask_cents: 20, yes_bid_cents: 0, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+        ];
+        let evals = evaluate_arb("TEST", "Test", &quotes, &params(5, 10, 1.0));
+        let long = evals.iter().find(|e| e.opportunity.direction == ArbDirection::Long).unwrap();
+        assert_eq!(long.rejected, Some("min_net_profit"));
+        assert!(long.is_near_miss());
+        // Short side: bids sum to 0, no edge at all → rejected but not a near miss
+        let short = evals.iter().find(|e| e.opportunity.direction == ArbDirection::Short).unwrap();
+        assert!(short.rejected.is_some());
+        assert!(!short.is_near_miss());
+    }
+
+    #[test]
+    fn test_sum_bounds_never_reject_a_passing_event() {
+        let p = params(5, 10, 1.0);
+        let bounds = SumBounds::new(4, &p);
+        // Asks summing to 85c pass LONG; the bound must admit them
+        let quotes: Vec<_> = [10, 25, 30, 20]
+            .iter()
+            .enumerate()
+            .map(|(i, &ask)| BracketQuote {
+                ticker: format!("T{}", i),
+                title: String::new(),
+                yes_ask_cents: ask,
+                yes_bid_cents: 0,
+                depth_at_no: 100,
+                depth_at_yes: 0,
+                ..Default::default()
+            })
+            .collect();
+        assert_eq!(detect_arb("E", "E", &quotes, &p).len(), 1);
+        assert!(bounds.max_long_sum >= 85);
+        assert!(!bounds.rules_out(85, 0, 0));
+        // LONG is out, but two unquoted brackets could still lift the bid sum enough for SHORT
+        assert!(!bounds.rules_out(101, 0, 2));
+        assert!(bounds.rules_out(101, 0, 0));
+        // SHORT: (sum - 100) * 5 >= 10 and >= 1% of the $5 liability
+        assert_eq!(bounds.min_short_sum, 102);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_sum_bounds_admit_every_detected_arb(
+            asks in proptest::collection::vec(1i64..100, 2..8),
+            bids in proptest::collection::vec(0i64..100, 2..8),
+        ) {
+            let n = asks.len().min(bids.len());
+            let quotes: Vec<_> = (0..n)
+                .map(|i| BracketQuote {
+                    ticker: format!("T{}", i),
+                    title: String::new(),
+                    yes_ask_cents: asks[i],
+                    yes_bid_cents: bids[i],
+                    depth_at_no: 100,
+                    depth_at_yes: 100,
+                    ..Default::default()
+                })
+                .collect();
+            let p = params(5, 10, 1.0);
+            if !detect_arb("E", "E", &quotes, &p).is_empty() {
+                let ask_sum: i64 = quotes.iter().map(|q| q.yes_ask_cents).sum();
+                let bid_sum: i64 = quotes.iter().map(|q| q.yes_bid_cents).sum();
+                prop_assert!(!SumBounds::new(n, &p).rules_out(ask_sum, bid_sum, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shadow_params_inherit_unset_fields() {
+        let risk = RiskConfig {
+            min_net_profit_cents: 10,
+            min_roi_pct: 1.0,
+            position_size: 5,
+            max_open_positions: 5,
+            expected_slippage_cents: 1,
+            min_level_quantity: 0,
+            sweep_levels: 1,
+            fill_wait_ms: 0,
+            execution_budget_ms: 3_000,
+            max_execution_ms: 0,
+            min_partial_size: Some(3),
+            requote_tolerance_cents: None,
+            min_annualized_roi_pct: None,
+            max_quote_age_ms: None,
+            cooldown_secs: 900,
+            adaptive_roi: None,
+            sizing: None,
+            halt_file: "STOP".into(),
+        };
+        let shadow = ShadowConfig {
+            position_size: Some(25),
+            min_net_profit_cents: None,
+            min_roi_pct: Some(0.25),
+            expected_slippage_cents: None,
+        };
+        let p = DetectParams::from_shadow(&risk, &shadow);
+        assert_eq!(p.position_size, 25);
+        assert_eq!(p.min_net_profit_cents, 10);
+        assert_eq!(p.min_roi_pct, 0.25);
+        assert_eq!(p.expected_slippage_cents, 1);
+        assert_eq!(p.min_partial_size, Some(3));
+    }
+
     #[test]
     fn test_quote_from_orderbook_unsorted() {
         let orderbook = Orderbook {
@@ -298,13 +1344,46 @@ mod tests {
                 PriceLevel { price: 20, quantity: 8 },
             ],
         };
-        let q = quote_from_orderbook("T", "Test", &orderbook).unwrap();
+        let q = quote_from_orderbook("T", "Test", &orderbook, 0).unwrap();
         // Best NO bid = 50 → yes_ask = 100 - 50 = 50
         assert_eq!(q.yes_ask_cents, 50);
         assert_eq!(q.depth_at_no, 20);
         // Best YES bid = 25
         assert_eq!(q.yes_bid_cents, 25);
         assert_eq!(q.depth_at_yes, 15);
+        // The NO side of the same book: buy NO at 100 - 25, sell NO into the 50 bid
+        assert_eq!((q.no_ask_cents, q.no_bid_cents), (75, 50));
+        assert_eq!(q.book_depth, 61);
+
+        // Spread 50 - 25 and 61 contracts against the [scanner] floors
+        let scanner =
+            |floors: &str| -> ScannerConfig { toml::from_str(&format!("interval_secs = 30\n{}", floors)).unwrap() };
+        assert_eq!(thin_bracket(&q, &scanner("")), None);
+        assert_eq!(thin_bracket(&q, &scanner("max_spread_cents = 20")), Some("spread"));
+        assert_eq!(thin_bracket(&q, &scanner("max_spread_cents = 25\nmin_book_depth = 80")), Some("book_depth"));
+        assert_eq!(thin_bracket(&q, &scanner("max_spread_cents = 25\nmin_book_depth = 61")), None);
+    }
+
+    #[test]
+    fn test_dust_filter_is_symmetric() {
+        // Same shape on both sides: 2 contracts of dust above a real level
+        let levels = vec![
+            PriceLevel { price: 45, quantity: 1 },
+            PriceLevel { price: 45, quantity: 1 },
+            PriceLevel { price: 40, quantity: 30 },
+        ];
+        let ob = Orderbook { no: levels.clone(), yes: levels };
+
+        let q = quote_from_orderbook("T", "Test", &ob, 0).unwrap();
+        assert_eq!((q.yes_ask_cents, q.depth_at_no), (55, 2));
+        assert_eq!((q.yes_bid_cents, q.depth_at_yes), (45, 2));
+
+        let q = quote_from_orderbook("T", "Test", &ob, 5).unwrap();
+        assert_eq!((q.yes_ask_cents, q.depth_at_no), (60, 30));
+        assert_eq!((q.yes_bid_cents, q.depth_at_yes), (40, 30));
+
+        // All NO levels are dust → no YES ask, same as an empty NO side
+        assert!(quote_from_orderbook("T", "Test", &ob, 50).is_none());
     }
 
     #[test]
@@ -314,14 +1393,14 @@ mod tests {
             no: vec![],
             yes: vec![PriceLevel { price: 30, quantity: 10 }],
         };
-        assert!(quote_from_orderbook("T", "Test", &ob1).is_none());
+        assert!(quote_from_orderbook("T", "Test", &ob1, 0).is_none());
 
         // Empty YES → Some with depth_at_yes: 0
         let ob2 = Orderbook {
             no: vec![PriceLevel { price: 60, quantity: 5 }],
             yes: vec![],
         };
-        let q = quote_from_orderbook("T", "Test", &ob2).unwrap();
+        let q = quote_from_orderbook("T", "Test", &ob2, 0).unwrap();
         assert_eq!(q.yes_ask_cents, 40);
         assert_eq!(q.yes_bid_cents, 0);
         assert_eq!(q.depth_at_no, 5);
@@ -332,18 +1411,18 @@ mod tests {
             no: vec![],
             yes: vec![],
         };
-        assert!(quote_from_orderbook("T", "Test", &ob3).is_none());
+        assert!(quote_from_orderbook("T", "Test", &ob3, 0).is_none());
     }
 
     #[test]
     fn test_gate_independence_long() {
         // depth_at_no sufficient, depth_at_yes = 0 → LONG fires, SHORT blocked
         let quotes = vec![
-            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 60, depth_at_no: 10, depth_at_yes: 0 },
-            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 60, depth_at_no: 10, depth_at_yes: 0 },
-            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 60, depth_at_no: 10, depth_at_yes: 0 },
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 60, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 60, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 60, depth_at_no: 10, depth_at_yes: 0, ..Default::default() },
         ];
-        let opps = detect_arb("TEST", "Test", &quotes, 5, 10, 1.0);
+        let opps = detect_arb("TEST", "Test", &quotes, &params(5, 10, 1.0));
         assert!(opps.iter().any(|o| o.direction == ArbDirection::Long), "LONG should fire");
         assert!(!opps.iter().any(|o| o.direction == ArbDirection::Short), "SHORT should be blocked by depth_at_yes=0");
         for opp in &opps {
@@ -357,11 +1436,11 @@ mod tests {
         // sum_yes_bids = 60+60+60 = 180. gross/contract = 180-100 = 80. gross = 400.
         // fees: fee(5,60)=9 * 3 = 27 (approx). net = 400-27 = 373.
         let quotes = vec![
-            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 60, depth_at_no: 0, depth_at_yes: 10 },
-            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 60, depth_at_no: 0, depth_at_yes: 10 },
-            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 60, depth_at_no: 0, depth_at_yes: 10 },
+            BracketQuote { ticker: "A".into(), title: "A".into(), yes_ask_cents: 20, yes_bid_cents: 60, depth_at_no: 0, depth_at_yes: 10, ..Default::default() },
+            BracketQuote { ticker: "B".into(), title: "B".into(), yes_ask_cents: 25, yes_bid_cents: 60, depth_at_no: 0, depth_at_yes: 10, ..Default::default() },
+            BracketQuote { ticker: "C".into(), title: "C".into(), yes_ask_cents: 40, yes_bid_cents: 60, depth_at_no: 0, depth_at_yes: 10, ..Default::default() },
         ];
-        let opps = detect_arb("TEST", "Test", &quotes, 5, 10, 1.0);
+        let opps = detect_arb("TEST", "Test", &quotes, &params(5, 10, 1.0));
         assert!(opps.iter().any(|o| o.direction == ArbDirection::Short), "SHORT should fire");
         assert!(!opps.iter().any(|o| o.direction == ArbDirection::Long), "LONG should be blocked by depth_at_no=0");
         for opp in &opps {
@@ -399,8 +1478,8 @@ mod tests {
                 yes: yes_shuffled,
             };
 
-            let q1 = quote_from_orderbook("T", "Test", &ob_original);
-            let q2 = quote_from_orderbook("T", "Test", &ob_shuffled);
+            let q1 = quote_from_orderbook("T", "Test", &ob_original, 0);
+            let q2 = quote_from_orderbook("T", "Test", &ob_shuffled, 0);
             prop_assert_eq!(q1, q2);
         }
     }
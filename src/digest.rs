@@ -0,0 +1,183 @@
+// Opt-in Telegram digest of detected opportunities. Every opportunity a cycle finds is
+// queued, and the queue goes out as one `opportunities` alert at most every
+// `min_interval_secs`, so a busy cycle sends one message rather than one per event.
+
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::alerts::{self, context};
+use crate::kalshi::types::{ArbDirection, ArbOpportunity};
+use crate::report::series_of;
+
+const KALSHI_MARKETS_URL: &str = "https://kalshi.com/markets";
+
+/// `[alerts.opportunities]` — present = a digest of detected opportunities is sent.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct OpportunityAlertsConfig {
+    /// Least time between digests; opportunities found in between wait for the next one.
+    #[serde(default = "default_min_interval_secs")]
+    pub min_interval_secs: u64,
+    /// Opportunities listed in full per digest (best net first); the rest are counted.
+    #[serde(default = "default_max_listed")]
+    pub max_listed: usize,
+}
+
+fn default_min_interval_secs() -> u64 { 300 }
+fn default_max_listed() -> usize { 10 }
+
+/// One leg as listed in the digest.
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestLeg {
+    pub name: String,
+    /// The price the leg would trade at: the ask for LONG, the bid for SHORT.
+    pub price_cents: i64,
+    /// Contracts at that price.
+    pub depth: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestEntry {
+    pub event: String,
+    pub title: String,
+    pub direction: String,
+    pub size: u32,
+    pub net_profit_cents: i64,
+    pub net_roi: String,
+    pub legs: Vec<DigestLeg>,
+    pub url: String,
+}
+
+impl DigestEntry {
+    fn new(opp: &ArbOpportunity) -> Self {
+        let legs = opp
+            .brackets
+            .iter()
+            .map(|b| {
+                let (price_cents, depth) = match opp.direction {
+                    ArbDirection::Long => (b.yes_ask_cents, b.depth_at_no),
+                    ArbDirection::Short => (b.yes_bid_cents, b.depth_at_yes),
+                };
+                DigestLeg { name: opp.bracket_name(&b.ticker), price_cents, depth }
+            })
+            .collect();
+        Self {
+            event: opp.event_ticker.clone(),
+            title: opp.event_title.clone(),
+            direction: opp.direction.to_string(),
+            size: opp.position_size,
+            net_profit_cents: opp.net_profit_cents,
+            net_roi: format!("{:.1}%", opp.net_roi_pct),
+            legs,
+            url: event_url(&opp.event_ticker),
+        }
+    }
+}
+
+/// The event's page on kalshi.com.
+pub fn event_url(event_ticker: &str) -> String {
+    format!(
+        "{}/{}/{}",
+        KALSHI_MARKETS_URL,
+        series_of(event_ticker).to_lowercase(),
+        event_ticker.to_lowercase()
+    )
+}
+
+/// Opportunities waiting for the next digest.
+#[derive(Debug)]
+pub struct OpportunityDigest {
+    config: OpportunityAlertsConfig,
+    pending: Vec<DigestEntry>,
+    last_sent: Option<DateTime<Utc>>,
+}
+
+impl OpportunityDigest {
+    pub fn new(config: OpportunityAlertsConfig) -> Self {
+        Self { config, pending: Vec::new(), last_sent: None }
+    }
+
+    /// Queue an opportunity. One still waiting for the same event and direction is
+    /// replaced, so a digest lists each arb once, as last seen.
+    pub fn push(&mut self, opp: &ArbOpportunity) {
+        let entry = DigestEntry::new(opp);
+        self.pending.retain(|e| e.event != entry.event || e.direction != entry.direction);
+        self.pending.push(entry);
+    }
+
+    /// The digest message, if anything is queued and the last one went out long enough ago.
+    pub fn take(&mut self, now: DateTime<Utc>) -> Option<String> {
+        let interval = Duration::seconds(self.config.min_interval_secs as i64);
+        if self.pending.is_empty() || self.last_sent.is_some_and(|t| now - t < interval) {
+            return None;
+        }
+        let mut entries = std::mem::take(&mut self.pending);
+        entries.sort_by_key(|e| std::cmp::Reverse(e.net_profit_cents));
+        let count = entries.len();
+        let more = count.saturating_sub(self.config.max_listed);
+        entries.truncate(self.config.max_listed);
+        self.last_sent = Some(now);
+        Some(alerts::render("opportunities", context! { count, opportunities => entries, more }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kalshi::types::BracketQuote;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn opp(event: &str, net: i64) -> ArbOpportunity {
+        let quote = |ticker: &str, label: &str| BracketQuote {
+            ticker: ticker.into(),
+            title: ticker.into(),
+            label: Some(label.into()),
+            yes_ask_cents: 45,
+            yes_bid_cents: 43,
+            depth_at_no: 30,
+            depth_at_yes: 12,
+            ..Default::default()
+        };
+        ArbOpportunity {
+            id: format!("{}-LONG-1", event),
+            uuid: Uuid::nil(),
+            event_ticker: event.into(),
+            event_title: "NYC high".into(),
+            direction: ArbDirection::Long,
+            brackets: vec![quote(&format!("{}-B1", event), "60 or below"), quote(&format!("{}-B2", event), "61+")],
+            position_size: 10,
+            sum_cents: 90,
+            total_fees_cents: 34,
+            gross_profit_cents: 100,
+            net_profit_cents: net,
+            expected_slippage_cents: 0,
+            gross_roi_pct: Decimal::ZERO,
+            net_roi_pct: Decimal::new(73, 1),
+            slippage_adj_roi_pct: Decimal::ZERO,
+            settles_at: None,
+        }
+    }
+
+    #[test]
+    fn test_batches_and_rate_limits() {
+        let now = Utc::now();
+        let mut digest = OpportunityDigest::new(OpportunityAlertsConfig { min_interval_secs: 300, max_listed: 1 });
+        assert_eq!(digest.take(now), None);
+
+        digest.push(&opp("KXHIGHNY-26OCT17", 66));
+        digest.push(&opp("KXHIGHNY-26OCT17", 40));
+        digest.push(&opp("KXHIGHMIA-26OCT17", 50));
+        assert_eq!(
+            digest.take(now).unwrap(),
+            "*OPPORTUNITIES* 2 found\n\n`KXHIGHMIA-26OCT17` LONG x10 NYC high\nNet $0.50 (7.3%)\n\
+             KXHIGHMIA-26OCT17-B1 (60 or below) 45c x30\nKXHIGHMIA-26OCT17-B2 (61+) 45c x30\n\
+             https://kalshi.com/markets/kxhighmia/kxhighmia-26oct17\n\n...and 1 more"
+        );
+
+        // Held until the interval has passed since the last digest
+        digest.push(&opp("KXHIGHNY-26OCT17", 66));
+        assert_eq!(digest.take(now + Duration::seconds(299)), None);
+        assert!(digest.take(now + Duration::seconds(300)).unwrap().starts_with("*OPPORTUNITIES* 1 found"));
+    }
+}
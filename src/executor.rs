@@ -1,12 +1,26 @@
-use anyhow::Result;
-use tracing::{error, info, warn};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use tokio::time::{sleep, timeout_at, Duration, Instant};
+use tracing::{debug, error, info, warn, Instrument};
+use uuid::Uuid;
 
+use crate::detector::quote_from_orderbook;
+use crate::fees::taker_fee_cents;
 use crate::kalshi::client::KalshiClient;
+use crate::kalshi::error::{ApiErrorKind, ErrorAction, KalshiError};
 use crate::kalshi::types::*;
+use crate::money::{Cents, Contracts};
+use crate::price;
+use crate::routing::{self, OrderRouter, TimeInForce};
 use crate::storage;
 
+/// Spacing between checks on resting legs in `await_fills`.
+const FILL_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 /// Classify an order into its execution bucket.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub event_ticker: String,
     pub direction: ArbDirection,
@@ -14,6 +28,18 @@ pub struct ExecutionResult {
     pub resting: Vec<(String, Order)>,
     pub other: Vec<(String, Order)>,
     pub api_failures: Vec<String>,
+    /// Why each of `api_failures` failed, when the exchange said (a `KalshiApiError`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failure_kinds: BTreeMap<String, ApiErrorKind>,
+    /// Placement ran past `[risk] max_execution_ms`: nothing is waited on any longer, and
+    /// whatever still rests is cancelled.
+    #[serde(default)]
+    pub aborted: bool,
+    /// Orders placed to complete or close a mixed execution once its resting orders were
+    /// cancelled (`unwind_mixed`). Immediate-or-cancel, so none rests; not in `orders()`,
+    /// which are the arb's own legs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unwind: Vec<(String, Order)>,
 }
 
 impl ExecutionResult {
@@ -29,14 +55,285 @@ impl ExecutionResult {
     pub fn is_total_failure(&self) -> bool {
         self.filled.is_empty() && self.resting.is_empty() && self.other.is_empty()
     }
+
+    /// Every order the exchange acknowledged, across all buckets.
+    pub fn orders(&self) -> impl Iterator<Item = &(String, Order)> {
+        self.filled.iter().chain(&self.resting).chain(&self.other)
+    }
+
+    /// Every order that filled at least one contract, unwind orders included: what the
+    /// execution's P&L and reconciliation are worked out from.
+    pub fn fills(&self) -> Vec<(String, Order)> {
+        self.orders().chain(&self.unwind).filter(|(_, o)| o.filled_count() > 0).cloned().collect()
+    }
+
+    /// Economics of the filled legs against the quotes they were placed at. None when a fill
+    /// count is negative or an amount overflows.
+    pub fn receipt(&self, opp: &ArbOpportunity) -> Option<Receipt> {
+        let legs: Vec<ReceiptLeg> = self
+            .filled
+            .iter()
+            .map(|(ticker, order)| {
+                let expected_cents = opp
+                    .brackets
+                    .iter()
+                    .find(|b| b.ticker == *ticker)
+                    .map(|b| match opp.direction {
+                        ArbDirection::Long => b.yes_ask_cents,
+                        ArbDirection::Short => b.yes_bid_cents,
+                    })
+                    .unwrap_or(0);
+                ReceiptLeg {
+                    ticker: ticker.clone(),
+                    label: opp.label(ticker).map(str::to_string),
+                    count: order.filled_count(),
+                    expected_cents,
+                    price_cents: order.yes_price.unwrap_or(0),
+                }
+            })
+            .collect();
+        let (mut total, mut fees, mut collateral) = (Cents::ZERO, Cents::ZERO, Cents::ZERO);
+        for leg in &legs {
+            let count = Contracts::from_i64(leg.count)?;
+            total = total.checked_add(Cents(leg.price_cents).checked_mul(count)?)?;
+            fees = fees.checked_add(Cents(taker_fee_cents(&leg.ticker, count.get(), leg.price_cents)))?;
+            collateral = collateral.checked_add(Cents(price::complement(leg.price_cents)).checked_mul(count)?)?;
+        }
+        // Long: the premium paid. Short: collateral of 100 per contract on each leg,
+        // less the premium received.
+        let capital = match opp.direction {
+            ArbDirection::Long => total.checked_add(fees)?,
+            ArbDirection::Short => collateral.checked_add(fees)?,
+        };
+        Some(Receipt {
+            legs,
+            total_cents: total.0,
+            fees_cents: fees.0,
+            net_cents: storage::realized_net_cents(opp, &self.filled)?,
+            capital_cents: capital.0,
+        })
+    }
+}
+
+/// One filled leg on an execution receipt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReceiptLeg {
+    pub ticker: String,
+    pub label: Option<String>,
+    pub count: i64,
+    pub expected_cents: i64,
+    pub price_cents: i64,
 }
 
-/// Build a CreateOrderRequest from a bracket quote and arb direction.
+/// What a fully filled arb cost and locked in, for the `execution_receipt` alert.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Receipt {
+    pub legs: Vec<ReceiptLeg>,
+    /// Premium paid (long) or received (short) across the legs.
+    pub total_cents: i64,
+    pub fees_cents: i64,
+    /// Profit locked in at the fill prices, after fees.
+    pub net_cents: i64,
+    /// Cash held until settlement.
+    pub capital_cents: i64,
+}
+
+/// An order that completes or closes one leg of a mixed execution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnwindLeg {
+    pub ticker: String,
+    pub count: Contracts,
+    pub price_cents: i64,
+}
+
+/// How to resolve a mixed execution once its resting orders are cancelled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unwind {
+    /// The arb still pays at current prices: take the missing contracts at the market.
+    Reprice(Vec<UnwindLeg>),
+    /// The edge is gone: close the filled contracts at the market. Filled brackets with no
+    /// quote to close into are `unquoted`, left open for the exits job.
+    SellBack { legs: Vec<UnwindLeg>, unquoted: Vec<String> },
+}
+
+impl Unwind {
+    pub fn describe(&self) -> String {
+        match self {
+            Unwind::Reprice(legs) => format!("repriced {} legs to the market", legs.len()),
+            Unwind::SellBack { legs, unquoted } if unquoted.is_empty() => format!("closed {} filled legs", legs.len()),
+            Unwind::SellBack { legs, unquoted } => {
+                format!("closed {} filled legs, {} left open with no quote", legs.len(), unquoted.len())
+            }
+        }
+    }
+
+    /// Filled brackets the plan couldn't close.
+    pub fn unquoted(&self) -> &[String] {
+        match self {
+            Unwind::Reprice(_) => &[],
+            Unwind::SellBack { unquoted, .. } => unquoted,
+        }
+    }
+}
+
+/// Choose between completing and closing a mixed execution at current `quotes`.
+/// Completing wins when the arb, at the prices already filled plus the current market for
+/// the missing contracts, still nets at least zero after fees; a missing leg that can't
+/// be quoted rules it out, as does a fill count or amount that doesn't fit. Closing
+/// leaves out, as unquoted, any filled bracket without a valid price to close at.
+pub fn plan_unwind(opp: &ArbOpportunity, result: &ExecutionResult, quotes: &[BracketQuote]) -> Unwind {
+    let size = opp.position_size as i64;
+    let filled_on =
+        |ticker: &str| -> i64 { result.orders().filter(|(t, _)| t == ticker).map(|(_, o)| o.filled_count()).sum() };
+    let quote = |ticker: &str| quotes.iter().find(|q| q.ticker == ticker);
+    // Completing takes the same side as the arb (LONG buys at the ask); closing takes the other
+    let complete_price = |q: &BracketQuote| match opp.direction {
+        ArbDirection::Long => q.yes_ask_cents,
+        ArbDirection::Short => q.yes_bid_cents,
+    };
+    let close_price = |q: &BracketQuote| match opp.direction {
+        ArbDirection::Long => q.yes_bid_cents,
+        ArbDirection::Short => q.yes_ask_cents,
+    };
+
+    // Net of the completed arb and the orders completing it
+    let complete = || -> Option<(Cents, Vec<UnwindLeg>)> {
+        let (mut total, mut fees) = (Cents::ZERO, Cents::ZERO);
+        let mut add = |ticker: &str, count: Contracts, price_cents: i64| -> Option<()> {
+            total = total.checked_add(Cents(price_cents).checked_mul(count)?)?;
+            fees = fees.checked_add(Cents(taker_fee_cents(ticker, count.get(), price_cents)))?;
+            Some(())
+        };
+        for (ticker, order) in result.orders() {
+            add(ticker, Contracts::from_i64(order.filled_count())?, order.yes_price.unwrap_or(0))?;
+        }
+        let mut missing = Vec::new();
+        for bracket in &opp.brackets {
+            let count = size - filled_on(&bracket.ticker);
+            if count <= 0 {
+                continue;
+            }
+            let count = Contracts::from_i64(count)?;
+            let price_cents = quote(&bracket.ticker).map(complete_price).filter(|&p| price::is_valid(p))?;
+            add(&bracket.ticker, count, price_cents)?;
+            missing.push(UnwindLeg { ticker: bracket.ticker.clone(), count, price_cents });
+        }
+        let payout = Cents(price::PAYOUT_CENTS).checked_mul(Contracts(opp.position_size))?;
+        let net = match opp.direction {
+            ArbDirection::Long => payout.checked_sub(total)?.checked_sub(fees)?,
+            ArbDirection::Short => total.checked_sub(payout)?.checked_sub(fees)?,
+        };
+        Some((net, missing))
+    };
+    if let Some((net, missing)) = complete().filter(|(net, _)| *net >= Cents::ZERO) {
+        debug!(event = %opp.event_ticker, net = %net, "Completing the arb still pays");
+        return Unwind::Reprice(missing);
+    }
+
+    let (mut legs, mut unquoted) = (Vec::new(), Vec::new());
+    for bracket in &opp.brackets {
+        let Some(count) = Contracts::from_i64(filled_on(&bracket.ticker)).filter(|&n| n > Contracts::ZERO) else {
+            continue;
+        };
+        match quote(&bracket.ticker).map(close_price).filter(|&p| price::is_valid(p)) {
+            Some(price_cents) => legs.push(UnwindLeg { ticker: bracket.ticker.clone(), count, price_cents }),
+            None => unquoted.push(bracket.ticker.clone()),
+        }
+    }
+    Unwind::SellBack { legs, unquoted }
+}
+
+/// Current quotes for an opportunity's brackets; brackets whose book can't be fetched
+/// or quoted are left out. Unwind orders are immediate-or-cancel, so the top level is
+/// used however small: they take what is there.
+pub async fn current_quotes(client: &KalshiClient, opp: &ArbOpportunity) -> Vec<BracketQuote> {
+    let mut quotes = Vec::new();
+    for bracket in &opp.brackets {
+        match client.get_orderbook(&bracket.ticker).await {
+            Ok(book) => quotes.extend(quote_from_orderbook(&bracket.ticker, &bracket.title, &book, 0)),
+            Err(e) => warn!(ticker = %bracket.ticker, error = %e, "Failed to fetch orderbook for unwind"),
+        }
+    }
+    quotes
+}
+
+/// Place an unwind plan's orders immediate-or-cancel, so none of them is left resting.
+/// Returns the orders the exchange accepted.
+pub async fn execute_unwind(client: &KalshiClient, opp: &ArbOpportunity, plan: &Unwind) -> Vec<(String, Order)> {
+    let (legs, action) = match (plan, opp.direction) {
+        (Unwind::Reprice(legs), ArbDirection::Long) | (Unwind::SellBack { legs, .. }, ArbDirection::Short) => {
+            (legs, "buy")
+        }
+        (Unwind::Reprice(legs), ArbDirection::Short) | (Unwind::SellBack { legs, .. }, ArbDirection::Long) => {
+            (legs, "sell")
+        }
+    };
+    let mut placed = Vec::new();
+    for leg in legs {
+        let client_order_id = unwind_client_order_id(&opp.uuid, &leg.ticker);
+        let req = CreateOrderRequest {
+            ticker: leg.ticker.clone(),
+            action: action.to_string(),
+            side: "yes".to_string(),
+            order_type: "limit".to_string(),
+            count: leg.count.get(),
+            yes_price: Some(leg.price_cents),
+            no_price: None,
+            time_in_force: Some("immediate_or_cancel".to_string()),
+            expiration_ts: None,
+            client_order_id: Some(client_order_id.clone()),
+        };
+        match place_or_find(client, &req.ticker.clone(), &client_order_id, client.create_order(&req)).await {
+            Ok(order) => {
+                info!(
+                    ticker = %leg.ticker,
+                    action,
+                    count = leg.count.get(),
+                    price = leg.price_cents,
+                    filled = order.filled_count(),
+                    "Unwind order placed"
+                );
+                placed.push((leg.ticker.clone(), order));
+            }
+            Err(e) => error!(ticker = %leg.ticker, action, error = %e, "Unwind order failed"),
+        }
+    }
+    placed
+}
+
+/// Resolve a mixed execution whose resting orders have all been cancelled: refresh them
+/// (they may have part-filled before the cancel landed), then complete or close the arb at
+/// the market. Returns the execution with its final orders and the unwind orders placed,
+/// and the plan followed.
+pub async fn unwind_mixed(
+    client: &KalshiClient,
+    opp: &ArbOpportunity,
+    result: &ExecutionResult,
+) -> (ExecutionResult, Unwind) {
+    let mut settled = result.clone();
+    for (_, order) in settled.resting.iter_mut().chain(settled.other.iter_mut()) {
+        match client.get_order(&order.order_id).await {
+            Ok(latest) => *order = latest,
+            Err(e) => warn!(order_id = %order.order_id, error = %e, "Failed to refresh cancelled order"),
+        }
+    }
+    let quotes = current_quotes(client, opp).await;
+    let plan = plan_unwind(opp, &settled, &quotes);
+    settled.unwind = execute_unwind(client, opp, &plan).await;
+    (settled, plan)
+}
+
+/// Build a CreateOrderRequest from a bracket quote and arb direction: the YES-side limit
+/// order at the quoted price that every `routing` policy starts from, resting per
+/// `time_in_force` from `now`.
 pub fn build_order_request(
     bracket: &BracketQuote,
     direction: ArbDirection,
     position_size: u32,
+    time_in_force: TimeInForce,
+    now: DateTime<Utc>,
 ) -> CreateOrderRequest {
+    let (time_in_force, expiration_ts) = time_in_force.order_fields(now);
     match direction {
         ArbDirection::Long => CreateOrderRequest {
             ticker: bracket.ticker.clone(),
@@ -46,6 +343,9 @@ pub fn build_order_request(
             count: position_size,
             yes_price: Some(bracket.yes_ask_cents),
             no_price: None,
+            time_in_force: time_in_force.clone(),
+            expiration_ts,
+            client_order_id: None,
         },
         ArbDirection::Short => CreateOrderRequest {
             ticker: bracket.ticker.clone(),
@@ -55,18 +355,204 @@ pub fn build_order_request(
             count: position_size,
             yes_price: Some(bracket.yes_bid_cents),
             no_price: None,
+            time_in_force,
+            expiration_ts,
+            client_order_id: None,
         },
     }
 }
 
+/// Something the account already has on an event that a leg would run into.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+    /// The leg would cross one of our own resting orders.
+    SelfTrade { ticker: String, order_id: String },
+    /// The bracket is already held on the side the leg would add to.
+    Held { ticker: String, position: i64 },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Conflict::SelfTrade { ticker, order_id } => {
+                write!(f, "{} would cross our resting order {}", ticker, order_id)
+            }
+            Conflict::Held { ticker, position } => write!(f, "{} already held ({:+})", ticker, position),
+        }
+    }
+}
+
+/// Why an arb, or one leg of it, didn't go out. A leg's failure doesn't fail the arb: it
+/// lands in `ExecutionResult::api_failures` with the rest still placed.
+#[derive(Debug, thiserror::Error)]
+pub enum ExecutorError {
+    #[error(transparent)]
+    Kalshi(#[from] KalshiError),
+    /// Positions or resting orders on the event couldn't be fetched, so conflicts couldn't
+    /// be ruled out.
+    #[error("checking {what} on the event")]
+    Preflight {
+        what: &'static str,
+        #[source]
+        source: KalshiError,
+    },
+    /// A leg would run into what the account already has on the event (see `conflicts`).
+    #[error("Skipping {event} {direction}: {}", joined(.conflicts))]
+    Conflict { event: String, direction: ArbDirection, conflicts: Vec<Conflict> },
+    /// A routed limit price off the tick grid; no leg went out.
+    #[error("{0}")]
+    InvalidPrice(String),
+    /// A leg whose reply didn't come back in time, and that isn't on the exchange either.
+    #[error("order {0} not on the exchange")]
+    NotOnExchange(String),
+}
+
+impl ExecutorError {
+    /// The exchange's classification, when a refused reply is behind the failure.
+    pub fn kind(&self) -> Option<ApiErrorKind> {
+        match self {
+            ExecutorError::Kalshi(e) | ExecutorError::Preflight { source: e, .. } => e.kind(),
+            _ => None,
+        }
+    }
+}
+
+fn joined(conflicts: &[Conflict]) -> String {
+    conflicts.iter().map(Conflict::to_string).collect::<Vec<_>>().join("; ")
+}
+
+/// Which way an order trades YES, and at what YES price: buying NO sells YES at the
+/// complement.
+fn yes_view(action: &str, side: &str, yes_price: Option<i64>, no_price: Option<i64>) -> Option<(bool, i64)> {
+    let buy = action == "buy";
+    match side {
+        "yes" => Some((buy, yes_price?)),
+        "no" => Some((!buy, price::complement(no_price?))),
+        _ => None,
+    }
+}
+
+/// Legs of `requests` that would trade against our own `resting` orders or add to a
+/// position already held in `positions`. A position on the other side is fine: the leg
+/// nets it down.
+pub fn conflicts(requests: &[CreateOrderRequest], positions: &[MarketPosition], resting: &[Order]) -> Vec<Conflict> {
+    let mut found = Vec::new();
+    for req in requests {
+        let Some((buy, limit)) = yes_view(&req.action, &req.side, req.yes_price, req.no_price) else {
+            continue;
+        };
+        for order in resting.iter().filter(|o| o.ticker == req.ticker) {
+            let crosses = match yes_view(&order.action, &order.side, order.yes_price, order.no_price) {
+                Some((resting_buy, price)) if resting_buy != buy => if buy { price <= limit } else { price >= limit },
+                _ => false,
+            };
+            if crosses {
+                found.push(Conflict::SelfTrade { ticker: req.ticker.clone(), order_id: order.order_id.clone() });
+            }
+        }
+        let held = positions.iter().find(|p| p.ticker == req.ticker).map_or(0, |p| p.position);
+        if (buy && held > 0) || (!buy && held < 0) {
+            found.push(Conflict::Held { ticker: req.ticker.clone(), position: held });
+        }
+    }
+    found
+}
+
 /// Execute a Dutch book arb by placing orders on all brackets concurrently.
 /// Returns an ExecutionResult classifying each order by status.
 /// Does NOT cancel resting orders — caller decides cancel policy.
+/// Refuses the arb when a leg would cross one of our resting orders on the event or add
+/// to a bracket already held (see `conflicts`), or when those can't be fetched.
+/// Legs not acknowledged within `budget` are looked up by client order ID instead of
+/// awaited (see `place_orders`); past `max_execution` the result comes back `aborted`.
 pub async fn execute_arb(
     client: &KalshiClient,
     opp: &ArbOpportunity,
     position_size: u32,
-) -> Result<ExecutionResult> {
+    budget: Duration,
+    max_execution: Option<Duration>,
+) -> Result<ExecutionResult, ExecutorError> {
+    let router = routing::router_for(&opp.event_ticker);
+    let requests = leg_requests(opp, position_size, router.as_ref(), routing::time_in_force(), Utc::now());
+    let (positions, resting) =
+        tokio::join!(client.get_event_positions(&opp.event_ticker), client.get_event_resting_orders(&opp.event_ticker));
+    let positions = positions.map_err(|source| ExecutorError::Preflight { what: "positions", source })?;
+    let resting = resting.map_err(|source| ExecutorError::Preflight { what: "resting orders", source })?;
+    let found = conflicts(&requests, &positions, &resting);
+    if !found.is_empty() {
+        return Err(ExecutorError::Conflict {
+            event: opp.event_ticker.clone(),
+            direction: opp.direction,
+            conflicts: found,
+        });
+    }
+    place_orders(client, opp, position_size, requests, budget, max_execution, true).await
+}
+
+/// Place the same order set as `execute_arb` without writing to the trade log
+/// (used for the demo mirror, whose orders aren't part of the book of record).
+pub async fn execute_arb_unlogged(
+    client: &KalshiClient,
+    opp: &ArbOpportunity,
+    position_size: u32,
+    budget: Duration,
+) -> Result<ExecutionResult, ExecutorError> {
+    let router = routing::router_for(&opp.event_ticker);
+    let requests = leg_requests(opp, position_size, router.as_ref(), routing::time_in_force(), Utc::now());
+    place_orders(client, opp, position_size, requests, budget, None, false).await
+}
+
+/// `execute_arb_unlogged` with an explicit router in place of the configured one
+/// (for comparing routing policies in `replay-exec`).
+pub async fn execute_arb_routed(
+    client: &KalshiClient,
+    opp: &ArbOpportunity,
+    position_size: u32,
+    budget: Duration,
+    router: &dyn OrderRouter,
+) -> Result<ExecutionResult, ExecutorError> {
+    let requests = leg_requests(opp, position_size, router, routing::time_in_force(), Utc::now());
+    place_orders(client, opp, position_size, requests, budget, None, false).await
+}
+
+/// Client order ID of `opportunity`'s leg in `ticker`: a UUID derived from the opportunity's,
+/// so a leg can be looked up again after a timeout and its opportunity found again from the
+/// execution store after a restart.
+pub fn leg_client_order_id(opportunity: &Uuid, ticker: &str) -> String {
+    Uuid::new_v5(opportunity, ticker.as_bytes()).to_string()
+}
+
+/// Client order ID of the unwind order `execute_unwind` sends in `ticker`.
+pub fn unwind_client_order_id(opportunity: &Uuid, ticker: &str) -> String {
+    Uuid::new_v5(opportunity, format!("unwind-{}", ticker).as_bytes()).to_string()
+}
+
+/// One order per bracket as `router` sends it, each under its `leg_client_order_id`.
+fn leg_requests(
+    opp: &ArbOpportunity,
+    position_size: u32,
+    router: &dyn OrderRouter,
+    time_in_force: TimeInForce,
+    now: DateTime<Utc>,
+) -> Vec<CreateOrderRequest> {
+    opp.brackets
+        .iter()
+        .map(|b| CreateOrderRequest {
+            client_order_id: Some(leg_client_order_id(&opp.uuid, &b.ticker)),
+            ..router.route(b, opp.direction, position_size, time_in_force, now)
+        })
+        .collect()
+}
+
+async fn place_orders(
+    client: &KalshiClient,
+    opp: &ArbOpportunity,
+    position_size: u32,
+    requests: Vec<CreateOrderRequest>,
+    budget: Duration,
+    max_execution: Option<Duration>,
+    log_trades: bool,
+) -> Result<ExecutionResult, ExecutorError> {
     info!(
         event = %opp.event_ticker,
         direction = %opp.direction,
@@ -75,39 +561,87 @@ pub async fn execute_arb(
         "Executing arb"
     );
 
+    // Every limit must be a legal tick before any leg goes out
+    for req in &requests {
+        price::validate(&req.ticker, req.yes_price.or(req.no_price).unwrap_or(0))
+            .map_err(|e| ExecutorError::InvalidPrice(e.to_string()))?;
+    }
+
     let mut handles = Vec::new();
 
-    for bracket in &opp.brackets {
-        let req = build_order_request(bracket, opp.direction, position_size);
+    for (bracket, req) in opp.brackets.iter().zip(requests) {
+        debug!(
+            ticker = %bracket.ticker,
+            bracket = %bracket.title,
+            action = %req.action,
+            side = %req.side,
+            yes_price = ?req.yes_price,
+            no_price = ?req.no_price,
+            "Placing leg"
+        );
 
         let ticker = bracket.ticker.clone();
+        let client_order_id = req.client_order_id.clone().unwrap_or_default();
         let client = client.clone();
-        handles.push(tokio::spawn(async move {
-            let result = client.create_order(&req).await;
-            (ticker, result)
-        }));
+        let handle = tokio::spawn(
+            async move {
+                let result = place_leg(&client, &ticker, &req).await;
+                (ticker, result)
+            }
+            .in_current_span(),
+        );
+        handles.push((bracket.ticker.clone(), client_order_id, handle));
     }
 
     let mut filled = Vec::new();
     let mut resting = Vec::new();
     let mut other = Vec::new();
     let mut api_failures = Vec::new();
+    let mut failure_kinds = BTreeMap::new();
 
-    for handle in handles {
-        match handle.await {
+    // Past the budget, a slow reply is worth less than a hedge decision: stop waiting
+    // and ask the exchange what became of the leg
+    let started = Instant::now();
+    let deadline = started + max_execution.map_or(budget, |max| max.min(budget));
+    for (ticker, client_order_id, mut handle) in handles {
+        let joined = match timeout_at(deadline, &mut handle).await {
+            Ok(joined) => joined,
+            Err(_) => {
+                handle.abort();
+                warn!(ticker = %ticker, client_order_id = %client_order_id, "Leg unacknowledged within execution budget");
+                Ok((ticker.clone(), find_order(client, &ticker, &client_order_id).await))
+            }
+        };
+        match joined {
             Ok((ticker, result)) => match result {
                 Ok(order) => {
                     info!(ticker = %ticker, order_id = %order.order_id, status = %order.status, "Order placed");
-                    storage::log_trade(opp, &ticker, &order, position_size)
-                        .unwrap_or_else(|e| warn!("Failed to log trade: {}", e));
+                    if log_trades {
+                        storage::log_trade(opp, &ticker, &order, position_size)
+                            .unwrap_or_else(|e| warn!("Failed to log trade: {}", e));
+                    }
                     match order.status.as_str() {
-                        "executed" => filled.push((ticker, order)),
+                        "executed" => {
+                            #[cfg(feature = "chaos")]
+                            if crate::chaos::duplicate_fill(&ticker) {
+                                filled.push((ticker.clone(), order.clone()));
+                            }
+                            filled.push((ticker, order));
+                        }
                         "resting" => resting.push((ticker, order)),
                         _ => other.push((ticker, order)),
                     }
                 }
                 Err(e) => {
-                    error!(ticker = %ticker, error = %e, "Order failed");
+                    let kind = e.kind();
+                    if kind.map(ApiErrorKind::action) == Some(ErrorAction::Reprice) {
+                        warn!(ticker = %ticker, error = %e, "Order refused on price, leaving it to a fresh quote");
+                    } else {
+                        error!(ticker = %ticker, kind = ?kind, error = %e, "Order failed");
+                    }
+                    if let Some(kind) = kind {
+                        failure_kinds.insert(ticker.clone(), kind);
+                    }
                     api_failures.push(ticker);
                 }
             },
@@ -124,9 +658,93 @@ pub async fn execute_arb(
         resting,
         other,
         api_failures,
+        failure_kinds,
+        aborted: max_execution.is_some_and(|max| started.elapsed() >= max),
+        unwind: Vec::new(),
     })
 }
 
+/// Place one leg. A failed placement is looked up on the exchange first (`place_or_find`);
+/// one that failed for a retryable reason and isn't there is sent once more.
+async fn place_leg(client: &KalshiClient, ticker: &str, req: &CreateOrderRequest) -> Result<Order, ExecutorError> {
+    let Some(id) = req.client_order_id.as_deref() else {
+        return Ok(client.create_order(req).await?);
+    };
+    match place_or_find(client, ticker, id, client.create_order(req)).await {
+        Err(e) if e.kind().map(ApiErrorKind::action) == Some(ErrorAction::Retry) => {
+            warn!(ticker, client_order_id = id, error = %e, "Order not on the exchange, sending it again");
+            place_or_find(client, ticker, id, client.resend_order(req)).await
+        }
+        result => result,
+    }
+}
+
+/// Await a placement; if it errors, look the order up by its client order ID before giving
+/// up on it. A 5xx or a dropped connection doesn't mean the exchange didn't take the order,
+/// and counting a leg that did land as failed would have the unwind order it again.
+async fn place_or_find(
+    client: &KalshiClient,
+    ticker: &str,
+    client_order_id: &str,
+    placement: impl std::future::Future<Output = Result<Order, KalshiError>>,
+) -> Result<Order, ExecutorError> {
+    match placement.await {
+        Ok(order) => Ok(order),
+        Err(e) => {
+            warn!(ticker, client_order_id, error = %e, "Order failed, checking whether it reached the exchange");
+            match find_order(client, ticker, client_order_id).await {
+                Ok(order) => {
+                    warn!(ticker, order_id = %order.order_id, "Failed order is on the exchange, keeping it");
+                    Ok(order)
+                }
+                Err(_) => Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Look up a leg by the client order ID it was placed with, for a placement whose reply
+/// didn't come back in time. Not finding it counts as a failed placement.
+async fn find_order(client: &KalshiClient, ticker: &str, client_order_id: &str) -> Result<Order, ExecutorError> {
+    let orders = client.get_market_orders(ticker).await?;
+    orders
+        .into_iter()
+        .find(|o| o.client_order_id.as_deref() == Some(client_order_id))
+        .ok_or_else(|| ExecutorError::NotOnExchange(client_order_id.to_string()))
+}
+
+/// Re-check resting legs until they fill or `wait` runs out. Legs that executed move to
+/// `filled` and legs the exchange ended some other way move to `other`; a leg that can't
+/// be checked stays resting. A resting order can fill seconds after placement, and this
+/// keeps the caller from cancelling and unwinding an arb that completed on its own.
+pub async fn await_fills(client: &KalshiClient, mut result: ExecutionResult, wait: Duration) -> ExecutionResult {
+    let deadline = Instant::now() + wait;
+    while !result.resting.is_empty() && Instant::now() < deadline {
+        sleep(FILL_POLL_INTERVAL.min(deadline - Instant::now())).await;
+        let mut still_resting = Vec::new();
+        for (ticker, order) in std::mem::take(&mut result.resting) {
+            let latest = match client.get_order(&order.order_id).await {
+                Ok(latest) => latest,
+                Err(e) => {
+                    warn!(ticker = %ticker, order_id = %order.order_id, error = %e, "Failed to poll resting order");
+                    still_resting.push((ticker, order));
+                    continue;
+                }
+            };
+            match latest.status.as_str() {
+                "resting" => still_resting.push((ticker, latest)),
+                "executed" => {
+                    info!(ticker = %ticker, order_id = %latest.order_id, "Resting order filled");
+                    result.filled.push((ticker, latest));
+                }
+                _ => result.other.push((ticker, latest)),
+            }
+        }
+        result.resting = still_resting;
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,13 +758,18 @@ mod tests {
             yes_bid_cents: yes_bid,
             depth_at_no: 100,
             depth_at_yes: 100,
+            ..Default::default()
         }
     }
 
+    fn gtc_order(bracket: &BracketQuote, direction: ArbDirection, size: u32) -> CreateOrderRequest {
+        build_order_request(bracket, direction, size, TimeInForce::GoodTillCancelled, Utc::now())
+    }
+
     #[test]
     fn test_build_order_long_payload() {
         let bracket = make_bracket("TICKER-A", 35, 20);
-        let req = build_order_request(&bracket, ArbDirection::Long, 5);
+        let req = gtc_order(&bracket, ArbDirection::Long, 5);
         let val = to_value(&req).unwrap();
         assert_eq!(
             val,
@@ -165,7 +788,7 @@ mod tests {
     #[test]
     fn test_build_order_short_payload() {
         let bracket = make_bracket("TICKER-B", 35, 20);
-        let req = build_order_request(&bracket, ArbDirection::Short, 3);
+        let req = gtc_order(&bracket, ArbDirection::Short, 3);
         let val = to_value(&req).unwrap();
         assert_eq!(
             val,
@@ -181,10 +804,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_order_time_in_force_payload() {
+        let bracket = make_bracket("TICKER-A", 35, 20);
+        let now = DateTime::from_timestamp(1_800_000_000, 0).unwrap();
+        let expiring = TimeInForce::Expiring { expiration_secs: 5 };
+        let val = to_value(build_order_request(&bracket, ArbDirection::Long, 5, expiring, now)).unwrap();
+        assert_eq!(val["expiration_ts"], json!(1_800_000_005));
+        assert!(val.get("time_in_force").is_none());
+
+        let ioc = TimeInForce::ImmediateOrCancel;
+        let val = to_value(build_order_request(&bracket, ArbDirection::Short, 3, ioc, now)).unwrap();
+        assert_eq!(
+            val,
+            json!({
+                "ticker": "TICKER-A",
+                "action": "sell",
+                "side": "yes",
+                "type": "limit",
+                "count": 3,
+                "yes_price": 20,
+                "no_price": null,
+                "time_in_force": "immediate_or_cancel",
+            })
+        );
+    }
+
     #[test]
     fn test_long_uses_ask_not_bid() {
         let bracket = make_bracket("T", 42, 18);
-        let req = build_order_request(&bracket, ArbDirection::Long, 1);
+        let req = gtc_order(&bracket, ArbDirection::Long, 1);
         assert_eq!(req.yes_price, Some(42), "Long must use yes_ask_cents");
         assert_ne!(req.yes_price, Some(18), "Long must NOT use yes_bid_cents");
     }
@@ -192,7 +841,7 @@ mod tests {
     #[test]
     fn test_short_uses_bid_not_ask() {
         let bracket = make_bracket("T", 42, 18);
-        let req = build_order_request(&bracket, ArbDirection::Short, 1);
+        let req = gtc_order(&bracket, ArbDirection::Short, 1);
         assert_eq!(req.yes_price, Some(18), "Short must use yes_bid_cents");
         assert_ne!(req.yes_price, Some(42), "Short must NOT use yes_ask_cents");
     }
@@ -200,7 +849,7 @@ mod tests {
     #[test]
     fn test_order_type_serializes_as_type() {
         let bracket = make_bracket("T", 50, 50);
-        let req = build_order_request(&bracket, ArbDirection::Long, 1);
+        let req = gtc_order(&bracket, ArbDirection::Long, 1);
         let val = to_value(&req).unwrap();
         assert!(val.get("type").is_some(), "JSON must have 'type' key");
         assert!(
@@ -213,7 +862,7 @@ mod tests {
     fn test_position_size_flows_through() {
         let bracket = make_bracket("T", 30, 20);
         for size in [1u32, 5, 100] {
-            let req = build_order_request(&bracket, ArbDirection::Long, size);
+            let req = gtc_order(&bracket, ArbDirection::Long, size);
             assert_eq!(req.count, size);
         }
     }
@@ -221,9 +870,198 @@ mod tests {
     #[test]
     fn test_no_price_always_null() {
         let bracket = make_bracket("T", 60, 40);
-        let long = build_order_request(&bracket, ArbDirection::Long, 1);
-        let short = build_order_request(&bracket, ArbDirection::Short, 1);
+        let long = gtc_order(&bracket, ArbDirection::Long, 1);
+        let short = gtc_order(&bracket, ArbDirection::Short, 1);
         assert_eq!(long.no_price, None, "Long no_price must be None");
         assert_eq!(short.no_price, None, "Short no_price must be None");
     }
+
+    #[test]
+    fn test_client_order_ids_derive_from_the_opportunity_uuid() {
+        let uuid = Uuid::parse_str("0b5d3c2e-8f61-4a7e-9d2c-5e4f3a1b6c7d").unwrap();
+        let ticker = "KXHIGHNY-26OCT17-B60";
+        let leg = leg_client_order_id(&uuid, ticker);
+        // Same every time, so a leg can be looked up again; a UUID however long the ticker
+        assert_eq!(leg, leg_client_order_id(&uuid, ticker));
+        assert_eq!(leg.len(), 36);
+        assert_ne!(leg, leg_client_order_id(&uuid, "KXHIGHNY-26OCT17-B62"));
+        assert_ne!(leg, leg_client_order_id(&Uuid::nil(), ticker));
+        assert_ne!(leg, unwind_client_order_id(&uuid, ticker));
+    }
+
+    #[test]
+    fn test_conflicts_with_resting_orders_and_positions() {
+        let requests = [
+            gtc_order(&make_bracket("A", 40, 38), ArbDirection::Long, 5),
+            gtc_order(&make_bracket("B", 50, 48), ArbDirection::Long, 5),
+        ];
+        let resting = |id: &str, ticker: &str, action: &str, side: &str, price: i64| -> Order {
+            let field = if side == "yes" { "yes_price" } else { "no_price" };
+            serde_json::from_value(json!({
+                "order_id": id, "ticker": ticker, "status": "resting", "action": action, "side": side,
+                "type": "limit", (field): price,
+            }))
+            .unwrap()
+        };
+        // Our exit offer at 40 is the ask the A leg would take; buying NO at 45 on B offers
+        // YES at 55, above the B leg's limit, and a resting bid is on the leg's own side
+        let orders = [
+            resting("o1", "A", "sell", "yes", 40),
+            resting("o2", "B", "buy", "no", 45),
+            resting("o3", "B", "buy", "yes", 30),
+        ];
+        let position = |ticker: &str, position| MarketPosition { ticker: ticker.into(), position, market_exposure: 0 };
+        let found = conflicts(&requests, &[position("B", -3)], &orders);
+        assert_eq!(found, vec![Conflict::SelfTrade { ticker: "A".into(), order_id: "o1".into() }]);
+
+        let found = conflicts(&requests, &[position("B", 5)], &orders[1..]);
+        assert_eq!(found, vec![Conflict::Held { ticker: "B".into(), position: 5 }]);
+        assert_eq!(found[0].to_string(), "B already held (+5)");
+
+        // Selling YES at the bid crosses our own bid at or above it
+        let short = [gtc_order(&make_bracket("B", 50, 30), ArbDirection::Short, 5)];
+        assert_eq!(conflicts(&short, &[position("B", 5)], &orders).len(), 1);
+    }
+
+    #[test]
+    fn test_short_receipt_economics() {
+        let fill = |ticker: &str, price: i64| {
+            let order = Order {
+                order_id: format!("o-{}", ticker),
+                ticker: ticker.to_string(),
+                status: "executed".to_string(),
+                action: "sell".to_string(),
+                side: "yes".to_string(),
+                order_type: "limit".to_string(),
+                yes_price: Some(price),
+                no_price: None,
+                count: Some(10),
+                remaining_count: Some(0),
+                fill_count: Some(10),
+                initial_count: Some(10),
+                taker_fill_cost: None,
+                maker_fill_cost: None,
+                client_order_id: None,
+            };
+            (ticker.to_string(), order)
+        };
+        let opp = ArbOpportunity {
+            id: "EV-SHORT-1".into(),
+            uuid: Uuid::nil(),
+            event_ticker: "EV".into(),
+            event_title: "Event".into(),
+            direction: ArbDirection::Short,
+            brackets: vec![make_bracket("A", 62, 60), make_bracket("B", 47, 45)],
+            position_size: 10,
+            sum_cents: 105,
+            total_fees_cents: 35,
+            gross_profit_cents: 50,
+            net_profit_cents: 15,
+            expected_slippage_cents: 0,
+            gross_roi_pct: rust_decimal::Decimal::ZERO,
+            net_roi_pct: rust_decimal::Decimal::ZERO,
+            slippage_adj_roi_pct: rust_decimal::Decimal::ZERO,
+            settles_at: None,
+        };
+        let result = ExecutionResult {
+            event_ticker: "EV".into(),
+            direction: ArbDirection::Short,
+            filled: vec![fill("A", 61), fill("B", 45)],
+            resting: vec![],
+            other: vec![],
+            api_failures: vec![],
+            failure_kinds: Default::default(),
+            aborted: false,
+            unwind: Vec::new(),
+        };
+
+        let receipt = result.receipt(&opp).unwrap();
+        assert_eq!(
+            receipt.legs[0],
+            ReceiptLeg { ticker: "A".into(), label: None, count: 10, expected_cents: 60, price_cents: 61 }
+        );
+        // Premium 610 + 450, fees 17 + 18; (39 + 55) x 10 collateral plus fees tied up
+        assert_eq!((receipt.total_cents, receipt.fees_cents), (1060, 35));
+        assert_eq!(receipt.net_cents, 25);
+        assert_eq!(receipt.capital_cents, 975);
+    }
+
+    #[test]
+    fn test_unwind_completes_or_closes() {
+        let fill = |ticker: &str| {
+            let order = Order {
+                order_id: format!("o-{}", ticker),
+                ticker: ticker.to_string(),
+                status: "executed".to_string(),
+                action: "buy".to_string(),
+                side: "yes".to_string(),
+                order_type: "limit".to_string(),
+                yes_price: Some(30),
+                no_price: None,
+                count: Some(10),
+                remaining_count: Some(0),
+                fill_count: Some(10),
+                initial_count: Some(10),
+                taker_fill_cost: None,
+                maker_fill_cost: None,
+                client_order_id: None,
+            };
+            (ticker.to_string(), order)
+        };
+        let opp = ArbOpportunity {
+            id: "EV-LONG-1".into(),
+            uuid: Uuid::nil(),
+            event_ticker: "EV".into(),
+            event_title: "Event".into(),
+            direction: ArbDirection::Long,
+            brackets: vec![make_bracket("A", 30, 28), make_bracket("B", 30, 28), make_bracket("C", 30, 28)],
+            position_size: 10,
+            sum_cents: 90,
+            total_fees_cents: 45,
+            gross_profit_cents: 100,
+            net_profit_cents: 55,
+            expected_slippage_cents: 0,
+            gross_roi_pct: rust_decimal::Decimal::ZERO,
+            net_roi_pct: rust_decimal::Decimal::ZERO,
+            slippage_adj_roi_pct: rust_decimal::Decimal::ZERO,
+            settles_at: None,
+        };
+        // C was rejected; A and B filled 10 at 30
+        let result = ExecutionResult {
+            event_ticker: "EV".into(),
+            direction: ArbDirection::Long,
+            filled: vec![fill("A"), fill("B")],
+            resting: vec![],
+            other: vec![],
+            api_failures: vec!["C".into()],
+            failure_kinds: Default::default(),
+            aborted: false,
+            unwind: Vec::new(),
+        };
+
+        // C now asks 35: 600 + 350 paid, fees 15 + 15 + 16, still nets 4c
+        let moved = [make_bracket("A", 32, 27), make_bracket("B", 31, 29), make_bracket("C", 35, 33)];
+        let leg = UnwindLeg { ticker: "C".into(), count: Contracts(10), price_cents: 35 };
+        assert_eq!(plan_unwind(&opp, &result, &moved), Unwind::Reprice(vec![leg]));
+
+        // At 40 the edge is gone: sell A and B back into their bids
+        let gone = [make_bracket("A", 32, 27), make_bracket("B", 31, 29), make_bracket("C", 40, 38)];
+        let legs = match plan_unwind(&opp, &result, &gone) {
+            Unwind::SellBack { legs, unquoted } if unquoted.is_empty() => legs,
+            other => panic!("expected sell-back, got {:?}", other),
+        };
+        let prices: Vec<(&str, i64)> = legs.iter().map(|l| (l.ticker.as_str(), l.price_cents)).collect();
+        assert_eq!(prices, vec![("A", 27), ("B", 29)]);
+
+        // An unquotable missing leg can't be completed
+        assert!(matches!(plan_unwind(&opp, &result, &moved[..2]), Unwind::SellBack { .. }));
+
+        // A filled leg with no bid to sell into is reported, not dropped
+        let plan = plan_unwind(&opp, &result, &gone[1..]);
+        let closed: Vec<&str> = match &plan {
+            Unwind::SellBack { legs, .. } => legs.iter().map(|l| l.ticker.as_str()).collect(),
+            other => panic!("expected sell-back, got {:?}", other),
+        };
+        assert_eq!((closed, plan.unquoted()), (vec!["B"], &["A".to_string()][..]));
+    }
 }
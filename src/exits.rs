@@ -0,0 +1,170 @@
+// Exit manager for one-sided positions: contracts an arb left unhedged when it never
+// completed and the unwind at execution time didn't close. The `exits` job posts a resting
+// order to close each one, starting at the passive side of the book and stepping toward the
+// other side the longer it goes unfilled, and alerts when the loss at the market grows large.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::executor::unwind_client_order_id;
+use crate::positions::UnhedgedLeg;
+use crate::price;
+use crate::scheduler::Schedule;
+
+/// `[exits]` — work off unhedged legs instead of holding them to settlement.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ExitConfig {
+    #[serde(default = "default_schedule")]
+    #[schemars(with = "String")]
+    pub schedule: Schedule,
+    /// How far the exit price moves toward the other side of the book per step.
+    #[serde(default = "default_step_cents")]
+    pub step_cents: u32,
+    #[serde(default = "default_step_secs")]
+    pub step_secs: u64,
+    /// Alert once the loss of closing a leg at the market reaches this much.
+    #[serde(default = "default_alert_loss_cents")]
+    pub alert_loss_cents: u32,
+}
+
+fn default_schedule() -> Schedule { Schedule::Every(std::time::Duration::from_secs(30)) }
+fn default_step_cents() -> u32 { 1 }
+fn default_step_secs() -> u64 { 60 }
+fn default_alert_loss_cents() -> u32 { 200 }
+
+/// Where to post the exit for `contracts` YES (held: sell; sold: buy back) after `steps`
+/// steps: one cent inside the passive side, moved `step_cents` per step, and never past the
+/// other side, where it takes the book. None when the side it would take is empty.
+pub fn exit_price(contracts: i64, bid: Option<i64>, ask: Option<i64>, steps: i64, step_cents: i64) -> Option<i64> {
+    let moved = steps.saturating_mul(step_cents);
+    let price = if contracts > 0 {
+        let bid = bid?;
+        ask.map_or(bid, |ask| (ask - 1).saturating_sub(moved).max(bid))
+    } else {
+        let ask = ask?;
+        bid.map_or(ask, |bid| (bid + 1).saturating_add(moved).min(ask))
+    };
+    price::is_valid(price).then_some(price)
+}
+
+/// What closing the leg by taking the book now would lose against its entry, before fees.
+/// Negative is a gain. None when that side of the book is empty.
+pub fn loss_at_market(leg: &UnhedgedLeg, bid: Option<i64>, ask: Option<i64>) -> Option<i64> {
+    if leg.contracts > 0 {
+        Some((leg.entry_cents - bid?) * leg.contracts)
+    } else {
+        Some((ask? - leg.entry_cents) * -leg.contracts)
+    }
+}
+
+/// An exit order resting on the exchange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkingExit {
+    pub order_id: String,
+    pub price_cents: i64,
+}
+
+/// Exit progress per `(opportunity, market)`: the order working it, when exiting began
+/// (what the price steps count from), and whether the loss alert has gone out.
+#[derive(Debug, Default)]
+pub struct ExitManager {
+    pub working: HashMap<(String, String), WorkingExit>,
+    started: HashMap<(String, String), DateTime<Utc>>,
+    alerted: HashSet<(String, String)>,
+}
+
+impl ExitManager {
+    /// Price steps taken on a leg by `now`, starting its clock the first time it's seen.
+    pub fn steps(&mut self, leg: &UnhedgedLeg, step_secs: u64, now: DateTime<Utc>) -> i64 {
+        let started = *self.started.entry(key(leg)).or_insert(now);
+        (now - started).num_seconds() / step_secs.max(1) as i64
+    }
+
+    /// Whether a leg's loss crossed the alert threshold since it was last below it.
+    pub fn crossed(&mut self, leg: &UnhedgedLeg, loss_cents: i64, threshold_cents: i64) -> bool {
+        if loss_cents < threshold_cents {
+            self.alerted.remove(&key(leg));
+            false
+        } else {
+            self.alerted.insert(key(leg))
+        }
+    }
+
+    /// Forget legs no longer unhedged. Returns those that were being worked.
+    pub fn retain(&mut self, legs: &[UnhedgedLeg]) -> Vec<(String, String)> {
+        let live: HashSet<(String, String)> = legs.iter().map(key).collect();
+        let closed: Vec<(String, String)> = self.started.keys().filter(|k| !live.contains(*k)).cloned().collect();
+        self.started.retain(|k, _| live.contains(k));
+        self.alerted.retain(|k| live.contains(k));
+        closed
+    }
+}
+
+pub fn key(leg: &UnhedgedLeg) -> (String, String) {
+    (leg.opportunity_id.clone(), leg.ticker.clone())
+}
+
+/// Whether `client_order_id` is one of the orders placed to complete or close `opportunity_id`
+/// in `ticker`: its unwind order, named after the opportunity's `uuid`, or an exit order.
+pub fn is_exit_order(client_order_id: Option<&str>, opportunity_id: &str, uuid: Option<&Uuid>, ticker: &str) -> bool {
+    let Some(id) = client_order_id else {
+        return false;
+    };
+    uuid.is_some_and(|uuid| id == unwind_client_order_id(uuid, ticker))
+        || id.strip_prefix(opportunity_id).is_some_and(|rest| rest.starts_with("-exit-"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::leg_client_order_id;
+
+    fn leg(contracts: i64, entry_cents: i64) -> UnhedgedLeg {
+        UnhedgedLeg {
+            opportunity_id: "OPP".into(),
+            event_ticker: "EV".into(),
+            ticker: "EV-A".into(),
+            contracts,
+            entry_cents,
+        }
+    }
+
+    #[test]
+    fn test_exit_price_steps_toward_the_other_side() {
+        // Held YES: start one inside the ask, step down to the bid and no further
+        assert_eq!(exit_price(5, Some(40), Some(46), 0, 2), Some(45));
+        assert_eq!(exit_price(5, Some(40), Some(46), 2, 2), Some(41));
+        assert_eq!(exit_price(5, Some(40), Some(46), 9, 2), Some(40));
+        assert_eq!(exit_price(5, None, Some(46), 0, 2), None);
+        // Sold YES: buy back from one above the bid up to the ask
+        assert_eq!(exit_price(-5, Some(40), Some(46), 1, 2), Some(43));
+        assert_eq!(exit_price(-5, Some(40), None, 0, 2), None);
+        assert_eq!(exit_price(-5, None, Some(46), 0, 2), Some(46));
+
+        assert_eq!(loss_at_market(&leg(5, 50), Some(40), None), Some(50));
+        assert_eq!(loss_at_market(&leg(-5, 50), None, Some(44)), Some(-30));
+
+        let mut exits = ExitManager::default();
+        let now = Utc::now();
+        assert_eq!(exits.steps(&leg(5, 50), 60, now), 0);
+        assert_eq!(exits.steps(&leg(5, 50), 60, now + chrono::Duration::seconds(150)), 2);
+        assert!(exits.crossed(&leg(5, 50), 250, 200));
+        assert!(!exits.crossed(&leg(5, 50), 300, 200));
+        assert!(!exits.crossed(&leg(5, 50), 100, 200));
+        assert!(exits.crossed(&leg(5, 50), 200, 200));
+        assert_eq!(exits.retain(&[]), [("OPP".to_string(), "EV-A".to_string())]);
+        assert_eq!(exits.steps(&leg(5, 50), 60, now + chrono::Duration::seconds(150)), 0);
+
+        let uuid = Uuid::nil();
+        let unwind = unwind_client_order_id(&uuid, "EV-A");
+        assert!(is_exit_order(Some(&unwind), "OPP", Some(&uuid), "EV-A"));
+        assert!(!is_exit_order(Some(&unwind), "OPP", Some(&uuid), "EV-B"));
+        assert!(!is_exit_order(Some(&unwind), "OPP", None, "EV-A"));
+        assert!(is_exit_order(Some("OPP-exit-EV-A-17"), "OPP", None, "EV-A"));
+        assert!(!is_exit_order(Some(&leg_client_order_id(&uuid, "EV-A")), "OPP", Some(&uuid), "EV-A"));
+        assert!(!is_exit_order(None, "OPP", Some(&uuid), "EV-A"));
+    }
+}
@@ -0,0 +1,262 @@
+// Kalshi trading fees: `rate * C * P * (1 - P)`, rounded up to the cent, with separate taker
+// and maker rates that can differ by series. Schedules are versioned by the day they take
+// effect, so the next one can be configured before it starts and is picked up on the day.
+
+use anyhow::{bail, Result};
+use chrono::{NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::kalshi::types::Order;
+use crate::price;
+use crate::report::series_of;
+
+/// One version of the fee schedule.
+pub trait FeeSchedule: Send + Sync {
+    fn version(&self) -> &str;
+    /// First day (UTC) the schedule applies.
+    fn effective(&self) -> NaiveDate;
+    /// Rate for taking liquidity in `series`, in basis points of `C * P * (1 - P)`.
+    fn taker_bps(&self, series: &str) -> i64;
+    /// Rate for resting orders that get filled.
+    fn maker_bps(&self, series: &str) -> i64;
+}
+
+/// A schedule as written under `[[fees.schedules]]`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct FeeTable {
+    pub version: String,
+    #[schemars(with = "String")]
+    pub effective: NaiveDate,
+    pub taker_bps: i64,
+    #[serde(default)]
+    pub maker_bps: i64,
+    /// Series charged other rates, keyed by series ticker (e.g. `KXINX`).
+    #[serde(default)]
+    pub series: HashMap<String, SeriesFees>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SeriesFees {
+    pub taker_bps: Option<i64>,
+    pub maker_bps: Option<i64>,
+}
+
+impl FeeSchedule for FeeTable {
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn effective(&self) -> NaiveDate {
+        self.effective
+    }
+
+    fn taker_bps(&self, series: &str) -> i64 {
+        self.series.get(series).and_then(|s| s.taker_bps).unwrap_or(self.taker_bps)
+    }
+
+    fn maker_bps(&self, series: &str) -> i64 {
+        self.series.get(series).and_then(|s| s.maker_bps).unwrap_or(self.maker_bps)
+    }
+}
+
+/// The schedule the bot was written against: 7% taker, no maker fee, every series alike.
+/// Source: https://kalshi.com/docs/kalshi-fee-schedule.pdf
+fn builtin() -> FeeTable {
+    FeeTable {
+        version: "kalshi-general".to_string(),
+        effective: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap_or_default(),
+        taker_bps: 700,
+        maker_bps: 0,
+        series: HashMap::new(),
+    }
+}
+
+/// `[fees]` — which fee schedule prices arbs.
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct FeesConfig {
+    /// Use this version whatever the date. By default, the latest schedule in effect.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Schedules besides the built-in `kalshi-general`.
+    #[serde(default)]
+    pub schedules: Vec<FeeTable>,
+}
+
+/// Every known schedule, oldest first, and the pinned one if any.
+pub struct Fees {
+    schedules: Vec<FeeTable>,
+    pinned: Option<usize>,
+}
+
+impl Fees {
+    pub fn new(config: &FeesConfig) -> Result<Self> {
+        let mut schedules = vec![builtin()];
+        for table in &config.schedules {
+            if schedules.iter().any(|s| s.version == table.version) {
+                bail!("Fee schedule {} is defined twice", table.version);
+            }
+            let rates = table.series.values().flat_map(|s| [s.taker_bps, s.maker_bps]).flatten();
+            if [table.taker_bps, table.maker_bps].into_iter().chain(rates).any(|bps| !(0..=10_000).contains(&bps)) {
+                bail!("Fee schedule {} has a rate outside 0..=10000 bps", table.version);
+            }
+            schedules.push(table.clone());
+        }
+        schedules.sort_by_key(|s| s.effective);
+        let pinned = match &config.version {
+            Some(version) => match schedules.iter().position(|s| &s.version == version) {
+                Some(i) => Some(i),
+                None => bail!("Unknown fee schedule {}", version),
+            },
+            None => None,
+        };
+        Ok(Self { schedules, pinned })
+    }
+
+    /// The pinned schedule, or else the latest one in effect on `day`.
+    pub fn on(&self, day: NaiveDate) -> &dyn FeeSchedule {
+        let i = self.pinned.unwrap_or_else(|| self.schedules.iter().rposition(|s| s.effective <= day).unwrap_or(0));
+        &self.schedules[i]
+    }
+
+    /// Whether any schedule charges nothing for taking in some series.
+    fn any_free(&self) -> bool {
+        self.schedules.iter().any(|s| s.taker_bps == 0 || s.series.values().any(|f| f.taker_bps == Some(0)))
+    }
+}
+
+static FEES: OnceLock<Fees> = OnceLock::new();
+
+/// Set the fee schedules from config. First call wins.
+pub fn init(config: &FeesConfig) -> Result<()> {
+    let fees = Fees::new(config)?;
+    FEES.get_or_init(|| fees);
+    Ok(())
+}
+
+/// The schedule in effect today (the built-in one before `init`).
+pub fn current() -> &'static dyn FeeSchedule {
+    static BUILTIN: OnceLock<FeeTable> = OnceLock::new();
+    match FEES.get() {
+        Some(fees) => fees.on(Utc::now().date_naive()),
+        None => BUILTIN.get_or_init(builtin),
+    }
+}
+
+/// Fee in cents on `contracts` at `price_cents` and `bps`: ceil(bps * C * P * (100 - P) / 1_000_000).
+pub fn fee_cents(bps: i64, contracts: u32, price_cents: i64) -> i64 {
+    if !price::is_valid(price_cents) {
+        return 0;
+    }
+    let numerator = bps * contracts as i64 * price_cents * price::complement(price_cents);
+    // Ceiling division: (a + b - 1) / b
+    (numerator + 999_999) / 1_000_000
+}
+
+/// Fee for taking `contracts` at `price_cents` in the market `ticker`.
+pub fn taker_fee_cents(ticker: &str, contracts: u32, price_cents: i64) -> i64 {
+    fee_cents(current().taker_bps(series_of(ticker)), contracts, price_cents)
+}
+
+/// Fee for a resting order in `ticker` filled for `contracts` at `price_cents`.
+pub fn maker_fee_cents(ticker: &str, contracts: u32, price_cents: i64) -> i64 {
+    fee_cents(current().maker_bps(series_of(ticker)), contracts, price_cents)
+}
+
+/// Fee on `count` contracts of `order` filled at `yes_price`: the maker rate on the share of
+/// the fill (by cost) that rested and was hit, the taker rate on the rest.
+pub fn fill_fee_cents(ticker: &str, order: &Order, count: u32, yes_price: i64) -> i64 {
+    let maker_cost = order.maker_fill_cost.unwrap_or(0).max(0);
+    let total_cost = maker_cost + order.taker_fill_cost.unwrap_or(0).max(0);
+    let maker = if total_cost == 0 {
+        0
+    } else {
+        ((count as i64 * maker_cost + total_cost / 2) / total_cost) as u32
+    };
+    maker_fee_cents(ticker, maker, yes_price) + taker_fee_cents(ticker, count - maker, yes_price)
+}
+
+/// The least a leg can pay in taker fees: a cent at any non-zero rate, since every fee is
+/// rounded up.
+pub fn min_leg_fee_cents() -> i64 {
+    match FEES.get() {
+        Some(fees) if fees.any_free() => 0,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_taker_fee_at_2_contracts() {
+        assert_eq!(taker_fee_cents("KXHIGHNY-26OCT17-B60", 2, 5), 1);   // $0.01
+        assert_eq!(taker_fee_cents("KXHIGHNY-26OCT17-B60", 2, 10), 2);  // $0.02
+        assert_eq!(taker_fee_cents("KXHIGHNY-26OCT17-B60", 2, 50), 4);  // $0.04
+    }
+
+    #[test]
+    fn test_taker_fee_at_5_contracts() {
+        let fee = |price| fee_cents(700, 5, price);
+        // 5 contracts at 5c:  7*5*5*95   = 16625,  ceil(16625/10000) = 2
+        assert_eq!(fee(5), 2);
+        // 5 contracts at 10c: 7*5*10*90  = 31500,  ceil(31500/10000) = 4
+        assert_eq!(fee(10), 4);
+        // 5 contracts at 20c: 7*5*20*80  = 56000,  ceil(56000/10000) = 6
+        assert_eq!(fee(20), 6);
+        // 5 contracts at 25c: 7*5*25*75  = 65625,  ceil(65625/10000) = 7
+        assert_eq!(fee(25), 7);
+        // 5 contracts at 33c: 7*5*33*67  = 77490,  ceil(77490/10000) = 8
+        assert_eq!(fee(33), 8);
+        // 5 contracts at 50c: 7*5*50*50  = 87500,  ceil(87500/10000) = 9
+        assert_eq!(fee(50), 9);
+    }
+
+    #[test]
+    fn test_taker_fee_edge_cases() {
+        assert_eq!(fee_cents(700, 5, 0), 0);
+        assert_eq!(fee_cents(700, 5, 100), 0);
+        assert_eq!(fee_cents(700, 0, 50), 0);
+
+        // 6 of 10 contracts rested and were hit: only the other 4 pay the taker rate
+        let order: Order = serde_json::from_str(
+            r#"{"order_id":"O","ticker":"EV-A","status":"executed","action":"buy","side":"yes","type":"limit",
+                "maker_fill_cost":300,"taker_fill_cost":200}"#,
+        )
+        .unwrap();
+        assert_eq!(fill_fee_cents("EV-A", &order, 10, 50), fee_cents(700, 4, 50));
+    }
+
+    #[test]
+    fn test_schedule_by_effective_date_and_series() {
+        let config: FeesConfig = toml::from_str(
+            r#"
+            [[schedules]]
+            version = "2026-11"
+            effective = "2026-11-01"
+            taker_bps = 700
+            maker_bps = 175
+            series.KXINX = { taker_bps = 350 }
+            "#,
+        )
+        .unwrap();
+        let fees = Fees::new(&config).unwrap();
+        let day = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert_eq!(fees.on(day("2026-10-31")).version(), "kalshi-general");
+        assert_eq!(fees.on(day("2026-10-31")).maker_bps("KXINX"), 0);
+        let next = fees.on(day("2026-11-01"));
+        assert_eq!((next.taker_bps("KXINX"), next.maker_bps("KXINX")), (350, 175));
+        assert_eq!(next.taker_bps("KXHIGHNY"), 700);
+
+        let pinned = FeesConfig { version: Some("kalshi-general".into()), ..config };
+        assert_eq!(Fees::new(&pinned).unwrap().on(day("2027-01-01")).version(), "kalshi-general");
+        let today = fees.on(day("2026-11-02"));
+        assert_eq!(fee_cents(today.maker_bps("KXINX"), 10, 50), 5);
+
+        let unknown = FeesConfig { version: Some("2027-01".into()), schedules: vec![] };
+        assert!(Fees::new(&unknown).is_err());
+    }
+}
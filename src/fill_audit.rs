@@ -0,0 +1,222 @@
+// Check of the exchange's fills against the execution store. Every fill should land on an
+// order the bot recorded, in that order's market and on its side, and never add up past
+// the order's size. One that doesn't means the bot's model of its positions is wrong, and
+// trading on a wrong model is the worst response: any anomaly trips the kill switch, which
+// stops new executions, cancels what's resting, and pages until the bot is restarted.
+
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::kalshi::types::Fill;
+use crate::storage::ExecutionStore;
+
+/// A fill the execution store can't account for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomaly {
+    /// On an order carrying this bot's client order ID that the store never recorded.
+    UnknownOrder { order_id: String, ticker: String, count: i64 },
+    /// In another market, or on another side or action, than the order was placed with.
+    Mismatch { order_id: String, placed: String, filled: String },
+    /// Fills on the order add up to more contracts than it was placed for.
+    Overfill { order_id: String, ticker: String, placed: i64, filled: i64 },
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anomaly::UnknownOrder { order_id, ticker, count } => {
+                write!(f, "{} filled on unrecorded order {} in {}", count, order_id, ticker)
+            }
+            Anomaly::Mismatch { order_id, placed, filled } => {
+                write!(f, "order {} placed as {} filled as {}", order_id, placed, filled)
+            }
+            Anomaly::Overfill { order_id, ticker, placed, filled } => {
+                write!(f, "order {} in {} filled {} of {}", order_id, ticker, filled, placed)
+            }
+        }
+    }
+}
+
+/// Fills checked so far this run.
+#[derive(Debug)]
+pub struct FillAudit {
+    /// Only fills from this run on are checked; earlier ones were the previous run's to check.
+    since: DateTime<Utc>,
+    /// Trade IDs already checked.
+    seen: HashSet<String>,
+    /// Contracts filled per recorded order, this run.
+    filled: HashMap<String, i64>,
+    /// Orders the store doesn't know, by whether their client order ID is this bot's.
+    owners: HashMap<String, bool>,
+}
+
+impl FillAudit {
+    pub fn new(since: DateTime<Utc>) -> Self {
+        Self { since, seen: HashSet::new(), filled: HashMap::new(), owners: HashMap::new() }
+    }
+
+    pub fn since(&self) -> DateTime<Utc> {
+        self.since
+    }
+
+    /// Orders of unchecked fills that the store doesn't know and that haven't been looked up.
+    pub fn unresolved(&self, fills: &[Fill], store: &ExecutionStore) -> Vec<String> {
+        let mut orders: Vec<String> = fills
+            .iter()
+            .filter(|f| !self.seen.contains(&f.trade_id) && store.order(&f.order_id).is_none())
+            .filter(|f| !self.owners.contains_key(&f.order_id))
+            .map(|f| f.order_id.clone())
+            .collect();
+        orders.sort();
+        orders.dedup();
+        orders
+    }
+
+    /// Record whether an order the store doesn't know was placed by this bot.
+    pub fn resolve(&mut self, order_id: &str, ours: bool) {
+        self.owners.insert(order_id.to_string(), ours);
+    }
+
+    /// Check fills not seen before. Fills on an unknown order that hasn't been resolved are
+    /// left for the next pass.
+    pub fn check(&mut self, fills: &[Fill], store: &ExecutionStore) -> Vec<Anomaly> {
+        let mut anomalies = Vec::new();
+        for fill in fills {
+            if self.seen.contains(&fill.trade_id) {
+                continue;
+            }
+            match (store.order(&fill.order_id), store.placed_order(&fill.order_id)) {
+                (Some(state), Some(order)) => {
+                    let placed = format!("{} {} {}", order.action, order.side, state.ticker);
+                    let filled = format!("{} {} {}", fill.action, fill.side, fill.ticker);
+                    if placed != filled {
+                        anomalies.push(Anomaly::Mismatch { order_id: fill.order_id.clone(), placed, filled });
+                    }
+                    let total = self.filled.entry(fill.order_id.clone()).or_default();
+                    *total += fill.count;
+                    let size = order.initial_count.or(order.count).unwrap_or(0);
+                    if *total > size {
+                        anomalies.push(Anomaly::Overfill {
+                            order_id: fill.order_id.clone(),
+                            ticker: fill.ticker.clone(),
+                            placed: size,
+                            filled: *total,
+                        });
+                    }
+                }
+                _ => match self.owners.get(&fill.order_id) {
+                    Some(true) => anomalies.push(Anomaly::UnknownOrder {
+                        order_id: fill.order_id.clone(),
+                        ticker: fill.ticker.clone(),
+                        count: fill.count,
+                    }),
+                    // Placed by hand or by another instance
+                    Some(false) => {}
+                    None => continue,
+                },
+            }
+            self.seen.insert(fill.trade_id.clone());
+        }
+        anomalies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::ExecutionResult;
+    use crate::kalshi::types::{ArbDirection, Order};
+    use crate::storage::ExecutionRecord;
+
+    fn order(id: &str, count: i64) -> Order {
+        Order {
+            order_id: id.into(),
+            ticker: "EV-A".into(),
+            status: "resting".into(),
+            action: "buy".into(),
+            side: "yes".into(),
+            order_type: "limit".into(),
+            yes_price: Some(40),
+            no_price: None,
+            count: Some(count),
+            remaining_count: Some(count),
+            fill_count: None,
+            initial_count: None,
+            taker_fill_cost: None,
+            maker_fill_cost: None,
+            client_order_id: None,
+        }
+    }
+
+    fn fill(trade_id: &str, order_id: &str, ticker: &str, side: &str, count: i64) -> Fill {
+        Fill {
+            trade_id: trade_id.into(),
+            order_id: order_id.into(),
+            ticker: ticker.into(),
+            side: side.into(),
+            action: "buy".into(),
+            count,
+            yes_price: Some(40),
+            created_time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_fills_checked_against_recorded_orders() {
+        let rec = ExecutionRecord {
+            instance: "test".into(),
+            opportunity_id: "EV-LONG-1".into(),
+            uuid: None,
+            recorded_at: Utc::now(),
+            result: ExecutionResult {
+                event_ticker: "EV".into(),
+                direction: ArbDirection::Long,
+                filled: vec![],
+                resting: vec![("EV-A".into(), order("o1", 5)), ("EV-B".into(), order("o2", 5))],
+                other: vec![],
+                api_failures: vec![],
+                failure_kinds: Default::default(),
+                aborted: false,
+                unwind: Vec::new(),
+            },
+            labels: Default::default(),
+        };
+        let mut line = serde_json::to_value(rec).unwrap();
+        line["kind"] = "execution".into();
+        let path = std::env::temp_dir().join(format!("arb-fill-audit-{}.jsonl", std::process::id()));
+        std::fs::write(&path, format!("{}\n", line)).unwrap();
+        let store = ExecutionStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let mut audit = FillAudit::new(Utc::now());
+        // Fills within the orders' size, in their markets, are fine
+        let fills = vec![fill("t1", "o1", "EV-A", "yes", 3), fill("t2", "o2", "EV-B", "yes", 5)];
+        assert!(audit.unresolved(&fills, &store).is_empty());
+        assert!(audit.check(&fills, &store).is_empty());
+
+        // Past the order's size, or on the wrong side; already-checked fills aren't recounted
+        let mut fills = fills;
+        fills.push(fill("t3", "o1", "EV-A", "yes", 3));
+        fills.push(fill("t4", "o2", "EV-B", "no", 1));
+        let anomalies = audit.check(&fills, &store);
+        assert_eq!(
+            anomalies[0],
+            Anomaly::Overfill { order_id: "o1".into(), ticker: "EV-A".into(), placed: 5, filled: 6 }
+        );
+        assert!(matches!(&anomalies[1], Anomaly::Mismatch { filled, .. } if filled == "buy no EV-B"));
+        assert_eq!(anomalies.len(), 3);
+
+        // Unknown orders wait for a lookup; only the bot's own are anomalies
+        let fills = vec![fill("t5", "x1", "EV-C", "yes", 2), fill("t6", "x2", "EV-C", "yes", 2)];
+        assert_eq!(audit.unresolved(&fills, &store), ["x1", "x2"]);
+        assert!(audit.check(&fills, &store).is_empty());
+        audit.resolve("x1", true);
+        audit.resolve("x2", false);
+        assert_eq!(
+            audit.check(&fills, &store),
+            [Anomaly::UnknownOrder { order_id: "x1".into(), ticker: "EV-C".into(), count: 2 }]
+        );
+        assert!(audit.check(&fills, &store).is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+// Operator halt: while `[risk] halt_file` exists or `HALT_TRADING` is set, no new orders
+// go out. Scanning, logging, and cleanup of what's already working (cancels, unwinds)
+// carry on, so stopping is a touch of a file rather than a hunt for the right tmux pane.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::path::Path;
+
+/// Env var that halts trading for the whole run when set (to anything but "0" / "false").
+pub const HALT_ENV: &str = "HALT_TRADING";
+
+/// Why trading is halted, if it is: the env var, else the halt file's first line (its
+/// path when the file is empty).
+pub fn reason(path: &Path) -> Option<String> {
+    if let Ok(value) = std::env::var(HALT_ENV) {
+        if !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false") {
+            return Some(format!("{} set", HALT_ENV));
+        }
+    }
+    let content = std::fs::read_to_string(path).ok()?;
+    Some(match content.lines().next().map(str::trim) {
+        Some(line) if !line.is_empty() => line.to_string(),
+        _ => format!("{} present", path.display()),
+    })
+}
+
+/// Create the halt file, recording why and when. Deleting it resumes trading.
+pub fn create(path: &Path, why: &str) -> Result<()> {
+    let line = format!("{} at {}\n", why, Utc::now().format("%Y-%m-%dT%H:%M:%SZ"));
+    std::fs::write(path, line).with_context(|| format!("Failed to create {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_halt_file_reason() {
+        let path = std::env::temp_dir().join(format!("arb-halt-test-{}", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        assert_eq!(reason(&path), None);
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(reason(&path), Some(format!("{} present", path.display())));
+
+        create(&path, "halted from Telegram").unwrap();
+        assert!(reason(&path).unwrap().starts_with("halted from Telegram at "));
+        std::fs::remove_file(&path).ok();
+    }
+}
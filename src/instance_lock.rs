@@ -0,0 +1,77 @@
+// One trading bot per data directory. The run loop holds an exclusive lock on
+// `data/instance.lock` for as long as it runs, so a second copy started in the same
+// directory (a double start under systemd, a forgotten tmux pane) exits before it sends
+// an order instead of doubling every position. The OS drops the lock with the process,
+// however it ends, so a crash never leaves it stuck.
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+
+pub const LOCK_PATH: &str = "data/instance.lock";
+
+/// The held lock. Released when dropped or when the process exits.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+/// Take the lock at `path` for `instance`, or fail naming the process that holds it.
+pub fn acquire(path: &Path, instance: &str) -> Result<InstanceLock> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    // Not truncated on open: until the lock is ours, the holder's line is what to report
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let holder = std::fs::read_to_string(path).unwrap_or_default();
+            bail!(
+                "Another bot is already trading from this directory ({}): {} is locked. Stop it first, \
+                 or give this one its own working directory",
+                holder.trim(),
+                path.display()
+            );
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+        }
+    }
+    let holder = format!(
+        "pid {}, instance {}, since {}",
+        std::process::id(),
+        instance,
+        Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    );
+    file.set_len(0)
+        .and_then(|()| writeln!(file, "{}", holder))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(InstanceLock { _file: file })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_acquire_fails_until_the_first_is_released() {
+        let path = std::env::temp_dir().join(format!("arb-lock-test-{}", std::process::id()));
+        let held = acquire(&path, "prod").unwrap();
+
+        let err = acquire(&path, "prod").unwrap_err().to_string();
+        assert!(err.contains(&format!("pid {}, instance prod", std::process::id())), "{}", err);
+
+        drop(held);
+        acquire(&path, "prod").unwrap();
+        std::fs::remove_file(&path).ok();
+    }
+}
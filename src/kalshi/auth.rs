@@ -17,7 +17,12 @@ impl KalshiAuth {
     pub fn new(pem_path: &Path, api_key_id: String) -> Result<Self> {
         let pem_content = std::fs::read_to_string(pem_path)
             .with_context(|| format!("Failed to read RSA key from {}", pem_path.display()))?;
-        let private_key = RsaPrivateKey::from_pkcs1_pem(&pem_content)
+        Self::from_pem(&pem_content, api_key_id)
+    }
+
+    /// Build from PEM text already in memory (e.g. a key generated for a test).
+    pub fn from_pem(pem_content: &str, api_key_id: String) -> Result<Self> {
+        let private_key = RsaPrivateKey::from_pkcs1_pem(pem_content)
             .context("Failed to parse RSA private key (PKCS#1 PEM)")?;
         let signing_key = SigningKey::<Sha256>::new(private_key);
         Ok(Self {
@@ -0,0 +1,131 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, info};
+
+/// `[kalshi.circuit]` — stop calling an API that keeps failing.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct CircuitConfig {
+    /// Consecutive 5xx responses or transport failures (timeouts, dropped connections)
+    /// that open the circuit (0 = never).
+    #[serde(default = "default_failures")]
+    pub failures: u32,
+    /// How long an open circuit refuses requests before letting one through again.
+    #[serde(default = "default_open_secs")]
+    pub open_secs: u64,
+}
+
+fn default_failures() -> u32 { 5 }
+fn default_open_secs() -> u64 { 60 }
+
+impl Default for CircuitConfig {
+    fn default() -> Self {
+        Self { failures: default_failures(), open_secs: default_open_secs() }
+    }
+}
+
+/// Consecutive-failure circuit breaker. Once `failures` requests in a row fail, requests
+/// are refused for `open_secs`. After that they go out again on trial: a success closes
+/// the circuit, a failure opens it again straight away.
+pub struct CircuitBreaker {
+    failures: u32,
+    open_for: Duration,
+    state: Mutex<CircuitState>,
+}
+
+#[derive(Default)]
+struct CircuitState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// Human-readable description of the last open or close, until someone takes it.
+    notice: Option<String>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: &CircuitConfig) -> Self {
+        Self {
+            failures: config.failures,
+            open_for: Duration::from_secs(config.open_secs),
+            state: Mutex::new(CircuitState::default()),
+        }
+    }
+
+    /// How much longer the circuit stays open, or None if a request may go out.
+    pub fn open_for(&self, now: Instant) -> Option<Duration> {
+        let until = self.state.lock().unwrap().open_until?;
+        Some(until.saturating_duration_since(now)).filter(|left| !left.is_zero())
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.open_until.take().is_some() {
+            info!("Kalshi API answering again, circuit closed");
+            state.notice = Some("Circuit closed: Kalshi API answering again".to_string());
+        }
+        state.consecutive_failures = 0;
+    }
+
+    /// Count a 5xx or transport failure; opens the circuit once the streak reaches the
+    /// threshold, or at once when a request on trial after an open period fails.
+    pub fn record_failure(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        if self.failures == 0 || state.consecutive_failures < self.failures {
+            return;
+        }
+        let reopened = state.open_until.is_some();
+        state.open_until = Some(now + self.open_for);
+        if !reopened {
+            error!(
+                failures = state.consecutive_failures,
+                open_secs = self.open_for.as_secs(),
+                "Kalshi API failing, circuit open"
+            );
+            state.notice = Some(format!(
+                "Circuit open after {} consecutive failures: requests refused for {}s",
+                state.consecutive_failures,
+                self.open_for.as_secs()
+            ));
+        }
+    }
+
+    /// Take the pending open/close notice, if any (for alerting).
+    pub fn take_notice(&self) -> Option<String> {
+        self.state.lock().unwrap().notice.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_streak_and_closes_on_success() {
+        let circuit = CircuitBreaker::new(&CircuitConfig { failures: 3, open_secs: 60 });
+        let now = Instant::now();
+        circuit.record_failure(now);
+        circuit.record_failure(now);
+        circuit.record_success(); // a success resets the streak
+        circuit.record_failure(now);
+        circuit.record_failure(now);
+        assert_eq!(circuit.open_for(now), None);
+        assert!(circuit.take_notice().is_none());
+
+        circuit.record_failure(now);
+        assert_eq!(circuit.open_for(now + Duration::from_secs(45)), Some(Duration::from_secs(15)));
+        assert!(circuit.take_notice().unwrap().contains("open after 3"));
+
+        // The trial request after the window fails: open again, without a second alert
+        let later = now + Duration::from_secs(60);
+        assert_eq!(circuit.open_for(later), None);
+        circuit.record_failure(later);
+        assert!(circuit.open_for(later).is_some());
+        assert!(circuit.take_notice().is_none());
+
+        circuit.record_success();
+        assert_eq!(circuit.open_for(later), None);
+        assert!(circuit.take_notice().unwrap().contains("closed"));
+    }
+}
@@ -1,48 +1,144 @@
-use anyhow::{bail, Context, Result};
-use reqwest::Client;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, StatusCode};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Instant};
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
+
+use crate::config::KalshiConfig;
+use crate::telemetry::Sampled;
 
 use super::auth::KalshiAuth;
+use super::circuit::CircuitBreaker;
+use super::error::{KalshiApiError, KalshiError};
+use super::failover::Endpoints;
+use super::rate_limit::RateLimiter;
+use super::retry::{RetryConfig, RetryPolicy};
 use super::types::*;
+use super::usage::{ApiUsage, UsageSnapshot};
 
 #[derive(Clone)]
 pub struct KalshiClient {
     http: Client,
-    auth: Arc<KalshiAuth>,
-    base_url: String,
-    last_read: Arc<Mutex<Instant>>,
-    read_delay: Duration,
+    /// None for an observer without a key: requests go out unsigned (public endpoints only).
+    auth: Option<Arc<KalshiAuth>>,
+    /// Observer mode: every non-GET request is refused before it is built.
+    read_only: bool,
+    endpoints: Arc<Endpoints>,
+    /// Refuses requests for a while after a run of 5xx responses or transport failures.
+    circuit: Arc<CircuitBreaker>,
+    /// Paces every request attempt, reads and writes alike (`[kalshi.rate_limit]`).
+    limiter: Arc<RateLimiter>,
+    retry: RetryConfig,
+    usage: Arc<ApiUsage>,
+    pagination_deadline: Duration,
+    series_resume: Arc<Mutex<Option<SeriesResume>>>,
+    /// Client order IDs sent within `SENT_ORDER_ID_TTL`, and when; a second order under one
+    /// is refused before it goes out. Kept in memory only, so it stops an in-process retry
+    /// and nothing more. Across a restart, dedup relies on the exchange: every ID derives
+    /// from its opportunity's UUID, new with each detection, so no later run sends one
+    /// again, and whether a leg sent before a restart landed is answered by looking it up
+    /// on the exchange under its client order ID (`executor::find_order`).
+    sent_order_ids: Arc<std::sync::Mutex<HashMap<String, Instant>>>,
+}
+
+/// Pages of a `list_series` that failed or ran out of time, for the next call to continue.
+#[derive(Debug)]
+struct SeriesResume {
+    cursor: String,
+    series: Vec<Series>,
+    pages: u32,
+    saved_at: Instant,
+}
+
+type Result<T, E = KalshiError> = std::result::Result<T, E>;
+
+/// Saved pages older than this are refetched: the listing may have shifted under the cursor.
+const SERIES_RESUME_MAX_AGE: Duration = Duration::from_secs(900);
+
+/// Sent client order IDs are forgotten after this. Retries of a placement all happen within
+/// one execution, seconds long; an hour is far past any of them.
+const SENT_ORDER_ID_TTL: Duration = Duration::from_secs(3600);
+
+/// Per-order results of a `cancel_orders` batch.
+#[derive(Debug, Default)]
+pub struct CancelOutcomes {
+    pub canceled: Vec<String>,
+    pub failed: Vec<(String, KalshiError)>,
 }
 
 impl KalshiClient {
-    pub fn new(auth: KalshiAuth, base_url: String, read_delay_ms: u64) -> Result<Self> {
-        let http = Client::builder()
+    pub fn new(auth: KalshiAuth, config: &KalshiConfig, read_delay_ms: u64) -> anyhow::Result<Self> {
+        Self::build(Some(auth), false, config, read_delay_ms)
+    }
+
+    /// A client that can only read: orders, cancels, and any other write are refused here,
+    /// whatever the caller does. Without `auth`, requests are sent unsigned, which reaches
+    /// Kalshi's public market data (series, events, orderbooks) but not the portfolio.
+    pub fn observer(auth: Option<KalshiAuth>, config: &KalshiConfig, read_delay_ms: u64) -> anyhow::Result<Self> {
+        Self::build(auth, true, config, read_delay_ms)
+    }
+
+    fn build(
+        auth: Option<KalshiAuth>,
+        read_only: bool,
+        config: &KalshiConfig,
+        read_delay_ms: u64,
+    ) -> anyhow::Result<Self> {
+        let mut builder = Client::builder()
             .timeout(Duration::from_secs(15))
-            .build()
-            .context("Failed to build HTTP client")?;
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms));
+        for (host, addr) in &config.resolve {
+            debug!(host = %host, addr = %addr, "Pinning host address");
+            builder = builder.resolve(host, *addr);
+        }
+        let http = builder.build().context("Failed to build HTTP client")?;
         Ok(Self {
             http,
-            auth: Arc::new(auth),
-            base_url,
-            last_read: Arc::new(Mutex::new(Instant::now())),
-            read_delay: Duration::from_millis(read_delay_ms),
+            auth: auth.map(Arc::new),
+            read_only,
+            endpoints: Arc::new(Endpoints::new(
+                config.base_url.clone(),
+                config.fallback_base_url.clone(),
+                config.failover_after,
+                Duration::from_secs(config.failback_secs),
+            )),
+            circuit: Arc::new(CircuitBreaker::new(&config.circuit)),
+            limiter: Arc::new(RateLimiter::new(&config.rate_limit, &config.usage, read_delay_ms, Instant::now())),
+            retry: config.retry.clone(),
+            usage: Arc::new(ApiUsage::new(&config.usage, Instant::now())),
+            pagination_deadline: Duration::from_secs(config.pagination_deadline_secs),
+            series_resume: Arc::new(Mutex::new(None)),
+            sent_order_ids: Arc::new(std::sync::Mutex::new(HashMap::new())),
         })
     }
 
-    /// Enforce minimum delay between read (GET) requests.
-    async fn throttle_read(&self) {
-        let mut last = self.last_read.lock().await;
-        let elapsed = last.elapsed();
-        if elapsed < self.read_delay {
-            let wait = self.read_delay - elapsed;
-            debug!(wait_ms = wait.as_millis(), "Throttling read request");
-            sleep(wait).await;
-        }
-        *last = Instant::now();
+    /// A client for `base_url` with every other `[kalshi]` setting at its default and reads
+    /// unpaced. Used to point the client at a mock or demo exchange.
+    pub fn with_base_url(auth: KalshiAuth, base_url: &str) -> anyhow::Result<Self> {
+        Self::new(auth, &KalshiConfig::with_base_url(base_url), 0)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Description of the last primary/fallback endpoint switch, if not yet reported.
+    pub fn take_endpoint_notice(&self) -> Option<String> {
+        self.endpoints.take_notice()
+    }
+
+    /// Description of the last circuit breaker open or close, if not yet reported.
+    pub fn take_circuit_notice(&self) -> Option<String> {
+        self.circuit.take_notice()
+    }
+
+    /// Requests sent in the last hour by endpoint class, and the last minute's rates.
+    pub fn usage(&self) -> UsageSnapshot {
+        self.usage.snapshot(Instant::now())
     }
 
     /// Log rate-limit related headers from the response at debug level.
@@ -58,13 +154,17 @@ impl KalshiClient {
         ];
         for name in &headers_to_check {
             if let Some(val) = resp.headers().get(*name) {
-                debug!(
-                    header = *name,
-                    value = ?val,
-                    method = method,
-                    path = path,
-                    "Rate limit header"
-                );
+                static HEADERS: Sampled = Sampled::new();
+                if let Some(dropped) = HEADERS.sample() {
+                    debug!(
+                        header = *name,
+                        value = ?val,
+                        method = method,
+                        path = path,
+                        dropped,
+                        "Rate limit header"
+                    );
+                }
             }
         }
     }
@@ -75,121 +175,187 @@ impl KalshiClient {
             .get("retry-after")
             .and_then(|v| v.to_str().ok())
             .and_then(|s| s.parse::<f64>().ok())
-            .map(|secs| Duration::from_secs_f64(secs))
+            .map(Duration::from_secs_f64)
     }
 
-    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
-        self.throttle_read().await;
-
-        let url = format!("{}{}", self.base_url, path);
-        let max_retries = 3u32;
+    /// Send a signed request, retrying 429 responses per `policy`. Every attempt waits its
+    /// turn with the rate limiter. While the circuit is open, reads and orders are refused
+    /// without a request; cancels still go out, since they take risk off.
+    /// Returns the final response whatever its status; a 429 here means retries ran out.
+    async fn send_with_retry<B: serde::Serialize + ?Sized>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+        policy: &RetryPolicy,
+    ) -> Result<reqwest::Response> {
+        if self.read_only && method != Method::GET {
+            return Err(refused(method.as_str(), path, "client is read-only (observer mode)".to_string()));
+        }
+        if let Some(left) = self.circuit.open_for(Instant::now()).filter(|_| method != Method::DELETE) {
+            let reason = format!("Kalshi API circuit open for {}s more", left.as_secs().max(1));
+            return Err(refused(method.as_str(), path, reason));
+        }
+        let mut attempt = 0u32;
 
-        for attempt in 0..=max_retries {
-            let headers = self.auth.headers("GET", path)?;
-            let mut req = self.http.get(&url);
+        loop {
+            let url = format!("{}{}", self.endpoints.base_url(Instant::now()), path);
+            let headers = match &self.auth {
+                Some(auth) => auth.headers(method.as_str(), path).map_err(|e| KalshiError::Signing {
+                    method: method.to_string(),
+                    path: path.to_string(),
+                    source: e.into(),
+                })?,
+                None => Vec::new(),
+            };
+            let mut req = self.http.request(method.clone(), &url);
+            if let Some(body) = body {
+                req = req.json(body);
+            }
             for (k, v) in &headers {
                 req = req.header(k, v);
             }
+            self.limiter.acquire(&method).await;
+            self.usage.record(method.as_str(), path, Instant::now());
+            #[cfg(feature = "chaos")]
+            crate::chaos::delay(method.as_str(), path).await;
 
-            let resp = req.send().await.context("HTTP GET failed")?;
-            let status = resp.status();
-
-            Self::log_rate_limit_headers(&resp, "GET", path);
-
-            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                if attempt == max_retries {
-                    let body = resp.text().await.unwrap_or_default();
-                    bail!("GET {} rate limited after {} retries: {}", path, max_retries, body);
+            let resp = match req.send().await {
+                Ok(resp) => {
+                    self.endpoints.record_success();
+                    if resp.status().is_server_error() {
+                        self.circuit.record_failure(Instant::now());
+                    } else {
+                        self.circuit.record_success();
+                    }
+                    resp
                 }
-                let wait = Self::parse_retry_after(&resp).unwrap_or_else(|| {
-                    let base = Duration::from_secs(1 << attempt);
-                    base.min(Duration::from_secs(10))
-                });
-                warn!(
-                    path = path,
-                    attempt = attempt + 1,
-                    wait_ms = wait.as_millis(),
-                    "Rate limited (429), backing off"
-                );
-                sleep(wait).await;
-                continue;
-            }
+                Err(e) => {
+                    if e.is_connect() {
+                        self.endpoints.record_connect_failure(Instant::now());
+                    }
+                    self.circuit.record_failure(Instant::now());
+                    self.usage.record_error(method.as_str(), path, &e.to_string());
+                    return Err(transport(method.as_str(), path, e));
+                }
+            };
+            Self::log_rate_limit_headers(&resp, method.as_str(), path);
 
-            if !status.is_success() {
-                let body = resp.text().await.unwrap_or_default();
-                bail!("GET {} returned {}: {}", path, status, body);
+            if resp.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= policy.max_retries {
+                return Ok(resp);
             }
-            return resp.json::<T>().await.context("Failed to parse response");
+            // Jittered either way, so tasks throttled together don't all retry together
+            let wait = match Self::parse_retry_after(&resp) {
+                Some(after) => after + policy.jitter(),
+                None => policy.backoff(attempt),
+            };
+            warn!(
+                method = %method,
+                path = path,
+                attempt = attempt + 1,
+                wait_ms = wait.as_millis(),
+                "Rate limited (429), backing off"
+            );
+            sleep(wait).await;
+            attempt += 1;
         }
-        unreachable!()
     }
 
-    async fn post<T: serde::de::DeserializeOwned, B: serde::Serialize>(
+    /// Turn a final response into a parsed body, or an error describing the failure: a
+    /// `KalshiApiError` for any non-success status, including a 429 that outlasted the
+    /// retries. The body's size and parse time go to the usage metrics.
+    async fn parse_response<T: serde::de::DeserializeOwned>(
         &self,
+        resp: reqwest::Response,
+        method: &str,
         path: &str,
-        body: &B,
     ) -> Result<T> {
-        let url = format!("{}{}", self.base_url, path);
-        let max_retries = 2u32;
-
-        for attempt in 0..=max_retries {
-            let headers = self.auth.headers("POST", path)?;
-            let mut req = self.http.post(&url).json(body);
-            for (k, v) in &headers {
-                req = req.header(k, v);
-            }
-
-            let resp = req.send().await.context("HTTP POST failed")?;
-            let status = resp.status();
-
-            Self::log_rate_limit_headers(&resp, "POST", path);
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            let error = KalshiApiError::new(method, path, status, body);
+            self.usage.record_error(method, path, &error.to_string());
+            return Err(error.into());
+        }
+        let body = resp.bytes().await.map_err(|e| transport(method, path, e))?;
+        let started = Instant::now();
+        let parsed = serde_json::from_slice::<T>(&body)
+            .map_err(|source| KalshiError::Parse { path: path.to_string(), source });
+        if let Some(growth) = self.usage.record_payload(method, path, body.len(), started.elapsed()) {
+            warn!(
+                endpoint = %growth.endpoint,
+                bytes = growth.bytes,
+                baseline_bytes = growth.baseline_bytes,
+                "Response far larger than usual for this endpoint"
+            );
+        }
+        parsed
+    }
 
-            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                if attempt == max_retries {
-                    let body = resp.text().await.unwrap_or_default();
-                    bail!("POST {} rate limited after {} retries: {}", path, max_retries, body);
-                }
-                let wait = Self::parse_retry_after(&resp).unwrap_or_else(|| {
-                    let base = Duration::from_secs(1 << attempt);
-                    base.min(Duration::from_secs(5))
-                });
-                warn!(
-                    path = path,
-                    attempt = attempt + 1,
-                    wait_ms = wait.as_millis(),
-                    "Rate limited (429) on POST, backing off"
-                );
-                sleep(wait).await;
-                continue;
-            }
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let policy = &self.retry.get;
+        let resp = self.send_with_retry::<()>(Method::GET, path, None, policy).await?;
+        self.parse_response(resp, "GET", path).await
+    }
 
-            if !status.is_success() {
-                let body = resp.text().await.unwrap_or_default();
-                bail!("POST {} returned {}: {}", path, status, body);
-            }
-            return resp.json::<T>().await.context("Failed to parse response");
-        }
-        unreachable!()
+    async fn post<T: serde::de::DeserializeOwned, B: serde::Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T> {
+        let policy = &self.retry.post;
+        let resp = self.send_with_retry(Method::POST, path, Some(body), policy).await?;
+        self.parse_response(resp, "POST", path).await
     }
 
-    /// List all series, paginating through all results.
+    /// List all series, paginating through all results. A fetch that fails or passes
+    /// `[kalshi] pagination_deadline_secs` part way keeps its pages, and the next call
+    /// resumes from the last cursor instead of starting over.
     pub async fn list_series(&self) -> Result<Vec<Series>> {
-        let mut all = Vec::new();
-        let mut cursor: Option<String> = None;
+        let started = Instant::now();
+        let (mut all, mut cursor, mut pages) = match self.series_resume.lock().await.take() {
+            Some(saved) if saved.saved_at.elapsed() < SERIES_RESUME_MAX_AGE => {
+                info!(pages = saved.pages, series = saved.series.len(), "Resuming series fetch from saved cursor");
+                (saved.series, Some(saved.cursor), saved.pages)
+            }
+            _ => (Vec::new(), None, 0),
+        };
 
         loop {
             let path = match &cursor {
                 Some(c) => format!("/series?cursor={}", c),
                 None => "/series".to_string(),
             };
-            let resp: SeriesResponse = self.get(&path).await?;
+            let fetched = if started.elapsed() >= self.pagination_deadline {
+                let reason = format!("passed the {}s deadline", self.pagination_deadline.as_secs());
+                Err(refused("GET", &path, reason))
+            } else {
+                self.get::<SeriesResponse>(&path).await
+            };
+            let resp = match fetched {
+                Ok(resp) => resp,
+                Err(e) => {
+                    if let Some(cursor) = cursor {
+                        warn!(pages, series = all.len(), "Series fetch interrupted, keeping pages for the next attempt");
+                        *self.series_resume.lock().await =
+                            Some(SeriesResume { cursor, series: all, pages, saved_at: Instant::now() });
+                    }
+                    return Err(KalshiError::Page { what: "Series fetch", page: pages + 1, source: Box::new(e) });
+                }
+            };
+            pages += 1;
             all.extend(resp.series);
             match resp.cursor {
                 Some(c) if !c.is_empty() => cursor = Some(c),
                 _ => break,
             }
+            if pages % 10 == 0 {
+                info!(pages, series = all.len(), elapsed_ms = started.elapsed().as_millis(), "Series fetch in progress");
+            } else {
+                debug!(pages, series = all.len(), "Fetched series page");
+            }
         }
-        debug!("Fetched {} series", all.len());
+        debug!("Fetched {} series in {} pages", all.len(), pages);
         Ok(all)
     }
 
@@ -220,6 +386,13 @@ impl KalshiClient {
         Ok(all)
     }
 
+    /// Get a single event by ticker, with nested markets.
+    pub async fn get_event(&self, event_ticker: &str) -> Result<Event> {
+        let path = format!("/events/{}?with_nested_markets=true", event_ticker);
+        let resp: EventResponse = self.get(&path).await?;
+        Ok(resp.event)
+    }
+
     /// Get orderbook for a single market.
     pub async fn get_orderbook(&self, ticker: &str) -> Result<Orderbook> {
         let path = format!("/markets/{}/orderbook?depth=5", ticker);
@@ -227,56 +400,210 @@ impl KalshiClient {
         Ok(resp.orderbook)
     }
 
-    /// Place a limit order.
-    pub async fn create_order(&self, req: &CreateOrderRequest) -> Result<Order> {
-        let path = "/portfolio/orders";
-        let resp: CreateOrderResponse = self.post(path, req).await?;
-        Ok(resp.order)
+    /// Get all non-zero market positions in the portfolio, paginating through all results.
+    pub async fn get_positions(&self) -> Result<Vec<MarketPosition>> {
+        let mut all = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let path = match &cursor {
+                Some(c) => format!("/portfolio/positions?count_filter=position&cursor={}", c),
+                None => "/portfolio/positions?count_filter=position".to_string(),
+            };
+            let resp: PositionsResponse = self.get(&path).await?;
+            all.extend(resp.market_positions);
+            match resp.cursor {
+                Some(c) if !c.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+        debug!("Fetched {} positions", all.len());
+        Ok(all)
     }
 
-    /// Cancel an order by ID.
-    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
-        let path = format!("/portfolio/orders/{}", order_id);
-        let url = format!("{}{}", self.base_url, path);
-        let max_retries = 2u32;
+    /// Non-zero positions in one event's markets (first page only).
+    pub async fn get_event_positions(&self, event_ticker: &str) -> Result<Vec<MarketPosition>> {
+        let path = format!("/portfolio/positions?count_filter=position&event_ticker={}", event_ticker);
+        let resp: PositionsResponse = self.get(&path).await?;
+        Ok(resp.market_positions)
+    }
 
-        for attempt in 0..=max_retries {
-            let headers = self.auth.headers("DELETE", &path)?;
-            let mut req = self.http.delete(&url);
-            for (k, v) in &headers {
-                req = req.header(k, v);
+    /// Get the account's available balance, in cents.
+    pub async fn get_balance(&self) -> Result<i64> {
+        let resp: BalanceResponse = self.get("/portfolio/balance").await?;
+        debug!("Balance: {}c", resp.balance);
+        Ok(resp.balance)
+    }
+
+    /// Get every settled market the account held a position in, paginating through all results.
+    pub async fn get_settlements(&self) -> Result<Vec<Settlement>> {
+        let mut all = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let path = match &cursor {
+                Some(c) => format!("/portfolio/settlements?cursor={}", c),
+                None => "/portfolio/settlements".to_string(),
+            };
+            let resp: SettlementsResponse = self.get(&path).await?;
+            all.extend(resp.settlements);
+            match resp.cursor {
+                Some(c) if !c.is_empty() => cursor = Some(c),
+                _ => break,
             }
+        }
+        debug!("Fetched {} settlements", all.len());
+        Ok(all)
+    }
 
-            let resp = req.send().await.context("HTTP DELETE failed")?;
-            let status = resp.status();
+    /// Fetch this account's fills since `since`, paginating through all results.
+    pub async fn get_fills(&self, since: DateTime<Utc>) -> Result<Vec<Fill>> {
+        let mut all = Vec::new();
+        let mut cursor: Option<String> = None;
 
-            Self::log_rate_limit_headers(&resp, "DELETE", &path);
+        loop {
+            let path = match &cursor {
+                Some(c) => format!("/portfolio/fills?min_ts={}&cursor={}", since.timestamp(), c),
+                None => format!("/portfolio/fills?min_ts={}", since.timestamp()),
+            };
+            let resp: FillsResponse = self.get(&path).await?;
+            all.extend(resp.fills);
+            match resp.cursor {
+                Some(c) if !c.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+        debug!("Fetched {} fills", all.len());
+        Ok(all)
+    }
 
-            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                if attempt == max_retries {
-                    warn!("Cancel order {} rate limited after {} retries", order_id, max_retries);
-                    return Ok(());
-                }
-                let wait = Self::parse_retry_after(&resp).unwrap_or_else(|| {
-                    let base = Duration::from_secs(1 << attempt);
-                    base.min(Duration::from_secs(5))
-                });
-                warn!(
-                    order_id = order_id,
-                    attempt = attempt + 1,
-                    wait_ms = wait.as_millis(),
-                    "Rate limited (429) on DELETE, backing off"
-                );
-                sleep(wait).await;
-                continue;
+    /// Contracts of a market traded since `since`, by anyone. Pages through trades only until
+    /// `enough` have traded, so a busy market costs one request however much it trades.
+    pub async fn recent_volume(&self, ticker: &str, since: DateTime<Utc>, enough: i64) -> Result<i64> {
+        let mut volume = 0;
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let path = match &cursor {
+                Some(c) => format!("/markets/trades?ticker={}&min_ts={}&cursor={}", ticker, since.timestamp(), c),
+                None => format!("/markets/trades?ticker={}&min_ts={}", ticker, since.timestamp()),
+            };
+            let resp: TradesResponse = self.get(&path).await?;
+            volume += resp.trades.iter().map(|t| t.count).sum::<i64>();
+            match resp.cursor {
+                Some(c) if !c.is_empty() && volume < enough => cursor = Some(c),
+                _ => break,
+            }
+        }
+        debug!(ticker, volume, "Fetched recent volume");
+        Ok(volume)
+    }
+
+    /// List portfolio orders, optionally filtered by status (e.g. "resting"),
+    /// paginating through all results.
+    pub async fn get_orders(&self, status: Option<&str>) -> Result<Vec<Order>> {
+        let mut all = Vec::new();
+        let mut cursor: Option<String> = None;
+        let filter = status.map(|s| format!("status={}", s)).unwrap_or_default();
+
+        loop {
+            let path = match &cursor {
+                Some(c) => format!("/portfolio/orders?{}&cursor={}", filter, c),
+                None => format!("/portfolio/orders?{}", filter),
+            };
+            let resp: OrdersResponse = self.get(&path).await?;
+            all.extend(resp.orders);
+            match resp.cursor {
+                Some(c) if !c.is_empty() => cursor = Some(c),
+                _ => break,
             }
+        }
+        debug!("Fetched {} orders", all.len());
+        Ok(all)
+    }
+
+    /// Orders on one market, newest first as the exchange lists them (first page only).
+    pub async fn get_market_orders(&self, ticker: &str) -> Result<Vec<Order>> {
+        let path = format!("/portfolio/orders?ticker={}", ticker);
+        let resp: OrdersResponse = self.get(&path).await?;
+        Ok(resp.orders)
+    }
+
+    /// Resting orders in one event's markets (first page only).
+    pub async fn get_event_resting_orders(&self, event_ticker: &str) -> Result<Vec<Order>> {
+        let path = format!("/portfolio/orders?event_ticker={}&status=resting", event_ticker);
+        let resp: OrdersResponse = self.get(&path).await?;
+        Ok(resp.orders)
+    }
 
-            if !status.is_success() {
-                let body = resp.text().await.unwrap_or_default();
-                warn!("Cancel order {} returned {}: {}", order_id, status, body);
+    /// Place a limit order. An order whose client order ID this client has sent before is
+    /// refused: whatever became of the first one, a second would double the leg.
+    pub async fn create_order(&self, req: &CreateOrderRequest) -> Result<Order> {
+        if let Some(id) = &req.client_order_id {
+            let now = Instant::now();
+            let mut sent = self.sent_order_ids.lock().unwrap();
+            sent.retain(|_, at| now.duration_since(*at) < SENT_ORDER_ID_TTL);
+            if sent.insert(id.clone(), now).is_some() {
+                let reason = format!("order {} already sent, refusing a duplicate", id);
+                return Err(refused("POST", "/portfolio/orders", reason));
             }
+        }
+        self.resend_order(req).await
+    }
+
+    /// Send an order whose earlier attempt failed without reaching the book (a 5xx reply,
+    /// and no order under its client order ID on the exchange). Skips the duplicate check:
+    /// the exchange itself refuses a client order ID it already has.
+    pub async fn resend_order(&self, req: &CreateOrderRequest) -> Result<Order> {
+        let path = "/portfolio/orders";
+        let resp: OrderResponse = self.post(path, req).await?;
+        Ok(resp.order)
+    }
+
+    /// Get the current state of one order.
+    pub async fn get_order(&self, order_id: &str) -> Result<Order> {
+        let path = format!("/portfolio/orders/{}", order_id);
+        let resp: OrderResponse = self.get(&path).await?;
+        Ok(resp.order)
+    }
+
+    /// Cancel an order by ID. Fails if the exchange doesn't confirm the cancel, including
+    /// when it stays rate limited through every retry (`ApiErrorKind::RateLimited`).
+    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        #[cfg(feature = "chaos")]
+        if crate::chaos::drop_cancel(order_id) {
             return Ok(());
         }
-        unreachable!()
+        let path = format!("/portfolio/orders/{}", order_id);
+        let policy = &self.retry.delete;
+        let resp = self.send_with_retry::<()>(Method::DELETE, &path, None, policy).await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(KalshiApiError::new("DELETE", &path, status, body).into());
+        }
+        Ok(())
+    }
+
+    /// Cancel each order in turn, paced by the write rate limit, and report which were
+    /// cancelled and why the rest weren't.
+    pub async fn cancel_orders(&self, order_ids: &[String]) -> CancelOutcomes {
+        let mut outcomes = CancelOutcomes::default();
+        for order_id in order_ids {
+            match self.cancel_order(order_id).await {
+                Ok(()) => outcomes.canceled.push(order_id.clone()),
+                Err(e) => outcomes.failed.push((order_id.clone(), e)),
+            }
+        }
+        debug!(canceled = outcomes.canceled.len(), failed = outcomes.failed.len(), "Bulk cancel finished");
+        outcomes
     }
 }
+
+fn refused(method: &str, path: &str, reason: String) -> KalshiError {
+    KalshiError::Refused { method: method.to_string(), path: path.to_string(), reason }
+}
+
+fn transport(method: &str, path: &str, source: reqwest::Error) -> KalshiError {
+    KalshiError::Transport { method: method.to_string(), path: path.to_string(), source }
+}
@@ -0,0 +1,227 @@
+// Kalshi API failures, classified by what the caller can do about them. Every client call
+// fails with a `KalshiError`, so callers branch on the variant (or on `kind()` for a reply
+// the exchange refused) instead of the message; past the caller it's an anyhow error like
+// any other, and `KalshiError::of` finds it again under the added context.
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// What went wrong, from the status and Kalshi's error code or message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorKind {
+    /// 401/403: the key is wrong, revoked, or the clock is off far enough to void the signature.
+    Unauthorized,
+    InsufficientBalance,
+    MarketClosed,
+    /// The limit price is outside what the exchange accepts for the market right now.
+    PriceOutOfBand,
+    /// The order would trade against one of our own resting orders.
+    SelfCross,
+    /// Still 429 after the retry policy ran out; sending more would only extend the throttle.
+    RateLimited,
+    /// 5xx: the exchange may or may not have acted on the request.
+    Server,
+    /// Any other refusal.
+    Rejected,
+}
+
+/// What to do about a failed order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Send the same order again; nothing about it was wrong.
+    Retry,
+    /// The price was the problem: leave the leg and let a fresh quote price it again.
+    Reprice,
+    /// Sending it again can't help.
+    Abort,
+}
+
+impl ApiErrorKind {
+    pub fn action(self) -> ErrorAction {
+        match self {
+            ApiErrorKind::Server => ErrorAction::Retry,
+            ApiErrorKind::PriceOutOfBand => ErrorAction::Reprice,
+            ApiErrorKind::Unauthorized
+            | ApiErrorKind::InsufficientBalance
+            | ApiErrorKind::MarketClosed
+            | ApiErrorKind::SelfCross
+            | ApiErrorKind::RateLimited
+            | ApiErrorKind::Rejected => ErrorAction::Abort,
+        }
+    }
+
+    /// Classify a non-success reply. Kalshi sends `{"error": {"code", "message"}}`; the code is
+    /// checked first, then the message, since not every refusal has a specific code.
+    pub fn classify(status: StatusCode, body: &str) -> Self {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return ApiErrorKind::RateLimited;
+        }
+        if status.is_server_error() {
+            return ApiErrorKind::Server;
+        }
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            return ApiErrorKind::Unauthorized;
+        }
+        let error = serde_json::from_str::<serde_json::Value>(body).ok().map(|v| v["error"].clone());
+        let field = |name: &str| {
+            error.as_ref().and_then(|e| e[name].as_str()).unwrap_or_default().to_ascii_lowercase()
+        };
+        let (code, message) = (field("code"), field("message"));
+        let text = if code.is_empty() && message.is_empty() {
+            body.to_ascii_lowercase()
+        } else {
+            code + " " + &message
+        };
+        let any = |needles: &[&str]| needles.iter().any(|n| text.contains(n));
+        if any(&["insufficient_balance", "insufficient balance", "insufficient funds"]) {
+            ApiErrorKind::InsufficientBalance
+        } else if any(&["market_closed", "market closed", "market_not_open", "market is not open", "trading closed"]) {
+            ApiErrorKind::MarketClosed
+        } else if any(&["self_cross", "self-cross", "self cross", "cross your own", "post_only_cross"]) {
+            ApiErrorKind::SelfCross
+        } else if any(&["price_out_of_band", "out of band", "price band", "invalid_price", "price out of range"]) {
+            ApiErrorKind::PriceOutOfBand
+        } else {
+            ApiErrorKind::Rejected
+        }
+    }
+}
+
+impl std::fmt::Display for ApiErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ApiErrorKind::Unauthorized => "unauthorized",
+            ApiErrorKind::InsufficientBalance => "insufficient balance",
+            ApiErrorKind::MarketClosed => "market closed",
+            ApiErrorKind::PriceOutOfBand => "price out of band",
+            ApiErrorKind::SelfCross => "self-cross",
+            ApiErrorKind::RateLimited => "rate limited",
+            ApiErrorKind::Server => "server error",
+            ApiErrorKind::Rejected => "rejected",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A non-success reply to `method path`.
+#[derive(Debug, thiserror::Error)]
+#[error("{method} {path} returned {status} ({kind}): {body}")]
+pub struct KalshiApiError {
+    pub method: String,
+    pub path: String,
+    pub status: StatusCode,
+    pub kind: ApiErrorKind,
+    pub body: String,
+}
+
+impl KalshiApiError {
+    pub fn new(method: &str, path: &str, status: StatusCode, body: String) -> Self {
+        let kind = ApiErrorKind::classify(status, &body);
+        Self { method: method.to_string(), path: path.to_string(), status, kind, body }
+    }
+}
+
+/// Why a `KalshiClient` call failed.
+#[derive(Debug, thiserror::Error)]
+pub enum KalshiError {
+    /// The exchange answered, with a non-success status.
+    #[error(transparent)]
+    Api(#[from] KalshiApiError),
+    /// Nothing went out: observer mode, an open circuit, a duplicate client order ID, or a
+    /// fetch past its deadline.
+    #[error("{method} {path} refused: {reason}")]
+    Refused { method: String, path: String, reason: String },
+    /// The request couldn't be signed (a bad or unreadable private key).
+    #[error("Failed to sign {method} {path}")]
+    Signing {
+        method: String,
+        path: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// No reply, or one cut off: DNS, connect, timeout, a dropped connection.
+    #[error("HTTP {method} {path} failed")]
+    Transport {
+        method: String,
+        path: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    /// A reply that isn't the JSON the endpoint documents.
+    #[error("Failed to parse {path} response")]
+    Parse {
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A paginated fetch that failed part way.
+    #[error("{what} failed on page {page}")]
+    Page {
+        what: &'static str,
+        page: u32,
+        #[source]
+        source: Box<KalshiError>,
+    },
+}
+
+impl KalshiError {
+    /// The classification of a reply the exchange refused; None for anything that never
+    /// got one (transport, signing, refused locally) or didn't parse.
+    pub fn kind(&self) -> Option<ApiErrorKind> {
+        match self {
+            KalshiError::Api(e) => Some(e.kind),
+            KalshiError::Page { source, .. } => source.kind(),
+            _ => None,
+        }
+    }
+
+    /// The credentials are the problem: retrying won't help until someone fixes them.
+    pub fn is_auth(&self) -> bool {
+        matches!(self, KalshiError::Signing { .. }) || self.kind() == Some(ApiErrorKind::Unauthorized)
+    }
+
+    /// The `KalshiError` under `e`'s context, if a client call is where it started.
+    pub fn of(e: &anyhow::Error) -> Option<&KalshiError> {
+        e.downcast_ref::<KalshiError>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classifies_by_code_then_message() {
+        let classify = ApiErrorKind::classify;
+        let body = r#"{"error":{"code":"insufficient_balance","message":"Insufficient balance"}}"#;
+        assert_eq!(classify(StatusCode::BAD_REQUEST, body), ApiErrorKind::InsufficientBalance);
+        let body = r#"{"error":{"code":"market_closed","message":"market is closed"}}"#;
+        assert_eq!(classify(StatusCode::BAD_REQUEST, body), ApiErrorKind::MarketClosed);
+        let body = r#"{"error":{"code":"invalid_order","message":"order would self-cross"}}"#;
+        assert_eq!(classify(StatusCode::CONFLICT, body), ApiErrorKind::SelfCross);
+        let body = r#"{"error":{"code":"invalid_price","message":"price is out of band"}}"#;
+        assert_eq!(classify(StatusCode::BAD_REQUEST, body), ApiErrorKind::PriceOutOfBand);
+        assert_eq!(classify(StatusCode::BAD_REQUEST, "not json"), ApiErrorKind::Rejected);
+        assert_eq!(classify(StatusCode::BAD_GATEWAY, body), ApiErrorKind::Server);
+        assert_eq!(classify(StatusCode::TOO_MANY_REQUESTS, ""), ApiErrorKind::RateLimited);
+        assert_eq!(classify(StatusCode::UNAUTHORIZED, body), ApiErrorKind::Unauthorized);
+
+        assert_eq!(ApiErrorKind::Server.action(), ErrorAction::Retry);
+        assert_eq!(ApiErrorKind::RateLimited.action(), ErrorAction::Abort);
+        assert_eq!(ApiErrorKind::PriceOutOfBand.action(), ErrorAction::Reprice);
+        assert_eq!(ApiErrorKind::InsufficientBalance.action(), ErrorAction::Abort);
+    }
+
+    #[test]
+    fn test_kind_survives_pages_and_context() {
+        let refused = KalshiApiError::new("GET", "/portfolio/balance", StatusCode::UNAUTHORIZED, String::new());
+        let error = KalshiError::Page { what: "Series fetch", page: 3, source: Box::new(refused.into()) };
+        assert_eq!(error.kind(), Some(ApiErrorKind::Unauthorized));
+        assert!(error.is_auth());
+
+        let error = anyhow::Error::new(error).context("Failed to list series");
+        assert!(KalshiError::of(&error).is_some_and(KalshiError::is_auth));
+        assert!(KalshiError::of(&anyhow::anyhow!("something else")).is_none());
+    }
+}
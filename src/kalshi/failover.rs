@@ -0,0 +1,145 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, info};
+
+/// Primary/fallback base URLs with connect-failure tracking.
+/// After `failover_after` consecutive connect-level failures (DNS, refused, connect timeout)
+/// on the primary, requests switch to the fallback. After `failback_after` on the fallback,
+/// the primary is tried again.
+pub struct Endpoints {
+    primary: String,
+    fallback: Option<String>,
+    failover_after: u32,
+    failback_after: Duration,
+    state: Mutex<EndpointState>,
+}
+
+#[derive(Default)]
+struct EndpointState {
+    on_fallback_since: Option<Instant>,
+    connect_failures: u32,
+    /// Human-readable description of the last switch, until someone takes it.
+    notice: Option<String>,
+}
+
+impl Endpoints {
+    pub fn new(
+        primary: String,
+        fallback: Option<String>,
+        failover_after: u32,
+        failback_after: Duration,
+    ) -> Self {
+        Self {
+            primary,
+            fallback,
+            failover_after: failover_after.max(1),
+            failback_after,
+            state: Mutex::new(EndpointState::default()),
+        }
+    }
+
+    /// Base URL to use for the next request.
+    pub fn base_url(&self, now: Instant) -> String {
+        let mut state = self.state.lock().unwrap();
+        if let (Some(since), Some(fallback)) = (state.on_fallback_since, &self.fallback) {
+            if now.duration_since(since) < self.failback_after {
+                return fallback.clone();
+            }
+            info!(primary = %self.primary, "Failback window elapsed, retrying primary endpoint");
+            state.on_fallback_since = None;
+            state.connect_failures = 0;
+            state.notice = Some(format!("Retrying primary endpoint {}", self.primary));
+        }
+        self.primary.clone()
+    }
+
+    /// Any response (even an error status) proves the endpoint is reachable.
+    pub fn record_success(&self) {
+        self.state.lock().unwrap().connect_failures = 0;
+    }
+
+    /// Count a connect-level failure; switches to the fallback once the threshold is hit.
+    pub fn record_connect_failure(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        state.connect_failures += 1;
+        let Some(fallback) = &self.fallback else {
+            return;
+        };
+        if state.on_fallback_since.is_none() && state.connect_failures >= self.failover_after {
+            error!(
+                primary = %self.primary,
+                fallback = %fallback,
+                failures = state.connect_failures,
+                "Primary endpoint unreachable, failing over"
+            );
+            state.on_fallback_since = Some(now);
+            state.connect_failures = 0;
+            state.notice = Some(format!(
+                "Failed over from {} to {} after {} connect failures",
+                self.primary, fallback, self.failover_after
+            ));
+        }
+    }
+
+    /// Take the pending switch notice, if any (for alerting).
+    pub fn take_notice(&self) -> Option<String> {
+        self.state.lock().unwrap().notice.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints() -> Endpoints {
+        Endpoints::new(
+            "https://primary".into(),
+            Some("https://fallback".into()),
+            3,
+            Duration::from_secs(600),
+        )
+    }
+
+    #[test]
+    fn test_fails_over_after_consecutive_connect_failures() {
+        let ep = endpoints();
+        let now = Instant::now();
+        ep.record_connect_failure(now);
+        ep.record_connect_failure(now);
+        ep.record_success(); // a response resets the streak
+        ep.record_connect_failure(now);
+        ep.record_connect_failure(now);
+        assert_eq!(ep.base_url(now), "https://primary");
+        assert!(ep.take_notice().is_none());
+
+        ep.record_connect_failure(now);
+        assert_eq!(ep.base_url(now), "https://fallback");
+        assert!(ep.take_notice().unwrap().contains("Failed over"));
+        assert!(ep.take_notice().is_none());
+    }
+
+    #[test]
+    fn test_fails_back_after_window() {
+        let ep = endpoints();
+        let now = Instant::now();
+        for _ in 0..3 {
+            ep.record_connect_failure(now);
+        }
+        ep.take_notice();
+        assert_eq!(ep.base_url(now + Duration::from_secs(599)), "https://fallback");
+        assert_eq!(ep.base_url(now + Duration::from_secs(600)), "https://primary");
+        assert!(ep.take_notice().unwrap().contains("primary"));
+    }
+
+    #[test]
+    fn test_no_fallback_stays_on_primary() {
+        let ep = Endpoints::new("https://primary".into(), None, 1, Duration::from_secs(60));
+        let now = Instant::now();
+        for _ in 0..10 {
+            ep.record_connect_failure(now);
+        }
+        assert_eq!(ep.base_url(now), "https://primary");
+        assert!(ep.take_notice().is_none());
+    }
+}
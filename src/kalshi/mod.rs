@@ -1,3 +1,10 @@
 pub mod auth;
+pub mod circuit;
 pub mod client;
+pub mod error;
+pub mod failover;
+pub mod rate_limit;
+pub mod retry;
 pub mod types;
+pub mod usage;
+pub mod ws;
@@ -0,0 +1,118 @@
+use reqwest::Method;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+use tracing::debug;
+
+use crate::telemetry::Sampled;
+
+use super::usage::UsageConfig;
+
+/// `[kalshi.rate_limit]` — requests per second the client sends, reads (GET) and writes
+/// (POST, DELETE) paced separately. Unset reads follow `[scanner] scan_delay_ms`, unset
+/// writes `[kalshi.usage] writes_per_sec`; 0 = unlimited.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub reads_per_sec: Option<f64>,
+    #[serde(default)]
+    pub writes_per_sec: Option<f64>,
+}
+
+/// Tokens refill at `rate` per second up to one second's worth, so a burst up to the rate
+/// goes out at once (an arb's legs) and a longer run is paced. A request that finds the
+/// bucket empty takes its token on credit and waits until it would have refilled.
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, now: Instant) -> Self {
+        let capacity = rate.max(1.0);
+        Self { rate, capacity, state: Mutex::new((capacity, now)) }
+    }
+
+    /// Take a token at `now`, returning how long to wait before using it.
+    fn reserve(&self, now: Instant) -> Duration {
+        if self.rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        let mut state = self.state.lock().unwrap();
+        let (tokens, last) = *state;
+        let refilled = now.saturating_duration_since(last).as_secs_f64() * self.rate;
+        let tokens = (tokens + refilled).min(self.capacity) - 1.0;
+        *state = (tokens, now.max(last));
+        if tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-tokens / self.rate)
+        }
+    }
+
+    pub async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            static WAITS: Sampled = Sampled::new();
+            if let Some(dropped) = WAITS.sample() {
+                debug!(wait_ms = wait.as_millis(), dropped, "Rate limiting request");
+            }
+            sleep(wait).await;
+        }
+    }
+}
+
+/// One bucket per request class, shared by every clone of a client.
+pub struct RateLimiter {
+    reads: TokenBucket,
+    writes: TokenBucket,
+}
+
+impl RateLimiter {
+    /// `read_delay_ms` is the read pacing when `reads_per_sec` is unset (0 = unlimited).
+    pub fn new(config: &RateLimitConfig, usage: &UsageConfig, read_delay_ms: u64, now: Instant) -> Self {
+        let default_reads = if read_delay_ms == 0 { 0.0 } else { 1_000.0 / read_delay_ms as f64 };
+        let reads = config.reads_per_sec.unwrap_or(default_reads);
+        let writes = config.writes_per_sec.unwrap_or(usage.writes_per_sec as f64);
+        Self { reads: TokenBucket::new(reads, now), writes: TokenBucket::new(writes, now) }
+    }
+
+    /// Wait for a token in `method`'s class.
+    pub async fn acquire(&self, method: &Method) {
+        if *method == Method::GET {
+            self.reads.acquire().await
+        } else {
+            self.writes.acquire().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_bursts_to_its_rate_then_paces() {
+        let start = Instant::now();
+        let bucket = TokenBucket::new(4.0, start);
+        // A second's worth goes out at once...
+        for _ in 0..4 {
+            assert_eq!(bucket.reserve(start), Duration::ZERO);
+        }
+        // ...then each request waits its turn, queued behind the ones already waiting
+        assert_eq!(bucket.reserve(start), Duration::from_millis(250));
+        assert_eq!(bucket.reserve(start), Duration::from_millis(500));
+        // Refills while idle, never past one second's worth
+        let later = start + Duration::from_secs(10);
+        for _ in 0..4 {
+            assert_eq!(bucket.reserve(later), Duration::ZERO);
+        }
+        assert!(bucket.reserve(later) > Duration::ZERO);
+        // 0 = unlimited
+        let open = TokenBucket::new(0.0, start);
+        assert!((0..100).all(|_| open.reserve(start).is_zero()));
+    }
+}
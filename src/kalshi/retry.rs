@@ -0,0 +1,103 @@
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Retry behaviour for one request class (GET, POST, or DELETE) on 429 responses.
+/// Backoff is `base * 2^attempt`, capped, plus uniform random jitter. A `Retry-After`
+/// header from the server takes the place of the computed backoff, jitter still added.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub backoff_base_ms: u64,
+    pub backoff_cap_ms: u64,
+    #[serde(default = "default_jitter_ms")]
+    pub jitter_ms: u64,
+}
+
+fn default_jitter_ms() -> u64 { 250 }
+
+impl RetryPolicy {
+    /// Backoff before retry number `attempt + 1`, without jitter.
+    pub fn base_backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+        let ms = self.backoff_base_ms.saturating_mul(factor).min(self.backoff_cap_ms);
+        Duration::from_millis(ms)
+    }
+
+    /// Backoff before retry number `attempt + 1`, including jitter.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.base_backoff(attempt) + self.jitter()
+    }
+
+    /// Uniform random extra wait in `0..=jitter_ms`, added to every retry wait.
+    pub fn jitter(&self) -> Duration {
+        if self.jitter_ms == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::thread_rng().gen_range(0..=self.jitter_ms))
+    }
+
+    fn default_get() -> Self {
+        Self { max_retries: 3, backoff_base_ms: 1_000, backoff_cap_ms: 10_000, jitter_ms: default_jitter_ms() }
+    }
+
+    fn default_write() -> Self {
+        Self { max_retries: 2, backoff_base_ms: 1_000, backoff_cap_ms: 5_000, jitter_ms: default_jitter_ms() }
+    }
+}
+
+/// `[kalshi.retry]` — one policy per request class.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RetryConfig {
+    #[serde(default = "RetryPolicy::default_get")]
+    pub get: RetryPolicy,
+    #[serde(default = "RetryPolicy::default_write")]
+    pub post: RetryPolicy,
+    #[serde(default = "RetryPolicy::default_write")]
+    pub delete: RetryPolicy,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            get: RetryPolicy::default_get(),
+            post: RetryPolicy::default_write(),
+            delete: RetryPolicy::default_write(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_then_caps() {
+        let p = RetryPolicy { max_retries: 5, backoff_base_ms: 250, backoff_cap_ms: 1_500, jitter_ms: 0 };
+        let waits: Vec<u64> = (0..5).map(|a| p.backoff(a).as_millis() as u64).collect();
+        assert_eq!(waits, vec![250, 500, 1_000, 1_500, 1_500]);
+        // Huge attempt numbers saturate instead of overflowing
+        assert_eq!(p.base_backoff(200), Duration::from_millis(1_500));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_range() {
+        let p = RetryPolicy { max_retries: 1, backoff_base_ms: 100, backoff_cap_ms: 100, jitter_ms: 50 };
+        for _ in 0..100 {
+            let ms = p.backoff(0).as_millis();
+            assert!((100..=150).contains(&ms), "backoff {}ms out of range", ms);
+        }
+    }
+
+    #[test]
+    fn test_defaults_match_previous_hardcoded_values() {
+        let cfg: RetryConfig = toml::from_str("[post]\nmax_retries = 0\nbackoff_base_ms = 10\nbackoff_cap_ms = 10\n").unwrap();
+        assert_eq!(cfg.get.max_retries, 3);
+        assert_eq!(cfg.get.backoff_cap_ms, 10_000);
+        assert_eq!(cfg.post.max_retries, 0);
+        assert_eq!(cfg.delete.max_retries, 2);
+        assert_eq!(cfg.delete.backoff_cap_ms, 5_000);
+        assert_eq!((cfg.get.jitter_ms, cfg.post.jitter_ms), (250, 250));
+    }
+}
@@ -1,5 +1,8 @@
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
 
 // --- Series ---
 
@@ -13,7 +16,6 @@ pub struct SeriesResponse {
 pub struct Series {
     pub ticker: String,
     pub title: String,
-    pub status: Option<String>,
 }
 
 // --- Events ---
@@ -24,12 +26,19 @@ pub struct EventsResponse {
     pub cursor: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct EventResponse {
+    pub event: Event,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Event {
     pub event_ticker: String,
     pub title: String,
     pub mutually_exclusive: bool,
-    pub status: Option<String>,
+    /// When the event's outcome is determined, for events that have a single date.
+    #[serde(default)]
+    pub strike_date: Option<DateTime<Utc>>,
     #[serde(default)]
     pub markets: Vec<Market>,
 }
@@ -42,7 +51,83 @@ pub struct Market {
     pub title: String,
     pub subtitle: Option<String>,
     pub status: String,
+    /// "yes" / "no" once determined; Kalshi sends "" while undetermined.
     pub result: Option<String>,
+    #[serde(default)]
+    pub close_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub expected_expiration_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub expiration_time: Option<DateTime<Utc>>,
+    /// Seconds from the outcome being determined to the payout.
+    #[serde(default)]
+    pub settlement_timer_seconds: Option<i64>,
+    /// What a YES on this market means, e.g. "3.4% to 3.6%".
+    #[serde(default)]
+    pub yes_sub_title: Option<String>,
+    /// "between", "greater", "greater_or_equal", "less", "less_or_equal", or a
+    /// non-numeric kind ("custom", "structured", ...).
+    #[serde(default)]
+    pub strike_type: Option<String>,
+    #[serde(default)]
+    pub floor_strike: Option<f64>,
+    #[serde(default)]
+    pub cap_strike: Option<f64>,
+}
+
+impl Market {
+    /// Exchange status says the market is accepting orders.
+    pub fn is_open_status(&self) -> bool {
+        self.status == "active" || self.status == "open"
+    }
+
+    /// The determined outcome ("yes" / "no"), if any. Empty strings are treated as undetermined.
+    pub fn settled_result(&self) -> Option<&str> {
+        match self.result.as_deref() {
+            Some(r) if r.eq_ignore_ascii_case("yes") => Some("yes"),
+            Some(r) if r.eq_ignore_ascii_case("no") => Some("no"),
+            _ => None,
+        }
+    }
+
+    /// True if the outcome is known or trading is over, even when `status` still lags at "active".
+    /// Checks lifecycle status, a populated result, and close/expiration timestamps in the past.
+    pub fn is_determined(&self, now: DateTime<Utc>) -> bool {
+        if matches!(
+            self.status.as_str(),
+            "closed" | "determined" | "settled" | "finalized"
+        ) {
+            return true;
+        }
+        if self.settled_result().is_some() {
+            return true;
+        }
+        [self.close_time, self.expiration_time]
+            .iter()
+            .flatten()
+            .any(|t| *t <= now)
+    }
+
+    /// Which bracket this is, for people reading logs and alerts: Kalshi's YES subtitle or
+    /// subtitle, else the range parsed from the strikes. None when there's neither.
+    pub fn bracket_label(&self) -> Option<String> {
+        let subtitle = [&self.yes_sub_title, &self.subtitle]
+            .into_iter()
+            .flatten()
+            .map(|s| s.trim())
+            .find(|s| !s.is_empty());
+        if let Some(subtitle) = subtitle {
+            return Some(subtitle.to_string());
+        }
+        match (self.strike_type.as_deref()?, self.floor_strike, self.cap_strike) {
+            ("between", Some(floor), Some(cap)) => Some(format!("{}–{}", floor, cap)),
+            ("greater", Some(floor), _) => Some(format!("above {}", floor)),
+            ("greater_or_equal", Some(floor), _) => Some(format!("{} or above", floor)),
+            ("less", _, Some(cap)) => Some(format!("below {}", cap)),
+            ("less_or_equal", _, Some(cap)) => Some(format!("{} or below", cap)),
+            _ => None,
+        }
+    }
 }
 
 // --- Orderbook ---
@@ -52,7 +137,7 @@ pub struct OrderbookResponse {
     pub orderbook: Orderbook,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Orderbook {
     #[serde(default, deserialize_with = "null_as_empty_vec")]
     pub yes: Vec<PriceLevel>,
@@ -70,7 +155,8 @@ where
 }
 
 /// A price level on the orderbook.
-/// Kalshi returns each level as a JSON tuple `[price_cents, quantity]`.
+/// Kalshi returns each level as a JSON tuple `[price_cents, quantity]`; recorded
+/// snapshots use the same form.
 #[derive(Debug, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct PriceLevel {
@@ -79,6 +165,15 @@ pub struct PriceLevel {
     pub quantity: i64,
 }
 
+impl Serialize for PriceLevel {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        (self.price, self.quantity).serialize(serializer)
+    }
+}
+
 impl<'de> Deserialize<'de> for PriceLevel {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -101,14 +196,32 @@ pub struct CreateOrderRequest {
     pub count: u32,
     pub yes_price: Option<i64>,
     pub no_price: Option<i64>,
+    /// "immediate_or_cancel" for orders that must not rest; omitted = good until cancelled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_in_force: Option<String>,
+    /// Unix time (seconds) at which the exchange cancels whatever of the order still rests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_ts: Option<i64>,
+    /// Our own ID for the order, echoed back by the exchange, so a leg whose reply never
+    /// arrived can still be found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct CreateOrderResponse {
-    pub order: Order,
+pub struct OrdersResponse {
+    #[serde(default)]
+    pub orders: Vec<Order>,
+    pub cursor: Option<String>,
 }
 
+/// Single-order envelope returned by create and get.
 #[derive(Debug, Deserialize)]
+pub struct OrderResponse {
+    pub order: Order,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub order_id: String,
     pub ticker: String,
@@ -125,35 +238,219 @@ pub struct Order {
     pub fill_count: Option<i64>,
     #[serde(default)]
     pub initial_count: Option<i64>,
+    /// Total cost in cents of the contracts filled so far, split by whether this order
+    /// took liquidity or rested and was hit. Priced on the order's side.
+    #[serde(default)]
+    pub taker_fill_cost: Option<i64>,
+    #[serde(default)]
+    pub maker_fill_cost: Option<i64>,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+}
+
+impl Order {
+    /// Contracts filled as of this order snapshot. Falls back to the placed count for
+    /// executed orders when the exchange omits `fill_count`.
+    pub fn filled_count(&self) -> i64 {
+        if let Some(n) = self.fill_count {
+            return n;
+        }
+        let placed = self.initial_count.or(self.count).unwrap_or(0);
+        match (self.status.as_str(), self.remaining_count) {
+            ("executed", _) => placed,
+            (_, Some(remaining)) => (placed - remaining).max(0),
+            _ => 0,
+        }
+    }
+
+    /// Average fill price in cents, when the exchange reported fill costs.
+    pub fn avg_fill_price_cents(&self) -> Option<f64> {
+        let filled = self.filled_count();
+        if filled == 0 || (self.taker_fill_cost.is_none() && self.maker_fill_cost.is_none()) {
+            return None;
+        }
+        let cost = self.taker_fill_cost.unwrap_or(0) + self.maker_fill_cost.unwrap_or(0);
+        Some(cost as f64 / filled as f64)
+    }
+}
+
+// --- Portfolio ---
+
+#[derive(Debug, Deserialize)]
+pub struct PositionsResponse {
+    #[serde(default)]
+    pub market_positions: Vec<MarketPosition>,
+    pub cursor: Option<String>,
+}
+
+/// Exchange-reported position in one market. `position` is signed:
+/// positive = YES contracts held, negative = NO contracts held.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketPosition {
+    pub ticker: String,
+    #[serde(default)]
+    pub position: i64,
+    /// Cost of the current position in cents.
+    #[serde(default)]
+    pub market_exposure: i64,
+}
+
+/// Available cash, in cents.
+#[derive(Debug, Deserialize)]
+pub struct BalanceResponse {
+    pub balance: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FillsResponse {
+    #[serde(default)]
+    pub fills: Vec<Fill>,
+    pub cursor: Option<String>,
+}
+
+/// One trade against an order of this account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fill {
+    pub trade_id: String,
+    pub order_id: String,
+    pub ticker: String,
+    pub side: String,
+    pub action: String,
+    pub count: i64,
+    #[serde(default)]
+    pub yes_price: Option<i64>,
+    pub created_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradesResponse {
+    #[serde(default)]
+    pub trades: Vec<Trade>,
+    pub cursor: Option<String>,
+}
+
+/// One trade in a market, between anyone.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Trade {
+    pub trade_id: String,
+    pub ticker: String,
+    pub count: i64,
+    #[serde(default)]
+    pub yes_price: Option<i64>,
+    pub created_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SettlementsResponse {
+    #[serde(default)]
+    pub settlements: Vec<Settlement>,
+    pub cursor: Option<String>,
+}
+
+/// One market this account held a position in when it settled.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Settlement {
+    pub ticker: String,
+    /// "yes", "no", or "void" (positions refunded).
+    pub market_result: String,
+    pub settled_time: DateTime<Utc>,
 }
 
 // --- Bracket analysis types (internal, not API) ---
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct BracketQuote {
     pub ticker: String,
     pub title: String,
+    /// The market's `bracket_label`, e.g. "3.4% to 3.6%".
+    pub label: Option<String>,
     pub yes_ask_cents: i64,  // cost to buy YES = 100 - best_no_bid
     pub yes_bid_cents: i64,  // revenue from selling YES = best_yes_bid
     pub depth_at_no: i64,    // quantity at best NO bid (LONG depth gate)
     pub depth_at_yes: i64,   // quantity at best YES bid (SHORT depth gate)
+    /// The same book from the NO side: buying NO takes the best YES bid at 100 - bid (0
+    /// without YES bids), selling NO hits the best NO bid. Depths are the YES fields'.
+    pub no_ask_cents: i64,
+    pub no_bid_cents: i64,
+    /// Contracts on both sides of the book, dust excluded.
+    pub book_depth: i64,
+    /// With `[risk] sweep_levels` > 1: the top YES ask / YES bid levels as (price, quantity),
+    /// best first. Empty = only the best level is taken.
+    pub ask_levels: Vec<(i64, i64)>,
+    pub bid_levels: Vec<(i64, i64)>,
+    /// When the scanner received the book this was quoted from (None = not timed, e.g. a
+    /// recorded snapshot or a re-quote).
+    pub fetched_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ArbOpportunity {
+    /// Readable form of the opportunity's identity, `{event_ticker}-{direction}-{unix_ms}`.
+    /// Prefixes its client order IDs, which is how orders on the exchange are traced back to
+    /// it, so the execution store indexes by it too.
+    pub id: String,
+    /// Unique per detection; what the opportunity, trade and reconciliation rows and the
+    /// state records reference.
+    pub uuid: Uuid,
     pub event_ticker: String,
     pub event_title: String,
     pub direction: ArbDirection,
     pub brackets: Vec<BracketQuote>,
+    /// Contracts per bracket; below `[risk] position_size` when sized down to the available depth.
+    pub position_size: u32,
+    /// Sum of the legs' limit prices. When levels are swept, profit and ROI come from the
+    /// volume-weighted cost instead.
     pub sum_cents: i64,
     pub total_fees_cents: i64,
     pub gross_profit_cents: i64,
     pub net_profit_cents: i64,
-    pub roi_pct: Decimal,
+    /// Total expected slippage across all legs (cents), per `[risk] expected_slippage_cents`.
+    pub expected_slippage_cents: i64,
+    /// Price edge only, before fees.
+    pub gross_roi_pct: Decimal,
+    /// After taker fees (the gated ROI).
+    pub net_roi_pct: Decimal,
+    /// After fees and expected slippage.
+    pub slippage_adj_roi_pct: Decimal,
+    /// When the last bracket is expected to settle, if the markets say (set by the
+    /// caller, which has the markets; the detector only sees quotes).
+    pub settles_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+impl ArbOpportunity {
+    /// A bracket's label, if it has one.
+    pub fn label(&self, ticker: &str) -> Option<&str> {
+        self.brackets.iter().find(|b| b.ticker == ticker)?.label.as_deref()
+    }
+
+    /// Net ROI scaled to a year by the time left to settlement (simple, not compounded), so a
+    /// 2% arb settling tomorrow outranks a 5% one settling in nine months. Under an hour
+    /// counts as an hour. None when the settlement time is unknown.
+    pub fn annualized_roi_pct(&self, now: DateTime<Utc>) -> Option<Decimal> {
+        let hours = (self.settles_at? - now).num_hours().max(1);
+        Some(self.net_roi_pct * Decimal::from(365 * 24) / Decimal::from(hours))
+    }
+
+    /// `TICKER (label)`, or just the ticker when the bracket has no label.
+    pub fn bracket_name(&self, ticker: &str) -> String {
+        match self.label(ticker) {
+            Some(label) => format!("{} ({})", ticker, label),
+            None => ticker.to_string(),
+        }
+    }
+
+    /// Labels of the brackets that have one, by ticker.
+    pub fn labels(&self) -> BTreeMap<String, String> {
+        self.brackets
+            .iter()
+            .filter_map(|b| Some((b.ticker.clone(), b.label.clone()?)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum ArbDirection {
     Long,  // Buy YES on every bracket
     Short, // Sell YES on every bracket
@@ -226,4 +523,79 @@ mod tests {
         assert!(resp.orderbook.no.is_empty());
         assert!(resp.orderbook.yes.is_empty());
     }
+
+    #[test]
+    fn test_deserialize_market_settlement_fields() {
+        let json = r#"{
+            "ticker": "KXHIGHNY-25JAN01-B40",
+            "title": "40-41",
+            "subtitle": null,
+            "status": "active",
+            "result": "no",
+            "can_close_early": true,
+            "close_time": "2025-01-02T04:59:00Z",
+            "expiration_time": "2025-01-09T15:00:00Z",
+            "settlement_timer_seconds": 3600
+        }"#;
+        let m: Market = serde_json::from_str(json).expect("market should deserialize");
+        assert_eq!(m.settled_result(), Some("no"));
+        assert_eq!(m.settlement_timer_seconds, Some(3600));
+        assert!(m.close_time.is_some());
+        // Status lags at "active" but the result is already in
+        assert!(m.is_determined(m.close_time.unwrap() - chrono::Duration::hours(1)));
+
+        // Missing timestamps and an empty result → still live
+        let json = r#"{"ticker":"T","title":"T","subtitle":null,"status":"active","result":""}"#;
+        let m: Market = serde_json::from_str(json).expect("sparse market should deserialize");
+        assert_eq!(m.settled_result(), None);
+        assert!(!m.is_determined(Utc::now()));
+        assert_eq!(m.bracket_label(), None);
+    }
+
+    #[test]
+    fn test_annualized_roi_scales_by_time_to_settlement() {
+        let now: DateTime<Utc> = "2026-10-17T12:00:00Z".parse().unwrap();
+        let mut opp = ArbOpportunity {
+            id: "EV-LONG-0".into(),
+            uuid: Uuid::nil(),
+            event_ticker: "EV".into(),
+            event_title: "Event".into(),
+            direction: ArbDirection::Long,
+            brackets: Vec::new(),
+            position_size: 1,
+            sum_cents: 95,
+            total_fees_cents: 0,
+            gross_profit_cents: 5,
+            net_profit_cents: 2,
+            expected_slippage_cents: 0,
+            gross_roi_pct: Decimal::from(5),
+            net_roi_pct: Decimal::from(2),
+            slippage_adj_roi_pct: Decimal::from(2),
+            settles_at: None,
+        };
+        assert_eq!(opp.annualized_roi_pct(now), None);
+        opp.settles_at = Some(now + chrono::Duration::days(365));
+        assert_eq!(opp.annualized_roi_pct(now), Some(Decimal::from(2)));
+        opp.settles_at = Some(now + chrono::Duration::days(73));
+        assert_eq!(opp.annualized_roi_pct(now), Some(Decimal::from(10)));
+        // Already past settlement: floored at an hour
+        opp.settles_at = Some(now - chrono::Duration::hours(3));
+        assert_eq!(opp.annualized_roi_pct(now), Some(Decimal::from(2 * 365 * 24)));
+    }
+
+    #[test]
+    fn test_bracket_label_from_subtitle_or_strikes() {
+        let market = |extra: &str| -> Market {
+            let json = format!(r#"{{"ticker":"T","title":"T","subtitle":"","status":"active","result":""{}}}"#, extra);
+            serde_json::from_str(&json).unwrap()
+        };
+        let labelled = market(r#","yes_sub_title":"3.4% to 3.6%","strike_type":"between","floor_strike":3.4"#);
+        assert_eq!(labelled.bracket_label().as_deref(), Some("3.4% to 3.6%"));
+        let between = market(r#","strike_type":"between","floor_strike":3.4,"cap_strike":3.6"#);
+        assert_eq!(between.bracket_label().as_deref(), Some("3.4–3.6"));
+        let above = market(r#","strike_type":"greater","floor_strike":40"#);
+        assert_eq!(above.bracket_label().as_deref(), Some("above 40"));
+        let custom = market(r#","strike_type":"custom","floor_strike":40"#);
+        assert_eq!(custom.bracket_label(), None);
+    }
 }
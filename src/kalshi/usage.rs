@@ -0,0 +1,284 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Minutes of history kept for hourly totals.
+const WINDOW_MINUTES: u64 = 60;
+/// Responses of an endpoint class seen before its size baseline is trusted.
+const BASELINE_SAMPLES: u64 = 20;
+/// Weight of each new response in the size baseline (an exponential moving average).
+const BASELINE_WEIGHT: f64 = 0.05;
+
+/// `[kalshi.usage]` — API tier limits used for budget alerts.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct UsageConfig {
+    #[serde(default = "default_reads_per_sec")]
+    pub reads_per_sec: u32,
+    #[serde(default = "default_writes_per_sec")]
+    pub writes_per_sec: u32,
+    /// Alert when the last minute's average rate reaches this share of a limit.
+    #[serde(default = "default_alert_pct")]
+    pub alert_pct: u32,
+    /// Warn when a response is this many times its endpoint's usual size (0 = never).
+    #[serde(default = "default_payload_growth_factor")]
+    pub payload_growth_factor: f64,
+}
+
+fn default_reads_per_sec() -> u32 { 20 }
+fn default_writes_per_sec() -> u32 { 10 }
+fn default_alert_pct() -> u32 { 80 }
+fn default_payload_growth_factor() -> f64 { 3.0 }
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            reads_per_sec: default_reads_per_sec(),
+            writes_per_sec: default_writes_per_sec(),
+            alert_pct: default_alert_pct(),
+            payload_growth_factor: default_payload_growth_factor(),
+        }
+    }
+}
+
+/// Endpoint class for a request: method plus the fixed path segments, with tickers
+/// and IDs dropped (`GET /markets/KX-1/orderbook?depth=5` → `GET /markets/orderbook`).
+pub fn endpoint_class(method: &str, path: &str) -> String {
+    let path = path.split('?').next().unwrap_or("");
+    let fixed: Vec<&str> = path
+        .split('/')
+        .filter(|seg| !seg.is_empty() && seg.chars().all(|c| c.is_ascii_lowercase() || c == '_'))
+        .collect();
+    format!("{} /{}", method, fixed.join("/"))
+}
+
+struct MinuteBucket {
+    minute: u64,
+    counts: HashMap<String, u64>,
+}
+
+#[derive(Default)]
+struct PayloadTracker {
+    responses: u64,
+    total_bytes: u64,
+    max_bytes: u64,
+    parse_micros: u64,
+    /// Moving average of response size, the yardstick for anomalous growth.
+    baseline_bytes: f64,
+    /// Over the growth threshold; warned once, re-armed when sizes fall back.
+    anomalous: bool,
+}
+
+/// Per-minute request counts by endpoint class over the last hour. Every attempt is
+/// counted, including retries, since each one spends rate budget. Successful responses
+/// also add their body size and parse time to per-class totals since start.
+pub struct ApiUsage {
+    start: Instant,
+    buckets: Mutex<VecDeque<MinuteBucket>>,
+    payloads: Mutex<HashMap<String, PayloadTracker>>,
+    growth_factor: f64,
+    last_error: Mutex<Option<ApiErrorRecord>>,
+}
+
+/// The most recent request that failed, for `/healthz` and `/status`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApiErrorRecord {
+    pub at: DateTime<Utc>,
+    pub endpoint: String,
+    pub error: String,
+}
+
+/// Response sizes and parse times of one endpoint class since start.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PayloadStats {
+    pub responses: u64,
+    pub avg_bytes: u64,
+    pub max_bytes: u64,
+    pub avg_parse_us: u64,
+    /// Recent typical size, which anomalous growth is measured against.
+    pub baseline_bytes: u64,
+}
+
+/// A response far larger than its endpoint class usually returns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadGrowth {
+    pub endpoint: String,
+    pub bytes: u64,
+    pub baseline_bytes: u64,
+}
+
+/// API usage as published in the status snapshot.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageSnapshot {
+    /// Requests in the last hour by endpoint class.
+    pub last_hour: BTreeMap<String, u64>,
+    /// Average requests per second over the last complete minute.
+    pub reads_per_sec: f64,
+    pub writes_per_sec: f64,
+    /// Response sizes and parse times by endpoint class.
+    pub payloads: BTreeMap<String, PayloadStats>,
+    pub last_error: Option<ApiErrorRecord>,
+}
+
+impl ApiUsage {
+    pub fn new(config: &UsageConfig, now: Instant) -> Self {
+        Self {
+            start: now,
+            buckets: Mutex::new(VecDeque::new()),
+            payloads: Mutex::new(HashMap::new()),
+            growth_factor: config.payload_growth_factor,
+            last_error: Mutex::new(None),
+        }
+    }
+
+    fn minute(&self, now: Instant) -> u64 {
+        now.duration_since(self.start).as_secs() / 60
+    }
+
+    pub fn record(&self, method: &str, path: &str, now: Instant) {
+        let minute = self.minute(now);
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.back().map(|b| b.minute) != Some(minute) {
+            buckets.push_back(MinuteBucket { minute, counts: HashMap::new() });
+        }
+        while buckets.front().is_some_and(|b| b.minute + WINDOW_MINUTES <= minute) {
+            buckets.pop_front();
+        }
+        let bucket = buckets.back_mut().expect("bucket pushed above");
+        *bucket.counts.entry(endpoint_class(method, path)).or_default() += 1;
+    }
+
+    /// Add a parsed response body. Returns the growth when this response is the first in a
+    /// run to pass `payload_growth_factor` times its class's baseline.
+    pub fn record_payload(&self, method: &str, path: &str, bytes: usize, parse: Duration) -> Option<PayloadGrowth> {
+        let endpoint = endpoint_class(method, path);
+        let bytes = bytes as u64;
+        let mut payloads = self.payloads.lock().unwrap();
+        let t = payloads.entry(endpoint.clone()).or_default();
+        let grown = self.growth_factor > 0.0
+            && t.responses >= BASELINE_SAMPLES
+            && bytes as f64 > t.baseline_bytes * self.growth_factor;
+        let warn = grown && !t.anomalous;
+        t.anomalous = grown;
+        let baseline_bytes = t.baseline_bytes as u64;
+        t.baseline_bytes = if t.responses == 0 {
+            bytes as f64
+        } else {
+            t.baseline_bytes + (bytes as f64 - t.baseline_bytes) * BASELINE_WEIGHT
+        };
+        t.responses += 1;
+        t.total_bytes += bytes;
+        t.max_bytes = t.max_bytes.max(bytes);
+        t.parse_micros += parse.as_micros() as u64;
+        warn.then_some(PayloadGrowth { endpoint, bytes, baseline_bytes })
+    }
+
+    /// Note a request that failed: a non-success status or no response at all.
+    pub fn record_error(&self, method: &str, path: &str, error: &str) {
+        let endpoint = endpoint_class(method, path);
+        let record = ApiErrorRecord { at: Utc::now(), endpoint, error: error.to_string() };
+        *self.last_error.lock().unwrap() = Some(record);
+    }
+
+    pub fn snapshot(&self, now: Instant) -> UsageSnapshot {
+        let minute = self.minute(now);
+        let buckets = self.buckets.lock().unwrap();
+        let mut snapshot = UsageSnapshot::default();
+        for bucket in buckets.iter().filter(|b| b.minute + WINDOW_MINUTES > minute) {
+            for (class, n) in &bucket.counts {
+                *snapshot.last_hour.entry(class.clone()).or_default() += n;
+                if bucket.minute + 1 == minute {
+                    if class.starts_with("GET ") {
+                        snapshot.reads_per_sec += *n as f64 / 60.0;
+                    } else {
+                        snapshot.writes_per_sec += *n as f64 / 60.0;
+                    }
+                }
+            }
+        }
+        for (class, t) in self.payloads.lock().unwrap().iter() {
+            let stats = PayloadStats {
+                responses: t.responses,
+                avg_bytes: t.total_bytes / t.responses.max(1),
+                max_bytes: t.max_bytes,
+                avg_parse_us: t.parse_micros / t.responses.max(1),
+                baseline_bytes: t.baseline_bytes as u64,
+            };
+            snapshot.payloads.insert(class.clone(), stats);
+        }
+        snapshot.last_error = self.last_error.lock().unwrap().clone();
+        snapshot
+    }
+}
+
+impl UsageSnapshot {
+    /// Description of each limit the last minute's rate is approaching, if any.
+    pub fn budget_warnings(&self, config: &UsageConfig) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let threshold = config.alert_pct as f64 / 100.0;
+        for (kind, rate, limit) in [
+            ("reads", self.reads_per_sec, config.reads_per_sec),
+            ("writes", self.writes_per_sec, config.writes_per_sec),
+        ] {
+            if limit > 0 && rate >= limit as f64 * threshold {
+                warnings.push(format!("{} at {:.1}/s of {}/s limit", kind, rate, limit));
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_class_drops_ids() {
+        assert_eq!(endpoint_class("GET", "/markets/KXHIGHNY-25JAN01-B40/orderbook?depth=5"), "GET /markets/orderbook");
+        assert_eq!(endpoint_class("DELETE", "/portfolio/orders/4f2a-9c1e"), "DELETE /portfolio/orders");
+        assert_eq!(endpoint_class("GET", "/events?series_ticker=KXBTC&cursor=abc"), "GET /events");
+    }
+
+    #[test]
+    fn test_hourly_window_and_rate() {
+        let t0 = Instant::now();
+        let usage = ApiUsage::new(&UsageConfig::default(), t0);
+        for _ in 0..1_200 {
+            usage.record("GET", "/series", t0);
+        }
+        usage.record("POST", "/portfolio/orders", t0 + Duration::from_secs(30));
+
+        // One minute later the first minute is complete: 1200 reads / 60s = 20/s
+        let snap = usage.snapshot(t0 + Duration::from_secs(61));
+        assert_eq!(snap.last_hour["GET /series"], 1_200);
+        assert_eq!(snap.reads_per_sec, 20.0);
+        let warnings = snap.budget_warnings(&UsageConfig::default());
+        assert_eq!(warnings, vec!["reads at 20.0/s of 20/s limit"]);
+
+        // After an hour the bucket ages out
+        let snap = usage.snapshot(t0 + Duration::from_secs(3_601));
+        assert!(snap.last_hour.is_empty());
+    }
+
+    #[test]
+    fn test_payload_growth_warns_once_per_run() {
+        let usage = ApiUsage::new(&UsageConfig::default(), Instant::now());
+        let parse = Duration::from_micros(100);
+        for _ in 0..BASELINE_SAMPLES {
+            assert_eq!(usage.record_payload("GET", "/events?series_ticker=A", 10_000, parse), None);
+        }
+        // Within 3x is normal; past it warns, but only on the first of a run
+        assert_eq!(usage.record_payload("GET", "/events?series_ticker=B", 29_000, parse), None);
+        let growth = usage.record_payload("GET", "/events?series_ticker=A", 40_000, parse).unwrap();
+        assert_eq!((growth.endpoint.as_str(), growth.baseline_bytes), ("GET /events", 10_950));
+        assert_eq!(usage.record_payload("GET", "/events", 40_000, parse), None);
+        usage.record_payload("GET", "/events", 10_000, parse);
+        assert!(usage.record_payload("GET", "/events", 60_000, parse).is_some());
+
+        let stats = &usage.snapshot(Instant::now()).payloads["GET /events"];
+        assert_eq!((stats.responses, stats.max_bytes, stats.avg_parse_us), (25, 60_000, 100));
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{bail, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout, Duration};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn, Instrument};
+
+use crate::telemetry::Sampled;
+
+use super::auth::KalshiAuth;
+use super::types::{Orderbook, PriceLevel};
+
+/// Kalshi pings every 10s; this much silence means the connection is dead.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// One market's book, maintained from a snapshot plus deltas. Price → resting quantity.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct LiveBook {
+    yes: BTreeMap<i64, i64>,
+    no: BTreeMap<i64, i64>,
+}
+
+impl LiveBook {
+    fn from_levels(yes: &[PriceLevel], no: &[PriceLevel]) -> Self {
+        let side = |levels: &[PriceLevel]| levels.iter().map(|l| (l.price, l.quantity)).collect();
+        Self { yes: side(yes), no: side(no) }
+    }
+
+    fn apply_delta(&mut self, side: &str, price: i64, delta: i64) -> Result<()> {
+        let levels = match side {
+            "yes" => &mut self.yes,
+            "no" => &mut self.no,
+            _ => bail!("unknown book side {:?}", side),
+        };
+        let qty = levels.entry(price).or_insert(0);
+        *qty += delta;
+        if *qty <= 0 {
+            levels.remove(&price);
+        }
+        Ok(())
+    }
+
+    /// Same shape as the REST orderbook: levels ascending by price.
+    fn to_orderbook(&self) -> Orderbook {
+        let side = |levels: &BTreeMap<i64, i64>| {
+            levels.iter().map(|(&price, &quantity)| PriceLevel { price, quantity }).collect()
+        };
+        Orderbook { yes: side(&self.yes), no: side(&self.no) }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FeedMessage {
+    OrderbookSnapshot { sid: u64, seq: u64, msg: SnapshotMsg },
+    OrderbookDelta { sid: u64, seq: u64, msg: DeltaMsg },
+    Error { msg: serde_json::Value },
+    /// Subscription acks and anything else we don't act on.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnapshotMsg {
+    market_ticker: String,
+    #[serde(default)]
+    yes: Option<Vec<PriceLevel>>,
+    #[serde(default)]
+    no: Option<Vec<PriceLevel>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeltaMsg {
+    market_ticker: String,
+    price: i64,
+    delta: i64,
+    side: String,
+}
+
+/// Books for one connection, plus the last sequence number seen per subscription.
+#[derive(Debug, Default)]
+struct FeedBooks {
+    books: HashMap<String, LiveBook>,
+    seqs: HashMap<u64, u64>,
+}
+
+impl FeedBooks {
+    /// Apply one feed message. A sequence gap or a delta for a market without a
+    /// snapshot is an error: the books can no longer be trusted and the caller reconnects.
+    fn apply(&mut self, text: &str) -> Result<()> {
+        let msg: FeedMessage = match serde_json::from_str(text) {
+            Ok(m) => m,
+            Err(e) => {
+                static UNPARSEABLE: Sampled = Sampled::new();
+                if let Some(dropped) = UNPARSEABLE.sample() {
+                    debug!(error = %e, dropped, "Ignoring unparseable feed message");
+                }
+                return Ok(());
+            }
+        };
+        match msg {
+            FeedMessage::OrderbookSnapshot { sid, seq, msg } => {
+                self.seqs.insert(sid, seq);
+                let book = LiveBook::from_levels(&msg.yes.unwrap_or_default(), &msg.no.unwrap_or_default());
+                self.books.insert(msg.market_ticker, book);
+            }
+            FeedMessage::OrderbookDelta { sid, seq, msg } => {
+                match self.seqs.insert(sid, seq) {
+                    Some(prev) if seq != prev + 1 => bail!("sequence gap on sid {}: {} -> {}", sid, prev, seq),
+                    _ => {}
+                }
+                self.books
+                    .get_mut(&msg.market_ticker)
+                    .with_context(|| format!("delta for {} before its snapshot", msg.market_ticker))?
+                    .apply_delta(&msg.side, msg.price, msg.delta)?;
+            }
+            FeedMessage::Error { msg } => bail!("feed error: {}", msg),
+            FeedMessage::Other => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct FeedState {
+    books: FeedBooks,
+    connected: bool,
+}
+
+/// Live orderbooks from Kalshi's `orderbook_delta` WebSocket channel. A background task
+/// keeps the connection up, resubscribing everything on reconnect; while it's down (or
+/// before a market's snapshot arrives) `orderbook` returns None and callers use REST.
+pub struct OrderbookFeed {
+    state: Arc<Mutex<FeedState>>,
+    watched: Mutex<HashSet<String>>,
+    watch_tx: mpsc::UnboundedSender<Vec<String>>,
+}
+
+impl OrderbookFeed {
+    pub fn spawn(auth: KalshiAuth, url: String) -> Self {
+        let state = Arc::new(Mutex::new(FeedState::default()));
+        let (watch_tx, watch_rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(auth, url, state.clone(), watch_rx).in_current_span());
+        Self { state, watched: Mutex::new(HashSet::new()), watch_tx }
+    }
+
+    /// Subscribe to any of these markets not already subscribed.
+    pub fn watch(&self, tickers: &[&str]) {
+        let mut watched = self.watched.lock().unwrap_or_else(|e| e.into_inner());
+        let new: Vec<String> = tickers
+            .iter()
+            .filter(|t| watched.insert(t.to_string()))
+            .map(|t| t.to_string())
+            .collect();
+        if !new.is_empty() {
+            self.watch_tx.send(new).ok();
+        }
+    }
+
+    /// Current book for a market, if the feed is connected and has it.
+    pub fn orderbook(&self, ticker: &str) -> Option<Orderbook> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if !state.connected {
+            return None;
+        }
+        state.books.books.get(ticker).map(LiveBook::to_orderbook)
+    }
+
+    /// Markets with a live book right now.
+    pub fn live_markets(&self) -> usize {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.connected { state.books.books.len() } else { 0 }
+    }
+}
+
+async fn run(
+    auth: KalshiAuth,
+    url: String,
+    state: Arc<Mutex<FeedState>>,
+    mut watch_rx: mpsc::UnboundedReceiver<Vec<String>>,
+) {
+    let mut markets: HashSet<String> = HashSet::new();
+    let mut backoff = Duration::from_secs(1);
+    loop {
+        match session(&auth, &url, &state, &mut markets, &mut watch_rx, &mut backoff).await {
+            Ok(()) => return,
+            Err(e) => warn!(error = %format!("{:#}", e), retry_secs = backoff.as_secs(), "Orderbook feed down, using REST"),
+        }
+        *state.lock().unwrap_or_else(|e| e.into_inner()) = FeedState::default();
+        // Up to a quarter extra, so feeds dropped together don't all reconnect together
+        let jitter = rand::thread_rng().gen_range(0.0..0.25);
+        sleep(backoff.mul_f64(1.0 + jitter)).await;
+        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+    }
+}
+
+/// One connection. Returns Ok only when the feed handle has been dropped.
+async fn session(
+    auth: &KalshiAuth,
+    url: &str,
+    state: &Mutex<FeedState>,
+    markets: &mut HashSet<String>,
+    watch_rx: &mut mpsc::UnboundedReceiver<Vec<String>>,
+    backoff: &mut Duration,
+) -> Result<()> {
+    let path = reqwest::Url::parse(url).context("Invalid ws_url")?.path().to_string();
+    let mut request = url.into_client_request().context("Invalid ws_url")?;
+    for (name, value) in auth.headers("GET", &path)? {
+        request.headers_mut().insert(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(&value)?);
+    }
+    let (mut ws, _) = tokio_tungstenite::connect_async(request).await.context("WebSocket connect failed")?;
+    info!(markets = markets.len(), "Orderbook feed connected");
+    *backoff = Duration::from_secs(1);
+    state.lock().unwrap_or_else(|e| e.into_inner()).connected = true;
+
+    let mut next_id = 1u64;
+    let mut subscribe = |tickers: Vec<&String>| {
+        let cmd = serde_json::json!({
+            "id": next_id,
+            "cmd": "subscribe",
+            "params": { "channels": ["orderbook_delta"], "market_tickers": tickers },
+        });
+        next_id += 1;
+        Message::Text(cmd.to_string().into())
+    };
+    if !markets.is_empty() {
+        ws.send(subscribe(markets.iter().collect())).await.context("Subscribe failed")?;
+    }
+
+    loop {
+        tokio::select! {
+            request = watch_rx.recv() => {
+                let Some(tickers) = request else { return Ok(()) };
+                let new: Vec<String> = tickers.into_iter().filter(|t| markets.insert(t.clone())).collect();
+                if !new.is_empty() {
+                    debug!(markets = new.len(), "Subscribing to orderbooks");
+                    ws.send(subscribe(new.iter().collect())).await.context("Subscribe failed")?;
+                }
+            }
+            msg = timeout(READ_TIMEOUT, ws.next()) => {
+                let msg = match msg {
+                    Err(_) => bail!("no message in {}s", READ_TIMEOUT.as_secs()),
+                    Ok(None) => bail!("connection closed"),
+                    Ok(Some(msg)) => msg.context("read failed")?,
+                };
+                match msg {
+                    Message::Text(text) => state.lock().unwrap_or_else(|e| e.into_inner()).books.apply(&text)?,
+                    Message::Close(frame) => bail!("server closed the connection: {:?}", frame),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_and_deltas_build_the_book() {
+        let mut feed = FeedBooks::default();
+        feed.apply(r#"{"type":"subscribed","id":1,"msg":{"channel":"orderbook_delta","sid":7}}"#).unwrap();
+        feed.apply(r#"{"type":"orderbook_snapshot","sid":7,"seq":1,"msg":{"market_ticker":"M1","yes":[[40,10],[42,5]],"no":null}}"#)
+            .unwrap();
+        feed.apply(r#"{"type":"orderbook_delta","sid":7,"seq":2,"msg":{"market_ticker":"M1","price":42,"delta":-5,"side":"yes"}}"#)
+            .unwrap();
+        feed.apply(r#"{"type":"orderbook_delta","sid":7,"seq":3,"msg":{"market_ticker":"M1","price":55,"delta":8,"side":"no"}}"#)
+            .unwrap();
+
+        let book = feed.books["M1"].to_orderbook();
+        assert_eq!(book.yes, vec![PriceLevel { price: 40, quantity: 10 }]);
+        assert_eq!(book.no, vec![PriceLevel { price: 55, quantity: 8 }]);
+
+        // A skipped sequence number or an unknown market invalidates the feed
+        let gap = r#"{"type":"orderbook_delta","sid":7,"seq":5,"msg":{"market_ticker":"M1","price":40,"delta":1,"side":"yes"}}"#;
+        assert!(feed.apply(gap).is_err());
+        let unknown = r#"{"type":"orderbook_delta","sid":7,"seq":6,"msg":{"market_ticker":"M9","price":40,"delta":1,"side":"yes"}}"#;
+        assert!(feed.apply(unknown).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+// The scanner's building blocks, as a library so integration tests under tests/ can drive
+// the client and executor against a mock exchange. The binary (main.rs, cli.rs) wires them up.
+pub mod alerts;
+pub mod backtest;
+pub mod book;
+pub mod backup;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod clock;
+pub mod config;
+pub mod config_check;
+pub mod cooldown;
+pub mod detector;
+pub mod digest;
+pub mod executor;
+pub mod exits;
+pub mod fees;
+pub mod fill_audit;
+pub mod halt;
+pub mod instance_lock;
+pub mod kalshi;
+pub mod lifecycle;
+pub mod mirror;
+pub mod money;
+pub mod ownership;
+pub mod paper;
+pub mod polymarket;
+pub mod positions;
+pub mod price;
+pub mod priority;
+pub mod recovery;
+pub mod relations;
+pub mod reload;
+pub mod replay;
+pub mod report;
+pub mod routing;
+pub mod scheduler;
+pub mod series_cache;
+pub mod signals;
+pub mod sim_exchange;
+pub mod skip_memo;
+pub mod slippage;
+pub mod status;
+pub mod storage;
+pub mod telegram;
+pub mod telemetry;
+pub mod venue;
+pub mod warmup;
+pub mod watch;
+pub mod watchlist;
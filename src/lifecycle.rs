@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::kalshi::types::Market;
+
+/// Where a market is in its lifecycle, as far as our orders and positions care.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Trading,
+    /// Not accepting orders but not closed (paused or deactivated).
+    Halted,
+    /// Trading is over without a result yet, including early closes.
+    Closed,
+    /// The outcome is known.
+    Determined,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::Trading => "TRADING",
+            Phase::Halted => "HALTED",
+            Phase::Closed => "CLOSED",
+            Phase::Determined => "DETERMINED",
+        })
+    }
+}
+
+pub fn phase(market: &Market, now: DateTime<Utc>) -> Phase {
+    if market.settled_result().is_some()
+        || matches!(market.status.as_str(), "determined" | "settled" | "finalized")
+    {
+        Phase::Determined
+    } else if market.is_determined(now) {
+        Phase::Closed
+    } else if market.is_open_status() {
+        Phase::Trading
+    } else {
+        Phase::Halted
+    }
+}
+
+/// Last phase seen per watched market, so each lifecycle change is acted on once.
+#[derive(Debug, Default)]
+pub struct LifecycleWatch {
+    phases: HashMap<String, Phase>,
+}
+
+impl LifecycleWatch {
+    /// Record a market's phase. Returns the previous phase (None if first seen) when
+    /// the market has left `Trading` since it was last observed.
+    pub fn observe(&mut self, ticker: &str, phase: Phase) -> Option<Option<Phase>> {
+        let previous = self.phases.insert(ticker.to_string(), phase);
+        (phase != Phase::Trading && previous != Some(phase)).then_some(previous)
+    }
+
+    /// Forget markets no longer watched.
+    pub fn retain(&mut self, tickers: &HashSet<String>) {
+        self.phases.retain(|t, _| tickers.contains(t));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_reported_once() {
+        let mut watch = LifecycleWatch::default();
+        assert_eq!(watch.observe("A", Phase::Trading), None);
+        assert_eq!(watch.observe("A", Phase::Halted), Some(Some(Phase::Trading)));
+        assert_eq!(watch.observe("A", Phase::Halted), None);
+        assert_eq!(watch.observe("A", Phase::Determined), Some(Some(Phase::Halted)));
+        // Already closed the first time it's seen: still acted on
+        assert_eq!(watch.observe("B", Phase::Closed), Some(None));
+
+        watch.retain(&HashSet::from(["B".to_string()]));
+        assert_eq!(watch.observe("A", Phase::Determined), Some(None));
+    }
+}
@@ -1,64 +1,232 @@
-mod config;
-mod detector;
-mod executor;
-mod kalshi;
-mod storage;
-mod telegram;
+mod cli;
+
+use bracket_arb::{
+    alerts, backtest, book, backup, clock, config, config_check, cooldown, detector, digest, executor, exits, fees,
+    fill_audit, halt, instance_lock, kalshi, lifecycle, mirror, money, ownership, paper, polymarket, positions,
+    recovery, relations, reload, replay, report, routing, scheduler, series_cache, priority, signals, skip_memo,
+    slippage, status, storage, telegram, telemetry, venue, warmup, watch, watchlist,
+};
 
 use anyhow::{Context, Result};
 use chrono::Utc;
+use clap::Parser;
+use futures_util::{stream, StreamExt};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::time::{sleep, Duration, Instant};
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
-use config::Config;
-use detector::{detect_arb, quote_from_orderbook};
+use cli::{Cli, Command, VenueArg};
+use clock::ExchangeClock;
+use config::{AdaptiveRoiConfig, Config, RiskConfig};
+use cooldown::Cooldown;
+use digest::OpportunityDigest;
+use detector::{
+    arb_candidate_markets, bracket_set_change, evaluate_arb, expected_settlement, quote_with_levels, DetectParams,
+    SumBounds, SumBoundsTable,
+};
+use executor::{ExecutionResult, ExecutorError};
+use exits::{ExitConfig, ExitManager, WorkingExit};
+use fill_audit::FillAudit;
 use kalshi::auth::KalshiAuth;
-use kalshi::client::KalshiClient;
-use kalshi::types::Series;
+use kalshi::client::{CancelOutcomes, KalshiClient};
+use kalshi::error::KalshiError;
+use kalshi::ws::OrderbookFeed;
+use kalshi::types::{ArbOpportunity, BracketQuote, Market, Order, Orderbook, Series};
+use lifecycle::LifecycleWatch;
+use money::{Cents, Contracts};
+use ownership::Strategy;
+use paper::PaperPortfolio;
+use polymarket::PolymarketClient;
+use priority::SeriesPriority;
+use positions::{PositionTracker, UnhedgedLeg};
+use recovery::{Orphan, OrphanPolicy, Recovery};
+use reload::ConfigWatcher;
+use scheduler::{Control, Job, Schedule, Scheduler};
+use series_cache::{SafeModeChange, SeriesCache};
+use skip_memo::SkipMemo;
+use slippage::SlippageTracker;
+use status::{CycleStats, RiskCounters, StatusSender, StatusSnapshot};
+use storage::{position_check, ExecutionStore, LocalPosition, OpportunityState, PnlAttribution, StorageError};
+use telemetry::Sampled;
+use warmup::Warmup;
+use watch::RiskWatch;
+use watchlist::WatchlistFile;
 
 // --- Hardcoded risk limits (not config — these are circuit breakers) ---
 const MAX_OPEN_ARBS: u32 = 5;
 const MAX_DAILY_LOSS_CENTS: i64 = 500; // $5.00 — halt if daily P&L drops below -$5
-const MAX_DAILY_ORDERS: u32 = 50;
+const MAX_DAILY_ORDERS: u32 = 50; // entries only: cancels and unwinds are never blocked
 
 struct RiskLimits {
     open_arbs: u32,
     daily_pnl_cents: i64,
+    /// Arb entry orders placed today, the count `MAX_DAILY_ORDERS` applies to.
     daily_orders: u32,
+    /// Cleanup sent today, counted apart so it can't use up the entry budget.
+    daily_cancels: u32,
+    daily_unwinds: u32,
+    clock: ExchangeClock,
     today: chrono::NaiveDate,
+    /// Realized vs expected profit of recent fully-filled arbs.
+    slippage: SlippageTracker,
+    /// ROI floor in effect under `[risk.adaptive_roi]` (None until the first cycle sets it).
+    min_roi_pct: Option<f64>,
+    /// Account balance under `[risk.sizing]`, from the latest successful fetch.
+    balance_cents: Option<i64>,
+    /// How long resting legs are re-checked after placement (`[risk] fill_wait_ms`).
+    fill_wait: Duration,
+    /// Wall-clock limit on placing an arb's legs (`[risk] execution_budget_ms`).
+    execution_budget: Duration,
+    /// Placement time past which an execution is aborted (`[risk] max_execution_ms`), None = off.
+    max_execution: Option<Duration>,
+    /// The first fill anomaly (`fill_audit.rs`); once set, nothing executes until restart.
+    kill_switch: Option<String>,
+    /// While this exists, no new orders (`[risk] halt_file`, see `halt.rs`).
+    halt_file: PathBuf,
+    /// Re-pricing right before placement (`[risk] requote_tolerance_cents`), None = off.
+    requote: Option<Requote>,
+    /// Today's reconciled arbs' actual net, broken down (`storage::attribute_pnl`).
+    daily_attribution: PnlAttribution,
+    /// Events held off after a failed or incomplete attempt (`[risk] cooldown_secs`).
+    cooldown: Cooldown,
+}
+
+/// How the stale-quote guard re-reads books and how far the edge may slip.
+struct Requote {
+    tolerance_cents: i64,
+    min_level_quantity: i64,
+    sweep_levels: usize,
 }
 
 impl RiskLimits {
-    fn new() -> Self {
+    fn new(clock: ExchangeClock, risk: &RiskConfig) -> Self {
         Self {
             open_arbs: 0,
             daily_pnl_cents: 0,
             daily_orders: 0,
-            today: Utc::now().date_naive(),
+            daily_cancels: 0,
+            daily_unwinds: 0,
+            clock,
+            today: clock.trading_day(Utc::now()),
+            slippage: load_slippage(risk),
+            min_roi_pct: None,
+            balance_cents: None,
+            fill_wait: Duration::from_millis(risk.fill_wait_ms),
+            execution_budget: Duration::from_millis(risk.execution_budget_ms),
+            max_execution: max_execution(risk),
+            kill_switch: None,
+            halt_file: risk.halt_file.clone(),
+            requote: risk.requote_tolerance_cents.map(|tolerance| Requote {
+                tolerance_cents: tolerance as i64,
+                min_level_quantity: risk.min_level_quantity as i64,
+                sweep_levels: risk.sweep_levels as usize,
+            }),
+            cooldown: Cooldown::new(risk.cooldown_secs),
+            daily_attribution: PnlAttribution::default(),
+        }
+    }
+
+    /// Take reloaded `[risk]` settings, keeping the day's counters and the kill switch.
+    /// The slippage history is re-read if the adaptive ROI window was `old_window`.
+    fn reconfigure(&mut self, risk: &RiskConfig, old_window: Option<usize>) {
+        self.fill_wait = Duration::from_millis(risk.fill_wait_ms);
+        self.execution_budget = Duration::from_millis(risk.execution_budget_ms);
+        self.max_execution = max_execution(risk);
+        self.requote = risk.requote_tolerance_cents.map(|tolerance| Requote {
+            tolerance_cents: tolerance as i64,
+            min_level_quantity: risk.min_level_quantity as i64,
+            sweep_levels: risk.sweep_levels as usize,
+        });
+        self.cooldown.set_base(risk.cooldown_secs);
+        self.halt_file = risk.halt_file.clone();
+        if risk.adaptive_roi.as_ref().map(|a| a.window) != old_window {
+            self.slippage = load_slippage(risk);
         }
     }
 
-    /// Reset counters if the date has rolled over.
+    /// Reset counters if the exchange-local trading day has rolled over.
     fn maybe_reset_day(&mut self) {
-        let now = Utc::now().date_naive();
+        let now = self.clock.trading_day(Utc::now());
         if now != self.today {
+            let (start, end) = self.clock.day_bounds(self.today);
             info!(
                 prev_day = %self.today,
+                day_hours = (end - start).num_hours(),
                 pnl_cents = self.daily_pnl_cents,
                 orders = self.daily_orders,
+                cancels = self.daily_cancels,
+                unwinds = self.daily_unwinds,
                 "Daily risk counters reset"
             );
             self.daily_pnl_cents = 0;
             self.daily_orders = 0;
+            self.daily_cancels = 0;
+            self.daily_unwinds = 0;
+            self.daily_attribution = PnlAttribution::default();
             self.today = now;
         }
     }
 
+    fn counters(&self) -> RiskCounters {
+        RiskCounters {
+            open_arbs: self.open_arbs,
+            max_open_arbs: MAX_OPEN_ARBS,
+            daily_pnl_cents: self.daily_pnl_cents,
+            max_daily_loss_cents: MAX_DAILY_LOSS_CENTS,
+            daily_orders: self.daily_orders,
+            max_daily_orders: MAX_DAILY_ORDERS,
+            daily_cancels: self.daily_cancels,
+            daily_unwinds: self.daily_unwinds,
+            trading_day: self.today,
+            resets_at: self.clock.day_bounds(self.today).1,
+            min_roi_pct: self.min_roi_pct,
+            balance_cents: self.balance_cents,
+            kill_switch: self.kill_switch.clone(),
+            halted: self.halted(),
+        }
+    }
+
+    /// Why the operator halted trading, if the halt file exists or `HALT_TRADING` is set.
+    fn halted(&self) -> Option<String> {
+        halt::reason(&self.halt_file)
+    }
+
+    /// Add realized P&L to the day. An amount that couldn't be computed (None) or that
+    /// overflows the day's total fails closed: the day is pinned at the loss limit.
+    fn record_pnl(&mut self, pnl: Option<Cents>) {
+        match pnl.and_then(|p| Cents(self.daily_pnl_cents).checked_add(p)) {
+            Some(total) => self.daily_pnl_cents = total.0,
+            None => {
+                error!(pnl_cents = self.daily_pnl_cents, "P&L overflowed, halting for the day");
+                self.daily_pnl_cents = self.daily_pnl_cents.min(-MAX_DAILY_LOSS_CENTS);
+            }
+        }
+    }
+
+    /// Pick up where earlier runs left the counters a restart would zero: arbs still open,
+    /// and the trading day's entry orders (execution store) and reconciled net (`data/`).
+    fn restore(&mut self, exec_store: &ExecutionStore, positions: &PositionTracker) -> Result<()> {
+        self.maybe_reset_day();
+        let (start, end) = self.clock.day_bounds(self.today);
+        self.open_arbs = positions.open_arbs()?;
+        self.daily_orders = exec_store
+            .executions()
+            .filter(|rec| rec.recorded_at >= start && rec.recorded_at < end)
+            .map(|rec| rec.result.orders().count() as u32)
+            .sum();
+        self.daily_pnl_cents = report::reconciled_net_cents(std::path::Path::new(backup::DATA_DIR), start, end)?;
+        Ok(())
+    }
+
     /// Returns Some("reason") if any limit blocks execution, None if clear.
     fn check(&mut self) -> Option<&'static str> {
         self.maybe_reset_day();
+        if self.kill_switch.is_some() {
+            return Some("KILL_SWITCH");
+        }
         if self.open_arbs >= MAX_OPEN_ARBS {
             return Some("MAX_OPEN_ARBS");
         }
@@ -72,56 +240,95 @@ impl RiskLimits {
     }
 }
 
-struct SeriesCache {
-    series: Vec<Series>,
-    fetched_at: Option<Instant>,
-    ttl: Duration,
-}
-
-impl SeriesCache {
-    fn new(ttl_secs: u64) -> Self {
-        Self {
-            series: Vec::new(),
-            fetched_at: None, // starts stale to force first fetch
-            ttl: Duration::from_secs(ttl_secs),
-        }
+/// The series list for this cycle, refreshed when stale. Alerts when a run of failed
+/// refreshes pushes the cache into safe mode, and when a refresh brings it back.
+async fn series_list<'a>(cache: &'a mut SeriesCache, client: &KalshiClient) -> Result<&'a [Series]> {
+    if !cache.is_stale(Instant::now()) {
+        debug!(cached_count = cache.series().len(), "Using cached series list");
+        return Ok(cache.series());
     }
-
-    fn is_stale(&self) -> bool {
-        match self.fetched_at {
-            None => true,
-            Some(t) => t.elapsed() >= self.ttl,
+    match client.list_series().await {
+        Ok(fresh) => {
+            info!(count = fresh.len(), "Refreshed series list");
+            if cache.refreshed(fresh, Instant::now()) == SafeModeChange::Cleared {
+                info!("Series refresh recovered, leaving safe mode");
+                let msg = alerts::render("series_safe_mode_cleared", alerts::context! {});
+                telegram::send_alert(&msg)
+                    .await
+                    .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+            }
         }
-    }
-
-    async fn get_or_refresh(&mut self, client: &KalshiClient) -> Result<&[Series]> {
-        if self.is_stale() {
-            match client.list_series().await {
-                Ok(fresh) => {
-                    info!(count = fresh.len(), "Refreshed series list");
-                    self.series = fresh;
-                    self.fetched_at = Some(Instant::now());
-                }
-                Err(e) => {
-                    if self.series.is_empty() {
-                        return Err(e.context("Failed to fetch series list (no cached data)"));
-                    }
-                    warn!(
-                        error = %e,
-                        cached_count = self.series.len(),
-                        "Failed to refresh series list, using stale cache"
-                    );
-                }
+        Err(e) => {
+            if cache.refresh_failed(Instant::now()) == SafeModeChange::Entered {
+                error!(
+                    error = %e,
+                    failing_secs = cache.max_staleness().as_secs(),
+                    safe_series = cache.series().len(),
+                    "Series refresh failing too long, entering safe mode"
+                );
+                let msg = alerts::render(
+                    "series_safe_mode",
+                    alerts::context! {
+                        staleness_secs => cache.max_staleness().as_secs(),
+                        error => e.to_string(),
+                        series => cache.series().len(),
+                    },
+                );
+                telegram::send_alert(&msg)
+                    .await
+                    .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
             }
-        } else {
-            debug!(cached_count = self.series.len(), "Using cached series list");
+            if cache.series().is_empty() {
+                return Err(e).context("Failed to fetch series list (no cached data)");
+            }
+            warn!(
+                error = %e,
+                cached_count = cache.series().len(),
+                safe_mode = cache.safe_mode(),
+                "Failed to refresh series list, using stale cache"
+            );
         }
-        Ok(&self.series)
     }
+    Ok(cache.series())
+}
+
+/// Scan-loop state carried across cycles.
+struct ScanState {
+    limits: RiskLimits,
+    series_cache: SeriesCache,
+    exec_store: ExecutionStore,
+    skip_memo: SkipMemo,
+    /// When the last API budget alert went out (they fire at most hourly).
+    last_usage_alert: Option<Instant>,
+    /// Live orderbooks, when `kalshi.ws_url` is set.
+    feed: Option<OrderbookFeed>,
+    watchlist: WatchlistFile,
+    /// Filled arb legs and settlements (`data/positions.db`).
+    positions: PositionTracker,
+    warmup: Warmup,
+    lifecycle: LifecycleWatch,
+    /// `relation/direction` keys of mispricings already alerted, until they close.
+    relation_alerts: HashSet<String>,
+    /// Paces `watch` passes made from inside scan cycles.
+    risk_watch: RiskWatch,
+    /// This run's fills checked so far; None without an API key (fills are a portfolio read).
+    fill_audit: Option<FillAudit>,
+    /// Dry-run fills and their settlements (`data/paper.jsonl`).
+    paper: PaperPortfolio,
+    /// Per-series scan pacing; None scans every series every cycle.
+    priority: Option<SeriesPriority>,
+    /// Orders working off unhedged legs (`[exits]`).
+    exits: ExitManager,
+    /// Detected opportunities waiting for the next digest alert (`[alerts.opportunities]`).
+    digest: Option<OpportunityDigest>,
+    /// Position discrepancies the last `position_mismatch` alert listed.
+    position_mismatches: Vec<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
@@ -129,337 +336,2333 @@ async fn main() -> Result<()> {
         )
         .init();
 
+    // Offline tooling runs before config.toml is loaded (for `config check` it may be the broken one)
+    match &cli.command {
+        Some(Command::Config { action }) => return cli::config(action),
+        Some(Command::ValidateConfig { path }) => return cli::validate_config(path),
+        Some(Command::AggregateReport { dirs, since, until, timezone }) => {
+            return cli::aggregate_report(dirs, *since, *until, *timezone);
+        }
+        Some(Command::Report { date, timezone, dir }) => return cli::report(dir, *date, *timezone),
+        Some(Command::Export { dir, since, until, timezone, out }) => {
+            return cli::export(dir, *since, *until, *timezone, out);
+        }
+        _ => {}
+    }
+
     let config = Config::load().context("Failed to load config")?;
-    let api_key_id = config::api_key_id()?;
-    let dry_run = config::is_dry_run();
+    config::set_instance_name(&config.instance.name);
+    alerts::init(&config.alerts).context("Failed to load alert templates")?;
+    routing::init(&config.routing);
+    telemetry::init(&config.telemetry);
+    storage::init(&config.storage);
+    fees::init(&config.fees).context("Invalid fee schedule")?;
+    #[cfg(feature = "chaos")]
+    bracket_arb::chaos::init().context("Failed to load chaos scenario")?;
+    if let Some(Command::Backtest { dir, min_roi_pct, size }) = &cli.command {
+        return cli::backtest(&config, dir, *min_roi_pct, *size);
+    }
+    if let Some(Command::ReplayExec { opportunity_id, dir }) = &cli.command {
+        return cli::replay_exec(&config, opportunity_id, dir.as_deref()).await;
+    }
+    if let Some(Command::VenueScan { venue: VenueArg::Polymarket, group }) = &cli.command {
+        return cli::venue_scan(&PolymarketClient::new(&config.polymarket)?, &config, group).await;
+    }
+
+    // Every log line (including spawned tasks that inherit the span) carries the instance
+    let span = info_span!("instance", name = %config.instance.name);
+    run(cli, config).instrument(span).await
+}
+
+async fn run(cli: Cli, mut config: Config) -> Result<()> {
+    // An observer may run keyless: its client reads public market data unsigned
+    let api_key_id = if cli.observer { config::api_key_id().ok() } else { Some(config::api_key_id()?) };
+    let dry_run = cli.observer || config::is_dry_run();
+    let once = matches!(cli.command, Some(Command::ScanOnce));
+
+    let auth = api_key_id.clone().map(|id| KalshiAuth::new(&config.kalshi.rsa_key_path, id)).transpose()?;
+    let client = match auth {
+        Some(auth) if !cli.observer => KalshiClient::new(auth, &config.kalshi, config.scanner.scan_delay_ms)?,
+        auth => {
+            info!(signed = auth.is_some(), "Observer mode: the client refuses every write");
+            KalshiClient::observer(auth, &config.kalshi, config.scanner.scan_delay_ms)?
+        }
+    };
+
+    match cli.command {
+        Some(Command::Preview { event_ticker }) => {
+            return cli::preview(&client, &config, &event_ticker).await;
+        }
+        Some(Command::Execute { event_ticker, direction, size }) => {
+            return cli::execute(&client, &config, dry_run, &event_ticker, direction.into(), size).await;
+        }
+        Some(Command::Positions { diff }) => {
+            return cli::positions(&client, diff).await;
+        }
+        Some(Command::Orders) => {
+            return cli::orders(&client).await;
+        }
+        Some(Command::CancelAll) => {
+            return cli::cancel_all(&client, dry_run).await;
+        }
+        Some(Command::VenueScan { venue: VenueArg::Kalshi, group }) => {
+            return cli::venue_scan(&client, &config, &group).await;
+        }
+        Some(
+            Command::Config { .. }
+            | Command::ValidateConfig { .. }
+            | Command::AggregateReport { .. }
+            | Command::Report { .. }
+            | Command::Export { .. }
+            | Command::Backtest { .. }
+            | Command::ReplayExec { .. }
+            | Command::VenueScan { venue: VenueArg::Polymarket, .. },
+        ) => {
+            unreachable!("handled in main")
+        }
+        Some(Command::Run | Command::ScanOnce) | None => {}
+    }
+
+    // Held until the run returns: a second live bot in this directory stops here
+    let _lock = if dry_run {
+        None
+    } else {
+        Some(instance_lock::acquire(std::path::Path::new(instance_lock::LOCK_PATH), config::instance_name())?)
+    };
 
     if dry_run {
         info!("DRY RUN mode — will scan but not place orders");
+    } else if let Some(canary) = &config.canary {
+        info!(
+            series = ?canary.series,
+            position_size = canary.position_size,
+            "CANARY mode — live on these series only, paper trading the rest"
+        );
     }
 
-    let auth = KalshiAuth::new(&config.kalshi.rsa_key_path, api_key_id)?;
-    let client = KalshiClient::new(auth, config.kalshi.base_url.clone(), config.scanner.scan_delay_ms)?;
-
     // Graceful shutdown
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
-    tokio::spawn(async move {
-        tokio::signal::ctrl_c().await.ok();
-        info!("Shutdown signal received");
-        r.store(false, Ordering::SeqCst);
-    });
+    let shutdown_reason = Arc::new(std::sync::Mutex::new("unknown"));
+    let reason = shutdown_reason.clone();
+    tokio::spawn(
+        async move {
+            let signal = signals::shutdown().await;
+            info!(signal, "Shutdown signal received");
+            *reason.lock().unwrap() = signal;
+            r.store(false, Ordering::SeqCst);
+        }
+        .in_current_span(),
+    );
 
     info!(
         interval_secs = config.scanner.interval_secs,
         position_size = config.risk.position_size,
+        max_open_positions = config.risk.max_open_positions,
         min_profit = config.risk.min_net_profit_cents,
         min_roi = config.risk.min_roi_pct,
         scan_delay_ms = config.scanner.scan_delay_ms,
         min_brackets = config.scanner.min_brackets,
         max_brackets = config.scanner.max_brackets,
         series_cache_secs = config.scanner.series_cache_secs,
+        fee_schedule = fees::current().version(),
         "Starting bracket arb scanner"
     );
 
-    let mut limits = RiskLimits::new();
-    let mut series_cache = SeriesCache::new(config.scanner.series_cache_secs);
+    let mirror_client = if dry_run {
+        None
+    } else {
+        mirror::client(&config).context("Failed to set up execution mirror")?
+    };
 
-    while running.load(Ordering::SeqCst) {
-        match scan_cycle(&client, &config, dry_run, &mut limits, &mut series_cache).await {
-            Ok(_) => {}
-            Err(e) => error!("Scan cycle error: {:#}", e),
-        }
+    check_data_integrity().await;
 
-        // Sleep with early exit on shutdown
-        for _ in 0..config.scanner.interval_secs {
-            if !running.load(Ordering::SeqCst) {
-                break;
-            }
-            sleep(Duration::from_secs(1)).await;
+    let feed = match (&config.kalshi.ws_url, api_key_id.clone()) {
+        (Some(url), Some(api_key_id)) => {
+            let auth = KalshiAuth::new(&config.kalshi.rsa_key_path, api_key_id)?;
+            Some(OrderbookFeed::spawn(auth, url.clone()))
         }
-    }
-
-    info!("Shut down cleanly");
-    Ok(())
-}
-
-async fn scan_cycle(
-    client: &KalshiClient,
-    config: &Config,
-    dry_run: bool,
-    limits: &mut RiskLimits,
-    series_cache: &mut SeriesCache,
-) -> Result<()> {
-    info!("Starting scan cycle");
-
-    let all_series = series_cache.get_or_refresh(client).await?;
+        (Some(_), None) => {
+            warn!("No API key: WebSocket feed disabled, orderbooks are polled over REST");
+            None
+        }
+        (None, _) => None,
+    };
 
-    let series_to_scan: Vec<_> = if config.scanner.series_filter.is_empty() {
-        all_series.to_vec()
-    } else {
-        all_series
-            .iter()
-            .filter(|s| config.scanner.series_filter.contains(&s.ticker))
-            .cloned()
-            .collect()
+    let mut state = ScanState {
+        limits: RiskLimits::new(ExchangeClock::from_config(&config.schedule), &config.risk),
+        series_cache: SeriesCache::new(&config.scanner),
+        exec_store: ExecutionStore::load().context("Failed to load execution store")?,
+        skip_memo: SkipMemo::load(config.scanner.min_brackets, config.scanner.max_brackets),
+        last_usage_alert: None,
+        feed,
+        watchlist: WatchlistFile::new(config.scanner.watchlist_path.clone()),
+        positions: PositionTracker::open(std::path::Path::new(positions::POSITIONS_PATH))?,
+        warmup: Warmup::new(if dry_run || once { 0 } else { config.scanner.warmup_cycles }),
+        lifecycle: LifecycleWatch::default(),
+        relation_alerts: HashSet::new(),
+        risk_watch: RiskWatch::new(match config.scheduler.watch {
+            Schedule::Every(interval) => Some(interval),
+            Schedule::DailyAt(_) => None,
+        }),
+        fill_audit: api_key_id.is_some().then(|| FillAudit::new(Utc::now())),
+        paper: PaperPortfolio::load(std::path::Path::new(paper::PAPER_PATH))?,
+        priority: config
+            .scanner
+            .priority
+            .clone()
+            .map(|p| SeriesPriority::load(p, std::path::Path::new(priority::OPPORTUNITIES_PATH)))
+            .transpose()
+            .context("Failed to load opportunity history")?,
+        exits: ExitManager::default(),
+        digest: config.alerts.opportunities.clone().map(OpportunityDigest::new),
+        position_mismatches: Vec::new(),
     };
+    if state.warmup.active() {
+        info!(cycles = config.scanner.warmup_cycles, "Warming up: first cycles are dry runs");
+    }
+    debug!(events = state.skip_memo.len(), "Loaded structural skip memo");
 
-    let series_count = series_to_scan.len();
-    let mut events_count = 0usize;
-    let mut opportunities_count = 0usize;
-    let mut trades_count = 0usize;
+    // Legs a previous run placed but never recorded are adopted or cancelled; without the
+    // exchange's view, orders still resting per the store are only reported
+    if api_key_id.is_none() || !recover_orders(&client, &config, &mut state, dry_run).await {
+        for order in state.exec_store.open_orders() {
+            warn!(
+                order_id = %order.order_id,
+                ticker = %order.ticker,
+                opportunity = %order.opportunity_id,
+                since = %order.updated_at,
+                "Order from a previous run may still be resting"
+            );
+        }
+    }
+    // ...and keep their events off-limits until the order refresh sees them end
+    for event in state.exec_store.open_events() {
+        if let Ok(guard) = ownership::claim(&event, Strategy::Arb) {
+            guard.retain();
+        }
+    }
+    // Arbs still open from previous runs count against MAX_OPEN_ARBS, and today's orders
+    // and P&L against the daily limits
+    state.positions.sync(&state.exec_store).context("Failed to sync position tracker")?;
+    state.limits.restore(&state.exec_store, &state.positions).context("Failed to restore risk counters")?;
+    info!(
+        open_arbs = state.limits.open_arbs,
+        daily_orders = state.limits.daily_orders,
+        daily_pnl_cents = state.limits.daily_pnl_cents,
+        "Restored risk counters"
+    );
+    if let Some(why) = state.limits.halted() {
+        warn!(halted = %why, "Trading halted: no new orders until the halt file is deleted");
+    }
 
-    for series in &series_to_scan {
-        let events = match client.get_events(&series.ticker).await {
-            Ok(e) => e,
-            Err(e) => {
-                warn!(series = %series.ticker, error = %e, "Failed to fetch events");
-                continue;
+    let (status_tx, status_rx) = tokio::sync::watch::channel(StatusSnapshot::new(
+        &config.instance.name,
+        dry_run,
+        config.status.recent_capacity,
+    ));
+    let warmup_cycles_left = state.warmup.remaining();
+    status::update(&status_tx, |s| s.warmup_cycles_left = warmup_cycles_left);
+    let (control_tx, mut control_rx) = tokio::sync::mpsc::unbounded_channel();
+    if config.telegram.commands {
+        let (status_rx, control_tx) = (status_rx.clone(), control_tx.clone());
+        let halt_file = config.risk.halt_file.clone();
+        tokio::spawn(
+            async move {
+                if let Err(e) = telegram::run_commands(status_rx, control_tx, !dry_run, halt_file).await {
+                    error!("Telegram commands stopped: {:#}", e);
+                }
             }
-        };
-
-        for event in &events {
-            // Gate: must be mutually exclusive
-            if !event.mutually_exclusive {
-                continue;
+            .in_current_span(),
+        );
+    }
+    if let Some(addr) = config.status.listen_addr.clone() {
+        let stall = chrono::Duration::seconds(config.status.stall_secs as i64);
+        tokio::spawn(
+            async move {
+                if let Err(e) = status::serve(addr, stall, status_rx, control_tx).await {
+                    error!("Status server stopped: {:#}", e);
+                }
             }
+            .in_current_span(),
+        );
+    }
 
-            // Gate: need enough active markets (but not too many)
-            let active_markets: Vec<_> = event
-                .markets
-                .iter()
-                .filter(|m| m.status == "active" || m.status == "open")
-                .collect();
+    let mut scheduler = Scheduler::new(ExchangeClock::from_config(&config.schedule));
+    let now = Utc::now();
+    scheduler.add(Job::Scan, Schedule::Every(Duration::from_secs(config.scanner.interval_secs)), now);
+    scheduler.add_from(Job::Heartbeat, config.scheduler.heartbeat, now);
+    scheduler.add_from(Job::DailyReport, config.scheduler.daily_report, now);
+    scheduler.add_from(Job::Settlement, config.scheduler.settlement, now);
+    scheduler.add_from(Job::Lifecycle, config.scheduler.lifecycle, now);
+    if let Some(backup) = &config.backup {
+        scheduler.add_from(Job::Backup, backup.schedule, now);
+    }
+    if !config.relations.is_empty() {
+        scheduler.add_from(Job::Relations, config.scheduler.relations, now);
+    }
+    if let Some(exits) = &config.exits {
+        scheduler.add_from(Job::Exits, exits.schedule, now);
+    }
+    scheduler.add_from(Job::Watch, config.scheduler.watch, now);
+    scheduler.add_from(Job::Reconcile, config.scheduler.reconcile, now);
+    scheduler.add_from(Job::BalanceSnapshot, config.scheduler.balance_snapshot, now);
+    if let Some(rotation) = &config.rotation {
+        scheduler.add_from(Job::RotateLogs, rotation.schedule, now);
+    }
+    for job in &config.scheduler.paused {
+        if scheduler.set_paused(*job, true) {
+            info!(job = job.name(), "Job starts paused");
+        }
+    }
+    if api_key_id.is_none() {
+        // Positions, settlements, and the balance are portfolio reads, which need a signed request
+        for job in [Job::Settlement, Job::Reconcile, Job::BalanceSnapshot] {
+            scheduler.set_paused(job, true);
+            info!(job = job.name(), "Job starts paused: no API key");
+        }
+    }
 
-            if active_markets.len() < config.scanner.min_brackets {
-                debug!(
-                    event = %event.event_ticker,
-                    markets = active_markets.len(),
-                    min = config.scanner.min_brackets,
-                    "Skipping event: too few active markets"
-                );
-                continue;
-            }
-            if active_markets.len() > config.scanner.max_brackets {
-                debug!(
-                    event = %event.event_ticker,
-                    markets = active_markets.len(),
-                    max = config.scanner.max_brackets,
-                    "Skipping event: too many active markets"
-                );
-                continue;
-            }
+    let config_watcher = ConfigWatcher::spawn(std::path::Path::new(config::CONFIG_PATH))
+        .inspect_err(|e| warn!("Config hot reload disabled: {:#}", e))
+        .ok();
 
-            events_count += 1;
+    if once {
+        // One scan, then the usual shutdown, so nothing it placed is left resting
+        if let Err(e) = scan_job(&client, mirror_client.as_ref(), &config, dry_run, &mut state, &status_tx).await {
+            error!(job = Job::Scan.name(), "Job failed: {:#}", e);
+        }
+        *shutdown_reason.lock().unwrap() = "scan-once";
+        running.store(false, Ordering::SeqCst);
+    }
 
-            // Fetch orderbooks for all markets in this event
-            let mut quotes = Vec::new();
-            let mut skip_event = false;
-
-            for market in &active_markets {
-                match client.get_orderbook(&market.ticker).await {
-                    Ok(ob) => {
-                        if let Some(quote) = quote_from_orderbook(
-                            &market.ticker,
-                            &market.title,
-                            &ob,
-                        ) {
-                            quotes.push(quote);
-                        } else {
-                            // No NO bids → can't compute YES ask → skip this event
-                            skip_event = true;
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        warn!(
-                            market = %market.ticker,
-                            error = %e,
-                            "Failed to fetch orderbook"
-                        );
-                        skip_event = true;
-                        break;
-                    }
+    // Dry run switched on remotely; a bot started dry can't be switched live
+    let mut remote_dry_run = false;
+    while running.load(Ordering::SeqCst) {
+        if let Some(watcher) = config_watcher.as_ref().filter(|w| w.take_changed()) {
+            reload_config(watcher.path(), &mut config, &mut state).await;
+        }
+        while let Ok(cmd) = control_rx.try_recv() {
+            match cmd {
+                Control::Job(cmd) => {
+                    scheduler.set_paused(cmd.job, cmd.paused);
+                    info!(job = cmd.job.name(), paused = cmd.paused, "Job control");
+                    let jobs = scheduler.snapshot();
+                    status::update(&status_tx, |s| s.jobs = jobs);
+                }
+                Control::DryRun(on) => {
+                    remote_dry_run = on;
+                    warn!(dry_run = dry_run || on, "Dry run switched remotely");
+                    status::update(&status_tx, |s| s.dry_run = dry_run || on);
                 }
             }
+        }
 
-            if skip_event || quotes.len() != active_markets.len() {
-                continue;
+        let Some(job) = scheduler.due(Utc::now()) else {
+            // Nothing due: tick, staying responsive to shutdown and job control
+            sleep(Duration::from_secs(1)).await;
+            continue;
+        };
+        let started = Utc::now();
+        let result = match job {
+            Job::Scan => {
+                let dry_run = dry_run || remote_dry_run;
+                scan_job(&client, mirror_client.as_ref(), &config, dry_run, &mut state, &status_tx).await
+            }
+            Job::Heartbeat => {
+                heartbeat(&mut state, &status_tx);
+                Ok(())
+            }
+            Job::DailyReport => {
+                daily_report(&mut state).await;
+                Ok(())
             }
+            Job::Backup => run_backup(&config).await,
+            Job::Settlement => settlement_job(&client, &mut state).await,
+            Job::Reconcile => reconcile_job(&client, &mut state).await,
+            Job::BalanceSnapshot => balance_snapshot(&client, &mut state).await,
+            Job::RotateLogs => rotate_logs(&config),
+            Job::Lifecycle => lifecycle_job(&client, &mut state).await,
+            // Warmup holds back whatever acts on live data outside the scan too
+            Job::Relations if state.warmup.active() => {
+                debug!("Warming up, relations not quoted yet");
+                Ok(())
+            }
+            Job::Relations => relations_job(&client, &config, &mut state).await,
+            Job::Exits => match &config.exits {
+                Some(exits) => {
+                    let dry_run = dry_run || remote_dry_run || state.warmup.active();
+                    exits_job(&client, exits, dry_run, &mut state).await
+                }
+                None => Ok(()),
+            },
+            Job::Watch => {
+                let ScanState { limits, exec_store, positions, feed, risk_watch, fill_audit, .. } = &mut state;
+                let audited = match fill_audit {
+                    Some(audit) => audit_fills(&client, &config, exec_store, limits, audit, &status_tx).await,
+                    None => Ok(()),
+                };
+                let watched = watch_job(&client, feed.as_ref(), exec_store, positions, risk_watch, &status_tx).await;
+                audited.and(watched)
+            }
+        };
+        match &result {
+            // Every job will fail the same way until someone fixes the key
+            Err(e) if KalshiError::of(e).is_some_and(KalshiError::is_auth) => error!(
+                job = job.name(),
+                "Job failed: Kalshi refused our credentials, check KALSHI_API_KEY_ID and rsa_key_path: {:#}",
+                e
+            ),
+            Err(e) => error!(job = job.name(), "Job failed: {:#}", e),
+            Ok(()) => {}
+        }
+        scheduler.finish(job, started, Utc::now(), result.err().map(|e| format!("{:#}", e)));
+        let jobs = scheduler.snapshot();
+        status::update(&status_tx, |s| s.jobs = jobs);
+    }
 
-            // Detect arb opportunities
-            let opps = detect_arb(
-                &event.event_ticker,
-                &event.title,
-                &quotes,
-                config.risk.position_size,
-                config.risk.min_net_profit_cents,
-                config.risk.min_roi_pct,
-            );
+    // Nothing is left working unattended: cancel what the store still has resting
+    let mut open: Vec<String> = state.exec_store.open_orders().iter().map(|o| o.order_id.clone()).collect();
+    open.extend(state.exits.working.values().map(|w| w.order_id.clone()));
+    if !dry_run && !open.is_empty() {
+        info!(orders = open.len(), "Cancelling resting orders before exit");
+        let outcomes = cancel_and_record(&client, &mut state.exec_store, &open).await;
+        for (order_id, e) in &outcomes.failed {
+            error!(order_id = %order_id, error = %e, "Cancel failed, order left resting");
+        }
+    }
 
-            for opp in &opps {
-                opportunities_count += 1;
-                info!(
-                    event = %opp.event_ticker,
-                    direction = %opp.direction,
-                    brackets = opp.brackets.len(),
-                    sum = format!("${:.2}", opp.sum_cents as f64 / 100.0),
-                    fees = format!("${:.2}", opp.total_fees_cents as f64 / 100.0),
-                    net_profit = format!("${:.2}", opp.net_profit_cents as f64 / 100.0),
-                    roi = format!("{:.1}%", opp.roi_pct),
-                    "ARB FOUND"
-                );
+    let reason = *shutdown_reason.lock().unwrap();
+    send_shutdown_report(&state, &status_tx.borrow(), reason).await;
+    info!("Shut down cleanly");
+    Ok(())
+}
 
-                if dry_run {
-                    storage::log_opportunity(opp, false)
-                        .unwrap_or_else(|e| warn!("Failed to log opportunity: {}", e));
-                    continue;
-                }
+/// Re-read the config file after an edit and apply its `[scanner]` and `[risk]` from the next
+/// job on. A file that doesn't validate is reported with a `config_rejected` alert and ignored.
+async fn reload_config(path: &std::path::Path, config: &mut Config, state: &mut ScanState) {
+    let old_window = config.risk.adaptive_roi.as_ref().map(|a| a.window);
+    let reload = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))
+        .and_then(|content| reload::apply(config, &content));
+    let reload = match reload {
+        Ok(reload) => reload,
+        Err(e) => {
+            error!(path = %path.display(), error = %format!("{:#}", e), "Config reload rejected");
+            let msg = alerts::render(
+                "config_rejected",
+                alerts::context! { path => path.display().to_string(), error => format!("{:#}", e) },
+            );
+            telegram::send_alert(&msg).await.unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+            return;
+        }
+    };
+    for c in &reload.applied {
+        info!(key = %c.key, old = ?c.old, new = ?c.new, "Config reloaded");
+    }
+    for c in &reload.needs_restart {
+        warn!(key = %c.key, old = ?c.old, new = ?c.new, "Config change takes effect on restart");
+    }
+    if !reload.applied.is_empty() {
+        state.limits.reconfigure(&config.risk, old_window);
+        state.series_cache.reconfigure(&config.scanner);
+    }
+}
 
-                // --- Pre-flight risk checks (hardcoded circuit breakers) ---
-                if let Some(reason) = limits.check() {
-                    warn!(
-                        event = %opp.event_ticker,
-                        reason = reason,
-                        open_arbs = limits.open_arbs,
-                        daily_pnl_cents = limits.daily_pnl_cents,
-                        daily_orders = limits.daily_orders,
-                        "RISK LIMIT HIT — skipping execution"
-                    );
-                    storage::log_opportunity(opp, false)
-                        .unwrap_or_else(|e| warn!("Failed to log: {}", e));
-                    let msg = format!(
-                        "*RISK LIMIT: {}*\nEvent: `{}`\nOpen arbs: {}/{}\nDaily P&L: ${:.2}\nDaily orders: {}/{}",
-                        reason,
-                        opp.event_ticker,
-                        limits.open_arbs, MAX_OPEN_ARBS,
-                        limits.daily_pnl_cents as f64 / 100.0,
-                        limits.daily_orders, MAX_DAILY_ORDERS,
-                    );
-                    telegram::send_alert(&msg).await.unwrap_or_else(|e| {
-                        warn!("Telegram alert failed: {}", e);
-                    });
-                    continue;
-                }
+/// Record the run's closing state in `data/shutdowns.jsonl` and send it as a `shutdown` alert.
+async fn send_shutdown_report(state: &ScanState, status: &StatusSnapshot, reason: &str) {
+    let stopped_at = Utc::now();
+    let report = storage::ShutdownReport {
+        instance: status.instance.clone(),
+        started_at: status.started_at,
+        stopped_at,
+        uptime_secs: (stopped_at - status.started_at).num_seconds(),
+        reason: reason.to_string(),
+        dry_run: status.dry_run,
+        cycles: status.cycles,
+        opportunities: status.total_opportunities,
+        executions: status.total_trades,
+        daily_pnl_cents: state.limits.daily_pnl_cents,
+        open_arbs: state.limits.open_arbs,
+        resting_orders: state.exec_store.open_orders().len(),
+    };
+    info!(?report, "Shutdown report");
+    storage::log_shutdown(&report).unwrap_or_else(|e| warn!("Failed to log shutdown report: {}", e));
+    let msg = alerts::render(
+        "shutdown",
+        alerts::context! {
+            reason => report.reason,
+            dry_run => report.dry_run,
+            uptime_secs => report.uptime_secs,
+            cycles => report.cycles,
+            opportunities => report.opportunities,
+            executions => report.executions,
+            daily_pnl_cents => report.daily_pnl_cents,
+            open_arbs => report.open_arbs,
+            resting_orders => report.resting_orders,
+        },
+    );
+    telegram::send_alert(&msg).await.unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+}
 
-                // Execute
-                storage::log_opportunity(opp, true)
-                    .unwrap_or_else(|e| warn!("Failed to log opportunity: {}", e));
+/// Cancel orders in one rate-paced batch and mark those the store tracks as cancelled.
+async fn cancel_and_record(
+    client: &KalshiClient,
+    store: &mut ExecutionStore,
+    order_ids: &[String],
+) -> CancelOutcomes {
+    let outcomes = client.cancel_orders(order_ids).await;
+    for order_id in &outcomes.canceled {
+        if store.order(order_id).is_some() {
+            store
+                .update_order_status(order_id, "canceled")
+                .unwrap_or_else(|e| warn!("Failed to record cancel: {}", e));
+        }
+    }
+    outcomes
+}
 
-                match executor::execute_arb(client, opp, config.risk.position_size).await {
-                    Ok(result) => {
-                        let order_count = result.filled.len() + result.resting.len() + result.other.len();
-                        limits.daily_orders += order_count as u32;
+/// Startup pass over the exchange's resting orders, logging a decision for each: legs of
+/// opportunities the store never recorded (a run that died mid-execution) are adopted into
+/// the store or cancelled per `[recovery] orphan_orders`. A dry run only logs. Returns
+/// false when the orders can't be listed.
+async fn recover_orders(client: &KalshiClient, config: &Config, state: &mut ScanState, dry_run: bool) -> bool {
+    let orders = match client.get_orders(Some("resting")).await {
+        Ok(orders) => orders,
+        Err(e) => {
+            warn!(error = %e, "Failed to list resting orders for startup recovery");
+            return false;
+        }
+    };
+    let mut adopt: BTreeMap<String, (Orphan, Vec<(String, Order)>)> = BTreeMap::new();
+    let mut cancel = Vec::new();
+    for order in orders {
+        let policy = config.recovery.orphan_orders;
+        let decision = recovery::classify(&order, &state.exec_store, &config.scanner.series_filter, policy);
+        info!(
+            order_id = %order.order_id,
+            ticker = %order.ticker,
+            client_order_id = ?order.client_order_id,
+            remaining = ?order.remaining_count,
+            decision = %decision,
+            dry_run,
+            "Resting order at startup"
+        );
+        match decision {
+            Recovery::Adopt(orphan) => adopt
+                .entry(orphan.opportunity_id.clone())
+                .or_insert_with(|| (orphan, Vec::new()))
+                .1
+                .push((order.ticker.clone(), order)),
+            Recovery::Cancel(_) => cancel.push(order.order_id),
+            Recovery::Tracked | Recovery::Foreign => {}
+        }
+    }
+    if dry_run || (adopt.is_empty() && cancel.is_empty()) {
+        return true;
+    }
 
-                        if result.is_fully_filled() {
-                            trades_count += result.filled.len();
-                            limits.open_arbs += 1;
-                            limits.daily_pnl_cents += opp.net_profit_cents;
-                            info!(
-                                event = %opp.event_ticker,
-                                orders = result.filled.len(),
-                                "All orders filled successfully"
-                            );
+    let mut adopted = 0;
+    for (opportunity_id, (orphan, resting)) in adopt {
+        let legs = resting.len();
+        let result = ExecutionResult {
+            event_ticker: orphan.event_ticker,
+            direction: orphan.direction,
+            filled: Vec::new(),
+            resting,
+            other: Vec::new(),
+            api_failures: Vec::new(),
+            failure_kinds: Default::default(),
+            aborted: false,
+            unwind: Vec::new(),
+        };
+        match state.exec_store.record(&opportunity_id, Some(orphan.uuid), &result, BTreeMap::new()) {
+            Ok(()) => adopted += legs,
+            Err(e) => error!(opportunity = %opportunity_id, error = %e, "Failed to adopt orphaned orders"),
+        }
+    }
+    let outcomes = cancel_and_record(client, &mut state.exec_store, &cancel).await;
+    state.limits.daily_cancels += outcomes.canceled.len() as u32;
+    for (order_id, e) in &outcomes.failed {
+        error!(order_id = %order_id, error = %e, "Failed to cancel orphaned order");
+    }
+    let msg = alerts::render(
+        "orphaned_orders",
+        alerts::context! {
+            adopted => adopted,
+            cancelled => outcomes.canceled.len(),
+            failed => outcomes.failed.len(),
+        },
+    );
+    telegram::send_alert(&msg)
+        .await
+        .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+    true
+}
 
-                            // Reconciliation: match filled orders to brackets by ticker
-                            storage::log_reconciliation(opp, &result.filled, false)
-                                .unwrap_or_else(|e| warn!("Failed to log reconciliation: {}", e));
-                        } else if result.is_total_failure() {
-                            error!(
-                                event = %opp.event_ticker,
-                                api_failures = result.api_failures.len(),
-                                "Total execution failure — no orders placed"
-                            );
-                            let msg = format!(
-                                "*TOTAL FAILURE*\nEvent: `{}`\nDirection: {}\nBrackets: {}\nAll {} orders failed",
-                                opp.event_ticker,
-                                opp.direction,
-                                opp.brackets.len(),
-                                result.api_failures.len(),
-                            );
-                            telegram::send_alert(&msg).await.unwrap_or_else(|e| {
-                                warn!("Telegram alert failed: {}", e);
-                            });
-                        } else {
-                            // Mixed state: some filled, some resting/failed
-                            // Worst-case loss: cost of filled orders (unhedged position)
-                            let loss: i64 = result.filled.iter()
-                                .map(|(_, o)| o.yes_price.unwrap_or(0) * o.count.unwrap_or(0))
-                                .sum();
-                            limits.daily_pnl_cents -= loss;
-
-                            warn!(
-                                event = %opp.event_ticker,
-                                filled = result.filled.len(),
-                                resting = result.resting.len(),
-                                other = result.other.len(),
-                                api_failures = result.api_failures.len(),
-                                loss_cents = loss,
-                                "Mixed execution state — cancelling resting orders"
-                            );
+/// Record the final state of orders the store has as resting but the exchange no
+/// longer does, so rested legs end up as filled or cancelled in the fill analytics.
+async fn settle_stopped_orders(client: &KalshiClient, store: &mut ExecutionStore, resting: &[Order]) {
+    let stopped: Vec<String> = store
+        .open_orders()
+        .into_iter()
+        .filter(|o| !resting.iter().any(|r| r.order_id == o.order_id))
+        .map(|o| o.order_id.clone())
+        .collect();
+    for order_id in stopped {
+        match client.get_order(&order_id).await {
+            Ok(order) if order.status != "resting" => {
+                debug!(order_id = %order_id, status = %order.status, "Order no longer resting");
+                store
+                    .update_order_status(&order_id, &order.status)
+                    .unwrap_or_else(|e| warn!("Failed to record order status: {}", e));
+            }
+            Ok(_) => {}
+            Err(e) => warn!(order_id = %order_id, error = %e, "Failed to fetch order"),
+        }
+    }
+}
 
-                            // Cancel all resting orders
-                            for (ticker, order) in &result.resting {
-                                if let Err(e) = client.cancel_order(&order.order_id).await {
-                                    error!(
-                                        ticker = %ticker,
-                                        order_id = %order.order_id,
-                                        error = %e,
-                                        "Cancel failed"
-                                    );
-                                }
-                            }
-                            // Cancel any other-status orders too
-                            for (ticker, order) in &result.other {
-                                if let Err(e) = client.cancel_order(&order.order_id).await {
-                                    error!(
-                                        ticker = %ticker,
-                                        order_id = %order.order_id,
-                                        error = %e,
-                                        "Cancel failed"
-                                    );
-                                }
-                            }
-
-                            // Log reconciliation for whatever did fill (incomplete arb)
-                            if !result.filled.is_empty() {
-                                storage::log_reconciliation(opp, &result.filled, true)
-                                    .unwrap_or_else(|e| warn!("Failed to log reconciliation: {}", e));
-                            }
-
-                            let msg = format!(
-                                "*PARTIAL FILL*\nEvent: `{}`\nDirection: {}\nBrackets: {}\nFilled: {}\nResting: {} (cancelled)\nFailed: {}\nExpected profit: ${:.2}",
-                                opp.event_ticker,
-                                opp.direction,
-                                opp.brackets.len(),
-                                result.filled.len(),
-                                result.resting.len(),
-                                result.api_failures.len() + result.other.len(),
-                                opp.net_profit_cents as f64 / 100.0,
-                            );
-                            telegram::send_alert(&msg).await.unwrap_or_else(|e| {
-                                warn!("Telegram alert failed: {}", e);
-                            });
-                        }
-                    }
-                    Err(e) => {
-                        error!(event = %opp.event_ticker, error = %e, "Execution failed");
-                    }
+/// Check this run's new fills against the execution store (`fill_audit.rs`). Any anomaly
+/// trips the kill switch: executions stop until restart, every order of ours still resting
+/// is cancelled, and a `kill_switch` alert pages with what didn't add up.
+async fn audit_fills(
+    client: &KalshiClient,
+    config: &Config,
+    exec_store: &mut ExecutionStore,
+    limits: &mut RiskLimits,
+    audit: &mut FillAudit,
+    status_tx: &StatusSender,
+) -> Result<()> {
+    let fills = client.get_fills(audit.since()).await.context("Failed to fetch fills")?;
+    // Orders the store never saw count as ours when their client order ID says so
+    let mut unrecorded = Vec::new();
+    for order_id in audit.unresolved(&fills, exec_store) {
+        match client.get_order(&order_id).await {
+            Ok(order) => {
+                let filter = &config.scanner.series_filter;
+                let ours = recovery::classify(&order, exec_store, filter, OrphanPolicy::Cancel) != Recovery::Foreign;
+                if ours && order.status == "resting" {
+                    unrecorded.push(order_id.clone());
                 }
+                audit.resolve(&order_id, ours);
             }
+            Err(e) => warn!(order_id = %order_id, error = %e, "Failed to look up filled order"),
         }
     }
+    let anomalies = audit.check(&fills, exec_store);
+    if anomalies.is_empty() {
+        return Ok(());
+    }
+    for anomaly in &anomalies {
+        error!(anomaly = %anomaly, "Fill anomaly");
+    }
+    if limits.kill_switch.is_none() {
+        error!(anomalies = anomalies.len(), "KILL SWITCH — fills don't match the execution store, stopping");
+        limits.kill_switch = Some(anomalies[0].to_string());
+    }
 
-    storage::log_scan(series_count, events_count, opportunities_count, trades_count)
-        .unwrap_or_else(|e| warn!("Failed to log scan: {}", e));
+    let mut cancel: Vec<String> = exec_store.open_orders().iter().map(|o| o.order_id.clone()).collect();
+    cancel.extend(unrecorded);
+    let outcomes = cancel_and_record(client, exec_store, &cancel).await;
+    limits.maybe_reset_day();
+    limits.daily_cancels += outcomes.canceled.len() as u32;
+    for (order_id, e) in &outcomes.failed {
+        error!(order_id = %order_id, error = %e, "Cancel failed, order left resting");
+    }
+    let risk = limits.counters();
+    let open_orders = exec_store.open_orders().len();
+    status::update(status_tx, |s| {
+        s.risk = risk;
+        s.open_orders = open_orders;
+    });
+    let msg = alerts::render(
+        "kill_switch",
+        alerts::context! {
+            anomalies => anomalies.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+            cancelled => outcomes.canceled.len(),
+            failed => outcomes.failed.len(),
+        },
+    );
+    telegram::send_alert(&msg).await.unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+    Ok(())
+}
 
-    info!(
+/// Refresh resting orders and re-quote the markets with live risk (`watch.rs`): orders
+/// that stopped resting are recorded, events left without orders released, and each
+/// market's quote published under `watched_markets`.
+async fn watch_job(
+    client: &KalshiClient,
+    feed: Option<&OrderbookFeed>,
+    exec_store: &mut ExecutionStore,
+    positions: &PositionTracker,
+    risk_watch: &mut RiskWatch,
+    status_tx: &StatusSender,
+) -> Result<()> {
+    risk_watch.ran(Instant::now());
+    let unhedged = positions.unhedged_tickers()?;
+    if exec_store.open_orders().is_empty() && unhedged.is_empty() {
+        // Nothing of ours rests: only the other orders from the last refresh still stand
+        status::update(status_tx, |s| {
+            s.watched_markets.clear();
+            if let Some(orders) = &mut s.exchange_orders {
+                orders.retain(|o| o.opportunity_id.is_none());
+            }
+        });
+        return Ok(());
+    }
+    let resting = client.get_orders(Some("resting")).await.context("Failed to refresh resting orders")?;
+    settle_stopped_orders(client, exec_store, &resting).await;
+    for event in ownership::release_settled(Strategy::Arb, &exec_store.open_events()) {
+        info!(event = %event, "No orders left in event — released");
+    }
+    let exchange_orders = status::open_order_entries(&resting, exec_store);
+
+    let mut markets = watch::hot_markets(&exec_store.open_orders(), &unhedged);
+    if let Some(feed) = feed {
+        feed.watch(&markets.iter().map(|m| m.ticker.as_str()).collect::<Vec<_>>());
+    }
+    for market in &mut markets {
+        let book = match feed.and_then(|f| f.orderbook(&market.ticker)) {
+            Some(book) => book,
+            None => match client.get_orderbook(&market.ticker).await {
+                Ok(book) => book,
+                Err(e) => {
+                    warn!(market = %market.ticker, error = %e, "Failed to fetch watched orderbook");
+                    continue;
+                }
+            },
+        };
+        let quote = detector::quote_from_orderbook(&market.ticker, "", &book, 0);
+        market.yes_bid_cents = quote.as_ref().map(|q| q.yes_bid_cents);
+        market.yes_ask_cents = quote.as_ref().map(|q| q.yes_ask_cents);
+        debug!(
+            market = %market.ticker,
+            resting = market.resting,
+            unhedged = market.unhedged,
+            bid = ?market.yes_bid_cents,
+            ask = ?market.yes_ask_cents,
+            "Watched market"
+        );
+    }
+    let open_orders = exec_store.open_orders().len();
+    status::update(status_tx, |s| {
+        s.open_orders = open_orders;
+        s.exchange_orders = Some(exchange_orders);
+        s.owned_events = ownership::snapshot();
+        s.watched_markets = markets;
+    });
+    Ok(())
+}
+
+/// One scan cycle plus the housekeeping that follows it: endpoint-switch and API
+/// budget alerts and the status snapshot. Resting orders are the watch job's to refresh.
+async fn scan_job(
+    client: &KalshiClient,
+    mirror_client: Option<&KalshiClient>,
+    config: &Config,
+    dry_run: bool,
+    state: &mut ScanState,
+    status_tx: &StatusSender,
+) -> Result<()> {
+    let warming_up = state.warmup.active();
+    let result = scan_cycle(client, mirror_client, config, dry_run || warming_up, state, status_tx).await;
+    let warmup_baseline_ms = state.warmup.record(&result);
+    if let Some(baseline_ms) = warmup_baseline_ms {
+        info!(baseline_ms, "Warmup complete, live execution enabled");
+    } else if warming_up && result.is_err() {
+        warn!("Warmup cycle failed, staying in dry run");
+    }
+    if let Some(notice) = client.take_endpoint_notice() {
+        warn!(notice = %notice, "Kalshi endpoint switched");
+        let msg = alerts::render("endpoint_switch", alerts::context! { notice });
+        telegram::send_alert(&msg)
+            .await
+            .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+    }
+    if let Some(notice) = client.take_circuit_notice() {
+        let msg = alerts::render("circuit_breaker", alerts::context! { notice });
+        telegram::send_alert(&msg)
+            .await
+            .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+    }
+
+    let open_orders = state.exec_store.open_orders().len();
+    let api_usage = client.usage();
+    let usage_warnings = api_usage.budget_warnings(&config.kalshi.usage);
+    if !usage_warnings.is_empty()
+        && state.last_usage_alert.is_none_or(|t| t.elapsed() >= Duration::from_secs(3_600))
+    {
+        warn!(warnings = ?usage_warnings, "API usage approaching tier limit");
+        let msg = alerts::render("api_budget", alerts::context! { warnings => usage_warnings });
+        telegram::send_alert(&msg)
+            .await
+            .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+        state.last_usage_alert = Some(Instant::now());
+    }
+    let risk = state.limits.counters();
+    let series_safe_mode = state.series_cache.safe_mode();
+    let live_orderbooks = state.feed.as_ref().map(|f| f.live_markets());
+    let (stats, error) = match result {
+        Ok(stats) => (Some(stats), None),
+        Err(e) => (None, Some(e)),
+    };
+    let last_error = error.as_ref().map(|e| format!("{:#}", e));
+    let warmup_cycles_left = state.warmup.remaining();
+    let paper = state.paper.summary();
+    status::update(status_tx, |s| {
+        s.cycles += 1;
+        s.warmup_cycles_left = warmup_cycles_left;
+        if warmup_baseline_ms.is_some() {
+            s.warmup_baseline_ms = warmup_baseline_ms;
+        }
+        s.risk = risk;
+        s.open_orders = open_orders;
+        s.series_safe_mode = series_safe_mode;
+        s.api_usage = api_usage;
+        s.owned_events = ownership::snapshot();
+        s.live_orderbooks = live_orderbooks;
+        s.paper = paper;
+        if let Some(stats) = &stats {
+            s.total_opportunities += stats.opportunities as u64;
+            s.total_trades += stats.trades as u64;
+        }
+        if stats.is_some() {
+            s.last_cycle = stats;
+            s.last_scan_ok_at = Some(Utc::now());
+        }
+        if last_error.is_some() {
+            s.last_error = last_error;
+        }
+    });
+    match error {
+        Some(e) => Err(e.context("Scan cycle error")),
+        None => Ok(()),
+    }
+}
+
+/// Startup check of `data/`: alert on torn, unparseable, shrunk, or rewritten files.
+/// A clean directory becomes the new baseline for the next check.
+async fn check_data_integrity() {
+    let dir = std::path::Path::new(backup::DATA_DIR);
+    match backup::check_integrity(dir) {
+        Ok(problems) if problems.is_empty() => {
+            backup::write_manifest(dir)
+                .unwrap_or_else(|e| warn!("Failed to write integrity manifest: {}", e));
+        }
+        Ok(problems) => {
+            error!(problems = ?problems, "Data directory failed integrity check");
+            let msg = alerts::render("data_integrity", alerts::context! { problems });
+            telegram::send_alert(&msg)
+                .await
+                .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+        }
+        Err(e) => warn!("Failed to check data integrity: {:#}", e),
+    }
+}
+
+/// Archive `data/` to the `[backup]` target; failures alert, since a silently
+/// missing backup is only noticed when it's needed.
+async fn run_backup(config: &Config) -> Result<()> {
+    let Some(backup) = &config.backup else {
+        return Ok(());
+    };
+    match backup::run(backup, &config.instance.name).await {
+        Ok(dest) => {
+            info!(dest = %dest, "Data directory backed up");
+            Ok(())
+        }
+        Err(e) => {
+            let msg = alerts::render("backup_failed", alerts::context! { error => format!("{:#}", e) });
+            telegram::send_alert(&msg)
+                .await
+                .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+            Err(e)
+        }
+    }
+}
+
+/// Roll over `data/` logs past `[rotation] max_mb`, then re-baseline the integrity
+/// manifest, which would otherwise report the rotated-out files missing.
+fn rotate_logs(config: &Config) -> Result<()> {
+    let Some(rotation) = &config.rotation else {
+        return Ok(());
+    };
+    let dir = std::path::Path::new(backup::DATA_DIR);
+    let rotated = storage::rotate_logs(dir, rotation.max_mb.saturating_mul(1_000_000), Utc::now())?;
+    if !rotated.is_empty() {
+        info!(files = ?rotated, "Logs rotated");
+        backup::write_manifest(dir)?;
+    }
+    Ok(())
+}
+
+/// Record available cash in `balances.md`, one row per run.
+async fn balance_snapshot(client: &KalshiClient, state: &mut ScanState) -> Result<()> {
+    let balance = client.get_balance().await.context("Failed to fetch balance")?;
+    state.limits.balance_cents = Some(balance);
+    info!(balance_cents = balance, open_arbs = state.limits.open_arbs, "Balance snapshot");
+    Ok(storage::log_balance(balance, state.limits.open_arbs)?)
+}
+
+/// Compare the positions the execution store derives from our fills with the exchange's,
+/// over the markets of arbs not yet settled, and send `position_mismatch` when they
+/// disagree. The same set of discrepancies is alerted once.
+async fn reconcile_job(client: &KalshiClient, state: &mut ScanState) -> Result<()> {
+    state.positions.sync(&state.exec_store)?;
+    let tickers = state.positions.unsettled_tickers()?;
+    let events = state.positions.unsettled_events()?;
+    let local: Vec<LocalPosition> =
+        state.exec_store.positions().into_iter().filter(|p| tickers.contains(&p.ticker)).collect();
+    // Positions in events without an open arb aren't ours to reconcile
+    let exchange: Vec<_> = client
+        .get_positions()
+        .await
+        .context("Failed to fetch positions")?
+        .into_iter()
+        .filter(|p| p.position != 0 && events.iter().any(|e| p.ticker.starts_with(&format!("{}-", e))))
+        .collect();
+
+    let mut all: Vec<&str> = local.iter().map(|p| p.ticker.as_str()).collect();
+    all.extend(exchange.iter().map(|p| p.ticker.as_str()));
+    all.sort_unstable();
+    all.dedup();
+    let mismatches: Vec<String> = all
+        .into_iter()
+        .filter_map(|ticker| {
+            let l = local.iter().find(|p| p.ticker == ticker);
+            let x = exchange.iter().find(|p| p.ticker == ticker);
+            let check = position_check(l, x);
+            (check != "ok").then(|| {
+                format!(
+                    "{} {}: local {}, exchange {}",
+                    ticker,
+                    check,
+                    l.map_or(0, |p| p.position),
+                    x.map_or(0, |p| p.position)
+                )
+            })
+        })
+        .collect();
+
+    if mismatches.is_empty() {
+        if !state.position_mismatches.is_empty() {
+            info!("Positions agree with the exchange again");
+        }
+    } else if mismatches != state.position_mismatches {
+        warn!(mismatches = ?mismatches, "Positions disagree with the exchange");
+        let msg = alerts::render("position_mismatch", alerts::context! { mismatches });
+        telegram::send_alert(&msg)
+            .await
+            .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+    }
+    state.position_mismatches = mismatches;
+    Ok(())
+}
+
+/// Bring the position tracker up to date with the execution store, mark arbs whose
+/// markets have all settled, and recount open arbs from what is still unsettled.
+async fn settlement_job(client: &KalshiClient, state: &mut ScanState) -> Result<()> {
+    let added = state.positions.sync(&state.exec_store)?;
+    if added > 0 {
+        debug!(legs = added, "Recorded filled arb legs");
+    }
+    let settlements = client.get_settlements().await.context("Failed to fetch settlements")?;
+    for arb in state.positions.settle(&settlements)? {
+        let uuid = state.exec_store.get(&arb.opportunity_id).and_then(|r| r.uuid);
+        info!(
+            opportunity = %arb.opportunity_id,
+            uuid = ?uuid,
+            event = %arb.event_ticker,
+            realized_pnl_cents = arb.realized_pnl_cents,
+            "Arb settled"
+        );
+        state
+            .exec_store
+            .transition(&arb.opportunity_id, uuid, OpportunityState::Settled, None)
+            .unwrap_or_else(|e| warn!("Failed to record opportunity state: {:#}", e));
+    }
+
+    // A leg with neither a position nor a settlement was closed outside the bot
+    // (or its settlement hasn't been published yet)
+    let held: HashSet<String> =
+        client.get_positions().await.context("Failed to fetch positions")?.into_iter().map(|p| p.ticker).collect();
+    for ticker in state.positions.unsettled_tickers()?.difference(&held) {
+        warn!(ticker = %ticker, "Open arb leg has no exchange position");
+    }
+
+    state.limits.open_arbs = state.positions.open_arbs()?;
+    settle_paper(client, &mut state.paper).await
+}
+
+/// Settle paper trades whose markets all have results.
+async fn settle_paper(client: &KalshiClient, paper: &mut PaperPortfolio) -> Result<()> {
+    let mut results = HashMap::new();
+    for event_ticker in paper.open_events() {
+        match client.get_event(&event_ticker).await {
+            Ok(event) => results.extend(
+                event.markets.iter().filter_map(|m| Some((m.ticker.clone(), m.settled_result()?.to_string()))),
+            ),
+            Err(e) => warn!(event = %event_ticker, error = %e, "Failed to fetch paper trade's event"),
+        }
+    }
+    for settled in paper.settle(&results)? {
+        info!(opportunity = %settled.opportunity_id, pnl = money::dollars(settled.pnl_cents), "Paper trade settled");
+    }
+    Ok(())
+}
+
+/// Check the markets of every event we hold a leg in or have orders resting in, between
+/// scans. When one stops trading (halt, early close, result), cancel our resting orders
+/// in its event — the arb can no longer complete — and alert.
+async fn lifecycle_job(client: &KalshiClient, state: &mut ScanState) -> Result<()> {
+    let mut events = state.positions.unsettled_events()?;
+    events.extend(state.exec_store.open_events());
+    let held = state.positions.unsettled_tickers()?;
+    let now = Utc::now();
+    let mut watched = HashSet::new();
+
+    for event_ticker in &events {
+        let event = match client.get_event(event_ticker).await {
+            Ok(e) => e,
+            Err(e) => {
+                warn!(event = %event_ticker, error = %e, "Failed to fetch event for lifecycle check");
+                continue;
+            }
+        };
+        for market in &event.markets {
+            watched.insert(market.ticker.clone());
+            let phase = lifecycle::phase(market, now);
+            let Some(previous) = state.lifecycle.observe(&market.ticker, phase) else {
+                continue;
+            };
+            let store = &state.exec_store;
+            let resting: Vec<String> = store
+                .open_orders()
+                .into_iter()
+                .filter(|o| store.get(&o.opportunity_id).is_some_and(|r| &r.result.event_ticker == event_ticker))
+                .map(|o| o.order_id.clone())
+                .collect();
+            warn!(
+                event = %event_ticker,
+                market = %market.ticker,
+                status = %market.status,
+                phase = %phase,
+                resting = resting.len(),
+                "Market stopped trading"
+            );
+            let outcomes = cancel_and_record(client, &mut state.exec_store, &resting).await;
+            state.limits.maybe_reset_day();
+            state.limits.daily_cancels += outcomes.canceled.len() as u32;
+            for (order_id, e) in &outcomes.failed {
+                error!(order_id = %order_id, error = %e, "Cancel failed");
+            }
+
+            let msg = alerts::render(
+                "market_lifecycle",
+                alerts::context! {
+                    event => event_ticker,
+                    market => market.ticker,
+                    label => market.bracket_label(),
+                    phase => phase.to_string(),
+                    previous => previous.map(|p| p.to_string()),
+                    status => market.status,
+                    result => market.settled_result(),
+                    held => held.contains(&market.ticker),
+                    cancelled => outcomes.canceled.len(),
+                    failed => outcomes.failed.len(),
+                },
+            );
+            telegram::send_alert(&msg)
+                .await
+                .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+        }
+    }
+    state.lifecycle.retain(&watched);
+    Ok(())
+}
+
+/// Work off the unhedged legs of incomplete arbs (`exits.rs`): record what unwind and exit
+/// orders have filled, then keep one resting order per leg at its stepped exit price,
+/// replacing it when the price moves. Sends `unhedged_loss` once a leg's loss at the market
+/// reaches `alert_loss_cents`. A dry run or a tripped kill switch only logs the exits.
+async fn exits_job(client: &KalshiClient, config: &ExitConfig, dry_run: bool, state: &mut ScanState) -> Result<()> {
+    state.positions.sync(&state.exec_store)?;
+    // Fills come from the exchange, so orders placed before a restart count too
+    for arb in state.positions.incomplete_arbs()? {
+        let worked = state.exits.working.keys().any(|(opp, _)| opp == &arb.opportunity_id);
+        if arb.unhedged.is_empty() && !worked {
+            continue;
+        }
+        let uuid = state.exec_store.get(&arb.opportunity_id).and_then(|r| r.uuid);
+        for ticker in &arb.brackets {
+            let orders = match client.get_market_orders(ticker).await {
+                Ok(orders) => orders,
+                Err(e) => {
+                    warn!(market = %ticker, error = %e, "Failed to fetch orders for exits");
+                    continue;
+                }
+            };
+            let ours = |o: &&Order| {
+                exits::is_exit_order(o.client_order_id.as_deref(), &arb.opportunity_id, uuid.as_ref(), ticker)
+            };
+            for order in orders.iter().filter(ours) {
+                state.positions.record_exit(&arb.opportunity_id, ticker, order)?;
+                let key = (arb.opportunity_id.clone(), ticker.clone());
+                if order.status == "resting" {
+                    // One a previous run left working
+                    let price_cents = order.yes_price.unwrap_or(0);
+                    let working = WorkingExit { order_id: order.order_id.clone(), price_cents };
+                    state.exits.working.entry(key).or_insert(working);
+                } else if state.exits.working.get(&key).is_some_and(|w| w.order_id == order.order_id) {
+                    state.exits.working.remove(&key);
+                }
+            }
+        }
+    }
+
+    let unhedged = |positions: &PositionTracker| -> Result<Vec<UnhedgedLeg>> {
+        Ok(positions.incomplete_arbs()?.into_iter().flat_map(|a| a.unhedged).collect())
+    };
+    let legs = unhedged(&state.positions)?;
+    for (opportunity, market) in state.exits.retain(&legs) {
+        info!(opportunity = %opportunity, market = %market, "Unhedged leg closed");
+    }
+    let hold = dry_run || state.limits.kill_switch.is_some() || state.limits.halted().is_some();
+    let now = Utc::now();
+    state.limits.maybe_reset_day();
+    for leg in &legs {
+        let key = exits::key(leg);
+        let book = match state.feed.as_ref().and_then(|f| f.orderbook(&leg.ticker)) {
+            Some(book) => book,
+            None => match client.get_orderbook(&leg.ticker).await {
+                Ok(book) => book,
+                Err(e) => {
+                    warn!(market = %leg.ticker, error = %e, "Failed to fetch orderbook for exit");
+                    continue;
+                }
+            },
+        };
+        let quote = detector::quote_from_orderbook(&leg.ticker, "", &book, 0);
+        let ask = quote.as_ref().map(|q| q.yes_ask_cents);
+        let bid = quote.as_ref().map(|q| q.yes_bid_cents).filter(|&b| b > 0);
+        let steps = state.exits.steps(leg, config.step_secs, now);
+        let price = exits::exit_price(leg.contracts, bid, ask, steps, config.step_cents as i64);
+
+        if let Some(loss_cents) = exits::loss_at_market(leg, bid, ask) {
+            if state.exits.crossed(leg, loss_cents, config.alert_loss_cents as i64) {
+                warn!(
+                    event = %leg.event_ticker,
+                    market = %leg.ticker,
+                    contracts = leg.contracts,
+                    loss_cents,
+                    "Unhedged leg losing"
+                );
+                let msg = alerts::render(
+                    "unhedged_loss",
+                    alerts::context! {
+                        event => leg.event_ticker,
+                        market => leg.ticker,
+                        contracts => leg.contracts,
+                        entry_cents => leg.entry_cents,
+                        market_cents => if leg.contracts > 0 { bid } else { ask },
+                        exit_cents => price,
+                        loss_cents,
+                    },
+                );
+                telegram::send_alert(&msg).await.unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+            }
+        }
+
+        let Some(price) = price else {
+            debug!(market = %leg.ticker, contracts = leg.contracts, "No book to exit into");
+            continue;
+        };
+        if state.exits.working.get(&key).is_some_and(|w| w.price_cents == price) {
+            continue;
+        }
+        if hold {
+            info!(market = %leg.ticker, contracts = leg.contracts, price, dry_run, "Exit not placed");
+            continue;
+        }
+        // Re-price: cancel the working order and exit whatever it didn't fill
+        let mut contracts = leg.contracts;
+        if let Some(working) = state.exits.working.remove(&key) {
+            if let Err(e) = client.cancel_order(&working.order_id).await {
+                warn!(order_id = %working.order_id, error = %e, "Failed to cancel exit order");
+                state.exits.working.insert(key, working);
+                continue;
+            }
+            state.limits.daily_cancels += 1;
+            let order = client.get_order(&working.order_id).await.context("Failed to fetch cancelled exit order")?;
+            state.positions.record_exit(&leg.opportunity_id, &leg.ticker, &order)?;
+            match unhedged(&state.positions)?.into_iter().find(|l| exits::key(l) == key) {
+                Some(l) => contracts = l.contracts,
+                None => continue,
+            }
+        }
+
+        let Some(count) = Contracts::from_i64(contracts.abs()) else {
+            continue;
+        };
+        let client_order_id = format!("{}-exit-{}-{}", leg.opportunity_id, leg.ticker, now.timestamp_millis());
+        let req = kalshi::types::CreateOrderRequest {
+            ticker: leg.ticker.clone(),
+            action: if contracts > 0 { "sell" } else { "buy" }.to_string(),
+            side: "yes".to_string(),
+            order_type: "limit".to_string(),
+            count: count.get(),
+            yes_price: Some(price),
+            no_price: None,
+            time_in_force: None,
+            expiration_ts: None,
+            client_order_id: Some(client_order_id),
+        };
+        match client.create_order(&req).await {
+            Ok(order) => {
+                state.limits.daily_unwinds += 1;
+                info!(
+                    market = %leg.ticker,
+                    action = %req.action,
+                    count = req.count,
+                    price,
+                    steps,
+                    filled = order.filled_count(),
+                    "Exit order placed"
+                );
+                state.positions.record_exit(&leg.opportunity_id, &leg.ticker, &order)?;
+                if order.status == "resting" {
+                    state.exits.working.insert(key, WorkingExit { order_id: order.order_id, price_cents: price });
+                }
+            }
+            Err(e) => warn!(market = %leg.ticker, error = %e, "Exit order failed"),
+        }
+    }
+    Ok(())
+}
+
+/// Quote every `[[relations]]` market and alert on mispricings between a market and the
+/// union of its parts. Detection only: nothing is traded. A mispricing alerts once, and
+/// again only after it has closed and reopened.
+async fn relations_job(client: &KalshiClient, config: &Config, state: &mut ScanState) -> Result<()> {
+    let min_level = config.risk.min_level_quantity as i64;
+    let mut open = HashSet::new();
+
+    for relation in &config.relations {
+        let mut quotes = Vec::new();
+        for ticker in relation.tickers() {
+            let quote = match client.get_orderbook(ticker).await {
+                Ok(book) => detector::quote_from_orderbook(ticker, ticker, &book, min_level),
+                Err(e) => {
+                    warn!(relation = %relation.name, market = %ticker, error = %e, "Failed to fetch orderbook");
+                    None
+                }
+            };
+            let Some(quote) = quote else { break };
+            quotes.push(quote);
+        }
+        if quotes.len() != relation.union_of.len() + 1 {
+            debug!(relation = %relation.name, "Relation not fully quoted, skipping");
+            continue;
+        }
+        let Some(m) = relations::evaluate(
+            relation,
+            &quotes[0],
+            &quotes[1..],
+            config.risk.position_size,
+            config.risk.min_net_profit_cents as i64,
+        ) else {
+            continue;
+        };
+
+        let key = format!("{}/{}", m.relation, m.direction);
+        info!(
+            relation = %m.relation,
+            direction = %m.direction,
+            size = m.size,
+            whole_cents = m.whole_cents,
+            parts_cents = m.parts_cents,
+            net_profit_cents = m.net_profit_cents,
+            "Cross-market mispricing"
+        );
+        if !state.relation_alerts.contains(&key) {
+            let msg = alerts::render(
+                "cross_market_arb",
+                alerts::context! {
+                    relation => m.relation,
+                    direction => m.direction.to_string(),
+                    market => relation.market,
+                    parts => relation.union_of,
+                    size => m.size,
+                    whole_cents => m.whole_cents,
+                    parts_cents => m.parts_cents,
+                    fees_cents => m.fees_cents,
+                    net_profit_cents => m.net_profit_cents,
+                },
+            );
+            telegram::send_alert(&msg)
+                .await
+                .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+        }
+        open.insert(key);
+    }
+    state.relation_alerts = open;
+    Ok(())
+}
+
+/// Per-leg fills and the economics of a fully filled arb, so successes show up in
+/// Telegram alongside the failures.
+async fn send_execution_receipt(opp: &ArbOpportunity, result: &ExecutionResult) {
+    let Some(receipt) = result.receipt(opp) else {
+        warn!(event = %opp.event_ticker, "Fill amounts overflow, not sending a receipt");
+        return;
+    };
+    let msg = alerts::render(
+        "execution_receipt",
+        alerts::context! {
+            event => opp.event_ticker,
+            direction => opp.direction.to_string(),
+            size => opp.position_size,
+            legs => receipt.legs,
+            total_cents => receipt.total_cents,
+            fees_cents => receipt.fees_cents,
+            net_cents => receipt.net_cents,
+            expected_net_cents => opp.net_profit_cents,
+            capital_cents => receipt.capital_cents,
+            settles => opp.settles_at.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()),
+        },
+    );
+    telegram::send_alert(&msg)
+        .await
+        .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+}
+
+/// Log a liveness line with the headline counters.
+fn heartbeat(state: &mut ScanState, status_tx: &StatusSender) {
+    state.limits.maybe_reset_day();
+    let cycles = status_tx.borrow().cycles;
+    info!(
+        cycles,
+        open_arbs = state.limits.open_arbs,
+        daily_pnl_cents = state.limits.daily_pnl_cents,
+        daily_orders = state.limits.daily_orders,
+        daily_cancels = state.limits.daily_cancels,
+        daily_unwinds = state.limits.daily_unwinds,
+        resting_orders = state.exec_store.open_orders().len(),
+        "Heartbeat"
+    );
+}
+
+/// Send the trading day's risk counters as a `daily_report` alert.
+async fn daily_report(state: &mut ScanState) {
+    state.limits.maybe_reset_day();
+    let risk = state.limits.counters();
+    let attribution = state.limits.daily_attribution;
+    info!(
+        trading_day = %risk.trading_day,
+        daily_pnl_cents = risk.daily_pnl_cents,
+        ?attribution,
+        "Sending daily report"
+    );
+    let msg = alerts::render(
+        "daily_report",
+        alerts::context! {
+            trading_day => risk.trading_day.to_string(),
+            daily_pnl_cents => risk.daily_pnl_cents,
+            daily_orders => risk.daily_orders,
+            daily_cancels => risk.daily_cancels,
+            daily_unwinds => risk.daily_unwinds,
+            open_arbs => risk.open_arbs,
+            resting_orders => state.exec_store.open_orders().len(),
+            edge_cents => attribution.edge_cents,
+            slippage_cents => attribution.slippage_cents,
+            fee_cents => attribution.fee_cents,
+            unfilled_cents => attribution.unfilled_cents,
+        },
+    );
+    telegram::send_alert(&msg)
+        .await
+        .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+}
+
+/// Recent slippage seeded from the reconciliation log, so the adaptive ROI floor
+/// survives restarts.
+fn load_slippage(risk: &RiskConfig) -> SlippageTracker {
+    let window = risk.adaptive_roi.as_ref().map_or(20, |a| a.window);
+    SlippageTracker::load(std::path::Path::new("data/reconciliation.md"), window).unwrap_or_else(|e| {
+        warn!("Failed to load reconciliation history: {}", e);
+        SlippageTracker::new(window)
+    })
+}
+
+/// The ROI floor for this cycle under `[risk.adaptive_roi]`, alerting when it moves.
+async fn adapt_roi_floor(limits: &mut RiskLimits, base_pct: f64, adaptive: &AdaptiveRoiConfig) -> f64 {
+    let floor = limits.slippage.min_roi_pct(base_pct, adaptive);
+    let previous = limits.min_roi_pct.replace(floor).unwrap_or(base_pct);
+    if floor != previous {
+        let share_pct = limits.slippage.slippage_share().unwrap_or(0.0) * 100.0;
+        warn!(previous, floor, base = base_pct, share_pct, "Adaptive ROI floor changed");
+        let msg = alerts::render(
+            "roi_floor",
+            alerts::context! {
+                previous,
+                floor,
+                base => base_pct,
+                share_pct => format!("{:.0}", share_pct),
+                samples => limits.slippage.len(),
+            },
+        );
+        telegram::send_alert(&msg)
+            .await
+            .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+    }
+    floor
+}
+
+async fn scan_cycle(
+    client: &KalshiClient,
+    mirror_client: Option<&KalshiClient>,
+    config: &Config,
+    dry_run: bool,
+    state: &mut ScanState,
+    status_tx: &StatusSender,
+) -> Result<CycleStats> {
+    info!("Starting scan cycle");
+    let ScanState {
+        limits,
+        series_cache,
+        exec_store,
+        skip_memo,
+        feed,
+        watchlist,
+        positions,
+        risk_watch,
+        fill_audit,
+        paper,
+        priority,
+        digest,
+        ..
+    } = state;
+    let started_at = Utc::now();
+    let cycle_start = Instant::now();
+    limits.cooldown.prune(started_at);
+
+    let all_series = series_list(series_cache, client).await?;
+
+    let watchlist = watchlist.refresh();
+    let allowed: Vec<_> = all_series
+        .iter()
+        .filter(|s| watchlist.allows_series(&s.ticker, &config.scanner.series_filter))
+        .cloned()
+        .collect();
+    let allowed_count = allowed.len();
+    let series_to_scan: Vec<_> = match priority.as_ref() {
+        Some(p) => allowed.into_iter().filter(|s| p.due(&s.ticker, started_at)).collect(),
+        None => allowed,
+    };
+
+    let mut detect_params = DetectParams::from_config(&config.risk);
+    if let Some(adaptive) = &config.risk.adaptive_roi {
+        detect_params.min_roi_pct = adapt_roi_floor(limits, config.risk.min_roi_pct, adaptive).await;
+    }
+    if config.risk.sizing.is_some() {
+        match client.get_balance().await {
+            Ok(balance) => limits.balance_cents = Some(balance),
+            Err(e) => warn!(last = ?limits.balance_cents, "Failed to fetch balance, sizing from the last known: {}", e),
+        }
+        detect_params.balance_cents = limits.balance_cents;
+    }
+    let shadow_params = config.shadow.as_ref().map(|shadow| DetectParams {
+        balance_cents: limits.balance_cents,
+        ..DetectParams::from_shadow(&config.risk, shadow)
+    });
+    // Early-skip bounds must admit anything either parameter set could take
+    let mut bound_params = vec![&detect_params];
+    bound_params.extend(shadow_params.as_ref());
+    let sum_bounds = SumBoundsTable::precompute(&bound_params, config.scanner.max_brackets);
+    let series_count = series_to_scan.len();
+    let mut events_count = 0usize;
+    let mut opportunities_count = 0usize;
+    let mut shadow_count = 0usize;
+    let mut shadow_net_cents = 0i64;
+    let mut trades_count = 0usize;
+    let mut canary_trades = 0usize;
+    let mut memo_skips = 0usize;
+
+    for series in &series_to_scan {
+        if risk_watch.due(Instant::now()) {
+            if let Some(audit) = fill_audit {
+                audit_fills(client, config, exec_store, limits, audit, status_tx)
+                    .await
+                    .unwrap_or_else(|e| warn!("Fill audit failed: {:#}", e));
+            }
+            watch_job(client, feed.as_ref(), exec_store, positions, risk_watch, status_tx)
+                .await
+                .unwrap_or_else(|e| warn!("Watch pass failed: {:#}", e));
+        }
+        let events = match client.get_events(&series.ticker).await {
+            Ok(e) => e,
+            Err(e) => {
+                warn!(series = %series.ticker, error = %e, "Failed to fetch events");
+                continue;
+            }
+        };
+        if let Some(p) = priority.as_mut() {
+            p.scanned(&series.ticker, Utc::now());
+        }
+
+        for event in events.iter().filter(|e| watchlist.allows_event(&e.event_ticker)) {
+            // Structural rejections hold until the event's market set changes
+            let fingerprint = skip_memo::fingerprint(&event.markets);
+            if skip_memo.is_skipped(&event.event_ticker, &fingerprint) {
+                memo_skips += 1;
+                continue;
+            }
+
+            // Gate: must be mutually exclusive
+            if !event.mutually_exclusive {
+                skip_memo.record(&event.event_ticker, fingerprint, "not_mutually_exclusive");
+                continue;
+            }
+
+            // Gate: drop determined brackets; skip events whose outcome is already known
+            let active_markets = match arb_candidate_markets(&event.markets, Utc::now()) {
+                Some(m) => m,
+                None => {
+                    skip_memo.record(&event.event_ticker, fingerprint, "decided_or_non_exhaustive");
+                    continue;
+                }
+            };
+
+            // Gate: the brackets must span every outcome, or buying them all is a bet
+            if let Some(gap) = detector::coverage_gap(&event.markets, Utc::now()) {
+                debug!(event = %event.event_ticker, gap = %gap, "Skipping event: brackets don't cover every outcome");
+                skip_memo.record(&event.event_ticker, fingerprint, "incomplete_brackets");
+                continue;
+            }
+
+            // Gate: need enough active markets (but not too many)
+            if active_markets.len() < config.scanner.min_brackets {
+                debug!(
+                    event = %event.event_ticker,
+                    markets = active_markets.len(),
+                    min = config.scanner.min_brackets,
+                    "Skipping event: too few active markets"
+                );
+                skip_memo.record(&event.event_ticker, fingerprint, "too_few_brackets");
+                continue;
+            }
+            if active_markets.len() > config.scanner.max_brackets {
+                debug!(
+                    event = %event.event_ticker,
+                    markets = active_markets.len(),
+                    max = config.scanner.max_brackets,
+                    "Skipping event: too many active markets"
+                );
+                skip_memo.record(&event.event_ticker, fingerprint, "too_many_brackets");
+                continue;
+            }
+
+            // Gate: settlement inside the configured window (not memoized: it moves with the clock)
+            let settles_at = expected_settlement(&active_markets).or(event.strike_date);
+            if let Some(reason) = detector::outside_settlement_window(settles_at, Utc::now(), &config.scanner) {
+                debug!(event = %event.event_ticker, settles_at = ?settles_at, reason, "Skipping event");
+                continue;
+            }
+
+            events_count += 1;
+
+            // Fetch orderbooks for all markets in this event
+            // Recording needs every book, so the early exit is off while it's on
+            let recording = config.scanner.snapshot_dir.is_some();
+            let bounds = if recording { None } else { sum_bounds.get(active_markets.len()) };
+            if let Some(feed) = feed.as_ref() {
+                feed.watch(&active_markets.iter().map(|m| m.ticker.as_str()).collect::<Vec<_>>());
+            }
+            let mut books = Vec::new();
+            let books_out = recording.then_some(&mut books);
+            let concurrency = config.scanner.quote_concurrency;
+            let quotes =
+                match fetch_quotes(client, feed.as_ref(), &active_markets, &config.risk, bounds, books_out, concurrency)
+                    .await
+                {
+                    Some(q) => q,
+                    None => continue,
+                };
+            if let Some(dir) = &config.scanner.snapshot_dir {
+                backtest::record_snapshot(dir, &event.event_ticker, &event.title, &active_markets, &books)
+                    .unwrap_or_else(|e| warn!("Failed to record snapshot: {}", e));
+            }
+
+            // Gate: every bracket's book must be tight and deep enough to believe its quote
+            let thin = quotes.iter().find_map(|q| Some((&q.ticker, detector::thin_bracket(q, &config.scanner)?)));
+            if let Some((market, reason)) = thin {
+                debug!(event = %event.event_ticker, market = %market, reason, "Skipping event: thin bracket");
+                continue;
+            }
+
+            // Detect arb opportunities
+            let mut evals = evaluate_arb(&event.event_ticker, &event.title, &quotes, &detect_params);
+            detector::gate_settlement(&mut evals, settles_at, &detect_params, Utc::now());
+            if evals.iter().any(|e| e.rejected.is_none()) {
+                if let Some((market, volume)) = detector::low_volume_bracket(client, &quotes, &config.scanner).await {
+                    debug!(event = %event.event_ticker, market = %market, volume, "Bracket barely trading, rejecting");
+                    for eval in evals.iter_mut().filter(|e| e.rejected.is_none()) {
+                        eval.rejected = Some("recent_volume");
+                    }
+                }
+            }
+            for eval in evals.iter().filter(|e| e.rejected.is_none() || e.is_near_miss()) {
+                status::update(status_tx, |s| s.recent.record_opportunity(eval));
+            }
+            let opps: Vec<_> = evals
+                .into_iter()
+                .filter(|e| e.rejected.is_none())
+                .map(|e| e.opportunity)
+                .collect();
+
+            // Shadow parameters see the same books; results are simulated and logged separately
+            if let Some(params) = &shadow_params {
+                let mut shadow_evals = evaluate_arb(&event.event_ticker, &event.title, &quotes, params);
+                detector::gate_settlement(&mut shadow_evals, settles_at, params, Utc::now());
+                let shadow_opps = shadow_evals.into_iter().filter(|e| e.rejected.is_none());
+                for eval in shadow_opps {
+                    let opp = &eval.opportunity;
+                    let primary = opps.iter().any(|o| o.direction == opp.direction);
+                    shadow_count += 1;
+                    shadow_net_cents += opp.net_profit_cents;
+                    debug!(
+                        id = %opp.id,
+                        event = %opp.event_ticker,
+                        direction = %opp.direction,
+                        net_profit = money::dollars(opp.net_profit_cents),
+                        primary,
+                        "Shadow arb"
+                    );
+                    storage::log_shadow_opportunity(opp, primary)
+                        .unwrap_or_else(|e| warn!("Failed to log shadow opportunity: {}", e));
+                }
+            }
+
+            for opp in &opps {
+                opportunities_count += 1;
+                if let Some(p) = priority.as_mut() {
+                    p.hit(&series.ticker, Utc::now());
+                }
+                if let Some(digest) = digest.as_mut() {
+                    digest.push(opp);
+                }
+                info!(
+                    id = %opp.id,
+                    event = %opp.event_ticker,
+                    title = %opp.event_title,
+                    direction = %opp.direction,
+                    brackets = opp.brackets.len(),
+                    size = opp.position_size,
+                    sum = money::dollars(opp.sum_cents),
+                    gross = money::dollars(opp.gross_profit_cents),
+                    fees = money::dollars(opp.total_fees_cents),
+                    net_profit = money::dollars(opp.net_profit_cents),
+                    gross_roi = format!("{:.1}%", opp.gross_roi_pct),
+                    roi = format!("{:.1}%", opp.net_roi_pct),
+                    slippage_adj_roi = format!("{:.1}%", opp.slippage_adj_roi_pct),
+                    annualized_roi = ?opp.annualized_roi_pct(Utc::now()).map(|r| format!("{:.1}%", r)),
+                    "ARB FOUND"
+                );
+
+                let size = match &config.canary {
+                    Some(canary) => canary.size_for(&series.ticker, opp.position_size),
+                    None => Some(opp.position_size),
+                };
+                let Some(size) = size.filter(|_| !dry_run) else {
+                    storage::log_opportunity(opp, false)
+                        .unwrap_or_else(|e| warn!("Failed to log opportunity: {}", e));
+                    paper_trade(client, feed.as_ref(), opp, paper, status_tx).await;
+                    continue;
+                };
+                // A canary cap is traded as its own arb: it has to pass the gates at that size
+                let resized;
+                let opp = if size < opp.position_size {
+                    match detector::evaluate_at_size(opp, &quotes, &detect_params, size, Utc::now()) {
+                        Some(eval) if eval.rejected.is_none() => {
+                            resized = eval.opportunity;
+                            &resized
+                        }
+                        eval => {
+                            info!(
+                                event = %opp.event_ticker,
+                                direction = %opp.direction,
+                                size,
+                                reason = eval.and_then(|e| e.rejected).unwrap_or("no evaluation"),
+                                "Arb doesn't pass at the canary size — skipping"
+                            );
+                            storage::log_opportunity(opp, false)
+                                .unwrap_or_else(|e| warn!("Failed to log opportunity: {}", e));
+                            continue;
+                        }
+                    }
+                } else {
+                    opp
+                };
+
+                if let Some(until) = limits.cooldown.until(opp, Utc::now()) {
+                    info!(
+                        event = %opp.event_ticker,
+                        direction = %opp.direction,
+                        until = %until,
+                        "Event cooling down after a failed attempt — skipping"
+                    );
+                    log_blocked(opp, exec_store, "cooling down");
+                    continue;
+                }
+                // Every line logged while executing carries the opportunity's UUID
+                let span = info_span!("opportunity", uuid = %opp.uuid);
+                let filled = execute_opportunity(client, mirror_client, opp, size, limits, exec_store, status_tx)
+                    .instrument(span)
+                    .await;
+                trades_count += filled;
+                // Only placements are recorded; opportunities the risk checks refused aren't canary results
+                if let Some(record) = exec_store.get(&opp.id).filter(|_| config.canary.is_some()) {
+                    canary_trades += filled;
+                    storage::log_canary(opp, size, &record.result)
+                        .unwrap_or_else(|e| warn!("Failed to log canary execution: {}", e));
+                }
+            }
+        }
+    }
+
+    storage::log_scan(series_count, events_count, opportunities_count, trades_count)
+        .unwrap_or_else(|e| warn!("Failed to log scan: {}", e));
+    if let Some(msg) = digest.as_mut().and_then(|d| d.take(Utc::now())) {
+        telegram::send_alert(&msg)
+            .await
+            .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+    }
+    skip_memo
+        .save()
+        .unwrap_or_else(|e| warn!("Failed to save skip memo: {}", e));
+
+    info!(
         series = series_count,
+        series_deferred = allowed_count - series_count,
         events = events_count,
         opportunities = opportunities_count,
         trades = trades_count,
+        canary_trades,
+        shadow_opportunities = shadow_count,
+        memo_skips,
         "Scan cycle complete"
     );
 
-    Ok(())
+    Ok(CycleStats {
+        started_at,
+        duration_ms: cycle_start.elapsed().as_millis() as u64,
+        series: series_count,
+        series_deferred: allowed_count - series_count,
+        events: events_count,
+        opportunities: opportunities_count,
+        trades: trades_count,
+        canary_trades,
+        shadow_opportunities: shadow_count,
+        shadow_net_cents,
+    })
+}
+
+/// Net profit of `opp` at `size` on books fetched now, all legs at once. None when a book
+/// can't be fetched or quoted, or no longer covers the size.
+async fn requoted_net_cents(client: &KalshiClient, opp: &ArbOpportunity, size: u32, requote: &Requote) -> Option<i64> {
+    let fetches = opp.brackets.iter().map(|b| async move {
+        let book = client.get_orderbook(&b.ticker).await;
+        (b, book)
+    });
+    let mut quotes = Vec::with_capacity(opp.brackets.len());
+    for (bracket, book) in futures_util::future::join_all(fetches).await {
+        let book = match book {
+            Ok(book) => book,
+            Err(e) => {
+                warn!(ticker = %bracket.ticker, error = %e, "Failed to re-fetch orderbook");
+                return None;
+            }
+        };
+        quotes.push(quote_with_levels(
+            &bracket.ticker,
+            &bracket.title,
+            &book,
+            requote.min_level_quantity,
+            requote.sweep_levels,
+        )?);
+    }
+    detector::repriced_net_cents(opp, &quotes, Contracts(size))
+}
+
+/// A dry run's stand-in for `execute_opportunity` (`paper.rs`): fill the opportunity against
+/// a fresh read of its books and add the result to the paper portfolio.
+async fn paper_trade(
+    client: &KalshiClient,
+    feed: Option<&OrderbookFeed>,
+    opp: &ArbOpportunity,
+    paper: &mut PaperPortfolio,
+    status_tx: &StatusSender,
+) {
+    let mut quotes = Vec::new();
+    for bracket in &opp.brackets {
+        let book = match feed.and_then(|f| f.orderbook(&bracket.ticker)) {
+            Some(book) => book,
+            None => match client.get_orderbook(&bracket.ticker).await {
+                Ok(book) => book,
+                Err(e) => {
+                    warn!(ticker = %bracket.ticker, error = %e, "Failed to fetch orderbook for paper fill");
+                    continue;
+                }
+            },
+        };
+        quotes.extend(quote_with_levels(&bracket.ticker, &bracket.title, &book, 0, paper::BOOK_LEVELS));
+    }
+    let trade = paper::simulate(opp, &quotes);
+    let filled: Vec<i64> = trade.legs.iter().map(|l| l.filled).collect();
+    info!(
+        id = %opp.id,
+        size = trade.size,
+        filled = ?filled,
+        complete = trade.is_complete(),
+        fees = money::dollars(trade.fees_cents()),
+        locked_net = ?trade.locked_net_cents().map(money::dollars),
+        expected_net = money::dollars(trade.expected_net_cents),
+        "PAPER TRADE"
+    );
+    paper.record(trade).unwrap_or_else(|e| warn!("Failed to record paper trade: {}", e));
+    let summary = paper.summary();
+    status::update(status_tx, |s| s.paper = summary);
+}
+
+/// Pre-flight risk checks, order placement, and result handling for one opportunity:
+/// persistence, mixed-state cancels, reconciliation, and alerts. Shared by the scan loop
+/// and the manual `execute` command. Returns the number of filled orders.
+/// With a mirror client, the same order set is then replayed there.
+async fn execute_opportunity(
+    client: &KalshiClient,
+    mirror_client: Option<&KalshiClient>,
+    opp: &ArbOpportunity,
+    position_size: u32,
+    limits: &mut RiskLimits,
+    exec_store: &mut ExecutionStore,
+    status_tx: &StatusSender,
+) -> usize {
+    // --- Operator halt: keep scanning, place nothing (no alert: the operator asked for it) ---
+    if let Some(why) = limits.halted() {
+        warn!(event = %opp.event_ticker, halted = %why, "Trading halted — skipping execution");
+        log_blocked(opp, exec_store, "halted");
+        let risk = limits.counters();
+        status::update(status_tx, |s| s.risk = risk);
+        return 0;
+    }
+
+    // --- Pre-flight risk checks (hardcoded circuit breakers) ---
+    if let Some(reason) = limits.check() {
+        warn!(
+            event = %opp.event_ticker,
+            reason = reason,
+            open_arbs = limits.open_arbs,
+            daily_pnl_cents = limits.daily_pnl_cents,
+            daily_orders = limits.daily_orders,
+            "RISK LIMIT HIT — skipping execution"
+        );
+        log_blocked(opp, exec_store, reason);
+        let risk = limits.counters();
+        status::update(status_tx, |s| {
+            s.recent.record_risk_event(
+                &opp.event_ticker,
+                reason,
+                risk.open_arbs,
+                risk.daily_pnl_cents,
+                risk.daily_orders,
+            );
+            s.risk = risk;
+        });
+        let msg = alerts::render(
+            "risk_limit",
+            alerts::context! {
+                reason,
+                event => opp.event_ticker,
+                roi => roi_tiers(opp),
+                open_arbs => limits.open_arbs,
+                max_open_arbs => MAX_OPEN_ARBS,
+                daily_pnl_cents => limits.daily_pnl_cents,
+                daily_orders => limits.daily_orders,
+                max_daily_orders => MAX_DAILY_ORDERS,
+            },
+        );
+        telegram::send_alert(&msg).await.unwrap_or_else(|e| {
+            warn!("Telegram alert failed: {}", e);
+        });
+        return 0;
+    }
+
+    // --- Event ownership: never place orders into an event another execution
+    // (or another strategy) still has orders working in ---
+    let guard = match ownership::claim(&opp.event_ticker, Strategy::Arb) {
+        Ok(guard) => guard,
+        Err(holder) => {
+            warn!(
+                event = %opp.event_ticker,
+                holder = %holder.strategy,
+                since = %holder.since,
+                "Event held by another execution — skipping"
+            );
+            log_blocked(opp, exec_store, "event owned");
+            let risk = limits.counters();
+            status::update(status_tx, |s| {
+                s.recent.record_risk_event(
+                    &opp.event_ticker,
+                    "event owned",
+                    risk.open_arbs,
+                    risk.daily_pnl_cents,
+                    risk.daily_orders,
+                );
+            });
+            return 0;
+        }
+    };
+
+    // --- Re-validate the bracket set: a bracket listed or deactivated since detection
+    // breaks the "covers all outcomes" assumption the arb is priced on ---
+    let priced: Vec<&str> = opp.brackets.iter().map(|b| b.ticker.as_str()).collect();
+    let change = match client.get_event(&opp.event_ticker).await {
+        Ok(event) => bracket_set_change(&priced, &event, Utc::now()),
+        Err(e) => Some(format!("could not re-fetch event: {}", e)),
+    };
+    if let Some(change) = change {
+        warn!(event = %opp.event_ticker, change = %change, "Bracket set changed since detection — aborting execution");
+        log_blocked(opp, exec_store, "bracket set changed");
+        let risk = limits.counters();
+        status::update(status_tx, |s| {
+            s.recent.record_risk_event(
+                &opp.event_ticker,
+                "bracket set changed",
+                risk.open_arbs,
+                risk.daily_pnl_cents,
+                risk.daily_orders,
+            );
+        });
+        let msg = alerts::render(
+            "brackets_changed",
+            alerts::context! {
+                event => opp.event_ticker,
+                direction => opp.direction.to_string(),
+                change,
+            },
+        );
+        telegram::send_alert(&msg).await.unwrap_or_else(|e| {
+            warn!("Telegram alert failed: {}", e);
+        });
+        return 0;
+    }
+
+    // --- Stale-quote guard: the books may have moved since detection; re-price the arb on
+    // fresh ones and abort if the edge slipped past the tolerance ---
+    if let Some(requote) = &limits.requote {
+        let detected = detector::repriced_net_cents(opp, &opp.brackets, Contracts(position_size));
+        let fresh = requoted_net_cents(client, opp, position_size, requote).await;
+        let slipped = match (detected, fresh) {
+            (Some(detected), Some(fresh)) => detected - fresh > requote.tolerance_cents,
+            _ => true,
+        };
+        if slipped {
+            warn!(
+                event = %opp.event_ticker,
+                detected_net_cents = ?detected,
+                fresh_net_cents = ?fresh,
+                tolerance_cents = requote.tolerance_cents,
+                "Quotes went stale since detection — aborting execution"
+            );
+            log_blocked(opp, exec_store, "stale quote");
+            let risk = limits.counters();
+            status::update(status_tx, |s| {
+                s.recent.record_risk_event(
+                    &opp.event_ticker,
+                    "stale quote",
+                    risk.open_arbs,
+                    risk.daily_pnl_cents,
+                    risk.daily_orders,
+                );
+            });
+            return 0;
+        }
+        debug!(event = %opp.event_ticker, detected_net_cents = ?detected, fresh_net_cents = ?fresh, "Re-quote passed");
+    }
+
+    // --- Buying power: an order the exchange rejects for funds part way through the legs
+    // leaves a partial arb, so the whole cost must be there before the first goes out ---
+    // (a cost too large to compute is more than any balance)
+    let required_cents = detector::required_capital_cents(&opp.brackets, opp.direction, Contracts(position_size))
+        .map_or(i64::MAX, |c| c.0);
+    match client.get_balance().await {
+        Ok(balance) => limits.balance_cents = Some(balance),
+        Err(e) => warn!(last = ?limits.balance_cents, "Failed to fetch balance, checking the last known: {}", e),
+    }
+    if let Some(balance_cents) = limits.balance_cents.filter(|&b| b < required_cents) {
+        warn!(
+            event = %opp.event_ticker,
+            required_cents,
+            balance_cents,
+            "Insufficient buying power — skipping execution"
+        );
+        log_blocked(opp, exec_store, "insufficient balance");
+        let risk = limits.counters();
+        status::update(status_tx, |s| {
+            s.recent.record_risk_event(
+                &opp.event_ticker,
+                "insufficient balance",
+                risk.open_arbs,
+                risk.daily_pnl_cents,
+                risk.daily_orders,
+            );
+            s.risk = risk;
+        });
+        let msg = alerts::render(
+            "insufficient_balance",
+            alerts::context! {
+                event => opp.event_ticker,
+                direction => opp.direction.to_string(),
+                size => position_size,
+                required_cents,
+                balance_cents,
+            },
+        );
+        telegram::send_alert(&msg).await.unwrap_or_else(|e| {
+            warn!("Telegram alert failed: {}", e);
+        });
+        return 0;
+    }
+
+    // Execute
+    storage::log_opportunity(opp, true)
+        .unwrap_or_else(|e| warn!("Failed to log opportunity: {}", e));
+    set_state(exec_store, opp, OpportunityState::Executed, None);
+
+    let mut trades = 0;
+    let mut orders_left = false;
+    match executor::execute_arb(client, opp, position_size, limits.execution_budget, limits.max_execution).await {
+        Ok(result) => {
+            // An aborted execution's edge is stale: cancel what rests rather than wait on it
+            let result = if result.aborted {
+                warn!(
+                    event = %opp.event_ticker,
+                    resting = result.resting.len(),
+                    "Execution aborted past max_execution_ms"
+                );
+                let risk = limits.counters();
+                status::update(status_tx, |s| {
+                    s.recent.record_risk_event(
+                        &opp.event_ticker,
+                        "execution aborted",
+                        risk.open_arbs,
+                        risk.daily_pnl_cents,
+                        risk.daily_orders,
+                    )
+                });
+                result
+            } else {
+                executor::await_fills(client, result, limits.fill_wait).await
+            };
+            exec_store
+                .record(&opp.id, Some(opp.uuid), &result, opp.labels())
+                .unwrap_or_else(|e| warn!("Failed to record execution: {}", e));
+            let (state, reason) = if result.is_fully_filled() {
+                (OpportunityState::Filled, None)
+            } else if !result.filled.is_empty() {
+                (OpportunityState::Partial, None)
+            } else if result.aborted {
+                (OpportunityState::Aborted, Some("max_execution_ms"))
+            } else {
+                (OpportunityState::Aborted, Some("nothing filled"))
+            };
+            set_state(exec_store, opp, state, reason);
+            status::update(status_tx, |s| s.recent.record_execution(opp, Some(&result)));
+            let order_count = result.filled.len() + result.resting.len() + result.other.len();
+            limits.daily_orders += order_count as u32;
+
+            if result.is_fully_filled() {
+                trades = result.filled.len();
+                limits.cooldown.succeeded(opp);
+                limits.open_arbs += 1;
+                limits.record_pnl(Some(Cents(opp.net_profit_cents)));
+                info!(
+                    event = %result.event_ticker,
+                    direction = %result.direction,
+                    orders = result.filled.len(),
+                    "All orders filled successfully"
+                );
+
+                send_execution_receipt(opp, &result).await;
+
+                // Reconciliation: match filled orders to brackets by ticker
+                if let Some(actual) = storage::realized_net_cents(opp, &result.filled) {
+                    limits.slippage.record(opp.net_profit_cents, actual);
+                }
+                storage::log_reconciliation(opp, &result.filled, false)
+                    .unwrap_or_else(|e| warn!("Failed to log reconciliation: {}", e));
+                if let Some(attribution) = storage::attribute_pnl(opp, &result.filled) {
+                    limits.daily_attribution.add(&attribution);
+                }
+            } else if result.is_total_failure() {
+                error!(
+                    event = %opp.event_ticker,
+                    api_failures = result.api_failures.len(),
+                    "Total execution failure — no orders placed"
+                );
+                let msg = alerts::render(
+                    "total_failure",
+                    alerts::context! {
+                        event => opp.event_ticker,
+                        direction => opp.direction.to_string(),
+                        brackets => opp.brackets.len(),
+                        bracket_names => opp.brackets.iter().map(|b| opp.bracket_name(&b.ticker)).collect::<Vec<_>>(),
+                        failed => result.api_failures.len(),
+                        reasons =>
+                            result.failure_kinds.iter().map(|(t, k)| format!("{}: {}", t, k)).collect::<Vec<_>>(),
+                    },
+                );
+                telegram::send_alert(&msg).await.unwrap_or_else(|e| {
+                    warn!("Telegram alert failed: {}", e);
+                });
+            } else {
+                // Mixed state: some filled, some resting/failed
+                let names =
+                    |tickers: &[String]| -> Vec<String> { tickers.iter().map(|t| opp.bracket_name(t)).collect() };
+                let leg_names = |legs: &[(String, Order)]| -> Vec<String> {
+                    legs.iter().map(|(ticker, _)| opp.bracket_name(ticker)).collect()
+                };
+                // Worst-case loss: cost of filled orders (unhedged position)
+                let loss = result.filled.iter().try_fold(Cents::ZERO, |loss, (_, o)| {
+                    loss.checked_add(Cents(o.yes_price.unwrap_or(0)).checked_mul(Contracts::from_i64(o.filled_count())?)?)
+                });
+
+                warn!(
+                    event = %opp.event_ticker,
+                    filled = result.filled.len(),
+                    resting = result.resting.len(),
+                    other = result.other.len(),
+                    api_failures = result.api_failures.len(),
+                    loss_cents = loss.map_or(i64::MAX, |l| l.0),
+                    "Mixed execution state — cancelling resting orders and unwinding"
+                );
+
+                // Cancel all resting orders, then any other-status orders too
+                let working: Vec<(&String, &Order)> =
+                    result.resting.iter().chain(&result.other).map(|(t, o)| (t, o)).collect();
+                let ids: Vec<String> = working.iter().map(|(_, o)| o.order_id.clone()).collect();
+                let outcomes = cancel_and_record(client, exec_store, &ids).await;
+                limits.maybe_reset_day();
+                limits.daily_cancels += outcomes.canceled.len() as u32;
+                for (order_id, e) in &outcomes.failed {
+                    orders_left = true;
+                    let ticker = working.iter().find(|(_, o)| &o.order_id == order_id).map(|(t, _)| t.as_str());
+                    error!(ticker = ?ticker, order_id = %order_id, error = %e, "Cancel failed");
+                }
+
+                // Complete the arb at the market if it still pays, otherwise close what filled
+                let (unwind, unquoted, fills) = if outcomes.failed.is_empty() {
+                    let (settled, plan) = executor::unwind_mixed(client, opp, &result).await;
+                    limits.daily_unwinds += settled.unwind.len() as u32;
+                    info!(
+                        event = %opp.event_ticker,
+                        plan = %plan.describe(),
+                        placed = settled.unwind.len(),
+                        "Unwound mixed execution"
+                    );
+                    if !plan.unquoted().is_empty() {
+                        warn!(
+                            event = %opp.event_ticker,
+                            legs = ?plan.unquoted(),
+                            "No quote to close filled legs: left for the exits job"
+                        );
+                    }
+                    // The final orders and the unwind's, so positions see what it closed
+                    exec_store
+                        .record(&opp.id, Some(opp.uuid), &settled, opp.labels())
+                        .unwrap_or_else(|e| warn!("Failed to record unwind: {}", e));
+                    let fills = settled.fills();
+                    limits.record_pnl(storage::realized_net_cents(opp, &fills).map(Cents));
+                    (Some(plan.describe()), names(plan.unquoted()), fills)
+                } else {
+                    warn!(event = %opp.event_ticker, "Not unwinding: orders that couldn't be cancelled may still fill");
+                    limits.record_pnl(loss.and_then(|l| Cents::ZERO.checked_sub(l)));
+                    (None, Vec::new(), result.filled.clone())
+                };
+
+                // Log reconciliation for whatever did fill (incomplete arb), unwind included
+                if !fills.is_empty() {
+                    storage::log_reconciliation(opp, &fills, true)
+                        .unwrap_or_else(|e| warn!("Failed to log reconciliation: {}", e));
+                    if let Some(attribution) = storage::attribute_pnl(opp, &fills) {
+                        limits.daily_attribution.add(&attribution);
+                    }
+                }
+
+                let msg = alerts::render(
+                    "partial_fill",
+                    alerts::context! {
+                        event => opp.event_ticker,
+                        direction => opp.direction.to_string(),
+                        brackets => opp.brackets.len(),
+                        filled => result.filled.len(),
+                        filled_legs => leg_names(&result.filled),
+                        resting => result.resting.len(),
+                        resting_legs => leg_names(&result.resting),
+                        failed => result.api_failures.len() + result.other.len(),
+                        net_profit_cents => opp.net_profit_cents,
+                        roi => roi_tiers(opp),
+                        unwind,
+                        unquoted_legs => unquoted,
+                    },
+                );
+                telegram::send_alert(&msg).await.unwrap_or_else(|e| {
+                    warn!("Telegram alert failed: {}", e);
+                });
+            }
+
+            if !result.is_fully_filled() {
+                start_cooldown(limits, opp);
+            }
+            if let Some(mirror_client) = mirror_client {
+                mirror::mirror_execution(mirror_client, opp, position_size, limits.execution_budget, &result).await;
+            }
+        }
+        Err(e) => {
+            // A conflict is the pre-flight check doing its job; anything else is a failure
+            if let ExecutorError::Conflict { .. } = e {
+                warn!(event = %opp.event_ticker, error = %e, "Execution skipped");
+            } else {
+                error!(event = %opp.event_ticker, kind = ?e.kind(), error = %e, "Execution failed");
+            }
+            set_state(exec_store, opp, OpportunityState::Aborted, Some(&format!("{:#}", e)));
+            status::update(status_tx, |s| s.recent.record_execution(opp, None));
+            start_cooldown(limits, opp);
+        }
+    }
+
+    // Orders still on the book keep the event ours until the order refresh sees them end
+    if orders_left {
+        guard.retain();
+    }
+    trades
+}
+
+/// Record a state change of `opp`; a failure to is logged, not fatal.
+fn set_state(exec_store: &mut ExecutionStore, opp: &ArbOpportunity, state: OpportunityState, reason: Option<&str>) {
+    match exec_store.transition(&opp.id, Some(opp.uuid), state, reason) {
+        Ok(()) => info!(id = %opp.id, state = %state, reason = ?reason, "Opportunity state"),
+        Err(e @ StorageError::IllegalTransition { .. }) => warn!("Opportunity state out of order: {}", e),
+        Err(e) => error!("Failed to record opportunity state: {:#}", e),
+    }
+}
+
+/// Log an opportunity a pre-flight check refused, and mark it `risk_blocked`.
+fn log_blocked(opp: &ArbOpportunity, exec_store: &mut ExecutionStore, reason: &str) {
+    storage::log_opportunity(opp, false).unwrap_or_else(|e| warn!("Failed to log: {}", e));
+    set_state(exec_store, opp, OpportunityState::RiskBlocked, Some(reason));
+}
+
+fn max_execution(risk: &RiskConfig) -> Option<Duration> {
+    Some(Duration::from_millis(risk.max_execution_ms)).filter(|max| !max.is_zero())
+}
+
+/// Hold the event off after an attempt that didn't fill in full, so the next cycles don't
+/// place the same orders into the same book.
+fn start_cooldown(limits: &mut RiskLimits, opp: &ArbOpportunity) {
+    if let Some(until) = limits.cooldown.failed(opp, Utc::now()) {
+        info!(event = %opp.event_ticker, direction = %opp.direction, until = %until, "Event cooling down");
+    }
+}
+
+/// Fetch orderbooks for every market (from the live feed when it has them) and extract quotes.
+/// Up to `concurrency` fetches are in flight at once; the client's read throttle still spaces
+/// the requests, but their round trips overlap. Books are handled in market order.
+/// Returns None if any bracket can't be quoted — a partial set is not an arb.
+async fn fetch_quotes(
+    client: &KalshiClient,
+    feed: Option<&OrderbookFeed>,
+    markets: &[&Market],
+    risk: &RiskConfig,
+    bounds: Option<SumBounds>,
+    mut books: Option<&mut Vec<Orderbook>>,
+    concurrency: usize,
+) -> Option<Vec<BracketQuote>> {
+    let fetches = markets.iter().map(|&market| async move {
+        let book = match feed.and_then(|f| f.orderbook(&market.ticker)) {
+            Some(ob) => Ok(ob),
+            None => client.get_orderbook(&market.ticker).await,
+        };
+        (market, book, Utc::now())
+    });
+    // Returning early drops the stream, abandoning fetches still in flight
+    let mut fetched = stream::iter(fetches).buffered(concurrency.max(1)).enumerate();
+    let mut quotes = Vec::with_capacity(markets.len());
+    let (mut ask_sum, mut bid_sum) = (0i64, 0i64);
+    while let Some((i, (market, book, fetched_at))) = fetched.next().await {
+        match book {
+            Ok(ob) => match quote_with_levels(
+                &market.ticker,
+                &market.title,
+                &ob,
+                risk.min_level_quantity as i64,
+                risk.sweep_levels as usize,
+            ) {
+                Some(mut quote) => {
+                    quote.label = market.bracket_label();
+                    quote.fetched_at = Some(fetched_at);
+                    if let Some(books) = books.as_deref_mut() {
+                        books.push(ob);
+                    }
+                    ask_sum += quote.yes_ask_cents;
+                    bid_sum += quote.yes_bid_cents;
+                    quotes.push(quote);
+                    let remaining = markets.len() - i - 1;
+                    if bounds.is_some_and(|b| b.rules_out(ask_sum, bid_sum, remaining)) {
+                        static RULED_OUT: Sampled = Sampled::new();
+                        if let Some(dropped) = RULED_OUT.sample() {
+                            debug!(
+                                market = %market.ticker,
+                                ask_sum,
+                                bid_sum,
+                                remaining,
+                                dropped,
+                                "No direction can clear fees and thresholds, skipping event"
+                            );
+                        }
+                        return None;
+                    }
+                }
+                None => {
+                    // No NO bids → can't compute YES ask → skip this event
+                    static NO_BIDS: Sampled = Sampled::new();
+                    if let Some(dropped) = NO_BIDS.sample() {
+                        debug!(market = %market.ticker, dropped, "No NO bids, cannot quote bracket");
+                    }
+                    return None;
+                }
+            },
+            Err(e) => {
+                warn!(
+                    market = %market.ticker,
+                    error = %e,
+                    "Failed to fetch orderbook"
+                );
+                return None;
+            }
+        }
+    }
+    Some(quotes)
+}
+
+/// One-line ROI breakdown for alerts: gross → net of fees → net of expected slippage.
+fn roi_tiers(opp: &ArbOpportunity) -> String {
+    format!(
+        "ROI: gross {:.1}% / net {:.1}% / after {} slippage {:.1}%",
+        opp.gross_roi_pct,
+        opp.net_roi_pct,
+        money::dollars(opp.expected_slippage_cents),
+        opp.slippage_adj_roi_pct,
+    )
 }
@@ -0,0 +1,126 @@
+use anyhow::{bail, Context, Result};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::alerts;
+use crate::config::{self, Config, KalshiConfig};
+use crate::executor::{self, ExecutionResult};
+use crate::kalshi::auth::KalshiAuth;
+use crate::kalshi::client::KalshiClient;
+use crate::kalshi::types::ArbOpportunity;
+use crate::storage;
+use crate::telegram;
+
+/// Build the mirror client from `[mirror]`, if configured. Refuses a mirror that points
+/// at the live account (see `ensure_separate`).
+pub fn client(config: &Config) -> Result<Option<KalshiClient>> {
+    let Some(mirror) = &config.mirror else {
+        return Ok(None);
+    };
+    let api_key_id = std::env::var(&mirror.api_key_id_env)
+        .with_context(|| format!("{} not set in environment or .env", mirror.api_key_id_env))?;
+    let live_key_id = config::api_key_id().ok();
+    ensure_separate(&config.kalshi, live_key_id.as_deref(), &mirror.kalshi, &api_key_id)?;
+    let auth = KalshiAuth::new(&mirror.kalshi.rsa_key_path, api_key_id)?;
+    let client = KalshiClient::new(auth, &mirror.kalshi, config.scanner.scan_delay_ms)?;
+    info!(base_url = %mirror.kalshi.base_url, "Mirroring live executions");
+    Ok(Some(client))
+}
+
+/// The mirror places every live execution's orders a second time, so a mirror on the live
+/// exchange or under the live key (a copy-pasted `base_url` or key variable) would double
+/// every position. Either is an error.
+fn ensure_separate(
+    live: &KalshiConfig,
+    live_key_id: Option<&str>,
+    mirror: &KalshiConfig,
+    mirror_key_id: &str,
+) -> Result<()> {
+    let url = |u: &str| u.trim_end_matches('/').to_ascii_lowercase();
+    let live_urls = [Some(&live.base_url), live.fallback_base_url.as_ref()];
+    if live_urls.into_iter().flatten().any(|u| url(u) == url(&mirror.base_url)) {
+        bail!("[mirror.kalshi] base_url {} is the live exchange: the mirror would double every order", mirror.base_url);
+    }
+    if live_key_id == Some(mirror_key_id) {
+        bail!("The mirror's API key ID is the live one: the mirror would double every order");
+    }
+    Ok(())
+}
+
+/// Replay a live execution on the mirror environment, log both outcomes side by side,
+/// and cancel whatever the mirror left open. Order rejections that the live side did
+/// not see are the signal this exists for, so they alert.
+pub async fn mirror_execution(
+    client: &KalshiClient,
+    opp: &ArbOpportunity,
+    position_size: u32,
+    budget: Duration,
+    live: &ExecutionResult,
+) {
+    let mirror = match executor::execute_arb_unlogged(client, opp, position_size, budget).await {
+        Ok(r) => r,
+        Err(e) => {
+            error!(event = %opp.event_ticker, error = %e, "Mirror execution failed");
+            let msg = alerts::render(
+                "mirror_failure",
+                alerts::context! { event => opp.event_ticker, error => e.to_string() },
+            );
+            telegram::send_alert(&msg)
+                .await
+                .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+            return;
+        }
+    };
+
+    storage::log_mirror(opp, &mirror, live)
+        .unwrap_or_else(|e| warn!("Failed to log mirror execution: {}", e));
+
+    for (ticker, order) in mirror.resting.iter().chain(&mirror.other) {
+        if let Err(e) = client.cancel_order(&order.order_id).await {
+            warn!(ticker = %ticker, order_id = %order.order_id, error = %e, "Mirror cancel failed");
+        }
+    }
+
+    let rejected: Vec<&String> = mirror
+        .api_failures
+        .iter()
+        .filter(|t| !live.api_failures.contains(t))
+        .collect();
+    if !rejected.is_empty() {
+        error!(
+            event = %opp.event_ticker,
+            rejected = rejected.len(),
+            "Mirror rejected orders the live environment accepted"
+        );
+        let msg = alerts::render(
+            "mirror_divergence",
+            alerts::context! {
+                event => opp.event_ticker,
+                direction => opp.direction.to_string(),
+                rejected,
+            },
+        );
+        telegram::send_alert(&msg)
+            .await
+            .unwrap_or_else(|e| warn!("Telegram alert failed: {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_must_not_be_the_live_account() {
+        let live = KalshiConfig::with_base_url("https://api.elections.kalshi.com/trade-api/v2");
+        let demo = KalshiConfig::with_base_url("https://demo-api.kalshi.co/trade-api/v2");
+        ensure_separate(&live, Some("live-key"), &demo, "demo-key").unwrap();
+
+        let prod = KalshiConfig::with_base_url("https://API.elections.kalshi.com/trade-api/v2/");
+        let err = ensure_separate(&live, Some("live-key"), &prod, "demo-key").unwrap_err();
+        assert!(err.to_string().contains("live exchange"), "{}", err);
+
+        let err = ensure_separate(&live, Some("live-key"), &demo, "live-key").unwrap_err();
+        assert!(err.to_string().contains("API key ID"), "{}", err);
+    }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An amount of money in cents, signed (profit or loss). Arithmetic is checked: overflow
+/// comes back as `None` for the caller to handle instead of wrapping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cents(pub i64);
+
+impl Cents {
+    pub const ZERO: Cents = Cents(0);
+
+    pub fn checked_add(self, other: Cents) -> Option<Cents> {
+        self.0.checked_add(other.0).map(Cents)
+    }
+
+    pub fn checked_sub(self, other: Cents) -> Option<Cents> {
+        self.0.checked_sub(other.0).map(Cents)
+    }
+
+    /// This price per contract, for `contracts` contracts.
+    pub fn checked_mul(self, contracts: Contracts) -> Option<Cents> {
+        self.0.checked_mul(contracts.0 as i64).map(Cents)
+    }
+
+    /// Sum of `amounts`, or None if any partial sum overflows.
+    pub fn checked_sum(amounts: impl IntoIterator<Item = Cents>) -> Option<Cents> {
+        amounts.into_iter().try_fold(Cents::ZERO, Cents::checked_add)
+    }
+}
+
+impl fmt::Display for Cents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&dollars(self.0))
+    }
+}
+
+/// A number of contracts: never negative and no more than an order can carry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Contracts(pub u32);
+
+impl Contracts {
+    pub const ZERO: Contracts = Contracts(0);
+
+    /// A count as the exchange or a book reports it; None when negative or too large for
+    /// an order, rather than a cast that wraps.
+    pub fn from_i64(n: i64) -> Option<Contracts> {
+        u32::try_from(n).ok().map(Contracts)
+    }
+
+    pub fn get(self) -> u32 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Contracts) -> Option<Contracts> {
+        self.0.checked_add(other.0).map(Contracts)
+    }
+
+    pub fn checked_sub(self, other: Contracts) -> Option<Contracts> {
+        self.0.checked_sub(other.0).map(Contracts)
+    }
+}
+
+impl fmt::Display for Contracts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Cents as dollars: `-$1,234.05`. Integer arithmetic, so no `-0.00` and no rounding drift
+/// between logs that format the same amount.
+pub fn dollars(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let abs = cents.unsigned_abs();
+    let whole = (abs / 100).to_string();
+    let mut grouped = String::with_capacity(whole.len() + whole.len() / 3);
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}${}.{:02}", sign, grouped, abs % 100)
+}
+
+/// A logged dollar amount back to cents: `"-$1,234.05"`, the older `"$-1.23"`, or with
+/// trailing text (`"$0.40 (INCOMPLETE)"`).
+pub fn parse_dollars(cell: &str) -> Option<i64> {
+    let cell = cell.split_whitespace().next()?;
+    let (negative, rest) = match cell.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, cell),
+    };
+    let amount = rest.strip_prefix('$')?.replace(',', "");
+    let (negative, amount) = match amount.strip_prefix('-') {
+        Some(a) => (!negative, a.to_string()),
+        None => (negative, amount),
+    };
+    let (whole, frac) = amount.split_once('.').unwrap_or((&amount, "0"));
+    if frac.is_empty() || frac.len() > 2 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let frac: i64 = format!("{:0<2}", frac).parse().ok()?;
+    let cents = whole.parse::<i64>().ok()? * 100 + frac;
+    Some(if negative { -cents } else { cents })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_and_parses_back() {
+        assert_eq!(dollars(0), "$0.00");
+        assert_eq!(dollars(-5), "-$0.05");
+        assert_eq!(dollars(123_456_789), "$1,234,567.89");
+        assert_eq!(dollars(-100_000), "-$1,000.00");
+        for cents in [0, 7, -7, 99, -150, 123_456, -98_765_432] {
+            assert_eq!(parse_dollars(&dollars(cents)), Some(cents));
+        }
+        // Rows written before the helper existed
+        assert_eq!(parse_dollars("$-1.50 (INCOMPLETE)"), Some(-150));
+        assert_eq!(parse_dollars("$0.4"), Some(40));
+        assert_eq!(parse_dollars("1.00"), None);
+    }
+
+    #[test]
+    fn test_checked_cents_and_contracts() {
+        assert_eq!(Cents(45).checked_mul(Contracts(10)), Some(Cents(450)));
+        assert_eq!(Cents(i64::MAX / 2).checked_mul(Contracts(3)), None);
+        assert_eq!(Cents::checked_sum([Cents(5), Cents(-7), Cents(2)]), Some(Cents::ZERO));
+        assert_eq!(Cents::checked_sum([Cents(i64::MAX), Cents(1)]), None);
+        assert_eq!(Cents(i64::MIN).checked_sub(Cents(1)), None);
+        assert_eq!(Cents(-150).to_string(), "-$1.50");
+        // A negative or oversized exchange count is refused, not wrapped
+        assert_eq!(Contracts::from_i64(7), Some(Contracts(7)));
+        assert_eq!(Contracts::from_i64(-1), None);
+        assert_eq!(Contracts::from_i64(1 << 40), None);
+        assert_eq!(Contracts(2).checked_sub(Contracts(3)), None);
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+/// A component that places orders. Only one may hold orders in an event at a time,
+/// so no two strategies build positions in the same event without knowing about
+/// each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// The Dutch-book executor (scan loop and the `execute` subcommand).
+    Arb,
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strategy::Arb => write!(f, "arb"),
+        }
+    }
+}
+
+/// Who holds an event, published in `/status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventClaim {
+    pub event: String,
+    pub strategy: Strategy,
+    pub since: DateTime<Utc>,
+    /// Kept after the placing call returned because orders were left on the book.
+    pub retained: bool,
+}
+
+fn registry() -> &'static Mutex<HashMap<String, EventClaim>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, EventClaim>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Holds an event for one strategy; dropping it releases the event unless `retain`ed.
+#[must_use]
+pub struct OwnershipGuard {
+    event: String,
+    retained: bool,
+}
+
+impl OwnershipGuard {
+    /// Keep the claim past this guard, for orders still working in the event. It is
+    /// released by `release_settled` once none are.
+    pub fn retain(mut self) {
+        if let Some(claim) = registry().lock().unwrap_or_else(|e| e.into_inner()).get_mut(&self.event) {
+            claim.retained = true;
+        }
+        self.retained = true;
+    }
+}
+
+impl Drop for OwnershipGuard {
+    fn drop(&mut self) {
+        if !self.retained {
+            registry().lock().unwrap_or_else(|e| e.into_inner()).remove(&self.event);
+        }
+    }
+}
+
+/// Take an event for `strategy`. Fails with the current holder while anyone, including
+/// another execution of the same strategy, holds it.
+pub fn claim(event: &str, strategy: Strategy) -> Result<OwnershipGuard, EventClaim> {
+    let mut claims = registry().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(holder) = claims.get(event) {
+        return Err(holder.clone());
+    }
+    claims.insert(
+        event.to_string(),
+        EventClaim { event: event.to_string(), strategy, since: Utc::now(), retained: false },
+    );
+    Ok(OwnershipGuard { event: event.to_string(), retained: false })
+}
+
+/// Release `strategy`'s retained claims on events not in `working` (events where it
+/// still has orders on the book). Returns the released events.
+pub fn release_settled(strategy: Strategy, working: &HashSet<String>) -> Vec<String> {
+    let mut claims = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let settled: Vec<String> = claims
+        .values()
+        .filter(|c| c.strategy == strategy && c.retained && !working.contains(&c.event))
+        .map(|c| c.event.clone())
+        .collect();
+    for event in &settled {
+        claims.remove(event);
+    }
+    settled
+}
+
+/// Current claims, oldest first.
+pub fn snapshot() -> Vec<EventClaim> {
+    let mut claims: Vec<_> = registry().lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect();
+    claims.sort_by_key(|c| c.since);
+    claims
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_holder_per_event() {
+        let guard = claim("OWN-EV1", Strategy::Arb).unwrap();
+        let holder = claim("OWN-EV1", Strategy::Arb).err().unwrap();
+        assert_eq!((holder.strategy, holder.retained), (Strategy::Arb, false));
+        assert!(claim("OWN-EV2", Strategy::Arb).is_ok());
+        drop(guard);
+        let guard = claim("OWN-EV1", Strategy::Arb).unwrap();
+
+        // Retained past the guard until the event has no working orders
+        guard.retain();
+        assert!(claim("OWN-EV1", Strategy::Arb).is_err());
+        let working = HashSet::from(["OWN-EV1".to_string()]);
+        assert!(release_settled(Strategy::Arb, &working).is_empty());
+        assert_eq!(release_settled(Strategy::Arb, &HashSet::new()), vec!["OWN-EV1"]);
+        assert!(claim("OWN-EV1", Strategy::Arb).is_ok());
+    }
+}
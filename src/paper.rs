@@ -0,0 +1,310 @@
+// Paper trading for dry runs. Instead of only logging an opportunity, a dry run "executes"
+// it against a fresh read of its books: each leg fills up to the depth at or better than
+// its limit, paying taker fees, so thin books show up as partial fills. The fills form a
+// virtual portfolio in `data/paper.jsonl` that settles from market results, so a dry run
+// tells what it would have earned.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::fees::taker_fee_cents;
+use crate::kalshi::types::{ArbDirection, ArbOpportunity, BracketQuote};
+use crate::price::PAYOUT_CENTS;
+use crate::storage::append_line;
+
+pub const PAPER_PATH: &str = "data/paper.jsonl";
+
+/// Book levels read per leg when filling.
+pub const BOOK_LEVELS: usize = 10;
+
+/// One leg of a paper trade: what the book at execution time would have filled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperLeg {
+    pub ticker: String,
+    pub filled: i64,
+    /// Paid for the contracts (LONG) or received for them (SHORT), before fees.
+    pub cost_cents: i64,
+    pub fee_cents: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperTrade {
+    pub opportunity_id: String,
+    pub event_ticker: String,
+    pub direction: ArbDirection,
+    pub placed_at: DateTime<Utc>,
+    /// Contracts asked for per leg.
+    pub size: u32,
+    pub legs: Vec<PaperLeg>,
+    /// Net profit the opportunity was detected at.
+    pub expected_net_cents: i64,
+}
+
+impl PaperTrade {
+    /// Every leg filled in full: the arb is locked whatever the outcome.
+    pub fn is_complete(&self) -> bool {
+        self.legs.iter().all(|l| l.filled == self.size as i64)
+    }
+
+    pub fn fees_cents(&self) -> i64 {
+        self.legs.iter().map(|l| l.fee_cents).sum()
+    }
+
+    /// Profit fixed at entry for a complete trade: one bracket pays out per contract.
+    pub fn locked_net_cents(&self) -> Option<i64> {
+        if !self.is_complete() {
+            return None;
+        }
+        let cost: i64 = self.legs.iter().map(|l| l.cost_cents).sum();
+        let payout = self.size as i64 * PAYOUT_CENTS;
+        let gross = match self.direction {
+            ArbDirection::Long => payout - cost,
+            ArbDirection::Short => cost - payout,
+        };
+        Some(gross - self.fees_cents())
+    }
+
+    /// Realized P&L once every filled leg's market has a result ("yes" / "no"), else None.
+    fn settle(&self, results: &HashMap<String, String>) -> Option<i64> {
+        let mut gross = 0;
+        for leg in self.legs.iter().filter(|l| l.filled > 0) {
+            let paid_out = match results.get(&leg.ticker)?.as_str() {
+                "yes" => leg.filled * PAYOUT_CENTS,
+                _ => 0,
+            };
+            gross += match self.direction {
+                ArbDirection::Long => paid_out - leg.cost_cents,
+                ArbDirection::Short => leg.cost_cents - paid_out,
+            };
+        }
+        Some(gross - self.fees_cents())
+    }
+}
+
+/// Fill an opportunity against `quotes`, a fresh read of its books taken with levels. Each
+/// leg is a limit at the price it was detected at, taking every level at or better than it.
+pub fn simulate(opp: &ArbOpportunity, quotes: &[BracketQuote]) -> PaperTrade {
+    let legs = opp
+        .brackets
+        .iter()
+        .map(|bracket| {
+            let book = quotes.iter().find(|q| q.ticker == bracket.ticker);
+            let levels: Vec<(i64, i64)> = match (book, opp.direction) {
+                (Some(q), ArbDirection::Long) => {
+                    let limit = bracket.ask_levels.last().map_or(bracket.yes_ask_cents, |l| l.0);
+                    q.ask_levels.iter().copied().filter(|&(p, _)| p <= limit).collect()
+                }
+                (Some(q), ArbDirection::Short) => {
+                    let limit = bracket.bid_levels.last().map_or(bracket.yes_bid_cents, |l| l.0);
+                    q.bid_levels.iter().copied().filter(|&(p, _)| p >= limit).collect()
+                }
+                (None, _) => Vec::new(),
+            };
+            let mut leg = PaperLeg { ticker: bracket.ticker.clone(), filled: 0, cost_cents: 0, fee_cents: 0 };
+            for (price, quantity) in levels {
+                let take = quantity.min(opp.position_size as i64 - leg.filled);
+                if take <= 0 {
+                    break;
+                }
+                leg.filled += take;
+                leg.cost_cents += take * price;
+                leg.fee_cents += taker_fee_cents(&leg.ticker, take as u32, price);
+            }
+            leg
+        })
+        .collect();
+    PaperTrade {
+        opportunity_id: opp.id.clone(),
+        event_ticker: opp.event_ticker.clone(),
+        direction: opp.direction,
+        placed_at: Utc::now(),
+        size: opp.position_size,
+        legs,
+        expected_net_cents: opp.net_profit_cents,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperSettlement {
+    pub opportunity_id: String,
+    pub settled_at: DateTime<Utc>,
+    pub pnl_cents: i64,
+}
+
+/// One line of `data/paper.jsonl`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum PaperLine {
+    Trade(PaperTrade),
+    Settlement(PaperSettlement),
+}
+
+/// The virtual portfolio at a glance, published as `paper` in `/status`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PaperSummary {
+    pub trades: usize,
+    /// Trades where some leg came up short of the size asked for.
+    pub partial: usize,
+    /// Trades whose markets haven't all settled.
+    pub open: usize,
+    /// Profit locked in by open complete trades.
+    pub locked_net_cents: i64,
+    pub realized_pnl_cents: i64,
+    pub fees_cents: i64,
+}
+
+/// Paper trades and their settlements, replayed from disk at startup.
+#[derive(Debug)]
+pub struct PaperPortfolio {
+    path: PathBuf,
+    trades: BTreeMap<String, PaperTrade>,
+    settled: HashMap<String, PaperSettlement>,
+}
+
+impl PaperPortfolio {
+    /// Replay `path`. A missing file is an empty portfolio; unparseable lines are skipped.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut portfolio = Self { path: path.to_path_buf(), trades: BTreeMap::new(), settled: HashMap::new() };
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(portfolio),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        for (i, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            match serde_json::from_str::<PaperLine>(line) {
+                Ok(entry) => portfolio.apply(entry),
+                Err(e) => warn!(line = i + 1, error = %e, "Skipping unparseable paper trade"),
+            }
+        }
+        Ok(portfolio)
+    }
+
+    fn apply(&mut self, entry: PaperLine) {
+        match entry {
+            PaperLine::Trade(trade) => {
+                self.trades.insert(trade.opportunity_id.clone(), trade);
+            }
+            PaperLine::Settlement(settlement) => {
+                self.settled.insert(settlement.opportunity_id.clone(), settlement);
+            }
+        }
+    }
+
+    fn persist(&mut self, entry: PaperLine) -> Result<()> {
+        let line = serde_json::to_string(&entry).context("Failed to serialize paper trade")?;
+        append_line(&self.path.to_string_lossy(), &line)?;
+        self.apply(entry);
+        Ok(())
+    }
+
+    pub fn record(&mut self, trade: PaperTrade) -> Result<()> {
+        self.persist(PaperLine::Trade(trade))
+    }
+
+    /// Events with a trade still waiting on market results.
+    pub fn open_events(&self) -> HashSet<String> {
+        self.open_trades().map(|t| t.event_ticker.clone()).collect()
+    }
+
+    fn open_trades(&self) -> impl Iterator<Item = &PaperTrade> {
+        self.trades.values().filter(|t| !self.settled.contains_key(&t.opportunity_id))
+    }
+
+    /// Settle every open trade whose markets all have a result in `results` (ticker → "yes" / "no").
+    pub fn settle(&mut self, results: &HashMap<String, String>) -> Result<Vec<PaperSettlement>> {
+        let now = Utc::now();
+        let due: Vec<PaperSettlement> = self
+            .open_trades()
+            .filter_map(|t| {
+                let pnl_cents = t.settle(results)?;
+                Some(PaperSettlement { opportunity_id: t.opportunity_id.clone(), settled_at: now, pnl_cents })
+            })
+            .collect();
+        for settlement in &due {
+            self.persist(PaperLine::Settlement(settlement.clone()))?;
+        }
+        Ok(due)
+    }
+
+    pub fn summary(&self) -> PaperSummary {
+        let open: Vec<&PaperTrade> = self.open_trades().collect();
+        PaperSummary {
+            trades: self.trades.len(),
+            partial: self.trades.values().filter(|t| !t.is_complete()).count(),
+            open: open.len(),
+            locked_net_cents: open.iter().filter_map(|t| t.locked_net_cents()).sum(),
+            realized_pnl_cents: self.settled.values().map(|s| s.pnl_cents).sum(),
+            fees_cents: self.trades.values().map(|t| t.fees_cents()).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn bracket(ticker: &str, ask: i64) -> BracketQuote {
+        BracketQuote { ticker: ticker.into(), yes_ask_cents: ask, ..Default::default() }
+    }
+
+    fn book(ticker: &str, ask_levels: Vec<(i64, i64)>) -> BracketQuote {
+        BracketQuote { ticker: ticker.into(), ask_levels, ..Default::default() }
+    }
+
+    #[test]
+    fn test_paper_fills_settle_into_portfolio() {
+        let opp = ArbOpportunity {
+            id: "EV-LONG-1".into(),
+            uuid: Uuid::nil(),
+            event_ticker: "EV".into(),
+            event_title: "Event".into(),
+            direction: ArbDirection::Long,
+            brackets: vec![bracket("EV-A", 40), bracket("EV-B", 50)],
+            position_size: 10,
+            sum_cents: 90,
+            total_fees_cents: 35,
+            gross_profit_cents: 100,
+            net_profit_cents: 65,
+            expected_slippage_cents: 0,
+            gross_roi_pct: Decimal::ZERO,
+            net_roi_pct: Decimal::ZERO,
+            slippage_adj_roi_pct: Decimal::ZERO,
+            settles_at: None,
+        };
+        // A fills in full across two levels; B's book moved and only 4 are left at the limit
+        let quotes = vec![book("EV-A", vec![(38, 6), (40, 10)]), book("EV-B", vec![(50, 4), (51, 20)])];
+        let trade = simulate(&opp, &quotes);
+        let fee_a = taker_fee_cents("EV-A", 6, 38) + taker_fee_cents("EV-A", 4, 40);
+        assert_eq!(trade.legs[0], PaperLeg { ticker: "EV-A".into(), filled: 10, cost_cents: 388, fee_cents: fee_a });
+        assert_eq!((trade.legs[1].filled, trade.legs[1].cost_cents), (4, 200));
+        assert!(!trade.is_complete());
+        assert_eq!(trade.locked_net_cents(), None);
+
+        let path = std::env::temp_dir().join(format!("arb-paper-{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let mut portfolio = PaperPortfolio::load(&path).unwrap();
+        portfolio.record(trade).unwrap();
+        assert_eq!(portfolio.open_events(), HashSet::from(["EV".to_string()]));
+
+        // Nothing settles until every filled leg's market has a result; B paying out 4 x 100
+        let results = HashMap::from([("EV-A".to_string(), "no".to_string())]);
+        assert!(portfolio.settle(&results).unwrap().is_empty());
+        let results = HashMap::from([("EV-A".to_string(), "no".to_string()), ("EV-B".to_string(), "yes".to_string())]);
+        let settled = portfolio.settle(&results).unwrap();
+        let fees = fee_a + taker_fee_cents("EV-B", 4, 50);
+        assert_eq!(settled[0].pnl_cents, 400 - 388 - 200 - fees);
+
+        // Replayed from disk, settled once
+        let portfolio = PaperPortfolio::load(&path).unwrap();
+        let summary = portfolio.summary();
+        assert_eq!((summary.trades, summary.partial, summary.open), (1, 1, 0));
+        assert_eq!(summary.realized_pnl_cents, 400 - 388 - 200 - fees);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,244 @@
+// Polymarket as a `Venue`: events and markets from the Gamma API, books from the CLOB.
+// Read-only. Polymarket orders are EIP-712 messages signed by a Polygon wallet, which
+// this bot has no signer for, so order placement and cancels are refused.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::kalshi::types::{CreateOrderRequest, Event, Market, Order, Orderbook, PriceLevel};
+use crate::price::{self, PAYOUT_CENTS};
+use crate::venue::Venue;
+
+/// Gamma events per page.
+const PAGE_SIZE: usize = 100;
+/// Pages read per listing before giving up on the rest.
+const MAX_PAGES: usize = 20;
+
+/// `[polymarket]` — endpoints for `venue-scan polymarket`. Needs no credentials.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct PolymarketConfig {
+    #[serde(default = "default_gamma_url")]
+    pub gamma_url: String,
+    #[serde(default = "default_clob_url")]
+    pub clob_url: String,
+}
+
+fn default_gamma_url() -> String { "https://gamma-api.polymarket.com".to_string() }
+fn default_clob_url() -> String { "https://clob.polymarket.com".to_string() }
+
+impl Default for PolymarketConfig {
+    fn default() -> Self {
+        Self { gamma_url: default_gamma_url(), clob_url: default_clob_url() }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GammaEvent {
+    slug: String,
+    title: String,
+    /// Polymarket's flag for outcome sets where exactly one market resolves YES.
+    #[serde(default)]
+    neg_risk: bool,
+    #[serde(default)]
+    markets: Vec<GammaMarket>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GammaMarket {
+    question: String,
+    /// Outcome label within a multi-market event (e.g. a candidate's name).
+    #[serde(default)]
+    group_item_title: Option<String>,
+    /// JSON-encoded array of token IDs, YES first.
+    #[serde(default)]
+    clob_token_ids: Option<String>,
+    #[serde(default)]
+    active: bool,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    accepting_orders: Option<bool>,
+    #[serde(default)]
+    end_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClobBook {
+    #[serde(default)]
+    bids: Vec<ClobLevel>,
+    #[serde(default)]
+    asks: Vec<ClobLevel>,
+}
+
+/// Price in dollars and size in shares, both as decimal strings.
+#[derive(Debug, Deserialize)]
+struct ClobLevel {
+    price: Decimal,
+    size: Decimal,
+}
+
+impl GammaEvent {
+    /// In Kalshi's shape: each market keyed by its YES token, so books can be fetched by ticker.
+    fn into_event(self) -> Event {
+        let markets = self
+            .markets
+            .into_iter()
+            .filter_map(|m| {
+                let tokens: Vec<String> = serde_json::from_str(m.clob_token_ids.as_deref()?).ok()?;
+                let yes_token = tokens.into_iter().next()?;
+                let trading = m.active && !m.closed && m.accepting_orders.unwrap_or(true);
+                Some(Market {
+                    ticker: yes_token,
+                    title: m.group_item_title.unwrap_or(m.question),
+                    subtitle: None,
+                    status: if trading { "active" } else { "closed" }.to_string(),
+                    result: None,
+                    close_time: m.end_date,
+                    expected_expiration_time: None,
+                    expiration_time: None,
+                    settlement_timer_seconds: None,
+                    yes_sub_title: None,
+                    strike_type: None,
+                    floor_strike: None,
+                    cap_strike: None,
+                })
+            })
+            .collect();
+        Event {
+            event_ticker: self.slug,
+            title: self.title,
+            mutually_exclusive: self.neg_risk,
+            strike_date: None,
+            markets,
+        }
+    }
+}
+
+impl ClobBook {
+    /// YES bids stay YES bids; a YES ask at p is a NO bid at 100 - p. Sub-cent prices round
+    /// against us (bids down, asks up) and fractional shares are dropped.
+    fn into_orderbook(self) -> Orderbook {
+        let cents = |level: &ClobLevel, round_up: bool| {
+            let c = level.price * Decimal::from(PAYOUT_CENTS);
+            let c = if round_up { c.ceil() } else { c.floor() };
+            Some((c.to_i64()?, level.size.floor().to_i64()?))
+        };
+        let yes = self
+            .bids
+            .iter()
+            .filter_map(|l| cents(l, false))
+            .filter(|&(p, q)| price::is_valid(p) && q > 0)
+            .map(|(price, quantity)| PriceLevel { price, quantity })
+            .collect();
+        let no = self
+            .asks
+            .iter()
+            .filter_map(|l| cents(l, true))
+            .map(|(p, q)| (price::complement(p), q))
+            .filter(|&(p, q)| price::is_valid(p) && q > 0)
+            .map(|(price, quantity)| PriceLevel { price, quantity })
+            .collect();
+        Orderbook { yes, no }
+    }
+}
+
+/// Unauthenticated client for Polymarket's public market data.
+#[derive(Clone)]
+pub struct PolymarketClient {
+    http: Client,
+    gamma_url: String,
+    clob_url: String,
+}
+
+impl PolymarketClient {
+    pub fn new(config: &PolymarketConfig) -> Result<Self> {
+        let http = Client::builder().timeout(Duration::from_secs(15)).build()?;
+        Ok(Self {
+            http,
+            gamma_url: config.gamma_url.trim_end_matches('/').to_string(),
+            clob_url: config.clob_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let resp = self.http.get(url).send().await.with_context(|| format!("GET {} failed", url))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            bail!("GET {} returned {}: {}", url, status, body);
+        }
+        resp.json::<T>().await.context("Failed to parse response")
+    }
+}
+
+impl Venue for PolymarketClient {
+    fn name(&self) -> &'static str {
+        "polymarket"
+    }
+
+    async fn list_events(&self, group: &str) -> Result<Vec<Event>> {
+        let mut all = Vec::new();
+        for page in 0..MAX_PAGES {
+            let url = format!(
+                "{}/events?active=true&closed=false&tag_slug={}&limit={}&offset={}",
+                self.gamma_url,
+                group,
+                PAGE_SIZE,
+                page * PAGE_SIZE
+            );
+            let events: Vec<GammaEvent> = self.get(&url).await?;
+            let done = events.len() < PAGE_SIZE;
+            all.extend(events.into_iter().map(GammaEvent::into_event));
+            if done {
+                break;
+            }
+        }
+        debug!("Fetched {} Polymarket events", all.len());
+        Ok(all)
+    }
+
+    async fn get_orderbook(&self, market: &str) -> Result<Orderbook> {
+        let book: ClobBook = self.get(&format!("{}/book?token_id={}", self.clob_url, market)).await?;
+        Ok(book.into_orderbook())
+    }
+
+    async fn place_order(&self, req: &CreateOrderRequest) -> Result<Order> {
+        bail!("Polymarket orders need a wallet signature; not placing {} on {}", req.action, req.ticker)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        bail!("Polymarket orders need a wallet signature; not cancelling {}", order_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gamma_event_and_clob_book_map_to_kalshi_shapes() {
+        let event: GammaEvent =
+            serde_json::from_str(include_str!("../tests/fixtures/polymarket_event.json")).unwrap();
+        let event = event.into_event();
+        assert!(event.mutually_exclusive);
+        assert_eq!(event.markets.len(), 3);
+        assert_eq!((event.markets[0].ticker.as_str(), event.markets[0].title.as_str()), ("1111", "Alice"));
+        // Resolved market: listed, but not trading
+        assert_eq!(event.markets[2].status, "closed");
+
+        let book: ClobBook = serde_json::from_str(include_str!("../tests/fixtures/polymarket_book.json")).unwrap();
+        let book = book.into_orderbook();
+        // 0.455 bid rounds down to 45c; 0.472 ask rounds up to 48c, a 52c NO bid
+        assert_eq!(book.yes.iter().map(|l| (l.price, l.quantity)).collect::<Vec<_>>(), [(45, 120), (44, 30)]);
+        assert_eq!(book.no.iter().map(|l| (l.price, l.quantity)).collect::<Vec<_>>(), [(52, 10)]);
+    }
+}
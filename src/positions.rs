@@ -0,0 +1,587 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::fees::fill_fee_cents;
+use crate::kalshi::types::{Order, Settlement};
+use crate::money::Contracts;
+use crate::price::PAYOUT_CENTS;
+use crate::storage::{ExecutionRecord, ExecutionStore};
+
+pub const POSITIONS_PATH: &str = "data/positions.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS arbs (
+    opportunity_id     TEXT PRIMARY KEY,
+    event_ticker       TEXT NOT NULL,
+    direction          TEXT NOT NULL,
+    complete           INTEGER NOT NULL,
+    opened_at          TEXT NOT NULL,
+    settled_at         TEXT,
+    realized_pnl_cents INTEGER
+);
+CREATE TABLE IF NOT EXISTS legs (
+    order_id       TEXT PRIMARY KEY,
+    opportunity_id TEXT NOT NULL REFERENCES arbs (opportunity_id),
+    ticker         TEXT NOT NULL,
+    action         TEXT NOT NULL,
+    side           TEXT NOT NULL,
+    yes_price      INTEGER NOT NULL,
+    count          INTEGER NOT NULL,
+    fee_cents      INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS brackets (
+    opportunity_id TEXT NOT NULL,
+    ticker         TEXT NOT NULL,
+    PRIMARY KEY (opportunity_id, ticker)
+);
+CREATE TABLE IF NOT EXISTS settlements (
+    ticker        TEXT PRIMARY KEY,
+    market_result TEXT NOT NULL,
+    settled_at    TEXT NOT NULL
+);
+";
+
+/// An arb whose markets have all settled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettledArb {
+    pub opportunity_id: String,
+    pub event_ticker: String,
+    pub realized_pnl_cents: i64,
+}
+
+/// Contracts of an arb that never completed which the other brackets don't match: what an
+/// exit has to close.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnhedgedLeg {
+    pub opportunity_id: String,
+    pub event_ticker: String,
+    pub ticker: String,
+    /// YES position: positive is held YES (closed by selling), negative is sold YES.
+    pub contracts: i64,
+    /// Average YES price of the fills that opened the position.
+    pub entry_cents: i64,
+}
+
+/// An unsettled arb that never completed, with every bracket it placed an order in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncompleteArb {
+    pub opportunity_id: String,
+    pub event_ticker: String,
+    pub brackets: Vec<String>,
+    /// Empty once the brackets match again (completed or closed out).
+    pub unhedged: Vec<UnhedgedLeg>,
+}
+
+struct Leg {
+    ticker: String,
+    action: String,
+    side: String,
+    yes_price: i64,
+    count: i64,
+    fee_cents: i64,
+}
+
+impl Leg {
+    /// Profit of this leg once its market resolved, fees included. A void market
+    /// refunds the position and its fees.
+    fn realized_cents(&self, market_result: &str) -> i64 {
+        let payout = match market_result {
+            "yes" => PAYOUT_CENTS,
+            "no" => 0,
+            _ => return 0,
+        };
+        // Buying YES or selling NO holds YES exposure; the reverse holds NO exposure.
+        let per_contract = match (self.action.as_str(), self.side.as_str()) {
+            ("buy", "yes") | ("sell", "no") => payout - self.yes_price,
+            _ => self.yes_price - payout,
+        };
+        per_contract * self.count - self.fee_cents
+    }
+
+    /// Signed YES contracts: buying YES or selling NO adds, the reverse subtracts.
+    fn yes_contracts(&self) -> i64 {
+        match (self.action.as_str(), self.side.as_str()) {
+            ("buy", "yes") | ("sell", "no") => self.count,
+            _ => -self.count,
+        }
+    }
+}
+
+/// Filled arb legs and their settlement, in SQLite (`data/positions.db`). Rebuilt from
+/// the execution store on each sync, so it can be deleted and recreated at any time;
+/// settled arbs keep the realized P&L computed from the legs and the market results.
+pub struct PositionTracker {
+    conn: Connection,
+}
+
+impl PositionTracker {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).ok();
+        }
+        let conn = Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA).context("Failed to create position tables")?;
+        Ok(Self { conn })
+    }
+
+    /// Record the filled legs of every execution in the store: what its orders filled,
+    /// including those that rested, and what its unwind orders filled. Idempotent; returns
+    /// how many legs were new or changed count.
+    pub fn sync(&mut self, store: &ExecutionStore) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut added = 0;
+        for rec in store.executions() {
+            added += record_execution(&tx, rec, store)?;
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// Fully-filled arbs whose markets haven't all settled: what `[risk] max_open_positions`
+    /// caps, as the run loop's counter does. Incomplete arbs aren't counted. Their unhedged
+    /// legs are closed by the unwind or the `exits` job rather than held, and each is listed by
+    /// `incomplete_arbs` until then. Counting them would hold a slot until settlement even
+    /// once they're flat.
+    pub fn open_arbs(&self) -> Result<u32> {
+        let n: u32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM arbs WHERE complete = 1 AND settled_at IS NULL
+             AND EXISTS (SELECT 1 FROM legs WHERE legs.opportunity_id = arbs.opportunity_id)",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(n)
+    }
+
+    /// Markets with a leg of an unsettled arb and no settlement yet.
+    pub fn unsettled_tickers(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT legs.ticker FROM legs JOIN arbs USING (opportunity_id)
+             WHERE arbs.settled_at IS NULL
+             AND legs.ticker NOT IN (SELECT ticker FROM settlements)",
+        )?;
+        let tickers = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        Ok(tickers)
+    }
+
+    /// Markets holding a leg of an unsettled arb that never completed, not yet settled.
+    pub fn unhedged_tickers(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT legs.ticker FROM legs JOIN arbs USING (opportunity_id)
+             WHERE arbs.complete = 0 AND arbs.settled_at IS NULL
+             AND legs.ticker NOT IN (SELECT ticker FROM settlements)",
+        )?;
+        let tickers = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        Ok(tickers)
+    }
+
+    /// Record the fills of an order that completes or closes part of an incomplete arb (an
+    /// unwind or exit order), as one of its legs. Re-recording an order updates its count,
+    /// so a partly filled order can be recorded as it fills. Returns false for no fills.
+    pub fn record_exit(&mut self, opportunity_id: &str, ticker: &str, order: &Order) -> Result<bool> {
+        let Some(count) = Contracts::from_i64(order.filled_count()).filter(|&n| n > Contracts::ZERO) else {
+            return Ok(false);
+        };
+        let yes_price = order.yes_price.unwrap_or(0);
+        self.conn.execute(
+            "INSERT INTO legs (order_id, opportunity_id, ticker, action, side, yes_price, count, fee_cents)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (order_id) DO UPDATE SET count = excluded.count, fee_cents = excluded.fee_cents",
+            params![
+                order.order_id,
+                opportunity_id,
+                ticker,
+                order.action,
+                order.side,
+                yes_price,
+                count.get(),
+                fill_fee_cents(ticker, order, count.get(), yes_price),
+            ],
+        )?;
+        Ok(true)
+    }
+
+    /// Unsettled arbs that never completed, and what each holds beyond the position all its
+    /// brackets share. A LONG arb is hedged up to the smallest YES position across its
+    /// brackets (none if a bracket has none), a SHORT one up to the smallest sold; the rest
+    /// is unhedged. Markets that have settled are left out: there's nothing to exit.
+    pub fn incomplete_arbs(&self) -> Result<Vec<IncompleteArb>> {
+        let mut arbs: Vec<(IncompleteArb, bool)> = Vec::new();
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT opportunity_id, event_ticker, direction, ticker FROM arbs JOIN brackets USING (opportunity_id)
+                 WHERE arbs.complete = 0 AND arbs.settled_at IS NULL
+                 AND EXISTS (SELECT 1 FROM legs WHERE legs.opportunity_id = arbs.opportunity_id)
+                 ORDER BY opportunity_id, ticker",
+            )?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let opportunity_id: String = row.get(0)?;
+                if arbs.last().is_none_or(|(a, _)| a.opportunity_id != opportunity_id) {
+                    let arb = IncompleteArb {
+                        opportunity_id,
+                        event_ticker: row.get(1)?,
+                        brackets: Vec::new(),
+                        unhedged: Vec::new(),
+                    };
+                    arbs.push((arb, row.get::<_, String>(2)? == "LONG"));
+                }
+                if let Some((arb, _)) = arbs.last_mut() {
+                    arb.brackets.push(row.get(3)?);
+                }
+            }
+        }
+        let settled: HashSet<String> = {
+            let mut stmt = self.conn.prepare("SELECT ticker FROM settlements")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT ticker, action, side, yes_price, count, fee_cents FROM legs WHERE opportunity_id = ?1",
+        )?;
+        for (arb, long) in &mut arbs {
+            let legs = stmt
+                .query_map([&arb.opportunity_id], |row| {
+                    Ok(Leg {
+                        ticker: row.get(0)?,
+                        action: row.get(1)?,
+                        side: row.get(2)?,
+                        yes_price: row.get(3)?,
+                        count: row.get(4)?,
+                        fee_cents: row.get(5)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let position = |ticker: &String| -> i64 {
+                legs.iter().filter(|l| &l.ticker == ticker).map(Leg::yes_contracts).sum()
+            };
+            let positions: Vec<i64> = arb.brackets.iter().map(position).collect();
+            let hedged = if *long {
+                positions.iter().copied().min().unwrap_or(0).max(0)
+            } else {
+                positions.iter().copied().max().unwrap_or(0).min(0)
+            };
+            for (ticker, position) in arb.brackets.iter().zip(positions) {
+                let contracts = position - hedged;
+                if contracts == 0 || settled.contains(ticker) {
+                    continue;
+                }
+                // Entry: the fills on the side the position is on
+                let (cost, count) = legs
+                    .iter()
+                    .filter(|l| &l.ticker == ticker && l.yes_contracts().signum() == contracts.signum())
+                    .fold((0, 0), |(cost, count), l| (cost + l.yes_price * l.count, count + l.count));
+                arb.unhedged.push(UnhedgedLeg {
+                    opportunity_id: arb.opportunity_id.clone(),
+                    event_ticker: arb.event_ticker.clone(),
+                    ticker: ticker.clone(),
+                    contracts,
+                    entry_cents: cost / count.max(1),
+                });
+            }
+        }
+        Ok(arbs.into_iter().map(|(arb, _)| arb).collect())
+    }
+
+    /// Events with an unsettled arb that has filled legs.
+    pub fn unsettled_events(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT arbs.event_ticker FROM arbs JOIN legs USING (opportunity_id)
+             WHERE arbs.settled_at IS NULL",
+        )?;
+        let events = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        Ok(events)
+    }
+
+    /// Store market settlements and mark every arb whose markets have now all
+    /// settled, with its realized P&L.
+    pub fn settle(&mut self, settlements: &[Settlement]) -> Result<Vec<SettledArb>> {
+        let tx = self.conn.transaction()?;
+        for s in settlements {
+            tx.execute(
+                "INSERT OR IGNORE INTO settlements (ticker, market_result, settled_at) VALUES (?1, ?2, ?3)",
+                params![s.ticker, s.market_result, s.settled_time],
+            )?;
+        }
+
+        let results: HashMap<String, (String, DateTime<Utc>)> = {
+            let mut stmt = tx.prepare("SELECT ticker, market_result, settled_at FROM settlements")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        let mut legs: HashMap<(String, String), Vec<Leg>> = HashMap::new();
+        {
+            let mut stmt = tx.prepare(
+                "SELECT arbs.opportunity_id, arbs.event_ticker, ticker, action, side, yes_price, count, fee_cents
+                 FROM legs JOIN arbs USING (opportunity_id) WHERE arbs.settled_at IS NULL",
+            )?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let leg = Leg {
+                    ticker: row.get(2)?,
+                    action: row.get(3)?,
+                    side: row.get(4)?,
+                    yes_price: row.get(5)?,
+                    count: row.get(6)?,
+                    fee_cents: row.get(7)?,
+                };
+                legs.entry((row.get(0)?, row.get(1)?)).or_default().push(leg);
+            }
+        }
+
+        let mut settled = Vec::new();
+        for ((opportunity_id, event_ticker), legs) in legs {
+            let Some(settled_at) = legs.iter().map(|l| results.get(&l.ticker).map(|r| r.1)).collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            let realized_pnl_cents = legs.iter().map(|l| l.realized_cents(&results[&l.ticker].0)).sum();
+            tx.execute(
+                "UPDATE arbs SET settled_at = ?2, realized_pnl_cents = ?3 WHERE opportunity_id = ?1",
+                params![opportunity_id, settled_at.into_iter().max(), realized_pnl_cents],
+            )?;
+            settled.push(SettledArb { opportunity_id, event_ticker, realized_pnl_cents });
+        }
+        tx.commit()?;
+        settled.sort_by(|a, b| a.opportunity_id.cmp(&b.opportunity_id));
+        Ok(settled)
+    }
+}
+
+/// Upsert one execution and its filled legs. An arb counts as complete once every
+/// order it placed has filled, immediately or after resting. Fees are taken at the
+/// taker rate, which overstates them for legs that rested.
+fn record_execution(tx: &rusqlite::Transaction, rec: &ExecutionRecord, store: &ExecutionStore) -> Result<usize> {
+    let result = &rec.result;
+    let rested_filled = |order_id: &str| store.order(order_id).is_some_and(|o| o.status == "executed");
+    let mut legs: Vec<(&String, &Order, i64)> =
+        result.filled.iter().map(|(t, o)| (t, o, o.filled_count())).collect();
+    // An order that rested counts what it filled, however it ended: all of it once the store
+    // has seen it execute, else the fills of its last recorded state (a cancel after a part fill)
+    for (ticker, order) in result.resting.iter().chain(&result.other) {
+        let count = if rested_filled(&order.order_id) {
+            order.initial_count.or(order.count).unwrap_or(0)
+        } else {
+            order.filled_count()
+        };
+        legs.push((ticker, order, count));
+    }
+    // Unwind orders complete or close legs: each keeps its own action and side
+    legs.extend(result.unwind.iter().map(|(t, o)| (t, o, o.filled_count())));
+    // Counts that don't fit a contract count are dropped along with empty ones
+    let legs: Vec<(&String, &Order, Contracts)> = legs
+        .into_iter()
+        .filter_map(|(t, o, count)| Some((t, o, Contracts::from_i64(count).filter(|&n| n > Contracts::ZERO)?)))
+        .collect();
+    if legs.is_empty() {
+        return Ok(0);
+    }
+
+    let complete = result.other.is_empty()
+        && result.api_failures.is_empty()
+        && result.resting.iter().all(|(_, o)| rested_filled(&o.order_id));
+    tx.execute(
+        "INSERT INTO arbs (opportunity_id, event_ticker, direction, complete, opened_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT (opportunity_id) DO UPDATE SET complete = excluded.complete",
+        params![rec.opportunity_id, result.event_ticker, result.direction.to_string(), complete, rec.recorded_at],
+    )?;
+    // Every bracket the arb tried, filled or not, so a missing leg shows as unhedged
+    for ticker in result.orders().map(|(t, _)| t).chain(&result.api_failures) {
+        tx.execute(
+            "INSERT OR IGNORE INTO brackets (opportunity_id, ticker) VALUES (?1, ?2)",
+            params![rec.opportunity_id, ticker],
+        )?;
+    }
+    let mut added = 0;
+    for (ticker, order, count) in legs {
+        let yes_price = order.yes_price.unwrap_or(0);
+        // A leg re-recorded with more fills (a rested order that went on filling) is updated
+        added += tx.execute(
+            "INSERT INTO legs (order_id, opportunity_id, ticker, action, side, yes_price, count, fee_cents)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (order_id) DO UPDATE SET count = excluded.count, fee_cents = excluded.fee_cents
+             WHERE legs.count != excluded.count",
+            params![
+                order.order_id,
+                rec.opportunity_id,
+                ticker,
+                order.action,
+                order.side,
+                yes_price,
+                count.get(),
+                fill_fee_cents(ticker, order, count.get(), yes_price),
+            ],
+        )?;
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::ExecutionResult;
+    use crate::kalshi::types::ArbDirection;
+
+    fn order(id: &str, status: &str, yes_price: i64) -> Order {
+        Order {
+            order_id: id.to_string(),
+            ticker: String::new(),
+            status: status.to_string(),
+            action: "buy".to_string(),
+            side: "yes".to_string(),
+            order_type: "limit".to_string(),
+            yes_price: Some(yes_price),
+            no_price: None,
+            count: Some(10),
+            remaining_count: None,
+            fill_count: None,
+            initial_count: None,
+            taker_fill_cost: None,
+            maker_fill_cost: None,
+            client_order_id: None,
+        }
+    }
+
+    fn settlement(ticker: &str, result: &str) -> Settlement {
+        Settlement { ticker: ticker.to_string(), market_result: result.to_string(), settled_time: Utc::now() }
+    }
+
+    #[test]
+    fn test_arbs_settle_with_realized_pnl() {
+        let path = std::env::temp_dir().join(format!("arb-positions-{}.jsonl", std::process::id()));
+        let record = |id: &str, filled: Vec<(String, Order)>, resting: Vec<(String, Order)>| {
+            let rec = ExecutionRecord {
+                instance: "test".into(),
+                opportunity_id: id.into(),
+                uuid: None,
+                recorded_at: Utc::now(),
+                result: ExecutionResult {
+                    event_ticker: format!("{}-EV", id),
+                    direction: ArbDirection::Long,
+                    filled,
+                    resting,
+                    other: vec![],
+                    api_failures: vec![],
+                    failure_kinds: Default::default(),
+                    aborted: false,
+                    unwind: Vec::new(),
+                },
+                labels: Default::default(),
+            };
+            let mut line = serde_json::to_value(rec).unwrap();
+            line["kind"] = "execution".into();
+            format!("{}\n", line)
+        };
+        let a = vec![("A-X".into(), order("a1", "executed", 40)), ("A-Y".into(), order("a2", "executed", 50))];
+        let b_filled = vec![("B-X".into(), order("b1", "executed", 45))];
+        let b_resting = vec![("B-Y".into(), order("b2", "resting", 50))];
+        let content = record("A1", a, vec![]) + &record("B1", b_filled.clone(), b_resting);
+        std::fs::write(&path, &content).unwrap();
+        let store = ExecutionStore::load_from(&path).unwrap();
+
+        let mut tracker = PositionTracker::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        assert_eq!(tracker.sync(&store).unwrap(), 3);
+        // Re-syncing adds nothing; B1 isn't counted until its resting leg fills
+        assert_eq!(tracker.sync(&store).unwrap(), 0);
+        assert_eq!(tracker.open_arbs().unwrap(), 1);
+
+        // B1's resting leg filled 4 before it was cancelled: counted from the re-recorded
+        // execution, and only the rest of B-X is unhedged
+        let mut part = order("b2", "canceled", 50);
+        part.fill_count = Some(4);
+        let content = content + &record("B1", b_filled, vec![("B-Y".into(), part)]);
+        std::fs::write(&path, content).unwrap();
+        let store = ExecutionStore::load_from(&path).unwrap();
+        assert_eq!(tracker.sync(&store).unwrap(), 1);
+        assert_eq!(tracker.sync(&store).unwrap(), 0);
+        let unhedged = &tracker.incomplete_arbs().unwrap()[0].unhedged;
+        assert_eq!((unhedged[0].ticker.as_str(), unhedged[0].contracts, unhedged.len()), ("B-X", 6, 1));
+        assert_eq!(tracker.open_arbs().unwrap(), 1);
+
+        // One market settled: nothing closes yet
+        assert!(tracker.settle(&[settlement("A-X", "no")]).unwrap().is_empty());
+        let unsettled = HashSet::from(["A-Y".to_string(), "B-X".to_string(), "B-Y".to_string()]);
+        assert_eq!(tracker.unsettled_tickers().unwrap(), unsettled);
+
+        // 10 contracts at 40 + 50, paid out 100 each on A-Y, less 17 + 18 cents in fees
+        let settled = tracker.settle(&[settlement("A-Y", "yes")]).unwrap();
+        assert_eq!(
+            settled,
+            vec![SettledArb { opportunity_id: "A1".into(), event_ticker: "A1-EV".into(), realized_pnl_cents: 65 }]
+        );
+        assert_eq!(tracker.open_arbs().unwrap(), 0);
+        assert!(tracker.settle(&[]).unwrap().is_empty());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_unhedged_legs_net_of_exits() {
+        let path = std::env::temp_dir().join(format!("arb-unhedged-{}.jsonl", std::process::id()));
+        let rec = ExecutionRecord {
+            instance: "test".into(),
+            opportunity_id: "C1".into(),
+            uuid: None,
+            recorded_at: Utc::now(),
+            result: ExecutionResult {
+                event_ticker: "C1-EV".into(),
+                direction: ArbDirection::Long,
+                filled: vec![("C-X".into(), order("c1", "executed", 40)), ("C-Y".into(), order("c2", "executed", 30))],
+                resting: vec![],
+                other: vec![],
+                api_failures: vec!["C-Z".into()],
+                failure_kinds: Default::default(),
+                aborted: false,
+                unwind: Vec::new(),
+            },
+            labels: Default::default(),
+        };
+        let line = |rec: &ExecutionRecord| {
+            let mut line = serde_json::to_value(rec).unwrap();
+            line["kind"] = "execution".into();
+            format!("{}\n", line)
+        };
+        std::fs::write(&path, line(&rec)).unwrap();
+        let store = ExecutionStore::load_from(&path).unwrap();
+        let mut tracker = PositionTracker::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        tracker.sync(&store).unwrap();
+
+        // C-Z never filled: both filled legs are unhedged
+        let arbs = tracker.incomplete_arbs().unwrap();
+        assert_eq!(arbs.len(), 1);
+        assert_eq!(arbs[0].brackets, ["C-X", "C-Y", "C-Z"]);
+        let contracts = |t: &PositionTracker| -> Vec<(String, i64)> {
+            t.incomplete_arbs().unwrap()[0].unhedged.iter().map(|l| (l.ticker.clone(), l.contracts)).collect()
+        };
+        assert_eq!(contracts(&tracker), [("C-X".to_string(), 10), ("C-Y".to_string(), 10)]);
+        assert_eq!(arbs[0].unhedged[0].entry_cents, 40);
+
+        // The unwind bought 6 of C-Z, re-recorded with the execution: 6 of each bracket are
+        // hedged. Then an exit sells 3 of C-X
+        let mut unwind = order("u1", "canceled", 25);
+        unwind.fill_count = Some(6);
+        let mut unwound = rec.clone();
+        unwound.result.unwind.push(("C-Z".into(), unwind));
+        std::fs::write(&path, line(&rec) + &line(&unwound)).unwrap();
+        assert_eq!(tracker.sync(&ExecutionStore::load_from(&path).unwrap()).unwrap(), 1);
+        assert_eq!(contracts(&tracker), [("C-X".to_string(), 4), ("C-Y".to_string(), 4)]);
+        let mut exit = order("e1", "resting", 38);
+        exit.action = "sell".into();
+        exit.fill_count = Some(1);
+        assert!(tracker.record_exit("C1", "C-X", &exit).unwrap());
+        exit.fill_count = Some(3);
+        assert!(tracker.record_exit("C1", "C-X", &exit).unwrap());
+        assert_eq!(contracts(&tracker), [("C-X".to_string(), 1), ("C-Y".to_string(), 4)]);
+
+        // Settled markets can't be exited
+        tracker.settle(&[settlement("C-Y", "no")]).unwrap();
+        assert_eq!(contracts(&tracker), [("C-X".to_string(), 1)]);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,52 @@
+use anyhow::{bail, Result};
+
+/// What a winning contract pays out, in cents. YES and NO prices of a market sum to this.
+pub const PAYOUT_CENTS: i64 = 100;
+/// Smallest price increment the exchange accepts.
+pub const TICK_CENTS: i64 = 1;
+/// Lowest and highest legal limit prices (one tick in from 0 and the payout).
+pub const MIN_PRICE_CENTS: i64 = TICK_CENTS;
+pub const MAX_PRICE_CENTS: i64 = PAYOUT_CENTS - TICK_CENTS;
+
+/// The other side's price: a NO bid at `price` is a YES ask at `complement(price)`.
+pub fn complement(price_cents: i64) -> i64 {
+    PAYOUT_CENTS - price_cents
+}
+
+/// A price strictly between 0 and the payout, on a tick.
+// The tick check is a no-op at 1c, but a tick change should only touch TICK_CENTS.
+#[allow(clippy::modulo_one)]
+pub fn is_valid(price_cents: i64) -> bool {
+    (MIN_PRICE_CENTS..=MAX_PRICE_CENTS).contains(&price_cents) && price_cents % TICK_CENTS == 0
+}
+
+/// `price_cents` if it is a legal limit price, else an error naming the market.
+pub fn validate(ticker: &str, price_cents: i64) -> Result<i64> {
+    if !is_valid(price_cents) {
+        bail!(
+            "{}: {}c is not a legal limit price ({}..={}c in {}c ticks)",
+            ticker,
+            price_cents,
+            MIN_PRICE_CENTS,
+            MAX_PRICE_CENTS,
+            TICK_CENTS
+        );
+    }
+    Ok(price_cents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_and_complements() {
+        assert!(is_valid(1) && is_valid(50) && is_valid(99));
+        assert!(!is_valid(0) && !is_valid(100) && !is_valid(-3));
+        assert_eq!(complement(37), 63);
+        assert_eq!(complement(complement(12)), 12);
+        assert_eq!(validate("T", 42).unwrap(), 42);
+        let err = validate("T", 0).unwrap_err().to_string();
+        assert!(err.contains("T: 0c"), "{}", err);
+    }
+}
@@ -0,0 +1,129 @@
+// Per-series scan pacing from hit history. Most series never show an arb, and scanning every
+// one every cycle spends the read budget on books that never pay. With `[scanner.priority]`,
+// a series that turned up n opportunities in the lookback is scanned at most every
+// `max_interval_secs / (n + 1)`: busy series every cycle, dead ones every `max_interval_secs`.
+// Hits are read from `data/opportunities.md` at startup and added as cycles find more.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::report::{read_rows, series_of};
+
+pub const OPPORTUNITIES_PATH: &str = "data/opportunities.md";
+
+/// `[scanner.priority]` — scan series by how often they've had opportunities.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct PriorityConfig {
+    /// Longest a series with no hits in the lookback goes between scans.
+    #[serde(default = "default_max_interval_secs")]
+    pub max_interval_secs: u64,
+    /// Opportunities older than this no longer count toward a series' hits.
+    #[serde(default = "default_lookback_hours")]
+    pub lookback_hours: u64,
+}
+
+fn default_max_interval_secs() -> u64 { 600 }
+fn default_lookback_hours() -> u64 { 168 }
+
+/// Hits and last scan per series.
+#[derive(Debug)]
+pub struct SeriesPriority {
+    config: PriorityConfig,
+    hits: HashMap<String, Vec<DateTime<Utc>>>,
+    last_scanned: HashMap<String, DateTime<Utc>>,
+}
+
+impl SeriesPriority {
+    pub fn new(config: PriorityConfig) -> Self {
+        Self { config, hits: HashMap::new(), last_scanned: HashMap::new() }
+    }
+
+    /// Seed hits from an opportunities log (missing = no history).
+    pub fn load(config: PriorityConfig, path: &Path) -> Result<Self> {
+        let mut priority = Self::new(config);
+        for row in read_rows(path)? {
+            let (Some(ts), Some(event)) = (row.first(), row.get(2)) else {
+                continue;
+            };
+            if let Ok(at) = ts.parse::<DateTime<Utc>>() {
+                priority.hit(series_of(event), at);
+            }
+        }
+        Ok(priority)
+    }
+
+    /// Count an opportunity found in `series`.
+    pub fn hit(&mut self, series: &str, at: DateTime<Utc>) {
+        self.hits.entry(series.to_string()).or_default().push(at);
+    }
+
+    /// Opportunities in `series` within the lookback.
+    pub fn hits(&self, series: &str, now: DateTime<Utc>) -> usize {
+        let since = now - chrono::Duration::hours(self.config.lookback_hours as i64);
+        self.hits.get(series).map_or(0, |hits| hits.iter().filter(|t| **t >= since).count())
+    }
+
+    /// How long `series` waits between scans.
+    pub fn interval(&self, series: &str, now: DateTime<Utc>) -> Duration {
+        let hits = u32::try_from(self.hits(series, now)).unwrap_or(u32::MAX);
+        Duration::from_secs(self.config.max_interval_secs) / hits.saturating_add(1)
+    }
+
+    /// Whether `series` should be scanned this cycle. Unscanned series always are.
+    pub fn due(&self, series: &str, now: DateTime<Utc>) -> bool {
+        match self.last_scanned.get(series) {
+            Some(last) => (now - *last).to_std().unwrap_or_default() >= self.interval(series, now),
+            None => true,
+        }
+    }
+
+    /// Note a scan of `series`, dropping its hits that have aged out.
+    pub fn scanned(&mut self, series: &str, now: DateTime<Utc>) {
+        self.last_scanned.insert(series.to_string(), now);
+        let since = now - chrono::Duration::hours(self.config.lookback_hours as i64);
+        if let Some(hits) = self.hits.get_mut(series) {
+            hits.retain(|t| *t >= since);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_series_scanned_more_often() {
+        let now: DateTime<Utc> = "2026-10-17T12:00:00Z".parse().unwrap();
+        let path = std::env::temp_dir().join(format!("arb-priority-{}.md", std::process::id()));
+        std::fs::write(
+            &path,
+            "| 2026-10-17T10:00:00Z | HOT-26OCT17-LONG-1 | HOT-26OCT17 | LONG |\n\
+             | 2026-10-16T10:00:00Z | HOT-26OCT16-LONG-2 | HOT-26OCT16 | LONG |\n\
+             | 2026-10-01T10:00:00Z | OLD-26OCT01-LONG-3 | OLD-26OCT01 | LONG |\n",
+        )
+        .unwrap();
+        let config = PriorityConfig { max_interval_secs: 600, lookback_hours: 168 };
+        let mut priority = SeriesPriority::load(config, &path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        // Two hits: every 200s; hits outside the lookback don't count
+        assert_eq!(priority.interval("HOT", now), Duration::from_secs(200));
+        assert_eq!(priority.interval("OLD", now), Duration::from_secs(600));
+        assert!(priority.due("HOT", now) && priority.due("OLD", now));
+
+        priority.scanned("HOT", now);
+        priority.scanned("OLD", now);
+        let later = now + chrono::Duration::seconds(300);
+        assert!(priority.due("HOT", later));
+        assert!(!priority.due("OLD", later));
+
+        // A fresh hit shortens the wait
+        priority.hit("OLD", later);
+        assert!(priority.due("OLD", later));
+    }
+}
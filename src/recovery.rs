@@ -0,0 +1,170 @@
+// Startup recovery of resting orders. A run that dies inside `execute_arb` leaves legs on
+// the book that never reached the execution store, so nothing refreshes, cancels, or
+// hedges them. Each leg's client order ID derives from its opportunity's UUID, recorded
+// with the opportunity's `executed` state before the legs went out, which is how they're
+// told apart from orders placed by hand.
+
+use chrono::{TimeZone, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+use crate::executor::leg_client_order_id;
+use crate::kalshi::types::{ArbDirection, Order};
+use crate::report::series_of;
+use crate::storage::ExecutionStore;
+
+/// `[recovery]` — what to do at startup with resting orders the store doesn't know.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct RecoveryConfig {
+    #[serde(default)]
+    pub orphan_orders: OrphanPolicy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanPolicy {
+    /// Cancel them: one leg of an arb whose other legs nobody is watching.
+    #[default]
+    Cancel,
+    /// Record them in the store under their opportunity, so the order refresh tracks them
+    /// to the end and their event stays claimed meanwhile.
+    Adopt,
+}
+
+/// What startup does with one resting order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recovery {
+    /// Already in the store; the order refresh keeps tracking it.
+    Tracked,
+    Adopt(Orphan),
+    Cancel(Orphan),
+    /// Not placed by this instance (no client order ID of one of its opportunities, or a
+    /// series it doesn't scan).
+    Foreign,
+}
+
+/// A resting leg of an opportunity whose execution the store never recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orphan {
+    pub opportunity_id: String,
+    pub uuid: Uuid,
+    pub event_ticker: String,
+    pub direction: ArbDirection,
+}
+
+impl fmt::Display for Recovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recovery::Tracked => write!(f, "tracked"),
+            Recovery::Adopt(_) => write!(f, "adopt"),
+            Recovery::Cancel(_) => write!(f, "cancel"),
+            Recovery::Foreign => write!(f, "foreign"),
+        }
+    }
+}
+
+/// Find the opportunity whose leg `order` is among those in `store`, and read the event
+/// and direction out of its `{event}-{direction}-{unix_ms}` ID.
+pub fn orphan_of(order: &Order, store: &ExecutionStore) -> Option<Orphan> {
+    let client_order_id = order.client_order_id.as_deref()?;
+    let (uuid, opportunity_id) =
+        store.uuids().find(|(uuid, _)| leg_client_order_id(uuid, &order.ticker) == client_order_id)?;
+    let (rest, ms) = opportunity_id.rsplit_once('-')?;
+    Utc.timestamp_millis_opt(ms.parse().ok()?).single()?;
+    let (event_ticker, direction) = rest.rsplit_once('-')?;
+    let direction = match direction {
+        "LONG" => ArbDirection::Long,
+        "SHORT" => ArbDirection::Short,
+        _ => return None,
+    };
+    Some(Orphan {
+        opportunity_id: opportunity_id.to_string(),
+        uuid: *uuid,
+        event_ticker: event_ticker.to_string(),
+        direction,
+    })
+}
+
+/// Decide what to do with a resting `order`. Orders in series outside `series_filter`
+/// (when set) are left to whichever instance scans them.
+pub fn classify(order: &Order, store: &ExecutionStore, series_filter: &[String], policy: OrphanPolicy) -> Recovery {
+    if store.order(&order.order_id).is_some() {
+        return Recovery::Tracked;
+    }
+    let ours = |orphan: &Orphan| {
+        series_filter.is_empty() || series_filter.iter().any(|s| s == series_of(&orphan.event_ticker))
+    };
+    match orphan_of(order, store) {
+        Some(orphan) if ours(&orphan) => match policy {
+            OrphanPolicy::Cancel => Recovery::Cancel(orphan),
+            OrphanPolicy::Adopt => Recovery::Adopt(orphan),
+        },
+        _ => Recovery::Foreign,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{OpportunityState, StateRecord};
+
+    fn order(order_id: &str, ticker: &str, client_order_id: Option<&str>) -> Order {
+        Order {
+            order_id: order_id.into(),
+            ticker: ticker.into(),
+            status: "resting".into(),
+            action: "buy".into(),
+            side: "yes".into(),
+            order_type: "limit".into(),
+            yes_price: Some(30),
+            no_price: None,
+            count: Some(5),
+            remaining_count: Some(5),
+            fill_count: None,
+            initial_count: None,
+            taker_fill_cost: None,
+            maker_fill_cost: None,
+            client_order_id: client_order_id.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_classify_resting_orders_at_startup() {
+        let uuid = Uuid::parse_str("0b5d3c2e-8f61-4a7e-9d2c-5e4f3a1b6c7d").unwrap();
+        let rec = StateRecord {
+            instance: "test".into(),
+            opportunity_id: "KXHIGHNY-26OCT17-SHORT-1792238400123".into(),
+            uuid: Some(uuid),
+            state: OpportunityState::Executed,
+            reason: None,
+            at: Utc::now(),
+        };
+        let mut line = serde_json::to_value(rec).unwrap();
+        line["kind"] = "state".into();
+        let path = std::env::temp_dir().join(format!("arb-recovery-{}.jsonl", std::process::id()));
+        std::fs::write(&path, format!("{}\n", line)).unwrap();
+        let store = ExecutionStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let ticker = "KXHIGHNY-26OCT17-B60";
+        let leg = order("o-1", ticker, Some(&leg_client_order_id(&uuid, ticker)));
+        let orphan = Orphan {
+            opportunity_id: "KXHIGHNY-26OCT17-SHORT-1792238400123".into(),
+            uuid,
+            event_ticker: "KXHIGHNY-26OCT17".into(),
+            direction: ArbDirection::Short,
+        };
+        assert_eq!(classify(&leg, &store, &[], OrphanPolicy::Cancel), Recovery::Cancel(orphan.clone()));
+        assert_eq!(classify(&leg, &store, &["KXHIGHNY".into()], OrphanPolicy::Adopt), Recovery::Adopt(orphan));
+        // Another instance's series, a hand-placed order, and an ID for another market
+        assert_eq!(classify(&leg, &store, &["KXBTC".into()], OrphanPolicy::Cancel), Recovery::Foreign);
+        assert_eq!(classify(&order("o-2", ticker, None), &store, &[], OrphanPolicy::Cancel), Recovery::Foreign);
+        let other = order("o-3", ticker, Some(&leg_client_order_id(&uuid, "KXHIGHNY-26OCT17-B65")));
+        assert_eq!(classify(&other, &store, &[], OrphanPolicy::Cancel), Recovery::Foreign);
+        // An opportunity this store never saw, e.g. another instance's
+        let unknown = order("o-4", ticker, Some(&leg_client_order_id(&Uuid::nil(), ticker)));
+        assert_eq!(classify(&unknown, &store, &[], OrphanPolicy::Cancel), Recovery::Foreign);
+    }
+}
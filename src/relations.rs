@@ -0,0 +1,171 @@
+// Cross-event relations: markets whose YES is logically the union of other markets' YES
+// (e.g. a "60° or above" market and the 60-64°, 65-69°, and 70°+ brackets of another event).
+// Equivalent payoffs must trade at equivalent prices; a gap wider than fees is an arb.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::fees::taker_fee_cents;
+use crate::kalshi::types::BracketQuote;
+
+/// `[[relations]]` — `market` resolves YES exactly when one of `union_of` does. The parts
+/// must be mutually exclusive (at most one resolves YES), like the brackets of one event.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Relation {
+    pub name: String,
+    pub market: String,
+    pub union_of: Vec<String>,
+}
+
+impl Relation {
+    /// Every market the relation prices: the whole first, then the parts.
+    pub fn tickers(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.market).chain(&self.union_of)
+    }
+}
+
+/// Which side of the relation is cheap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationDirection {
+    /// Buy YES on every part, sell YES on the whole.
+    BuyParts,
+    /// Buy YES on the whole, sell YES on every part.
+    BuyWhole,
+}
+
+impl fmt::Display for RelationDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelationDirection::BuyParts => write!(f, "BUY_PARTS"),
+            RelationDirection::BuyWhole => write!(f, "BUY_WHOLE"),
+        }
+    }
+}
+
+/// A priced gap between a market and the union of its parts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mispricing {
+    pub relation: String,
+    pub direction: RelationDirection,
+    pub size: u32,
+    /// Whole market's price per contract: the bid sold into, or the ask paid.
+    pub whole_cents: i64,
+    /// Sum of the parts' prices per contract.
+    pub parts_cents: i64,
+    pub fees_cents: i64,
+    pub net_profit_cents: i64,
+}
+
+/// Best mispricing of `relation` at up to `max_size` contracts, if either direction clears
+/// fees and `min_net_profit_cents`. `parts` are quoted in `union_of` order. Size is capped
+/// by the thinnest leg's depth at the best level.
+pub fn evaluate(
+    relation: &Relation,
+    whole: &BracketQuote,
+    parts: &[BracketQuote],
+    max_size: u32,
+    min_net_profit_cents: i64,
+) -> Option<Mispricing> {
+    if parts.is_empty() || parts.len() != relation.union_of.len() {
+        return None;
+    }
+    [RelationDirection::BuyParts, RelationDirection::BuyWhole]
+        .into_iter()
+        .filter_map(|direction| price(relation, direction, whole, parts, max_size))
+        .filter(|m| m.net_profit_cents >= min_net_profit_cents)
+        .max_by_key(|m| m.net_profit_cents)
+}
+
+fn price(
+    relation: &Relation,
+    direction: RelationDirection,
+    whole: &BracketQuote,
+    parts: &[BracketQuote],
+    max_size: u32,
+) -> Option<Mispricing> {
+    // Buying takes the YES ask (depth at the NO bid); selling hits the YES bid
+    let (whole_cents, whole_depth, part_prices, part_depth) = match direction {
+        RelationDirection::BuyParts => (
+            whole.yes_bid_cents,
+            whole.depth_at_yes,
+            parts.iter().map(|p| p.yes_ask_cents).collect::<Vec<_>>(),
+            parts.iter().map(|p| p.depth_at_no).min()?,
+        ),
+        RelationDirection::BuyWhole => (
+            whole.yes_ask_cents,
+            whole.depth_at_no,
+            parts.iter().map(|p| p.yes_bid_cents).collect(),
+            parts.iter().map(|p| p.depth_at_yes).min()?,
+        ),
+    };
+    if whole_cents <= 0 || part_prices.contains(&0) {
+        return None;
+    }
+    let size = (max_size as i64).min(whole_depth).min(part_depth);
+    if size <= 0 {
+        return None;
+    }
+    let size = size as u32;
+
+    let parts_cents: i64 = part_prices.iter().sum();
+    let fees_cents = taker_fee_cents(&whole.ticker, size, whole_cents)
+        + parts.iter().zip(&part_prices).map(|(part, &p)| taker_fee_cents(&part.ticker, size, p)).sum::<i64>();
+    let edge = match direction {
+        RelationDirection::BuyParts => whole_cents - parts_cents,
+        RelationDirection::BuyWhole => parts_cents - whole_cents,
+    };
+    Some(Mispricing {
+        relation: relation.name.clone(),
+        direction,
+        size,
+        whole_cents,
+        parts_cents,
+        fees_cents,
+        net_profit_cents: edge * size as i64 - fees_cents,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(ticker: &str, ask: i64, bid: i64, depth: i64) -> BracketQuote {
+        BracketQuote {
+            ticker: ticker.into(),
+            yes_ask_cents: ask,
+            yes_bid_cents: bid,
+            depth_at_no: depth,
+            depth_at_yes: depth,
+            ..Default::default()
+        }
+    }
+
+    fn relation() -> Relation {
+        Relation { name: "above-60".into(), market: "WHOLE".into(), union_of: vec!["A".into(), "B".into()] }
+    }
+
+    #[test]
+    fn test_evaluate_finds_either_side_of_the_gap() {
+        // Parts ask 20 + 25 = 45c, whole bids 60c: buy the parts, sell the whole
+        let whole = quote("WHOLE", 62, 60, 8);
+        let parts = [quote("A", 20, 18, 50), quote("B", 25, 23, 50)];
+        let m = evaluate(&relation(), &whole, &parts, 10, 1).unwrap();
+        assert_eq!((m.direction, m.size, m.whole_cents, m.parts_cents), (RelationDirection::BuyParts, 8, 60, 45));
+        // 8 x 15c, less 14 + 9 + 11 cents of fees at 60c, 20c, and 25c
+        assert_eq!((m.fees_cents, m.net_profit_cents), (34, 86));
+
+        // Parts bid 40 + 30 = 70c, whole asks 50c: buy the whole, sell the parts
+        let whole = quote("WHOLE", 50, 48, 10);
+        let parts = [quote("A", 42, 40, 10), quote("B", 32, 30, 10)];
+        let m = evaluate(&relation(), &whole, &parts, 10, 1).unwrap();
+        assert_eq!(m.direction, RelationDirection::BuyWhole);
+        assert_eq!(m.parts_cents - m.whole_cents, 20);
+
+        // Consistent prices, a missing bid, or a missing part: nothing
+        let parts = [quote("A", 20, 18, 50), quote("B", 25, 23, 50)];
+        assert!(evaluate(&relation(), &quote("WHOLE", 46, 44, 10), &parts, 10, 1).is_none());
+        assert!(evaluate(&relation(), &quote("WHOLE", 99, 0, 10), &parts, 10, 1).is_none());
+        assert!(evaluate(&relation(), &whole, &parts[..1], 10, 1).is_none());
+    }
+}
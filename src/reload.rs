@@ -0,0 +1,164 @@
+// Hot reload of config.toml. A file watcher notes edits; the main loop re-reads the file
+// between jobs, validates it like `config check`, and takes the new `[scanner]` and `[risk]`
+// sections without restarting, so the day's risk counters and the series cache survive.
+
+use anyhow::{bail, Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::config_check::{self, ConfigChange};
+
+/// How long the file must go without events before it's read, so a save that arrives as
+/// several writes (or a write then a rename) is read once, whole.
+const QUIET: Duration = Duration::from_millis(500);
+
+/// `[scanner]` settings set up once at startup: the scan interval, the client's pacing, the
+/// watchlist file, warmup, series priority, and the skip memo's bracket bounds.
+const STARTUP_ONLY: &[&str] = &[
+    "scanner.interval_secs",
+    "scanner.scan_delay_ms",
+    "scanner.watchlist_path",
+    "scanner.warmup_cycles",
+    "scanner.priority",
+    "scanner.min_brackets",
+    "scanner.max_brackets",
+];
+
+fn reloadable(key: &str) -> bool {
+    let under = |prefix: &str| key.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('.'));
+    (under("scanner") || under("risk")) && !STARTUP_ONLY.iter().any(|s| under(s))
+}
+
+/// Watches the config file's directory (editors often replace the file rather than write it).
+pub struct ConfigWatcher {
+    path: PathBuf,
+    last_event: Arc<Mutex<Option<Instant>>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    pub fn spawn(path: &Path) -> Result<Self> {
+        let name = path.file_name().context("Config path has no file name")?.to_owned();
+        let last_event = Arc::new(Mutex::new(None));
+        let last = last_event.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else { return };
+            if !event.kind.is_access() && event.paths.iter().any(|p| p.file_name() == Some(name.as_os_str())) {
+                *last.lock().unwrap() = Some(Instant::now());
+            }
+        })?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+        Ok(Self { path: path.to_path_buf(), last_event, _watcher: watcher })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// True once per burst of edits, after it has settled.
+    pub fn take_changed(&self) -> bool {
+        let mut last = self.last_event.lock().unwrap();
+        match *last {
+            Some(at) if at.elapsed() >= QUIET => {
+                *last = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// What a reload did: the settings now in effect, and those that changed in the file but
+/// only take effect on restart (every section but `[scanner]` and `[risk]`, and the few
+/// settings in those read once at startup).
+#[derive(Debug, Default)]
+pub struct Reload {
+    pub applied: Vec<ConfigChange>,
+    pub needs_restart: Vec<ConfigChange>,
+}
+
+/// Validate `content` and fold its `[scanner]` and `[risk]` into `running`. A file that
+/// doesn't parse, has unknown keys, or has invalid alert templates leaves `running` untouched.
+pub fn apply(running: &mut Config, content: &str) -> Result<Reload> {
+    let value: toml::Value = toml::from_str(content).context("Not valid TOML")?;
+    let unknown = config_check::unknown_keys(&config_check::schema(), &value);
+    if !unknown.is_empty() {
+        bail!("Unknown key(s): {}", unknown.join(", "));
+    }
+    let candidate: Config = toml::from_str(content).context("Does not match the config schema")?;
+    crate::alerts::AlertTemplates::new(&candidate.alerts).context("Invalid alert templates")?;
+
+    let (applied, needs_restart): (Vec<_>, Vec<_>) =
+        config_check::diff(running, &candidate).into_iter().partition(|c| reloadable(&c.key));
+    if applied.is_empty() {
+        return Ok(Reload { applied, needs_restart });
+    }
+    let mut scanner = candidate.scanner;
+    scanner.interval_secs = running.scanner.interval_secs;
+    scanner.scan_delay_ms = running.scanner.scan_delay_ms;
+    scanner.watchlist_path = std::mem::take(&mut running.scanner.watchlist_path);
+    scanner.warmup_cycles = running.scanner.warmup_cycles;
+    scanner.priority = running.scanner.priority.take();
+    scanner.min_brackets = running.scanner.min_brackets;
+    scanner.max_brackets = running.scanner.max_brackets;
+    running.scanner = scanner;
+    running.risk = candidate.risk;
+    Ok(Reload { applied, needs_restart })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"
+[scanner]
+interval_secs = 30
+series_filter = ["KXHIGHNY"]
+
+[risk]
+min_net_profit_cents = 10
+min_roi_pct = 1.0
+position_size = 5
+max_open_positions = 5
+
+[kalshi]
+base_url = "https://example"
+rsa_key_path = "secrets/kalshi_rsa.pem"
+"#;
+
+    #[test]
+    fn test_reload_applies_scanner_and_risk_only() {
+        let mut running: Config = toml::from_str(BASE).unwrap();
+        let edited = BASE
+            .replace("interval_secs = 30", "interval_secs = 10")
+            .replace("[\"KXHIGHNY\"]", "[\"KXHIGHNY\", \"KXRAIN\"]")
+            .replace("min_roi_pct = 1.0", "min_roi_pct = 2.0")
+            .replace("https://example", "https://other");
+        let reload = apply(&mut running, &edited).unwrap();
+        let keys = |c: &[ConfigChange]| c.iter().map(|c| c.key.clone()).collect::<Vec<_>>();
+        assert_eq!(keys(&reload.applied), ["risk.min_roi_pct", "scanner.series_filter"]);
+        assert_eq!(keys(&reload.needs_restart), ["kalshi.base_url", "scanner.interval_secs"]);
+        assert_eq!(running.risk.min_roi_pct, 2.0);
+        assert_eq!(running.scanner.series_filter, ["KXHIGHNY", "KXRAIN"]);
+        assert_eq!(running.scanner.interval_secs, 30);
+        assert_eq!(running.kalshi.base_url, "https://example");
+
+        // Rejected edits change nothing
+        let typo = edited.replace("min_roi_pct = 2.0", "min_roi_pct = 3.0\nmin_roi_pcnt = 3.0");
+        assert!(apply(&mut running, &typo).unwrap_err().to_string().contains("risk.min_roi_pcnt"));
+        assert!(apply(&mut running, &edited.replace("position_size = 5", "position_size = \"5\"")).is_err());
+        assert_eq!(running.risk.min_roi_pct, 2.0);
+        assert!(apply(&mut running, &edited).unwrap().applied.is_empty());
+        assert!(!reloadable("scanner.priority.max_interval_secs") && reloadable("risk.sizing.max_size"));
+        assert!(!reloadable("riskless"));
+    }
+}
@@ -0,0 +1,310 @@
+// Execution replay: one recorded opportunity re-run through the executor against the
+// simulated exchange, seeded with the books recorded at that moment, once per routing
+// policy, to see which would have done best.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use rsa::pkcs1::{EncodeRsaPrivateKey, LineEnding};
+use rsa::RsaPrivateKey;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tracing::warn;
+
+use crate::backtest::Snapshot;
+use crate::config::RiskConfig;
+use crate::detector::{evaluate_arb, quote_with_levels, DetectParams};
+use crate::executor::{execute_arb_routed, leg_client_order_id};
+use crate::fees::taker_fee_cents;
+use crate::kalshi::auth::KalshiAuth;
+use crate::kalshi::client::KalshiClient;
+use crate::kalshi::types::{ArbDirection, ArbOpportunity, CreateOrderRequest};
+use crate::price::PAYOUT_CENTS;
+use crate::report::series_of;
+use crate::routing::{self, OrderRouter, RoutePolicy, YesLimit};
+use crate::sim_exchange::{Scenario, SimEvent, SimExchange, SimMarket, SimOrder, SimSeries};
+use crate::storage::ExecutionRecord;
+
+/// How the legs are sent in one replay run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayPolicy {
+    /// All legs at once, as the executor places them, under a routing policy.
+    Routed(RoutePolicy),
+    /// YES-side limits one leg at a time, stopping at the first leg that doesn't fully fill.
+    Sequential,
+}
+
+impl fmt::Display for ReplayPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayPolicy::Routed(RoutePolicy::YesLimit) => write!(f, "yes_limit"),
+            ReplayPolicy::Routed(RoutePolicy::NoSide) => write!(f, "no_side"),
+            ReplayPolicy::Routed(RoutePolicy::Buffered { buffer_cents }) => write!(f, "buffered {}c", buffer_cents),
+            ReplayPolicy::Routed(RoutePolicy::Ioc) => write!(f, "ioc"),
+            ReplayPolicy::Sequential => write!(f, "sequential"),
+        }
+    }
+}
+
+/// The policies `replay-exec` compares.
+pub fn policies() -> Vec<ReplayPolicy> {
+    vec![
+        ReplayPolicy::Routed(RoutePolicy::YesLimit),
+        ReplayPolicy::Routed(RoutePolicy::NoSide),
+        ReplayPolicy::Routed(RoutePolicy::Ioc),
+        ReplayPolicy::Routed(RoutePolicy::Buffered { buffer_cents: 1 }),
+        ReplayPolicy::Routed(RoutePolicy::Buffered { buffer_cents: 2 }),
+        ReplayPolicy::Sequential,
+    ]
+}
+
+/// What one policy got out of the recorded books.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyOutcome {
+    pub policy: ReplayPolicy,
+    /// Contracts filled on every leg: the arb actually locked in.
+    pub complete_sets: i64,
+    /// Contracts filled beyond the complete sets, left without their other legs.
+    pub unhedged: i64,
+    /// Profit on the complete sets at their fill prices, after fees.
+    pub net_cents: i64,
+}
+
+/// A recorded opportunity and how each policy would have executed it.
+#[derive(Debug)]
+pub struct Replay {
+    pub opportunity: ArbOpportunity,
+    pub detected_at: DateTime<Utc>,
+    pub books_at: DateTime<Utc>,
+    pub outcomes: Vec<PolicyOutcome>,
+}
+
+impl Replay {
+    /// Most net profit, then fewest unhedged contracts; ties go to the policy listed first.
+    pub fn best(&self) -> Option<&PolicyOutcome> {
+        self.outcomes.iter().min_by_key(|o| (-o.net_cents, o.unhedged))
+    }
+}
+
+/// When the opportunity was detected, from the `{event}-{direction}-{unix_ms}` ID, or when
+/// its execution was recorded if the ID doesn't end in a timestamp.
+pub fn detected_at(record: &ExecutionRecord) -> DateTime<Utc> {
+    record
+        .opportunity_id
+        .rsplit_once('-')
+        .and_then(|(_, ms)| ms.parse::<i64>().ok())
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .unwrap_or(record.recorded_at)
+}
+
+/// The latest snapshot of `event_ticker` taken at or before `at`.
+pub fn snapshot_at<'a>(snapshots: &'a [Snapshot], event_ticker: &str, at: DateTime<Utc>) -> Option<&'a Snapshot> {
+    snapshots.iter().filter(|s| s.event_ticker == event_ticker && s.time <= at).max_by_key(|s| s.time)
+}
+
+/// The opportunity as the detector would have priced it on `snapshot`, in the recorded
+/// direction and at the recorded order size.
+fn rebuild_opportunity(record: &ExecutionRecord, snapshot: &Snapshot, risk: &RiskConfig) -> Result<ArbOpportunity> {
+    let quotes = snapshot
+        .brackets
+        .iter()
+        .map(|b| quote_with_levels(&b.ticker, &b.title, &b.orderbook, risk.min_level_quantity as i64, 1))
+        .collect::<Option<Vec<_>>>()
+        .with_context(|| format!("Recorded books of {} don't quote every bracket", snapshot.event_ticker))?;
+    let size = record
+        .result
+        .orders()
+        .filter_map(|(_, o)| o.initial_count.or(o.count))
+        .max()
+        .map_or(risk.position_size, |n| n as u32);
+    let params = DetectParams {
+        position_size: size,
+        min_partial_size: None,
+        sizing: None,
+        ..DetectParams::from_config(risk)
+    };
+    let eval = evaluate_arb(&snapshot.event_ticker, &snapshot.title, &quotes, &params)
+        .into_iter()
+        .find(|e| e.opportunity.direction == record.result.direction)
+        .context("Detector produced no evaluation for the recorded direction")?;
+    let uuid = record.uuid.unwrap_or(eval.opportunity.uuid);
+    Ok(ArbOpportunity { id: record.opportunity_id.clone(), uuid, ..eval.opportunity })
+}
+
+/// A scenario holding only the snapshot's event, matching orders against its books.
+fn scenario(snapshot: &Snapshot) -> Scenario {
+    let series_ticker = series_of(&snapshot.event_ticker).to_string();
+    let levels = |levels: &[crate::kalshi::types::PriceLevel]| levels.iter().map(|l| (l.price, l.quantity)).collect();
+    Scenario {
+        series: vec![SimSeries { ticker: series_ticker.clone(), title: series_ticker.clone() }],
+        events: vec![SimEvent {
+            event_ticker: snapshot.event_ticker.clone(),
+            series_ticker,
+            title: snapshot.title.clone(),
+            mutually_exclusive: true,
+            markets: snapshot
+                .brackets
+                .iter()
+                .map(|b| SimMarket {
+                    ticker: b.ticker.clone(),
+                    title: b.title.clone(),
+                    status: "active".into(),
+                    yes: levels(&b.orderbook.yes),
+                    no: levels(&b.orderbook.no),
+                    fill: None,
+                    reject: None,
+                    halt_after_orders: None,
+                })
+                .collect(),
+        }],
+        balance_cents: i64::MAX / 2,
+        rate_limit_first: 0,
+        rate_limit_every: 0,
+        match_book: true,
+    }
+}
+
+/// Score the orders a policy left on the exchange. Fills are converted to YES terms (a NO
+/// fill at q is a YES trade at 100 - q) and the complete sets are priced at each leg's
+/// average fill.
+fn score(policy: ReplayPolicy, opp: &ArbOpportunity, orders: &[SimOrder]) -> PolicyOutcome {
+    let mut legs: HashMap<&str, (i64, i64)> = HashMap::new();
+    for order in orders {
+        let yes_cost = if order.side == "no" { PAYOUT_CENTS * order.filled - order.fill_cost } else { order.fill_cost };
+        let leg = legs.entry(order.ticker.as_str()).or_default();
+        leg.0 += order.filled;
+        leg.1 += yes_cost;
+    }
+    let filled = |ticker: &str| legs.get(ticker).copied().unwrap_or_default();
+    let complete = opp.brackets.iter().map(|b| filled(&b.ticker).0).min().unwrap_or(0);
+    let unhedged = opp.brackets.iter().map(|b| filled(&b.ticker).0 - complete).sum();
+
+    let (mut total, mut fees) = (0, 0);
+    for bracket in &opp.brackets {
+        let (count, cost) = filled(&bracket.ticker);
+        if complete == 0 || count == 0 {
+            continue;
+        }
+        let avg = (cost + count / 2) / count;
+        total += cost * complete / count;
+        fees += taker_fee_cents(&bracket.ticker, complete as u32, avg);
+    }
+    let net_cents = match opp.direction {
+        ArbDirection::Long => PAYOUT_CENTS * complete - total - fees,
+        ArbDirection::Short => total - PAYOUT_CENTS * complete - fees,
+    };
+    PolicyOutcome { policy, complete_sets: complete, unhedged, net_cents: if complete == 0 { 0 } else { net_cents } }
+}
+
+/// Place YES-side limits one leg at a time, stopping once a leg comes back short.
+async fn place_sequentially(client: &KalshiClient, opp: &ArbOpportunity) {
+    for bracket in &opp.brackets {
+        let req = CreateOrderRequest {
+            client_order_id: Some(leg_client_order_id(&opp.uuid, &bracket.ticker)),
+            ..YesLimit.route(bracket, opp.direction, opp.position_size, routing::time_in_force(), Utc::now())
+        };
+        match client.create_order(&req).await {
+            Ok(order) if order.filled_count() >= opp.position_size as i64 => {}
+            Ok(_) => break,
+            Err(e) => {
+                warn!(ticker = %bracket.ticker, error = %e, "Replay leg failed");
+                break;
+            }
+        }
+    }
+}
+
+/// Run `opp` once under `policy` against a fresh simulated exchange holding `snapshot`'s books.
+async fn run_policy(
+    policy: ReplayPolicy,
+    opp: &ArbOpportunity,
+    snapshot: &Snapshot,
+    pem: &str,
+    budget: Duration,
+) -> Result<PolicyOutcome> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.context("Failed to bind the simulated exchange")?;
+    let addr = listener.local_addr()?;
+    let sim = SimExchange::new(scenario(snapshot));
+    let server = tokio::spawn(sim.clone().serve(listener));
+
+    let client = KalshiClient::with_base_url(KalshiAuth::from_pem(pem, "replay".into())?, &format!("http://{}", addr))?;
+    let placed = match &policy {
+        ReplayPolicy::Routed(route) => {
+            execute_arb_routed(&client, opp, opp.position_size, budget, route.router().as_ref()).await.map(|_| ())
+        }
+        ReplayPolicy::Sequential => {
+            place_sequentially(&client, opp).await;
+            Ok(())
+        }
+    };
+    server.abort();
+    placed?;
+    Ok(score(policy, opp, &sim.orders()))
+}
+
+/// Replay `record` under every policy in `policies()`, against the latest of `snapshots`
+/// taken for its event at or before it was detected.
+pub async fn run(record: &ExecutionRecord, snapshots: &[Snapshot], risk: &RiskConfig) -> Result<Replay> {
+    let detected_at = detected_at(record);
+    let Some(snapshot) = snapshot_at(snapshots, &record.result.event_ticker, detected_at) else {
+        bail!("No snapshot of {} at or before {}", record.result.event_ticker, detected_at);
+    };
+    let opportunity = rebuild_opportunity(record, snapshot, risk)?;
+
+    // The simulated exchange ignores signatures, but the client signs every request
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).context("Failed to generate a replay key")?;
+    let pem = key.to_pkcs1_pem(LineEnding::LF).context("Failed to encode the replay key")?;
+    let budget = Duration::from_millis(risk.execution_budget_ms);
+    let mut outcomes = Vec::new();
+    for policy in policies() {
+        outcomes.push(run_policy(policy, &opportunity, snapshot, &pem, budget).await?);
+    }
+    Ok(Replay { opportunity, detected_at, books_at: snapshot.time, outcomes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kalshi::types::BracketQuote;
+
+    fn order(ticker: &str, side: &str, filled: i64, fill_cost: i64) -> SimOrder {
+        SimOrder {
+            order_id: String::new(),
+            ticker: ticker.into(),
+            status: "executed".into(),
+            action: "buy".into(),
+            side: side.into(),
+            yes_price: None,
+            no_price: None,
+            count: filled,
+            filled,
+            fill_cost,
+            client_order_id: None,
+        }
+    }
+
+    #[test]
+    fn test_score_prices_complete_sets_and_counts_unhedged() {
+        let quotes = ["A", "B"].map(|t| BracketQuote { ticker: t.into(), ..Default::default() });
+        let params = DetectParams {
+            position_size: 5,
+            min_net_profit_cents: 0,
+            min_roi_pct: 0.0,
+            expected_slippage_cents: 0,
+            min_partial_size: None,
+            sizing: None,
+            balance_cents: None,
+            min_annualized_roi_pct: None,
+            max_quote_age_ms: None,
+        };
+        let opp = evaluate_arb("EV", "Event", &quotes, &params).remove(0).opportunity;
+        assert_eq!(opp.direction, ArbDirection::Long);
+        // A: 5 at 30c. B: 3 on the NO side at 58c, i.e. YES at 42c
+        let outcome = score(ReplayPolicy::Sequential, &opp, &[order("A", "yes", 5, 150), order("B", "no", 3, 174)]);
+        assert_eq!((outcome.complete_sets, outcome.unhedged), (3, 2));
+        // 3 x (100 - 30 - 42), less 5 + 6 cents of fees
+        assert_eq!(outcome.net_cents, 73);
+        assert_eq!(score(ReplayPolicy::Sequential, &opp, &[order("A", "yes", 5, 150)]).net_cents, 0);
+    }
+}
@@ -0,0 +1,377 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::clock::ExchangeClock;
+use crate::kalshi::types::Order;
+use crate::money::parse_dollars;
+use crate::storage::{ExecutionRecord, ExecutionStore, PnlAttribution};
+
+/// Inclusive range of trading days applied to timestamped rows. `None` = unbounded.
+#[derive(Debug, Clone, Copy)]
+pub struct Period {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    /// Where the days start and end: the exchange's midnight, not UTC's.
+    pub clock: ExchangeClock,
+}
+
+impl Period {
+    pub fn contains(&self, ts: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|d| ts >= self.clock.day_bounds(d).0)
+            && self.until.is_none_or(|d| ts < self.clock.day_bounds(d).1)
+    }
+}
+
+/// Totals for one instance's data directory over a period. Position and resting-order
+/// counts are current state, not period-bound.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InstanceReport {
+    pub source: String,
+    /// Instance names found in the directory's rows.
+    pub instances: Vec<String>,
+    pub cycles: usize,
+    pub opportunities: usize,
+    pub executions: usize,
+    pub failed_legs: usize,
+    pub reconciled: usize,
+    pub incomplete: usize,
+    pub expected_net_cents: i64,
+    pub actual_net_cents: i64,
+    /// The actual net of reconciled arbs logged with an attribution, broken down.
+    pub attribution: PnlAttribution,
+    pub open_positions: usize,
+    pub resting_orders: usize,
+    /// Leg fill outcomes keyed by series ticker.
+    pub fills: BTreeMap<String, FillStats>,
+}
+
+/// How the legs placed in one series turned out. Legs still resting aren't counted.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FillStats {
+    /// Filled on placement at the limit price.
+    pub immediate: usize,
+    /// Filled on placement at a better price than the limit.
+    pub improved: usize,
+    /// Rested on the book, then filled.
+    pub rested_filled: usize,
+    /// Rested on the book, then cancelled (possibly after a partial fill).
+    pub rested_cancelled: usize,
+    /// Per-contract price improvement summed over the improved legs, in cents.
+    pub improvement_cents: f64,
+}
+
+impl FillStats {
+    pub fn legs(&self) -> usize {
+        self.immediate + self.improved + self.rested_filled + self.rested_cancelled
+    }
+
+    /// Average per-contract improvement of the improved legs, in cents.
+    pub fn avg_improvement_cents(&self) -> Option<f64> {
+        (self.improved > 0).then(|| self.improvement_cents / self.improved as f64)
+    }
+
+    fn record(&mut self, rec: &ExecutionRecord, store: &ExecutionStore) {
+        for (_, order) in &rec.result.filled {
+            match price_improvement(order) {
+                Some(cents) => {
+                    self.improved += 1;
+                    self.improvement_cents += cents;
+                }
+                None => self.immediate += 1,
+            }
+        }
+        for (_, order) in &rec.result.resting {
+            match store.order(&order.order_id).map(|o| o.status.as_str()) {
+                Some("executed") => self.rested_filled += 1,
+                Some("canceled") => self.rested_cancelled += 1,
+                _ => {}
+            }
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.immediate += other.immediate;
+        self.improved += other.improved;
+        self.rested_filled += other.rested_filled;
+        self.rested_cancelled += other.rested_cancelled;
+        self.improvement_cents += other.improvement_cents;
+    }
+}
+
+/// Cents per contract by which an order filled better than its limit, if it did.
+fn price_improvement(order: &Order) -> Option<f64> {
+    let avg = order.avg_fill_price_cents()?;
+    let limit = order.yes_price? as f64;
+    let better = if order.action == "sell" { avg - limit } else { limit - avg };
+    (better > 0.0).then_some(better)
+}
+
+/// Series ticker of an event ticker (`KXHIGHNY-26OCT17` → `KXHIGHNY`).
+pub fn series_of(event_ticker: &str) -> &str {
+    event_ticker.split('-').next().unwrap_or(event_ticker)
+}
+
+impl InstanceReport {
+    /// Read the storage outputs in `dir` (a bot's `data/` directory). Missing files count as empty.
+    pub fn load(dir: &Path, period: Period) -> Result<Self> {
+        let mut report = Self { source: dir.display().to_string(), ..Default::default() };
+
+        for row in read_log_rows(dir, "scans")? {
+            if report.take(&row, period) {
+                report.cycles += 1;
+            }
+        }
+        for row in read_log_rows(dir, "opportunities")? {
+            if report.take(&row, period) {
+                report.opportunities += 1;
+            }
+        }
+        for row in read_log_rows(dir, "reconciliation")? {
+            if !report.take(&row, period) || row.len() < 8 {
+                continue;
+            }
+            report.reconciled += 1;
+            report.expected_net_cents += parse_dollars(&row[5]).unwrap_or(0);
+            report.actual_net_cents += parse_dollars(&row[6]).unwrap_or(0);
+            if row[7].contains("INCOMPLETE") {
+                report.incomplete += 1;
+            }
+            // Rows from before attribution end at the slippage column
+            if let [edge, slippage, fee, unfilled, _, ..] = &row[8..] {
+                let cents = |cell: &String| parse_dollars(cell).unwrap_or(0);
+                report.attribution.add(&PnlAttribution {
+                    edge_cents: cents(edge),
+                    slippage_cents: cents(slippage),
+                    fee_cents: cents(fee),
+                    unfilled_cents: cents(unfilled),
+                });
+            }
+        }
+
+        let store = ExecutionStore::load_from(&dir.join("executions.jsonl"))?;
+        for rec in store.executions().filter(|r| period.contains(r.recorded_at)) {
+            report.executions += 1;
+            report.failed_legs += rec.result.api_failures.len();
+            report.note_instance(&rec.instance);
+            let series = series_of(&rec.result.event_ticker).to_string();
+            report.fills.entry(series).or_default().record(rec, &store);
+        }
+        report.open_positions = store.positions().len();
+        report.resting_orders = store.open_orders().len();
+        report.instances.sort();
+        Ok(report)
+    }
+
+    /// Whether a row falls in the period; records the row's instance name if so.
+    fn take(&mut self, row: &[String], period: Period) -> bool {
+        let Some(ts) = row.first().and_then(|c| c.parse::<DateTime<Utc>>().ok()) else {
+            return false;
+        };
+        if !period.contains(ts) {
+            return false;
+        }
+        if let Some(name) = row.last() {
+            self.note_instance(name);
+        }
+        true
+    }
+
+    fn note_instance(&mut self, name: &str) {
+        // Rows written before instance names existed end in a numeric/dollar column
+        if !name.is_empty() && parse_dollars(name).is_none() && name.parse::<f64>().is_err()
+            && !self.instances.iter().any(|n| n == name)
+        {
+            self.instances.push(name.to_string());
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.cycles += other.cycles;
+        self.opportunities += other.opportunities;
+        self.executions += other.executions;
+        self.failed_legs += other.failed_legs;
+        self.reconciled += other.reconciled;
+        self.incomplete += other.incomplete;
+        self.expected_net_cents += other.expected_net_cents;
+        self.actual_net_cents += other.actual_net_cents;
+        self.attribution.add(&other.attribution);
+        self.open_positions += other.open_positions;
+        self.resting_orders += other.resting_orders;
+        for (series, stats) in &other.fills {
+            self.fills.entry(series.clone()).or_default().add(stats);
+        }
+    }
+}
+
+/// Per-instance reports plus their sum.
+pub fn aggregate(dirs: &[PathBuf], period: Period) -> Result<(Vec<InstanceReport>, InstanceReport)> {
+    let mut reports = Vec::new();
+    let mut total = InstanceReport { source: "TOTAL".to_string(), ..Default::default() };
+    for dir in dirs {
+        let report = InstanceReport::load(dir, period)
+            .with_context(|| format!("Failed to read {}", dir.display()))?;
+        total.add(&report);
+        reports.push(report);
+    }
+    Ok((reports, total))
+}
+
+/// Actual net of the arbs reconciled in `dir` from `start` up to `end`: the trading day's
+/// realized P&L, for the risk limits to pick up where a previous run left off.
+pub fn reconciled_net_cents(dir: &Path, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<i64> {
+    let mut net = 0;
+    for row in read_log_rows(dir, "reconciliation")? {
+        let Some(ts) = row.first().and_then(|c| c.parse::<DateTime<Utc>>().ok()) else {
+            continue;
+        };
+        if ts >= start && ts < end && row.len() >= 8 {
+            net += parse_dollars(&row[6]).unwrap_or(0);
+        }
+    }
+    Ok(net)
+}
+
+/// Cells of each markdown table row in `path`; header and separator rows come back
+/// too and are dropped by the timestamp check.
+pub fn read_rows(path: &Path) -> Result<Vec<Vec<String>>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    Ok(content.lines().filter_map(parse_row).collect())
+}
+
+/// Rows of the markdown log `{name}.md` in `dir`, oldest first: the files `rotate_logs`
+/// renamed aside (`{name}.{timestamp}.md`), then the live one.
+pub fn read_log_rows(dir: &Path, name: &str) -> Result<Vec<Vec<String>>> {
+    let is_rotated = |path: &Path| {
+        let file = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let stamp = file.strip_prefix(name).and_then(|r| r.strip_prefix('.')).and_then(|r| r.strip_suffix(".md"));
+        stamp.is_some_and(|s| !s.is_empty() && !s.contains('.'))
+    };
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| is_rotated(p)).collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    paths.sort();
+    paths.push(dir.join(format!("{}.md", name)));
+    let mut rows = Vec::new();
+    for path in paths {
+        rows.extend(read_rows(&path)?);
+    }
+    Ok(rows)
+}
+
+fn parse_row(line: &str) -> Option<Vec<String>> {
+    let inner = line.trim().strip_prefix('|')?.strip_suffix('|')?;
+    Some(inner.split('|').map(|c| c.trim().to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        std::fs::write(dir.join(name), content).unwrap();
+    }
+
+    #[test]
+    fn test_instance_report_from_markdown_rows() {
+        let dir = std::env::temp_dir().join(format!("arb-report-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write(&dir, "scans.md", "| Time | Series | Events | Opps | Trades | Instance |\n|---|---|---|---|---|---|\n\
+            | 2026-09-30T23:59:00Z | 5 | 10 | 1 | 0 | weather |\n\
+            | 2026-10-01T00:01:00Z | 5 | 10 | 1 | 1 | weather |\n\
+            | 2026-10-02T00:01:00Z | 5 | 10 | 0 | 0 | weather |\n");
+        write(&dir, "opportunities.md", "\
+            | 2026-10-01T00:01:00Z | id1 | EV-1 | LONG | 3 | $0.95 | $0.03 | $0.12 | 5.3% | 2.5% | 2.5% | YES | weather |\n\
+            | 2026-10-02T00:01:00Z | id2 | EV-2 | LONG | 3 | $0.97 | $0.03 | $0.02 | 3.1% | 0.5% | 0.5% | NO | weather |\n");
+        write(&dir, "reconciliation.md", "\
+            | 2026-10-01T00:02:00Z | EV-1 | LONG | a, b, c | executed, executed, executed | $0.12 | $0.10 | $-0.02 | weather |\n\
+            | 2026-10-02T00:02:00Z | EV-3 | SHORT | d | executed | $0.20 | $-1.50 | $-1.70 (INCOMPLETE) | $0.25 | $0.01 | $-0.02 | $-1.74 | weather |\n");
+
+        let utc = ExchangeClock::new(chrono_tz::UTC);
+        let october = Period { since: NaiveDate::from_ymd_opt(2026, 10, 1), until: None, clock: utc };
+        let report = InstanceReport::load(&dir, october).unwrap();
+        assert_eq!(report.instances, vec!["weather"]);
+        assert_eq!((report.cycles, report.opportunities), (2, 2));
+        assert_eq!((report.reconciled, report.incomplete), (2, 1));
+        assert_eq!(report.expected_net_cents, 32);
+        assert_eq!(report.actual_net_cents, -140);
+        assert_eq!(report.executions, 0);
+        assert_eq!(report.attribution.net_cents(), -150);
+        assert_eq!(report.attribution.unfilled_cents, -174);
+
+        let first_day = Period { until: NaiveDate::from_ymd_opt(2026, 10, 1), ..october };
+        let report = InstanceReport::load(&dir, first_day).unwrap();
+        assert_eq!((report.cycles, report.reconciled, report.actual_net_cents), (1, 1, 10));
+        // New York's October 1 runs 04:00Z to 04:00Z: the next day's early UTC rows are in it
+        let new_york = Period { clock: ExchangeClock::new(chrono_tz::America::New_York), ..first_day };
+        let report = InstanceReport::load(&dir, new_york).unwrap();
+        assert_eq!((report.cycles, report.reconciled, report.actual_net_cents), (1, 1, -150));
+
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(reconciled_net_cents(&dir, at("2026-10-01T00:00:00Z"), at("2026-10-03T00:00:00Z")).unwrap(), -140);
+        assert_eq!(reconciled_net_cents(&dir, at("2026-10-01T04:00:00Z"), at("2026-10-02T04:00:00Z")).unwrap(), -150);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    fn order_json(id: &str, status: &str, limit: i64, taker_fill_cost: Option<i64>) -> serde_json::Value {
+        serde_json::json!({
+            "order_id": id, "ticker": id, "status": status, "action": "buy", "side": "yes",
+            "type": "limit", "yes_price": limit, "no_price": null, "count": 5,
+            "remaining_count": null, "taker_fill_cost": taker_fill_cost,
+        })
+    }
+
+    #[test]
+    fn test_fill_outcomes_by_series() {
+        let dir = std::env::temp_dir().join(format!("arb-report-fills-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let execution = |id: &str, event: &str, filled: Vec<serde_json::Value>, resting: Vec<serde_json::Value>| {
+            serde_json::json!({
+                "kind": "execution", "instance": "weather", "opportunity_id": id,
+                "recorded_at": "2026-10-01T00:01:00Z",
+                "result": {
+                    "event_ticker": event, "direction": "LONG",
+                    "filled": filled.into_iter().map(|o| (o["ticker"].clone(), o)).collect::<Vec<_>>(),
+                    "resting": resting.into_iter().map(|o| (o["ticker"].clone(), o)).collect::<Vec<_>>(),
+                    "other": [], "api_failures": [],
+                },
+            })
+            .to_string()
+        };
+        let cancel = serde_json::json!({
+            "kind": "order_state", "opportunity_id": "o1", "order_id": "c", "ticker": "c",
+            "status": "canceled", "updated_at": "2026-10-01T00:01:05Z",
+        });
+        let lines = [
+            // 5 contracts at 40c limit for 190c total: 2c better per contract
+            execution(
+                "o1",
+                "KXHIGHNY-26OCT01",
+                vec![order_json("a", "executed", 40, Some(190)), order_json("b", "executed", 30, Some(150))],
+                vec![order_json("c", "resting", 20, None), order_json("d", "resting", 10, None)],
+            ),
+            cancel.to_string(),
+            execution("o2", "KXRAIN-26OCT01", vec![order_json("e", "executed", 50, None)], vec![]),
+        ];
+        std::fs::write(dir.join("executions.jsonl"), lines.join("\n") + "\n").unwrap();
+
+        let all = Period { since: None, until: None, clock: ExchangeClock::new(chrono_tz::UTC) };
+        let report = InstanceReport::load(&dir, all).unwrap();
+        let ny = &report.fills["KXHIGHNY"];
+        assert_eq!((ny.immediate, ny.improved, ny.rested_filled, ny.rested_cancelled), (1, 1, 0, 1));
+        assert_eq!(ny.avg_improvement_cents(), Some(2.0));
+        // Still resting: not counted yet
+        assert_eq!(ny.legs(), 3);
+        assert_eq!(report.fills["KXRAIN"].immediate, 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
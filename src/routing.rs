@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::executor::build_order_request;
+use crate::kalshi::types::{ArbDirection, BracketQuote, CreateOrderRequest};
+use crate::price::{self, MAX_PRICE_CENTS, MIN_PRICE_CENTS};
+use crate::report::series_of;
+
+/// Decides the order sent for one leg of an arb: side, price, and order type.
+pub trait OrderRouter: Send + Sync {
+    /// The order for one leg, sent at `now` and resting per `time_in_force` unless the
+    /// policy decides otherwise.
+    fn route(
+        &self,
+        bracket: &BracketQuote,
+        direction: ArbDirection,
+        size: u32,
+        time_in_force: TimeInForce,
+        now: DateTime<Utc>,
+    ) -> CreateOrderRequest;
+}
+
+/// YES-side limit at the quoted price, resting if it doesn't fill.
+pub struct YesLimit;
+
+impl OrderRouter for YesLimit {
+    fn route(
+        &self,
+        bracket: &BracketQuote,
+        direction: ArbDirection,
+        size: u32,
+        time_in_force: TimeInForce,
+        now: DateTime<Utc>,
+    ) -> CreateOrderRequest {
+        build_order_request(bracket, direction, size, time_in_force, now)
+    }
+}
+
+/// The same trade on the NO side: buying YES at p is selling NO at 100 - p, and
+/// selling YES at p is buying NO at 100 - p.
+pub struct NoSide;
+
+impl OrderRouter for NoSide {
+    fn route(
+        &self,
+        bracket: &BracketQuote,
+        direction: ArbDirection,
+        size: u32,
+        time_in_force: TimeInForce,
+        now: DateTime<Utc>,
+    ) -> CreateOrderRequest {
+        let req = build_order_request(bracket, direction, size, time_in_force, now);
+        CreateOrderRequest {
+            action: if req.action == "buy" { "sell" } else { "buy" }.to_string(),
+            side: "no".to_string(),
+            yes_price: None,
+            no_price: req.yes_price.map(price::complement),
+            ..req
+        }
+    }
+}
+
+/// YES-side limit moved `cents` toward the market (paying up to buy, selling lower), so a
+/// small move between quote and order still fills.
+pub struct Buffered {
+    pub cents: i64,
+}
+
+impl OrderRouter for Buffered {
+    fn route(
+        &self,
+        bracket: &BracketQuote,
+        direction: ArbDirection,
+        size: u32,
+        time_in_force: TimeInForce,
+        now: DateTime<Utc>,
+    ) -> CreateOrderRequest {
+        let req = build_order_request(bracket, direction, size, time_in_force, now);
+        let yes_price = req.yes_price.map(|p| match direction {
+            ArbDirection::Long => (p + self.cents).min(MAX_PRICE_CENTS),
+            ArbDirection::Short => (p - self.cents).max(MIN_PRICE_CENTS),
+        });
+        CreateOrderRequest { yes_price, ..req }
+    }
+}
+
+/// YES-side limit at the quoted price, immediate-or-cancel: whatever doesn't fill at
+/// once is cancelled rather than left resting.
+pub struct ImmediateOrCancel;
+
+impl OrderRouter for ImmediateOrCancel {
+    fn route(
+        &self,
+        bracket: &BracketQuote,
+        direction: ArbDirection,
+        size: u32,
+        _time_in_force: TimeInForce,
+        now: DateTime<Utc>,
+    ) -> CreateOrderRequest {
+        build_order_request(bracket, direction, size, TimeInForce::ImmediateOrCancel, now)
+    }
+}
+
+/// A routing policy as written in config.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum RoutePolicy {
+    #[default]
+    YesLimit,
+    NoSide,
+    Buffered { buffer_cents: i64 },
+    Ioc,
+}
+
+impl RoutePolicy {
+    pub fn router(&self) -> Box<dyn OrderRouter> {
+        match self {
+            RoutePolicy::YesLimit => Box::new(YesLimit),
+            RoutePolicy::NoSide => Box::new(NoSide),
+            RoutePolicy::Buffered { buffer_cents } => Box::new(Buffered { cents: *buffer_cents }),
+            RoutePolicy::Ioc => Box::new(ImmediateOrCancel),
+        }
+    }
+}
+
+/// How long a leg may rest, whatever its routing policy (`ioc` legs never rest).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum TimeInForce {
+    /// Rest until filled or cancelled; the executor cancels what's left after `fill_wait_ms`.
+    #[default]
+    GoodTillCancelled,
+    /// Fill what crosses at once and cancel the rest, leaving nothing resting to clean up.
+    ImmediateOrCancel,
+    /// Rest at most `expiration_secs`, after which the exchange cancels the rest.
+    Expiring { expiration_secs: u64 },
+}
+
+impl TimeInForce {
+    /// `time_in_force` and `expiration_ts` for an order sent at `now`.
+    pub fn order_fields(&self, now: DateTime<Utc>) -> (Option<String>, Option<i64>) {
+        match self {
+            TimeInForce::GoodTillCancelled => (None, None),
+            TimeInForce::ImmediateOrCancel => (Some("immediate_or_cancel".to_string()), None),
+            TimeInForce::Expiring { expiration_secs } => (None, Some(now.timestamp() + *expiration_secs as i64)),
+        }
+    }
+}
+
+/// `[routing]` — how legs are sent: a default policy and overrides per series.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct RoutingConfig {
+    #[serde(default)]
+    pub default: RoutePolicy,
+    /// Keyed by series ticker (e.g. `KXHIGHNY`).
+    #[serde(default)]
+    pub series: HashMap<String, RoutePolicy>,
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+}
+
+impl RoutingConfig {
+    pub fn policy_for(&self, event_ticker: &str) -> &RoutePolicy {
+        self.series.get(series_of(event_ticker)).unwrap_or(&self.default)
+    }
+}
+
+static ROUTING: OnceLock<RoutingConfig> = OnceLock::new();
+
+/// Install the configured routing for `router_for`. First call wins.
+pub fn init(config: &RoutingConfig) {
+    ROUTING.get_or_init(|| config.clone());
+}
+
+/// The configured time in force for arb legs.
+pub fn time_in_force() -> TimeInForce {
+    ROUTING.get_or_init(RoutingConfig::default).time_in_force
+}
+
+/// The router for legs of an event, per its series' policy.
+pub fn router_for(event_ticker: &str) -> Box<dyn OrderRouter> {
+    ROUTING.get_or_init(RoutingConfig::default).policy_for(event_ticker).router()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policies_per_series() {
+        let config: RoutingConfig = toml::from_str(
+            "[default]\npolicy = \"ioc\"\n\n[series.KXHIGHNY]\npolicy = \"buffered\"\nbuffer_cents = 2\n\n\
+             [series.KXBTC]\npolicy = \"no_side\"\n",
+        )
+        .unwrap();
+        let bracket = BracketQuote { ticker: "T".into(), yes_ask_cents: 98, yes_bid_cents: 40, ..Default::default() };
+        let now = DateTime::from_timestamp(1_800_000_000, 0).unwrap();
+        let gtc = TimeInForce::GoodTillCancelled;
+        let route = |event: &str, direction| config.policy_for(event).router().route(&bracket, direction, 5, gtc, now);
+
+        let ioc = route("KXRAIN-26OCT17", ArbDirection::Long);
+        assert_eq!((ioc.yes_price, ioc.time_in_force.as_deref()), (Some(98), Some("immediate_or_cancel")));
+        // Buffered toward the market, clamped to the legal range
+        assert_eq!(route("KXHIGHNY-26OCT17", ArbDirection::Long).yes_price, Some(99));
+        assert_eq!(route("KXHIGHNY-26OCT17", ArbDirection::Short).yes_price, Some(38));
+        let no = route("KXBTC-26OCT17", ArbDirection::Short);
+        assert_eq!((no.action.as_str(), no.side.as_str(), no.no_price, no.yes_price), ("buy", "no", Some(60), None));
+
+        let tif: RoutingConfig = toml::from_str("[time_in_force]\nmode = \"expiring\"\nexpiration_secs = 5\n").unwrap();
+        assert_eq!(tif.time_in_force.order_fields(now), (None, Some(1_800_000_005)));
+        assert_eq!(config.time_in_force.order_fields(now), (None, None));
+    }
+}
@@ -0,0 +1,323 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::clock::ExchangeClock;
+
+/// Periodic work run by the main loop. Jobs run one at a time on the loop's task, so
+/// they can share scan state without locking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Job {
+    /// Scan cycle plus per-cycle housekeeping (order refresh, usage alerts, status).
+    Scan,
+    /// Liveness log line with the headline counters.
+    Heartbeat,
+    /// End-of-day summary alert.
+    DailyReport,
+    /// Archive `data/` to the `[backup]` target.
+    Backup,
+    /// Poll positions and settlements; mark settled arbs with realized P&L.
+    Settlement,
+    /// Watch markets of events with positions or resting orders for halts, closes, and results.
+    Lifecycle,
+    /// Price the `[[relations]]` markets and alert on cross-event mispricings.
+    Relations,
+    /// Refresh resting orders and re-quote the markets where the bot has live risk.
+    Watch,
+    /// Post and re-price orders closing the unhedged legs of incomplete arbs (`[exits]`).
+    Exits,
+    /// Compare locally tracked positions with the exchange's and alert on discrepancies.
+    Reconcile,
+    /// Record available cash in `data/balances.md`.
+    BalanceSnapshot,
+    /// Roll over `data/` logs grown past `[rotation] max_mb`.
+    RotateLogs,
+}
+
+impl Job {
+    pub fn name(self) -> &'static str {
+        match self {
+            Job::Scan => "scan",
+            Job::Heartbeat => "heartbeat",
+            Job::DailyReport => "daily_report",
+            Job::Backup => "backup",
+            Job::Settlement => "settlement",
+            Job::Lifecycle => "lifecycle",
+            Job::Relations => "relations",
+            Job::Watch => "watch",
+            Job::Exits => "exits",
+            Job::Reconcile => "reconcile",
+            Job::BalanceSnapshot => "balance_snapshot",
+            Job::RotateLogs => "rotate_logs",
+        }
+    }
+}
+
+impl FromStr for Job {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "scan" => Ok(Job::Scan),
+            "heartbeat" => Ok(Job::Heartbeat),
+            "daily_report" => Ok(Job::DailyReport),
+            "backup" => Ok(Job::Backup),
+            "settlement" => Ok(Job::Settlement),
+            "lifecycle" => Ok(Job::Lifecycle),
+            "relations" => Ok(Job::Relations),
+            "watch" => Ok(Job::Watch),
+            "exits" => Ok(Job::Exits),
+            "reconcile" => Ok(Job::Reconcile),
+            "balance_snapshot" => Ok(Job::BalanceSnapshot),
+            "rotate_logs" => Ok(Job::RotateLogs),
+            _ => bail!("unknown job {:?}", s),
+        }
+    }
+}
+
+/// When a job runs: a fixed gap after its previous run finishes, or daily at an
+/// exchange-local wall time. Written as `"every 30s"`, `"every 5m"`, `"every 1h"`,
+/// or `"daily 17:00"`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Schedule {
+    Every(Duration),
+    DailyAt(NaiveTime),
+}
+
+impl Schedule {
+    /// Next run after a run that finished at `finished`.
+    pub fn next_run(&self, finished: DateTime<Utc>, clock: &ExchangeClock) -> DateTime<Utc> {
+        match self {
+            Schedule::Every(gap) => finished + chrono::Duration::from_std(*gap).unwrap_or(chrono::Duration::MAX),
+            Schedule::DailyAt(time) => clock.next_local_time(finished, *time),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, arg) = s.trim().split_once(' ').context("expected \"every <n><s|m|h>\" or \"daily HH:MM\"")?;
+        match kind {
+            "every" => {
+                let arg = arg.trim();
+                let Some((unit_at, _)) = arg.char_indices().last() else {
+                    bail!("empty interval");
+                };
+                let n: u64 = arg[..unit_at].parse().with_context(|| format!("bad interval {:?}", arg))?;
+                let secs = match &arg[unit_at..] {
+                    "s" => n,
+                    "m" => n * 60,
+                    "h" => n * 3_600,
+                    _ => bail!("interval {:?} needs an s, m, or h suffix", arg),
+                };
+                if secs == 0 {
+                    bail!("interval must be positive");
+                }
+                Ok(Schedule::Every(Duration::from_secs(secs)))
+            }
+            "daily" => {
+                let time = NaiveTime::parse_from_str(arg.trim(), "%H:%M")
+                    .with_context(|| format!("bad time of day {:?}", arg))?;
+                Ok(Schedule::DailyAt(time))
+            }
+            _ => bail!("unknown schedule {:?}", s),
+        }
+    }
+}
+
+impl TryFrom<String> for Schedule {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Schedule> for String {
+    fn from(s: Schedule) -> Self {
+        s.to_string()
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Every(gap) => {
+                let secs = gap.as_secs();
+                if secs % 3_600 == 0 {
+                    write!(f, "every {}h", secs / 3_600)
+                } else if secs % 60 == 0 {
+                    write!(f, "every {}m", secs / 60)
+                } else {
+                    write!(f, "every {}s", secs)
+                }
+            }
+            Schedule::DailyAt(time) => write!(f, "daily {}", time.format("%H:%M")),
+        }
+    }
+}
+
+/// Observable state of one job, published in `/status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub job: Job,
+    pub schedule: String,
+    pub paused: bool,
+    pub runs: u64,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_duration_ms: u64,
+    pub last_error: Option<String>,
+    pub next_run: DateTime<Utc>,
+}
+
+/// Pause or resume a job from outside the main loop.
+#[derive(Debug, Clone, Copy)]
+pub struct JobControl {
+    pub job: Job,
+    pub paused: bool,
+}
+
+/// A request to the main loop from a control surface (the status server, Telegram commands).
+#[derive(Debug, Clone, Copy)]
+pub enum Control {
+    Job(JobControl),
+    /// Stop (true) or resume (false) placing orders from the next job on.
+    DryRun(bool),
+}
+
+pub type ControlSender = mpsc::UnboundedSender<Control>;
+
+struct Entry {
+    schedule: Schedule,
+    status: JobStatus,
+}
+
+/// Registered jobs and when each is next due. The main loop asks for the next due
+/// job, runs it, and reports back; the scheduler never runs anything itself.
+pub struct Scheduler {
+    clock: ExchangeClock,
+    entries: Vec<Entry>,
+}
+
+impl Scheduler {
+    pub fn new(clock: ExchangeClock) -> Self {
+        Self { clock, entries: Vec::new() }
+    }
+
+    /// Register a job, first due at `first_run`.
+    pub fn add(&mut self, job: Job, schedule: Schedule, first_run: DateTime<Utc>) {
+        self.entries.push(Entry {
+            schedule,
+            status: JobStatus {
+                job,
+                schedule: schedule.to_string(),
+                paused: false,
+                runs: 0,
+                last_run: None,
+                last_duration_ms: 0,
+                last_error: None,
+                next_run: first_run,
+            },
+        });
+    }
+
+    /// Register a job first due at its schedule's next occurrence after `now`.
+    pub fn add_from(&mut self, job: Job, schedule: Schedule, now: DateTime<Utc>) {
+        let first = schedule.next_run(now, &self.clock);
+        self.add(job, schedule, first);
+    }
+
+    /// The unpaused job that has been due the longest, if any is due.
+    pub fn due(&self, now: DateTime<Utc>) -> Option<Job> {
+        self.entries
+            .iter()
+            .filter(|e| !e.status.paused && e.status.next_run <= now)
+            .min_by_key(|e| e.status.next_run)
+            .map(|e| e.status.job)
+    }
+
+    /// Record a finished run and schedule the next one.
+    pub fn finish(&mut self, job: Job, started: DateTime<Utc>, finished: DateTime<Utc>, error: Option<String>) {
+        let clock = self.clock;
+        let Some(entry) = self.entries.iter_mut().find(|e| e.status.job == job) else {
+            return;
+        };
+        let status = &mut entry.status;
+        status.runs += 1;
+        status.last_run = Some(started);
+        status.last_duration_ms = (finished - started).num_milliseconds().max(0) as u64;
+        status.last_error = error;
+        status.next_run = entry.schedule.next_run(finished, &clock);
+    }
+
+    /// Returns false if the job isn't registered. A resumed job that missed its slot runs at once.
+    pub fn set_paused(&mut self, job: Job, paused: bool) -> bool {
+        match self.entries.iter_mut().find(|e| e.status.job == job) {
+            Some(entry) => {
+                entry.status.paused = paused;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn snapshot(&self) -> Vec<JobStatus> {
+        self.entries.iter().map(|e| e.status.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_schedule_round_trips_through_text() {
+        for text in ["every 30s", "every 5m", "every 2h", "daily 17:05"] {
+            assert_eq!(text.parse::<Schedule>().unwrap().to_string(), text);
+        }
+        assert_eq!("every 90s".parse::<Schedule>().unwrap(), Schedule::Every(Duration::from_secs(90)));
+        for bad in ["every 0s", "every 5", "every 5d", "hourly 5", "daily 25:00", "daily"] {
+            assert!(bad.parse::<Schedule>().is_err(), "{} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn test_due_order_pause_and_reschedule() {
+        let clock = ExchangeClock::new(chrono_tz::America::New_York);
+        let t0 = utc("2026-07-01T20:00:00Z");
+        let mut s = Scheduler::new(clock);
+        s.add(Job::Scan, Schedule::Every(Duration::from_secs(30)), t0);
+        s.add_from(Job::DailyReport, "daily 17:00".parse().unwrap(), t0);
+        assert_eq!(s.snapshot()[1].next_run, utc("2026-07-01T21:00:00Z"));
+
+        assert_eq!(s.due(t0), Some(Job::Scan));
+        s.finish(Job::Scan, t0, t0 + chrono::Duration::seconds(12), None);
+        assert_eq!(s.snapshot()[0].next_run, utc("2026-07-01T20:00:42Z"));
+        assert_eq!(s.due(utc("2026-07-01T20:00:41Z")), None);
+
+        // Both overdue: the one waiting longest goes first, unless paused
+        let late = utc("2026-07-01T21:00:05Z");
+        assert_eq!(s.due(late), Some(Job::Scan));
+        assert!(s.set_paused(Job::Scan, true));
+        assert_eq!(s.due(late), Some(Job::DailyReport));
+        assert!(!s.set_paused(Job::Heartbeat, true));
+
+        s.finish(Job::DailyReport, late, late, Some("boom".into()));
+        let report = &s.snapshot()[1];
+        assert_eq!((report.runs, report.last_error.as_deref()), (1, Some("boom")));
+        assert_eq!(report.next_run, utc("2026-07-02T21:00:00Z"));
+    }
+}
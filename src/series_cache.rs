@@ -0,0 +1,150 @@
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::config::ScannerConfig;
+use crate::kalshi::types::Series;
+
+/// What a refresh did to safe mode, for the caller to alert on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafeModeChange {
+    Unchanged,
+    /// Refreshes have failed for `max_series_staleness_secs`: the cached list was dropped
+    /// for `safe_mode_series`.
+    Entered,
+    /// A refresh succeeded while in safe mode.
+    Cleared,
+}
+
+/// The series list, refetched every `series_cache_secs`. While refreshes fail the last
+/// list is served; once they have failed for `max_series_staleness_secs` it is replaced
+/// by `safe_mode_series` until a refresh succeeds.
+pub struct SeriesCache {
+    series: Vec<Series>,
+    fetched_at: Option<Instant>,
+    ttl: Duration,
+    /// Start of the current run of failed refreshes.
+    failing_since: Option<Instant>,
+    max_staleness: Duration,
+    safe_mode_series: Vec<String>,
+    /// Serving `safe_mode_series` instead of a fetched list.
+    safe_mode: bool,
+}
+
+impl SeriesCache {
+    pub fn new(config: &ScannerConfig) -> Self {
+        Self {
+            series: Vec::new(),
+            fetched_at: None, // starts stale to force first fetch
+            ttl: Duration::from_secs(config.series_cache_secs),
+            failing_since: None,
+            max_staleness: Duration::from_secs(config.max_series_staleness_secs),
+            safe_mode_series: config.safe_mode_series.clone(),
+            safe_mode: false,
+        }
+    }
+
+    /// Take reloaded `[scanner]` settings, keeping the cached list.
+    pub fn reconfigure(&mut self, config: &ScannerConfig) {
+        self.ttl = Duration::from_secs(config.series_cache_secs);
+        self.max_staleness = Duration::from_secs(config.max_series_staleness_secs);
+        self.safe_mode_series = config.safe_mode_series.clone();
+    }
+
+    pub fn series(&self) -> &[Series] {
+        &self.series
+    }
+
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    pub fn max_staleness(&self) -> Duration {
+        self.max_staleness
+    }
+
+    pub fn is_stale(&self, now: Instant) -> bool {
+        match self.fetched_at {
+            None => true,
+            Some(t) => now.duration_since(t) >= self.ttl,
+        }
+    }
+
+    /// Take a freshly fetched list, leaving safe mode if it was on.
+    pub fn refreshed(&mut self, fresh: Vec<Series>, now: Instant) -> SafeModeChange {
+        self.series = fresh;
+        self.fetched_at = Some(now);
+        self.failing_since = None;
+        if std::mem::take(&mut self.safe_mode) {
+            SafeModeChange::Cleared
+        } else {
+            SafeModeChange::Unchanged
+        }
+    }
+
+    /// Note a failed refresh. The cached list stays until refreshes have been failing
+    /// for `max_staleness`, then gives way to `safe_mode_series`.
+    pub fn refresh_failed(&mut self, now: Instant) -> SafeModeChange {
+        let failing_since = *self.failing_since.get_or_insert(now);
+        if self.safe_mode || now.duration_since(failing_since) < self.max_staleness {
+            return SafeModeChange::Unchanged;
+        }
+        self.series = self
+            .safe_mode_series
+            .iter()
+            .map(|t| Series { ticker: t.clone(), title: t.clone() })
+            .collect();
+        self.safe_mode = true;
+        SafeModeChange::Entered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(tickers: &[&str]) -> Vec<Series> {
+        tickers.iter().map(|t| Series { ticker: t.to_string(), title: t.to_string() }).collect()
+    }
+
+    fn tickers(cache: &SeriesCache) -> Vec<&str> {
+        cache.series().iter().map(|s| s.ticker.as_str()).collect()
+    }
+
+    #[test]
+    fn test_safe_mode_after_max_staleness_and_cleared_by_refresh() {
+        let config: ScannerConfig = toml::from_str(
+            "interval_secs = 30\nseries_cache_secs = 300\nmax_series_staleness_secs = 600\n\
+             safe_mode_series = [\"KXHIGHNY\"]\n",
+        )
+        .unwrap();
+        let mut cache = SeriesCache::new(&config);
+        let t0 = Instant::now();
+        assert!(cache.is_stale(t0));
+        assert_eq!(cache.refreshed(series(&["KXHIGHNY", "KXRAIN"]), t0), SafeModeChange::Unchanged);
+        assert!(!cache.is_stale(t0 + Duration::from_secs(299)));
+        assert!(cache.is_stale(t0 + Duration::from_secs(300)));
+
+        // Failing refreshes serve the last list until they have failed for 600s
+        let first_failure = t0 + Duration::from_secs(300);
+        assert_eq!(cache.refresh_failed(first_failure), SafeModeChange::Unchanged);
+        assert_eq!(cache.refresh_failed(first_failure + Duration::from_secs(599)), SafeModeChange::Unchanged);
+        assert_eq!(tickers(&cache), ["KXHIGHNY", "KXRAIN"]);
+        assert!(!cache.safe_mode());
+
+        assert_eq!(cache.refresh_failed(first_failure + Duration::from_secs(600)), SafeModeChange::Entered);
+        assert!(cache.safe_mode());
+        assert_eq!(tickers(&cache), ["KXHIGHNY"]);
+        // Entered once per run of failures
+        assert_eq!(cache.refresh_failed(first_failure + Duration::from_secs(900)), SafeModeChange::Unchanged);
+
+        let recovered = first_failure + Duration::from_secs(1_200);
+        assert_eq!(cache.refreshed(series(&["KXBTC"]), recovered), SafeModeChange::Cleared);
+        assert!(!cache.safe_mode());
+        assert_eq!(tickers(&cache), ["KXBTC"]);
+
+        // A success restarts the clock: the next failure is a new run
+        assert_eq!(cache.refresh_failed(recovered + Duration::from_secs(300)), SafeModeChange::Unchanged);
+        assert_eq!(cache.refresh_failed(recovered + Duration::from_secs(899)), SafeModeChange::Unchanged);
+        assert_eq!(tickers(&cache), ["KXBTC"]);
+    }
+}
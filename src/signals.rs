@@ -0,0 +1,72 @@
+// Requests to stop, per platform, so the bot shuts down cleanly (cancelling resting orders
+// and writing its shutdown report) under whatever supervises it: systemd or launchd on
+// Unix, a service wrapper or a console on Windows.
+
+use tracing::warn;
+
+/// Wait for a request to stop, returning its name for the shutdown report.
+pub async fn shutdown() -> &'static str {
+    imp::shutdown().await
+}
+
+/// Ctrl-C on any platform. If the handler can't be installed this never returns, rather
+/// than stopping a bot nobody asked to stop.
+async fn ctrl_c(name: &'static str) -> &'static str {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        warn!(error = %e, "Failed to listen for Ctrl-C");
+        std::future::pending::<()>().await;
+    }
+    name
+}
+
+#[cfg(unix)]
+mod imp {
+    use tokio::signal::unix::{signal, SignalKind};
+    use tracing::warn;
+
+    /// SIGINT (Ctrl-C), SIGTERM (systemd, launchd, `docker stop`), or SIGHUP (the
+    /// controlling terminal went away).
+    pub async fn shutdown() -> &'static str {
+        let (Ok(mut term), Ok(mut hup)) = (signal(SignalKind::terminate()), signal(SignalKind::hangup())) else {
+            warn!("Failed to listen for SIGTERM/SIGHUP, only Ctrl-C stops the bot cleanly");
+            return super::ctrl_c("SIGINT").await;
+        };
+        tokio::select! {
+            name = super::ctrl_c("SIGINT") => name,
+            _ = term.recv() => "SIGTERM",
+            _ = hup.recv() => "SIGHUP",
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use tokio::signal::windows::{ctrl_break, ctrl_close, ctrl_logoff, ctrl_shutdown};
+    use tracing::warn;
+
+    /// Console control events: Ctrl-C, Ctrl-Break (what service wrappers such as WinSW and
+    /// NSSM send to stop a console program), the console closing, logoff, or system shutdown.
+    /// For the last three Windows ends the process a few seconds after the event.
+    pub async fn shutdown() -> &'static str {
+        let (Ok(mut brk), Ok(mut close), Ok(mut logoff), Ok(mut down)) =
+            (ctrl_break(), ctrl_close(), ctrl_logoff(), ctrl_shutdown())
+        else {
+            warn!("Failed to listen for console control events, only Ctrl-C stops the bot cleanly");
+            return super::ctrl_c("CTRL_C").await;
+        };
+        tokio::select! {
+            name = super::ctrl_c("CTRL_C") => name,
+            _ = brk.recv() => "CTRL_BREAK",
+            _ = close.recv() => "CTRL_CLOSE",
+            _ = logoff.recv() => "CTRL_LOGOFF",
+            _ = down.recv() => "CTRL_SHUTDOWN",
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    pub async fn shutdown() -> &'static str {
+        super::ctrl_c("SIGINT").await
+    }
+}
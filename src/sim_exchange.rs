@@ -0,0 +1,502 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info};
+
+use crate::price;
+
+/// Kalshi's REST prefix; accepted and ignored so `base_url` can be the real shape.
+const API_PREFIX: &str = "/trade-api/v2";
+
+/// A `sim-exchange` scenario: the markets on offer and how the exchange misbehaves.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    #[serde(default)]
+    pub series: Vec<SimSeries>,
+    #[serde(default)]
+    pub events: Vec<SimEvent>,
+    #[serde(default = "default_balance_cents")]
+    pub balance_cents: i64,
+    /// Answer the first N requests with 429.
+    #[serde(default)]
+    pub rate_limit_first: u64,
+    /// After those, answer every Nth request with 429 (0 = never).
+    #[serde(default)]
+    pub rate_limit_every: u64,
+    /// Fill orders against the books, at each level's price, and take the filled contracts
+    /// off them. Off, books are display-only and orders fill per `fill`.
+    #[serde(default)]
+    pub match_book: bool,
+}
+
+fn default_balance_cents() -> i64 { 100_000 }
+fn default_true() -> bool { true }
+fn default_market_status() -> String { "active".into() }
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimSeries {
+    pub ticker: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimEvent {
+    pub event_ticker: String,
+    pub series_ticker: String,
+    pub title: String,
+    #[serde(default = "default_true")]
+    pub mutually_exclusive: bool,
+    #[serde(default)]
+    pub markets: Vec<SimMarket>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimMarket {
+    pub ticker: String,
+    pub title: String,
+    #[serde(default = "default_market_status")]
+    pub status: String,
+    /// Book levels as `[price_cents, quantity]`, in any order. Fills only consume them
+    /// with `match_book`.
+    #[serde(default)]
+    pub yes: Vec<(i64, i64)>,
+    #[serde(default)]
+    pub no: Vec<(i64, i64)>,
+    /// Contracts filled per order; the rest rests (or is cancelled for IOC). Unset = fill all
+    /// (with `match_book`, all that the book holds at the limit).
+    #[serde(default)]
+    pub fill: Option<i64>,
+    /// Reject every order on this market with a 400 carrying this message.
+    #[serde(default)]
+    pub reject: Option<String>,
+    /// Pause the market once this many orders have been placed on it.
+    #[serde(default)]
+    pub halt_after_orders: Option<usize>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// An order as the simulated exchange holds it.
+#[derive(Debug, Clone)]
+pub struct SimOrder {
+    pub order_id: String,
+    pub ticker: String,
+    pub status: String,
+    pub action: String,
+    pub side: String,
+    pub yes_price: Option<i64>,
+    pub no_price: Option<i64>,
+    pub count: i64,
+    pub filled: i64,
+    /// Total price of the filled contracts, on the order's side.
+    pub fill_cost: i64,
+    pub client_order_id: Option<String>,
+}
+
+impl SimOrder {
+    fn to_json(&self) -> Value {
+        json!({
+            "order_id": self.order_id,
+            "ticker": self.ticker,
+            "status": self.status,
+            "action": self.action,
+            "side": self.side,
+            "type": "limit",
+            "yes_price": self.yes_price,
+            "no_price": self.no_price,
+            "count": self.count,
+            "initial_count": self.count,
+            "fill_count": self.filled,
+            "remaining_count": if self.status == "resting" { self.count - self.filled } else { 0 },
+            "taker_fill_cost": self.fill_cost,
+            "client_order_id": self.client_order_id,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct SimState {
+    scenario: Scenario,
+    orders: Vec<SimOrder>,
+    /// Net YES contracts held per market.
+    positions: HashMap<String, i64>,
+    requests: u64,
+}
+
+/// In-process stand-in for the Kalshi REST API, covering the endpoints the bot calls.
+#[derive(Debug, Clone)]
+pub struct SimExchange {
+    state: Arc<Mutex<SimState>>,
+}
+
+type Reply = (&'static str, Value);
+
+fn not_found(what: &str) -> Reply {
+    ("404 Not Found", json!({ "error": format!("{} not found", what) }))
+}
+
+impl SimExchange {
+    pub fn new(scenario: Scenario) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SimState {
+                scenario,
+                orders: Vec::new(),
+                positions: HashMap::new(),
+                requests: 0,
+            })),
+        }
+    }
+
+    /// Every order placed so far, in placement order, at its current state.
+    pub fn orders(&self) -> Vec<SimOrder> {
+        self.state.lock().unwrap().orders.clone()
+    }
+
+    /// Accept connections until the listener fails. One request per connection.
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        info!(addr = %listener.local_addr()?, "Simulated exchange listening");
+        loop {
+            let (stream, _) = listener.accept().await.context("Simulated exchange accept failed")?;
+            let exchange = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = exchange.respond(stream).await {
+                    debug!(error = %e, "Simulated exchange request failed");
+                }
+            });
+        }
+    }
+
+    async fn respond(&self, mut stream: TcpStream) -> Result<()> {
+        let (method, target, body) = read_request(&mut stream).await?;
+        let (code, reply) = self.handle(&method, &target, &body);
+        debug!(method = %method, target = %target, code = code, "Simulated exchange request");
+        let body = reply.to_string();
+        // Scripted 429s carry no real backoff
+        let retry_after = if code.starts_with("429") { "Retry-After: 0\r\n" } else { "" };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            code,
+            body.len(),
+            retry_after,
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Answer one request. `target` is the path and query string as sent.
+    pub fn handle(&self, method: &str, target: &str, body: &str) -> Reply {
+        let mut state = self.state.lock().unwrap();
+        state.requests += 1;
+        let (first, every) = (state.scenario.rate_limit_first, state.scenario.rate_limit_every);
+        if state.requests <= first || (every > 0 && state.requests.is_multiple_of(every)) {
+            return ("429 Too Many Requests", json!({ "error": "rate limited" }));
+        }
+
+        let target = target.strip_prefix(API_PREFIX).unwrap_or(target);
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query: HashMap<&str, &str> = query.split('&').filter_map(|kv| kv.split_once('=')).collect();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["series"]) => {
+                let series: Vec<Value> = state
+                    .scenario
+                    .series
+                    .iter()
+                    .map(|s| json!({ "ticker": s.ticker, "title": s.title, "status": "active" }))
+                    .collect();
+                ("200 OK", json!({ "series": series, "cursor": null }))
+            }
+            ("GET", ["events"]) => {
+                let events: Vec<Value> = state
+                    .scenario
+                    .events
+                    .iter()
+                    .filter(|e| query.get("series_ticker").is_none_or(|s| *s == e.series_ticker))
+                    .map(event_json)
+                    .collect();
+                ("200 OK", json!({ "events": events, "cursor": null }))
+            }
+            ("GET", ["events", ticker]) => match state.scenario.events.iter().find(|e| e.event_ticker == *ticker) {
+                Some(event) => ("200 OK", json!({ "event": event_json(event) })),
+                None => not_found(ticker),
+            },
+            ("GET", ["markets", ticker, "orderbook"]) => match state.market(ticker) {
+                Some(market) => {
+                    let (mut yes, mut no) = (market.yes.clone(), market.no.clone());
+                    yes.sort();
+                    no.sort();
+                    ("200 OK", json!({ "orderbook": { "yes": yes, "no": no } }))
+                }
+                None => not_found(ticker),
+            },
+            ("GET", ["portfolio", "balance"]) => ("200 OK", json!({ "balance": state.scenario.balance_cents })),
+            ("GET", ["portfolio", "positions"]) => {
+                let positions: Vec<Value> = state
+                    .positions
+                    .iter()
+                    .filter(|(_, n)| **n != 0)
+                    .map(|(ticker, n)| json!({ "ticker": ticker, "position": n }))
+                    .collect();
+                ("200 OK", json!({ "market_positions": positions, "cursor": null }))
+            }
+            ("GET", ["portfolio", "settlements"]) => ("200 OK", json!({ "settlements": [], "cursor": null })),
+            ("GET", ["portfolio", "fills"]) => ("200 OK", json!({ "fills": [], "cursor": null })),
+            ("GET", ["portfolio", "orders"]) => {
+                let orders: Vec<Value> = state
+                    .orders
+                    .iter()
+                    .filter(|o| query.get("status").is_none_or(|s| s.is_empty() || *s == o.status))
+                    .filter(|o| query.get("ticker").is_none_or(|t| *t == o.ticker))
+                    .map(SimOrder::to_json)
+                    .collect();
+                ("200 OK", json!({ "orders": orders, "cursor": null }))
+            }
+            ("GET", ["portfolio", "orders", id]) => match state.orders.iter().find(|o| o.order_id == *id) {
+                Some(order) => ("200 OK", json!({ "order": order.to_json() })),
+                None => not_found(id),
+            },
+            ("POST", ["portfolio", "orders"]) => match serde_json::from_str::<Value>(body) {
+                Ok(req) => state.place(&req),
+                Err(e) => ("400 Bad Request", json!({ "error": e.to_string() })),
+            },
+            ("DELETE", ["portfolio", "orders", id]) => {
+                match state.orders.iter_mut().find(|o| o.order_id == *id && o.status == "resting") {
+                    Some(order) => {
+                        order.status = "canceled".into();
+                        ("200 OK", json!({ "order": order.to_json() }))
+                    }
+                    None => not_found(id),
+                }
+            }
+            _ => not_found(path),
+        }
+    }
+}
+
+impl SimState {
+    fn market(&self, ticker: &str) -> Option<&SimMarket> {
+        self.scenario.events.iter().flat_map(|e| &e.markets).find(|m| m.ticker == ticker)
+    }
+
+    /// Fill up to `count` contracts of an order at `limit` (on its own side) from the book,
+    /// best level first, each at its own price, and remove them from the book. Buying one
+    /// side takes the other side's bids at their complement; selling hits the same side's bids.
+    /// Returns contracts filled and their total price on the order's side.
+    fn take_from_book(&mut self, ticker: &str, buy: bool, yes_side: bool, limit: i64, count: i64) -> (i64, i64) {
+        let Some(market) = self.scenario.events.iter_mut().flat_map(|e| &mut e.markets).find(|m| m.ticker == ticker)
+        else {
+            return (0, 0);
+        };
+        let levels = if buy == yes_side { &mut market.no } else { &mut market.yes };
+        levels.sort_by_key(|&(bid, _)| std::cmp::Reverse(bid));
+        let (mut filled, mut cost) = (0, 0);
+        for (bid, quantity) in levels.iter_mut() {
+            let price = if buy { price::complement(*bid) } else { *bid };
+            let crosses = if buy { price <= limit } else { price >= limit };
+            if filled == count || !crosses {
+                break;
+            }
+            let n = (*quantity).min(count - filled);
+            *quantity -= n;
+            filled += n;
+            cost += n * price;
+        }
+        levels.retain(|&(_, quantity)| quantity > 0);
+        (filled, cost)
+    }
+
+    fn place(&mut self, req: &Value) -> Reply {
+        let ticker = req["ticker"].as_str().unwrap_or_default().to_string();
+        let Some(market) = self.market(&ticker).cloned() else {
+            return not_found(&ticker);
+        };
+        if let Some(reason) = &market.reject {
+            return ("400 Bad Request", json!({ "error": reason }));
+        }
+        if market.status != "active" {
+            return ("400 Bad Request", json!({ "error": format!("market {} is {}", ticker, market.status) }));
+        }
+
+        let count = req["count"].as_i64().unwrap_or(0);
+        let buy = req["action"].as_str() != Some("sell");
+        let yes_side = req["side"].as_str() != Some("no");
+        let limit = if yes_side { req["yes_price"].as_i64() } else { req["no_price"].as_i64() }.unwrap_or(0);
+        let scripted = market.fill.unwrap_or(count).clamp(0, count);
+        let (filled, fill_cost) = if self.scenario.match_book {
+            self.take_from_book(&ticker, buy, yes_side, limit, scripted)
+        } else {
+            (scripted, scripted * limit)
+        };
+        let ioc = req["time_in_force"].as_str() == Some("immediate_or_cancel");
+        let status = match (filled == count, ioc) {
+            (true, _) => "executed",
+            (false, true) => "canceled",
+            (false, false) => "resting",
+        };
+        let order = SimOrder {
+            order_id: format!("sim-{}", self.orders.len() + 1),
+            ticker: ticker.clone(),
+            status: status.into(),
+            action: req["action"].as_str().unwrap_or("buy").into(),
+            side: req["side"].as_str().unwrap_or("yes").into(),
+            yes_price: req["yes_price"].as_i64(),
+            no_price: req["no_price"].as_i64(),
+            count,
+            filled,
+            fill_cost,
+            client_order_id: req["client_order_id"].as_str().map(String::from),
+        };
+        // Buying YES or selling NO adds YES exposure
+        let sign = if (order.action == "buy") == (order.side == "yes") { 1 } else { -1 };
+        *self.positions.entry(ticker.clone()).or_default() += sign * filled;
+        let reply = json!({ "order": order.to_json() });
+        self.orders.push(order);
+
+        let placed = self.orders.iter().filter(|o| o.ticker == ticker).count();
+        if market.halt_after_orders.is_some_and(|n| placed >= n) {
+            info!(market = %ticker, orders = placed, "Halting simulated market");
+            for m in self.scenario.events.iter_mut().flat_map(|e| &mut e.markets).filter(|m| m.ticker == ticker) {
+                m.status = "paused".into();
+            }
+        }
+        ("201 Created", reply)
+    }
+}
+
+fn event_json(event: &SimEvent) -> Value {
+    let markets: Vec<Value> = event
+        .markets
+        .iter()
+        .map(|m| json!({ "ticker": m.ticker, "title": m.title, "status": m.status, "result": "" }))
+        .collect();
+    json!({
+        "event_ticker": event.event_ticker,
+        "series_ticker": event.series_ticker,
+        "title": event.title,
+        "mutually_exclusive": event.mutually_exclusive,
+        "status": "open",
+        "markets": markets,
+    })
+}
+
+/// Read one HTTP/1.1 request: method, target, and the body per `Content-Length`.
+async fn read_request(stream: &mut TcpStream) -> Result<(String, String, String)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        anyhow::ensure!(n > 0, "connection closed mid-request");
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let length = head
+        .lines()
+        .filter_map(|l| l.split_once(':'))
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + length {
+        let n = stream.read(&mut chunk).await?;
+        anyhow::ensure!(n > 0, "connection closed mid-body");
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let mut parts = head.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+    let body = String::from_utf8_lossy(&buf[header_end..header_end + length]).to_string();
+    Ok((method, target, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_fills_rate_limits_and_halts() {
+        let scenario: Scenario = toml::from_str(
+            r#"
+            rate_limit_first = 1
+
+            [[events]]
+            event_ticker = "EV"
+            series_ticker = "S"
+            title = "Event"
+
+            [[events.markets]]
+            ticker = "EV-B1"
+            title = "B1"
+            no = [[70, 50], [65, 10]]
+            fill = 2
+            halt_after_orders = 1
+            "#,
+        )
+        .unwrap();
+        let sim = SimExchange::new(scenario);
+        assert_eq!(sim.handle("GET", "/trade-api/v2/events?series_ticker=S", "").0, "429 Too Many Requests");
+
+        let (code, book) = sim.handle("GET", "/trade-api/v2/markets/EV-B1/orderbook?depth=5", "");
+        assert_eq!(code, "200 OK");
+        assert_eq!(book["orderbook"]["no"], json!([[65, 10], [70, 50]]));
+
+        let order = r#"{"ticker":"EV-B1","action":"buy","side":"yes","type":"limit","count":5,"yes_price":30}"#;
+        let (code, placed) = sim.handle("POST", "/portfolio/orders", order);
+        assert_eq!(code, "201 Created");
+        assert_eq!((placed["order"]["status"].as_str(), placed["order"]["remaining_count"].as_i64()), (Some("resting"), Some(3)));
+        // Halted after one order: the next is refused and the market reads as paused
+        assert_eq!(sim.handle("POST", "/portfolio/orders", order).0, "400 Bad Request");
+        assert_eq!(sim.handle("GET", "/events/EV", "").1["event"]["markets"][0]["status"], "paused");
+
+        assert_eq!(sim.handle("DELETE", "/portfolio/orders/sim-1", "").0, "200 OK");
+        assert_eq!(sim.orders()[0].status, "canceled");
+        assert_eq!(sim.handle("DELETE", "/portfolio/orders/sim-1", "").0, "404 Not Found");
+    }
+    #[test]
+    fn test_match_book_fills_at_level_prices_and_consumes_depth() {
+        let scenario: Scenario = toml::from_str(
+            r#"
+            match_book = true
+
+            [[events]]
+            event_ticker = "EV"
+            series_ticker = "S"
+            title = "Event"
+
+            [[events.markets]]
+            ticker = "EV-B1"
+            title = "B1"
+            yes = [[25, 4]]
+            no = [[70, 3], [68, 10]]
+            "#,
+        )
+        .unwrap();
+        let sim = SimExchange::new(scenario);
+        // YES asks are 30 x 3 and 32 x 10: a 31c limit takes only the first level
+        let order = json!({
+            "ticker": "EV-B1", "action": "buy", "side": "yes", "count": 5, "yes_price": 31,
+            "time_in_force": "immediate_or_cancel",
+        });
+        let placed = &sim.handle("POST", "/portfolio/orders", &order.to_string()).1["order"];
+        assert_eq!((placed["status"].as_str(), placed["fill_count"].as_i64()), (Some("canceled"), Some(3)));
+        assert_eq!(placed["taker_fill_cost"], 90);
+        // Buying NO at 75 takes the 25c YES bid; what's left of the NO side is the 68 level
+        let order = r#"{"ticker":"EV-B1","action":"buy","side":"no","count":5,"no_price":75}"#;
+        let placed = &sim.handle("POST", "/portfolio/orders", order).1["order"];
+        assert_eq!((placed["fill_count"].as_i64(), placed["taker_fill_cost"].as_i64()), (Some(4), Some(300)));
+        let book = sim.handle("GET", "/markets/EV-B1/orderbook", "").1;
+        assert_eq!((book["orderbook"]["yes"].clone(), book["orderbook"]["no"].clone()), (json!([]), json!([[68, 10]])));
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+use crate::kalshi::types::Market;
+
+const SKIP_MEMO_PATH: &str = "data/skip_memo.json";
+/// Entries older than this are dropped on load and re-checked once.
+const MAX_ENTRY_AGE_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SkipEntry {
+    fingerprint: String,
+    reason: String,
+    since: DateTime<Utc>,
+}
+
+/// Events rejected for structural reasons (not mutually exclusive, bracket count out of
+/// range, decided or non-exhaustive bracket set), keyed by event ticker. An entry holds
+/// until the event's market set — tickers, statuses, results — changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SkipMemo {
+    /// `(min_brackets, max_brackets)` the rejections were made under; a change discards the memo.
+    bracket_range: (usize, usize),
+    entries: HashMap<String, SkipEntry>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+/// Stable digest of an event's market set.
+pub fn fingerprint(markets: &[Market]) -> String {
+    let mut keys: Vec<String> = markets
+        .iter()
+        .map(|m| format!("{}|{}|{}", m.ticker, m.status, m.result.as_deref().unwrap_or("")))
+        .collect();
+    keys.sort_unstable();
+    let digest = Sha256::digest(keys.join("\n").as_bytes());
+    digest[..16].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl SkipMemo {
+    /// Load `data/skip_memo.json`. A missing or unreadable file, or one written under a
+    /// different bracket range, yields an empty memo.
+    pub fn load(min_brackets: usize, max_brackets: usize) -> Self {
+        let empty = Self {
+            bracket_range: (min_brackets, max_brackets),
+            ..Self::default()
+        };
+        let content = match std::fs::read_to_string(SKIP_MEMO_PATH) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return empty,
+            Err(e) => {
+                warn!(error = %e, "Failed to read skip memo, starting empty");
+                return empty;
+            }
+        };
+        let mut memo: Self = match serde_json::from_str(&content) {
+            Ok(m) => m,
+            Err(e) => {
+                warn!(error = %e, "Unparseable skip memo, starting empty");
+                return empty;
+            }
+        };
+        if memo.bracket_range != empty.bracket_range {
+            debug!("Bracket range changed, discarding skip memo");
+            return Self { dirty: true, ..empty };
+        }
+        let cutoff = Utc::now() - Duration::days(MAX_ENTRY_AGE_DAYS);
+        let before = memo.entries.len();
+        memo.entries.retain(|_, e| e.since >= cutoff);
+        memo.dirty = memo.entries.len() != before;
+        memo
+    }
+
+    /// True if the event was structurally rejected with this same market set.
+    /// A stale entry (market set changed) is removed.
+    pub fn is_skipped(&mut self, event_ticker: &str, fingerprint: &str) -> bool {
+        match self.entries.get(event_ticker) {
+            Some(e) if e.fingerprint == fingerprint => true,
+            Some(_) => {
+                self.entries.remove(event_ticker);
+                self.dirty = true;
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub fn record(&mut self, event_ticker: &str, fingerprint: String, reason: &str) {
+        self.entries.insert(
+            event_ticker.to_string(),
+            SkipEntry {
+                fingerprint,
+                reason: reason.to_string(),
+                since: Utc::now(),
+            },
+        );
+        self.dirty = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Rewrite the memo file if anything changed since the last save.
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let json = serde_json::to_string(self).context("Failed to serialize skip memo")?;
+        std::fs::write(SKIP_MEMO_PATH, json)
+            .with_context(|| format!("Failed to write {}", SKIP_MEMO_PATH))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(ticker: &str, status: &str) -> Market {
+        serde_json::from_value(serde_json::json!({
+            "ticker": ticker,
+            "title": ticker,
+            "status": status,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_entry_holds_until_market_set_changes() {
+        let mut memo = SkipMemo::default();
+        let markets = vec![market("A", "active"), market("B", "active")];
+        let fp = fingerprint(&markets);
+        // Order of markets doesn't matter
+        assert_eq!(fp, fingerprint(&[market("B", "active"), market("A", "active")]));
+
+        memo.record("EV", fp.clone(), "too_few_brackets");
+        assert!(memo.is_skipped("EV", &fp));
+
+        let changed = fingerprint(&[market("A", "active"), market("B", "closed")]);
+        assert!(!memo.is_skipped("EV", &changed));
+        // The stale entry is gone, so the original set is re-evaluated too
+        assert!(!memo.is_skipped("EV", &fp));
+        assert_eq!(memo.len(), 0);
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::path::Path;
+
+use crate::config::AdaptiveRoiConfig;
+use crate::money;
+use crate::report;
+
+/// Expected vs realized net profit of the most recent fully-filled arbs, used to raise
+/// the ROI floor while fills keep coming in worse than quoted.
+#[derive(Debug, Default)]
+pub struct SlippageTracker {
+    window: usize,
+    /// (expected, actual) net cents, oldest first.
+    samples: VecDeque<(i64, i64)>,
+}
+
+impl SlippageTracker {
+    pub fn new(window: usize) -> Self {
+        Self { window: window.max(1), samples: VecDeque::new() }
+    }
+
+    /// Seed from the complete (non-INCOMPLETE) rows of a reconciliation log.
+    pub fn load(path: &Path, window: usize) -> Result<Self> {
+        let mut tracker = Self::new(window);
+        for row in report::read_rows(path)? {
+            if row.len() < 8 || row[7].contains("INCOMPLETE") {
+                continue;
+            }
+            if let (Some(expected), Some(actual)) = (money::parse_dollars(&row[5]), money::parse_dollars(&row[6])) {
+                tracker.record(expected, actual);
+            }
+        }
+        Ok(tracker)
+    }
+
+    pub fn record(&mut self, expected_cents: i64, actual_cents: i64) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((expected_cents, actual_cents));
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Fraction of expected profit lost to slippage across the window (negative when
+    /// fills beat the quotes). None when nothing positive was expected.
+    pub fn slippage_share(&self) -> Option<f64> {
+        let expected: i64 = self.samples.iter().map(|(e, _)| e).sum();
+        let actual: i64 = self.samples.iter().map(|(_, a)| a).sum();
+        (expected > 0).then(|| (expected - actual) as f64 / expected as f64)
+    }
+
+    /// The ROI floor to gate on. When slippage has been taking more than the configured
+    /// share of expected profit, the floor is scaled so the *realized* ROI would still
+    /// clear `base_pct` at that slippage rate, rounded up to 0.1 and capped.
+    pub fn min_roi_pct(&self, base_pct: f64, config: &AdaptiveRoiConfig) -> f64 {
+        let Some(share) = self.slippage_share() else {
+            return base_pct;
+        };
+        if self.len() < config.min_samples || share <= config.max_slippage_share {
+            return base_pct;
+        }
+        let raised = if share >= 1.0 { config.max_min_roi_pct } else { base_pct / (1.0 - share) };
+        ((raised * 10.0).ceil() / 10.0).clamp(base_pct, config.max_min_roi_pct.max(base_pct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_floor_rises_with_sustained_slippage() {
+        let config = AdaptiveRoiConfig { window: 4, min_samples: 3, max_slippage_share: 0.25, max_min_roi_pct: 3.0 };
+        let mut tracker = SlippageTracker::new(config.window);
+        tracker.record(100, 40);
+        tracker.record(100, 40);
+        // Not enough samples yet
+        assert_eq!(tracker.min_roi_pct(0.5, &config), 0.5);
+
+        // 60% of expected profit lost: 0.5 / 0.4 = 1.25, rounded up
+        tracker.record(100, 40);
+        assert_eq!(tracker.min_roi_pct(0.5, &config), 1.3);
+        // Wiped out entirely: capped
+        tracker.record(100, -200);
+        assert_eq!(tracker.min_roi_pct(0.5, &config), 3.0);
+
+        // Clean fills push the bad ones out of the window
+        for _ in 0..4 {
+            tracker.record(100, 95);
+        }
+        assert_eq!(tracker.len(), 4);
+        assert_eq!(tracker.min_roi_pct(0.5, &config), 0.5);
+    }
+}
@@ -0,0 +1,602 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::VecDeque;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tracing::{debug, info};
+
+use crate::detector::Evaluation;
+use crate::executor::ExecutionResult;
+use crate::kalshi::types::*;
+use crate::kalshi::usage::{ApiErrorRecord, UsageSnapshot};
+use crate::ownership::EventClaim;
+use crate::scheduler::{Control, ControlSender, Job, JobControl, JobStatus};
+use crate::paper::PaperSummary;
+use crate::storage::ExecutionStore;
+use crate::watch::WatchedMarket;
+
+/// A detected opportunity, or a near miss (`rejected` names the failed gate).
+#[derive(Debug, Clone, Serialize)]
+pub struct OpportunityEntry {
+    pub at: DateTime<Utc>,
+    pub id: String,
+    pub event_ticker: String,
+    pub direction: ArbDirection,
+    pub brackets: usize,
+    pub sum_cents: i64,
+    pub net_profit_cents: i64,
+    pub net_roi_pct: Decimal,
+    pub rejected: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionEntry {
+    pub at: DateTime<Utc>,
+    pub opportunity_id: String,
+    pub event_ticker: String,
+    pub direction: ArbDirection,
+    /// "filled", "partial", "failed", or "error"
+    pub outcome: &'static str,
+    pub filled: usize,
+    pub resting: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskEntry {
+    pub at: DateTime<Utc>,
+    pub event_ticker: String,
+    pub reason: &'static str,
+    pub open_arbs: u32,
+    pub daily_pnl_cents: i64,
+    pub daily_orders: u32,
+}
+
+/// Last-N opportunities, executions, and risk events, held in memory only.
+/// Answers "what just happened" without reading the data files.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentActivity {
+    #[serde(skip)]
+    capacity: usize,
+    pub opportunities: VecDeque<OpportunityEntry>,
+    pub executions: VecDeque<ExecutionEntry>,
+    pub risk_events: VecDeque<RiskEntry>,
+}
+
+fn push_bounded<T>(buf: &mut VecDeque<T>, item: T, capacity: usize) {
+    if buf.len() >= capacity {
+        buf.pop_front();
+    }
+    buf.push_back(item);
+}
+
+impl RecentActivity {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            opportunities: VecDeque::with_capacity(capacity),
+            executions: VecDeque::with_capacity(capacity),
+            risk_events: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn record_opportunity(&mut self, eval: &Evaluation) {
+        let opp = &eval.opportunity;
+        let entry = OpportunityEntry {
+            at: Utc::now(),
+            id: opp.id.clone(),
+            event_ticker: opp.event_ticker.clone(),
+            direction: opp.direction,
+            brackets: opp.brackets.len(),
+            sum_cents: opp.sum_cents,
+            net_profit_cents: opp.net_profit_cents,
+            net_roi_pct: opp.net_roi_pct,
+            rejected: eval.rejected,
+        };
+        push_bounded(&mut self.opportunities, entry, self.capacity);
+    }
+
+    /// Record an execution outcome. `result` is None when `execute_arb` itself errored.
+    pub fn record_execution(&mut self, opp: &ArbOpportunity, result: Option<&ExecutionResult>) {
+        let (outcome, filled, resting, failed) = match result {
+            Some(r) => {
+                let outcome = if r.is_fully_filled() {
+                    "filled"
+                } else if r.is_total_failure() {
+                    "failed"
+                } else {
+                    "partial"
+                };
+                (outcome, r.filled.len(), r.resting.len(), r.api_failures.len() + r.other.len())
+            }
+            None => ("error", 0, 0, opp.brackets.len()),
+        };
+        let entry = ExecutionEntry {
+            at: Utc::now(),
+            opportunity_id: opp.id.clone(),
+            event_ticker: opp.event_ticker.clone(),
+            direction: opp.direction,
+            outcome,
+            filled,
+            resting,
+            failed,
+        };
+        push_bounded(&mut self.executions, entry, self.capacity);
+    }
+
+    pub fn record_risk_event(
+        &mut self,
+        event_ticker: &str,
+        reason: &'static str,
+        open_arbs: u32,
+        daily_pnl_cents: i64,
+        daily_orders: u32,
+    ) {
+        let entry = RiskEntry {
+            at: Utc::now(),
+            event_ticker: event_ticker.to_string(),
+            reason,
+            open_arbs,
+            daily_pnl_cents,
+            daily_orders,
+        };
+        push_bounded(&mut self.risk_events, entry, self.capacity);
+    }
+}
+
+/// An order resting on the exchange, matched to the opportunity that placed it.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenOrderEntry {
+    pub order_id: String,
+    pub ticker: String,
+    pub action: String,
+    pub side: String,
+    pub yes_price: Option<i64>,
+    pub remaining: Option<i64>,
+    /// None when the order isn't in the local execution store (manual, or another instance).
+    pub opportunity_id: Option<String>,
+}
+
+/// Cross-reference exchange orders with the local execution store.
+pub fn open_order_entries(orders: &[Order], store: &ExecutionStore) -> Vec<OpenOrderEntry> {
+    orders
+        .iter()
+        .map(|o| OpenOrderEntry {
+            order_id: o.order_id.clone(),
+            ticker: o.ticker.clone(),
+            action: o.action.clone(),
+            side: o.side.clone(),
+            yes_price: o.yes_price,
+            remaining: o.remaining_count,
+            opportunity_id: store.order(&o.order_id).map(|s| s.opportunity_id.clone()),
+        })
+        .collect()
+}
+
+/// Stats from the most recently completed scan cycle.
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleStats {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub series: usize,
+    /// Series left for a later cycle because their `[scanner.priority]` interval hadn't passed.
+    pub series_deferred: usize,
+    pub events: usize,
+    pub opportunities: usize,
+    pub trades: usize,
+    /// Filled orders placed under `[canary]`; included in `trades`.
+    pub canary_trades: usize,
+    /// Opportunities under the `[shadow]` parameters and their simulated net profit.
+    pub shadow_opportunities: usize,
+    pub shadow_net_cents: i64,
+}
+
+/// Risk counters and their hardcoded limits.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RiskCounters {
+    pub open_arbs: u32,
+    pub max_open_arbs: u32,
+    pub daily_pnl_cents: i64,
+    pub max_daily_loss_cents: i64,
+    /// Entry orders; `max_daily_orders` applies to these only.
+    pub daily_orders: u32,
+    pub max_daily_orders: u32,
+    pub daily_cancels: u32,
+    pub daily_unwinds: u32,
+    /// Exchange-local date the daily counters belong to.
+    pub trading_day: NaiveDate,
+    /// Next exchange-local midnight, when the daily counters reset.
+    pub resets_at: DateTime<Utc>,
+    /// ROI floor in effect when `[risk.adaptive_roi]` is on.
+    pub min_roi_pct: Option<f64>,
+    /// Live balance the sizing engine works from when `[risk.sizing]` is on.
+    pub balance_cents: Option<i64>,
+    /// The fill anomaly that stopped executions, if the kill switch has tripped.
+    pub kill_switch: Option<String>,
+    /// Why no new orders are placed, while the halt file exists or `HALT_TRADING` is set.
+    pub halted: Option<String>,
+}
+
+/// Point-in-time view of the bot for control surfaces (HTTP /status, Telegram commands).
+/// The scan loop is the only writer; readers clone from a watch channel and never
+/// touch scan-loop state directly.
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusSnapshot {
+    pub instance: String,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub dry_run: bool,
+    /// Startup dry-run cycles still to go before orders are placed.
+    pub warmup_cycles_left: u32,
+    /// Average duration of the warmup cycles, once they're done.
+    pub warmup_baseline_ms: Option<u64>,
+    pub cycles: u64,
+    /// Opportunities found and trades executed since startup.
+    pub total_opportunities: u64,
+    pub total_trades: u64,
+    pub last_cycle: Option<CycleStats>,
+    /// When the last scan cycle that didn't fail finished.
+    pub last_scan_ok_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub risk: RiskCounters,
+    /// Orders this bot placed whose last known state is resting.
+    pub open_orders: usize,
+    /// Orders resting on the exchange as of the watch job's last refresh (None until the
+    /// first one succeeds).
+    pub exchange_orders: Option<Vec<OpenOrderEntry>>,
+    /// Scanning only `safe_mode_series` because the series list can't be refreshed.
+    pub series_safe_mode: bool,
+    pub api_usage: UsageSnapshot,
+    /// Markets with a live WebSocket orderbook (None when `kalshi.ws_url` is unset).
+    pub live_orderbooks: Option<usize>,
+    /// Events a strategy currently holds orders in (see `ownership`).
+    pub owned_events: Vec<EventClaim>,
+    /// Markets with resting orders or unhedged legs, as of the last `watch` pass.
+    pub watched_markets: Vec<WatchedMarket>,
+    /// Dry-run paper trades (see `paper`).
+    pub paper: PaperSummary,
+    /// Scheduler jobs: schedule, paused flag, last/next run.
+    pub jobs: Vec<JobStatus>,
+    pub recent: RecentActivity,
+}
+
+impl StatusSnapshot {
+    pub fn new(instance: &str, dry_run: bool, recent_capacity: usize) -> Self {
+        let now = Utc::now();
+        Self {
+            instance: instance.to_string(),
+            started_at: now,
+            updated_at: now,
+            dry_run,
+            warmup_cycles_left: 0,
+            warmup_baseline_ms: None,
+            cycles: 0,
+            total_opportunities: 0,
+            total_trades: 0,
+            last_cycle: None,
+            last_scan_ok_at: None,
+            last_error: None,
+            risk: RiskCounters::default(),
+            open_orders: 0,
+            exchange_orders: None,
+            series_safe_mode: false,
+            api_usage: UsageSnapshot::default(),
+            live_orderbooks: None,
+            owned_events: Vec::new(),
+            watched_markets: Vec::new(),
+            paper: PaperSummary::default(),
+            jobs: Vec::new(),
+            recent: RecentActivity::new(recent_capacity),
+        }
+    }
+}
+
+pub type StatusSender = watch::Sender<StatusSnapshot>;
+pub type StatusReceiver = watch::Receiver<StatusSnapshot>;
+
+/// Apply an update to the published snapshot, stamping `updated_at`.
+pub fn update(tx: &StatusSender, f: impl FnOnce(&mut StatusSnapshot)) {
+    tx.send_modify(|s| {
+        f(s);
+        s.updated_at = Utc::now();
+    });
+}
+
+/// Prometheus text exposition of the snapshot's counters, labelled by instance.
+fn render_metrics(s: &StatusSnapshot) -> String {
+    let instance = &s.instance;
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, value: String| {
+        out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n", name, help, name));
+        out.push_str(&format!("{}{{instance=\"{}\"}} {}\n", name, instance, value));
+    };
+    gauge("arb_cycles", "Scan cycles completed", s.cycles.to_string());
+    gauge("arb_open_orders", "Orders this bot believes are resting", s.open_orders.to_string());
+    gauge("arb_daily_pnl_cents", "Daily P&L tracked by risk limits", s.risk.daily_pnl_cents.to_string());
+    gauge("arb_daily_orders", "Arb entry orders placed today", s.risk.daily_orders.to_string());
+    gauge("arb_daily_cancels", "Cancels sent today", s.risk.daily_cancels.to_string());
+    gauge("arb_daily_unwinds", "Unwind orders placed today", s.risk.daily_unwinds.to_string());
+    gauge("arb_api_reads_per_sec", "API reads per second over the last minute", format!("{:.3}", s.api_usage.reads_per_sec));
+    gauge("arb_api_writes_per_sec", "API writes per second over the last minute", format!("{:.3}", s.api_usage.writes_per_sec));
+
+    out.push_str("# HELP arb_job_runs Scheduler job runs since start\n# TYPE arb_job_runs gauge\n");
+    for j in &s.jobs {
+        out.push_str(&format!("arb_job_runs{{instance=\"{}\",job=\"{}\"}} {}\n", instance, j.job.name(), j.runs));
+    }
+    out.push_str("# HELP arb_job_paused Whether a scheduler job is paused\n# TYPE arb_job_paused gauge\n");
+    for j in &s.jobs {
+        out.push_str(&format!(
+            "arb_job_paused{{instance=\"{}\",job=\"{}\"}} {}\n",
+            instance,
+            j.job.name(),
+            u8::from(j.paused)
+        ));
+    }
+
+    out.push_str("# HELP arb_api_requests_last_hour API requests in the last hour by endpoint class\n");
+    out.push_str("# TYPE arb_api_requests_last_hour gauge\n");
+    for (endpoint, n) in &s.api_usage.last_hour {
+        out.push_str(&format!(
+            "arb_api_requests_last_hour{{instance=\"{}\",endpoint=\"{}\"}} {}\n",
+            instance, endpoint, n
+        ));
+    }
+    out.push_str("# HELP arb_api_response_bytes_avg Average response body size by endpoint class\n");
+    out.push_str("# TYPE arb_api_response_bytes_avg gauge\n");
+    for (endpoint, p) in &s.api_usage.payloads {
+        out.push_str(&format!(
+            "arb_api_response_bytes_avg{{instance=\"{}\",endpoint=\"{}\"}} {}\n",
+            instance, endpoint, p.avg_bytes
+        ));
+    }
+    out.push_str("# HELP arb_api_parse_us_avg Average response parse time in microseconds by endpoint class\n");
+    out.push_str("# TYPE arb_api_parse_us_avg gauge\n");
+    for (endpoint, p) in &s.api_usage.payloads {
+        out.push_str(&format!(
+            "arb_api_parse_us_avg{{instance=\"{}\",endpoint=\"{}\"}} {}\n",
+            instance, endpoint, p.avg_parse_us
+        ));
+    }
+    out
+}
+
+/// Liveness summary served at `/healthz`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Health {
+    pub ok: bool,
+    pub instance: String,
+    pub uptime_secs: i64,
+    pub last_scan_ok_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_api_error: Option<ApiErrorRecord>,
+    pub open_arbs: u32,
+    pub daily_orders: u32,
+    pub daily_cancels: u32,
+    pub daily_unwinds: u32,
+    pub daily_pnl_cents: i64,
+    pub kill_switch: Option<String>,
+    pub halted: Option<String>,
+    /// Unpaused jobs more than `stall_secs` past their next run: the main loop is stuck.
+    pub stalled_jobs: Vec<String>,
+}
+
+/// Whether the main loop is keeping up with its schedule. Only a stalled job makes the bot
+/// unhealthy: failed scans and a tripped kill switch are reported but need a person, not a
+/// restart (which would also clear the kill switch).
+pub fn health(s: &StatusSnapshot, stall: chrono::Duration, now: DateTime<Utc>) -> Health {
+    let stalled_jobs: Vec<String> = s
+        .jobs
+        .iter()
+        .filter(|j| !j.paused && now - j.next_run > stall)
+        .map(|j| j.job.name().to_string())
+        .collect();
+    Health {
+        ok: stalled_jobs.is_empty(),
+        instance: s.instance.clone(),
+        uptime_secs: (now - s.started_at).num_seconds(),
+        last_scan_ok_at: s.last_scan_ok_at,
+        last_error: s.last_error.clone(),
+        last_api_error: s.api_usage.last_error.clone(),
+        open_arbs: s.risk.open_arbs,
+        daily_orders: s.risk.daily_orders,
+        daily_cancels: s.risk.daily_cancels,
+        daily_unwinds: s.risk.daily_unwinds,
+        daily_pnl_cents: s.risk.daily_pnl_cents,
+        kill_switch: s.risk.kill_switch.clone(),
+        halted: s.risk.halted.clone(),
+        stalled_jobs,
+    }
+}
+
+/// Minimal HTTP listener: `GET /status` returns the latest snapshot as JSON,
+/// `GET /healthz` a liveness summary (503 when the main loop has stalled),
+/// `GET /metrics` its counters in Prometheus text format, and
+/// `POST /jobs/<job>/pause` / `POST /jobs/<job>/resume` control the scheduler.
+pub async fn serve(
+    addr: String,
+    stall: chrono::Duration,
+    status: StatusReceiver,
+    control: ControlSender,
+) -> Result<()> {
+    let listener = TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind status server on {}", addr))?;
+    info!(addr = %addr, "Status server listening");
+
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                debug!(error = %e, "Status server accept failed");
+                continue;
+            }
+        };
+        let status = status.clone();
+        let control = control.clone();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            let n = stream.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let mut parts = request.split_whitespace();
+            let method = parts.next().unwrap_or("");
+            let path = parts.next().unwrap_or("");
+            debug!(peer = %peer, method = method, path = path, "Status request");
+
+            const JSON: &str = "application/json";
+            let (code, content_type, body) = match path {
+                _ if path.starts_with("/jobs/") => {
+                    let (code, body) = job_control(method, &path["/jobs/".len()..], &control);
+                    (code, JSON, body)
+                }
+                "/status" => {
+                    let snapshot = status.borrow().clone();
+                    match serde_json::to_string(&snapshot) {
+                        Ok(json) => ("200 OK", JSON, json),
+                        Err(e) => ("500 Internal Server Error", JSON, format!("{{\"error\":\"{}\"}}", e)),
+                    }
+                }
+                "/healthz" => {
+                    let health = health(&status.borrow(), stall, Utc::now());
+                    let code = if health.ok { "200 OK" } else { "503 Service Unavailable" };
+                    (code, JSON, serde_json::to_string(&health).unwrap_or_default())
+                }
+                "/metrics" => ("200 OK", "text/plain; version=0.0.4", render_metrics(&status.borrow())),
+                _ => ("404 Not Found", JSON, "{\"error\":\"not found\"}".to_string()),
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                code,
+                content_type,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.ok();
+        });
+    }
+}
+
+/// Handle `POST /jobs/<job>/pause|resume`; `rest` is the path after `/jobs/`.
+fn job_control(method: &str, rest: &str, control: &ControlSender) -> (&'static str, String) {
+    if method != "POST" {
+        return ("405 Method Not Allowed", "{\"error\":\"use POST\"}".to_string());
+    }
+    let Some((name, action)) = rest.split_once('/') else {
+        return ("404 Not Found", "{\"error\":\"not found\"}".to_string());
+    };
+    let Ok(job) = name.parse::<Job>() else {
+        return ("404 Not Found", format!("{{\"error\":\"unknown job {}\"}}", name));
+    };
+    let paused = match action {
+        "pause" => true,
+        "resume" => false,
+        _ => return ("404 Not Found", "{\"error\":\"not found\"}".to_string()),
+    };
+    match control.send(Control::Job(JobControl { job, paused })) {
+        Ok(()) => ("202 Accepted", format!("{{\"job\":\"{}\",\"paused\":{}}}", job.name(), paused)),
+        Err(_) => ("503 Service Unavailable", "{\"error\":\"scheduler stopped\"}".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::ExecutionRecord;
+
+    fn resting(order_id: &str, ticker: &str) -> Order {
+        Order {
+            order_id: order_id.into(),
+            ticker: ticker.into(),
+            status: "resting".into(),
+            action: "buy".into(),
+            side: "yes".into(),
+            order_type: "limit".into(),
+            yes_price: Some(30),
+            no_price: None,
+            count: Some(5),
+            remaining_count: Some(3),
+            fill_count: None,
+            initial_count: None,
+            taker_fill_cost: None,
+            maker_fill_cost: None,
+            client_order_id: None,
+        }
+    }
+
+    #[test]
+    fn test_open_orders_matched_to_their_opportunity() {
+        let rec = ExecutionRecord {
+            instance: "test".into(),
+            opportunity_id: "EV-LONG-1".into(),
+            uuid: None,
+            recorded_at: Utc::now(),
+            result: ExecutionResult {
+                event_ticker: "EV".into(),
+                direction: ArbDirection::Long,
+                filled: vec![],
+                resting: vec![("EV-A".into(), resting("o1", "EV-A"))],
+                other: vec![],
+                api_failures: vec![],
+                failure_kinds: Default::default(),
+                aborted: false,
+                unwind: Vec::new(),
+            },
+            labels: Default::default(),
+        };
+        let mut line = serde_json::to_value(rec).unwrap();
+        line["kind"] = "execution".into();
+        let path = std::env::temp_dir().join(format!("arb-status-orders-{}.jsonl", std::process::id()));
+        std::fs::write(&path, format!("{}\n", line)).unwrap();
+        let store = ExecutionStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        // Ours by order ID; a hand-placed order in the same event matches nothing
+        let entries = open_order_entries(&[resting("o1", "EV-A"), resting("o9", "EV-B")], &store);
+        let matched: Vec<_> = entries.iter().map(|e| (e.order_id.as_str(), e.opportunity_id.as_deref())).collect();
+        assert_eq!(matched, [("o1", Some("EV-LONG-1")), ("o9", None)]);
+        assert_eq!((entries[0].ticker.as_str(), entries[0].remaining), ("EV-A", Some(3)));
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_last_n() {
+        let mut activity = RecentActivity::new(3);
+        for i in 0..5 {
+            activity.record_risk_event(&format!("EV{}", i), "MAX_OPEN_ARBS", 5, 0, i);
+        }
+        assert_eq!(activity.risk_events.len(), 3);
+        let tickers: Vec<_> = activity.risk_events.iter().map(|e| e.event_ticker.as_str()).collect();
+        assert_eq!(tickers, vec!["EV2", "EV3", "EV4"]);
+    }
+
+    #[test]
+    fn test_health_fails_only_on_stalled_jobs() {
+        let now = Utc::now();
+        let mut s = StatusSnapshot::new("test", false, 1);
+        s.last_error = Some("scan failed".into());
+        s.jobs = vec![JobStatus {
+            job: Job::Scan,
+            schedule: "every 60s".into(),
+            paused: false,
+            runs: 3,
+            last_run: Some(now - chrono::Duration::minutes(11)),
+            last_duration_ms: 500,
+            last_error: None,
+            next_run: now - chrono::Duration::minutes(10),
+        }];
+        let stall = chrono::Duration::minutes(5);
+        let h = health(&s, stall, now);
+        assert!(!h.ok);
+        assert_eq!(h.stalled_jobs, ["scan"]);
+
+        // Paused, or due soon: fine, whatever the last scan said
+        s.jobs[0].paused = true;
+        assert!(health(&s, stall, now).ok);
+        s.jobs[0].paused = false;
+        s.jobs[0].next_run = now - chrono::Duration::minutes(1);
+        let h = health(&s, stall, now);
+        assert!(h.ok && h.last_error.is_some());
+    }
+}
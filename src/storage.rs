@@ -1,36 +1,207 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field as ArrowField, Schema};
+use chrono::{DateTime, Utc};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rust_decimal::prelude::ToPrimitive;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use tracing::warn;
+use uuid::Uuid;
 
-use crate::detector::taker_fee_cents;
+use crate::config::instance_name;
+use crate::fees::fill_fee_cents;
+use crate::money::{dollars, parse_dollars, Cents, Contracts};
+use crate::executor::ExecutionResult;
 use crate::kalshi::types::*;
+use crate::price::{self, PAYOUT_CENTS};
+use crate::report::{read_log_rows, Period};
 
-fn append_line(path: &str, line: &str) -> Result<()> {
-    let mut file = OpenOptions::new()
+const EXECUTIONS_PATH: &str = "data/executions.jsonl";
+
+/// Bumped when a field of the JSON opportunity, trade, or scan records changes meaning or
+/// goes away. New fields don't bump it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Why a log row or execution record didn't get written (or the store didn't load).
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("Failed to read {path}")]
+    Read {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Failed to write {path}")]
+    Write {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Failed to serialize {what}")]
+    Serialize {
+        what: &'static str,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A status change for an order the store never recorded.
+    #[error("Unknown order {0}")]
+    UnknownOrder(String),
+    /// A state change `OpportunityState::can_become` refuses.
+    #[error("{id}: {from} can't become {to}")]
+    IllegalTransition { id: String, from: OpportunityState, to: OpportunityState },
+}
+
+/// `[storage]` — how opportunities, trades, and scans are logged.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct StorageConfig {
+    #[serde(default)]
+    pub format: LogFormat,
+}
+
+/// Markdown tables (`*.md`), newline-delimited JSON (`*.jsonl`), or both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Markdown,
+    Json,
+    Both,
+}
+
+static STORAGE: OnceLock<StorageConfig> = OnceLock::new();
+
+/// Install the configured log format. First call wins.
+pub fn init(config: &StorageConfig) {
+    STORAGE.get_or_init(|| config.clone());
+}
+
+fn format() -> LogFormat {
+    STORAGE.get().map(|c| c.format).unwrap_or_default()
+}
+
+/// Append a row to `{name}.md` and/or a record to `{name}.jsonl` under `data/`, per `[storage] format`.
+fn log_row(name: &str, row: impl FnOnce() -> String, record: impl FnOnce() -> Value) -> Result<(), StorageError> {
+    let format = format();
+    if format != LogFormat::Json {
+        append_line(&format!("data/{}.md", name), &row())?;
+    }
+    if format != LogFormat::Markdown {
+        append_line(&format!("data/{}.jsonl", name), &record().to_string())?;
+    }
+    Ok(())
+}
+
+/// `fields` after the schema version, timestamp, and instance every JSON record starts with.
+fn json_record(fields: Value) -> Value {
+    let mut record = json!({
+        "schema_version": SCHEMA_VERSION,
+        "ts": Utc::now(),
+        "instance": instance_name(),
+    });
+    if let (Some(record), Value::Object(fields)) = (record.as_object_mut(), fields) {
+        record.extend(fields);
+    }
+    record
+}
+
+/// Everything the markdown row drops: every bracket's quote and depth, and the event title.
+fn opportunity_record(opp: &ArbOpportunity, flag: &str, value: bool) -> Value {
+    let brackets: Vec<Value> = opp
+        .brackets
+        .iter()
+        .map(|b| {
+            json!({
+                "ticker": b.ticker,
+                "title": b.title,
+                "label": b.label,
+                "yes_ask_cents": b.yes_ask_cents,
+                "yes_bid_cents": b.yes_bid_cents,
+                "depth_at_no": b.depth_at_no,
+                "depth_at_yes": b.depth_at_yes,
+                "ask_levels": b.ask_levels,
+                "bid_levels": b.bid_levels,
+                "fetched_at": b.fetched_at,
+            })
+        })
+        .collect();
+    json_record(json!({
+        "id": opp.id,
+        "uuid": opp.uuid,
+        "event_ticker": opp.event_ticker,
+        "event_title": opp.event_title,
+        "direction": opp.direction,
+        "position_size": opp.position_size,
+        "sum_cents": opp.sum_cents,
+        "total_fees_cents": opp.total_fees_cents,
+        "gross_profit_cents": opp.gross_profit_cents,
+        "net_profit_cents": opp.net_profit_cents,
+        "expected_slippage_cents": opp.expected_slippage_cents,
+        "gross_roi_pct": opp.gross_roi_pct.to_f64(),
+        "net_roi_pct": opp.net_roi_pct.to_f64(),
+        "slippage_adj_roi_pct": opp.slippage_adj_roi_pct.to_f64(),
+        "settles_at": opp.settles_at,
+        flag: value,
+        "brackets": brackets,
+    }))
+}
+
+pub(crate) fn append_line(path: &str, line: &str) -> Result<(), StorageError> {
+    OpenOptions::new()
         .create(true)
         .append(true)
         .open(path)
-        .with_context(|| format!("Failed to open {}", path))?;
-    writeln!(file, "{}", line)?;
-    Ok(())
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|source| StorageError::Write { path: path.to_string(), source })
 }
 
-pub fn log_opportunity(opp: &ArbOpportunity, executed: bool) -> Result<()> {
+fn opportunity_row(opp: &ArbOpportunity, flag: &str) -> String {
     let ts = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
-    let line = format!(
-        "| {} | {} | {} | {} | ${:.2} | ${:.2} | ${:.2} | {:.1}% | {} |",
+    format!(
+        "| {} | {} | {} | {} | {} | {} | {} | {} | {:.1}% | {:.1}% | {:.1}% | {} | {} | {} |",
         ts,
+        opp.id,
         opp.event_ticker,
         opp.direction,
         opp.brackets.len(),
-        opp.sum_cents as f64 / 100.0,
-        opp.total_fees_cents as f64 / 100.0,
-        opp.net_profit_cents as f64 / 100.0,
-        opp.roi_pct,
-        if executed { "YES" } else { "NO" },
-    );
-    append_line("data/opportunities.md", &line)
+        dollars(opp.sum_cents),
+        dollars(opp.total_fees_cents),
+        dollars(opp.net_profit_cents),
+        opp.gross_roi_pct,
+        opp.net_roi_pct,
+        opp.slippage_adj_roi_pct,
+        flag,
+        opp.uuid,
+        instance_name(),
+    )
+}
+
+pub fn log_opportunity(opp: &ArbOpportunity, executed: bool) -> Result<(), StorageError> {
+    log_row(
+        "opportunities",
+        || opportunity_row(opp, if executed { "YES" } else { "NO" }),
+        || opportunity_record(opp, "executed", executed),
+    )
+}
+
+/// Log an opportunity found under the shadow parameters. Shadow opportunities are
+/// simulated as filled at the quoted prices (the depth gate has passed at shadow size).
+/// `primary` is whether the primary parameters also took this event and direction.
+pub fn log_shadow_opportunity(opp: &ArbOpportunity, primary: bool) -> Result<(), StorageError> {
+    log_row(
+        "shadow_opportunities",
+        || opportunity_row(opp, if primary { "PRIMARY" } else { "SHADOW_ONLY" }),
+        || opportunity_record(opp, "primary", primary),
+    )
 }
 
 pub fn log_trade(
@@ -38,27 +209,47 @@ pub fn log_trade(
     ticker: &str,
     order: &Order,
     position_size: u32,
-) -> Result<()> {
+) -> Result<(), StorageError> {
     let ts = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
     let price_cents = order.yes_price.unwrap_or(0);
-    let fee = taker_fee_cents(position_size, price_cents);
+    let fee = fill_fee_cents(ticker, order, position_size, price_cents);
     let side = match opp.direction {
         ArbDirection::Long => "BUY_YES",
         ArbDirection::Short => "SELL_YES",
     };
-    let line = format!(
-        "| {} | {} | {} | {} | ${:.2} | {} | ${:.2} | {} | {} |",
-        ts,
-        opp.event_ticker,
-        ticker,
-        side,
-        price_cents as f64 / 100.0,
-        position_size,
-        fee as f64 / 100.0,
-        order.order_id,
-        order.status,
-    );
-    append_line("data/trades.md", &line)
+    let row = || {
+        format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+            ts,
+            opp.event_ticker,
+            opp.bracket_name(ticker),
+            side,
+            dollars(price_cents),
+            position_size,
+            dollars(fee),
+            order.order_id,
+            order.status,
+            opp.uuid,
+            instance_name(),
+        )
+    };
+    let record = || {
+        json_record(json!({
+            "opportunity_id": opp.id,
+            "opportunity_uuid": opp.uuid,
+            "event_ticker": opp.event_ticker,
+            "ticker": ticker,
+            "label": opp.label(ticker),
+            "side": side,
+            "price_cents": price_cents,
+            "size": position_size,
+            "fee_cents": fee,
+            "order_id": order.order_id,
+            "status": order.status,
+            "client_order_id": order.client_order_id,
+        }))
+    };
+    log_row("trades", row, record)
 }
 
 pub fn log_scan(
@@ -66,66 +257,92 @@ pub fn log_scan(
     events_count: usize,
     opportunities: usize,
     trades: usize,
-) -> Result<()> {
+) -> Result<(), StorageError> {
     let ts = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
-    let line = format!(
-        "| {} | {} | {} | {} | {} |",
-        ts, series_count, events_count, opportunities, trades,
-    );
-    append_line("data/scans.md", &line)
+    let row = || {
+        format!(
+            "| {} | {} | {} | {} | {} | {} |",
+            ts, series_count, events_count, opportunities, trades, instance_name(),
+        )
+    };
+    let record = || {
+        json_record(json!({
+            "series": series_count,
+            "events": events_count,
+            "opportunities": opportunities,
+            "trades": trades,
+        }))
+    };
+    log_row("scans", row, record)
 }
 
-/// Log reconciliation data for filled orders, matching them to brackets by ticker.
-/// `incomplete` is true when the arb was only partially filled.
-pub fn log_reconciliation(
-    opp: &ArbOpportunity,
-    filled_orders: &[(String, Order)],
-    incomplete: bool,
-) -> Result<()> {
-    let ts = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+/// Where an arb's realized net came from: the edge at the quoted prices and detected size,
+/// price slippage on what filled, the fees paid, and what contracts that never filled did
+/// to it. The four sum to the realized net.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PnlAttribution {
+    pub edge_cents: i64,
+    /// Positive when fills came in better than quoted.
+    pub slippage_cents: i64,
+    /// Fees paid, as a negative amount.
+    pub fee_cents: i64,
+    /// Edge never captured on sets that didn't complete, plus the worst case of the legs
+    /// left unhedged.
+    pub unfilled_cents: i64,
+}
 
-    let order_ids: Vec<&str> = filled_orders
-        .iter()
-        .map(|(_, o)| o.order_id.as_str())
-        .collect();
+impl PnlAttribution {
+    pub fn net_cents(&self) -> i64 {
+        self.edge_cents + self.slippage_cents + self.fee_cents + self.unfilled_cents
+    }
 
-    let statuses: Vec<&str> = filled_orders
-        .iter()
-        .map(|(_, o)| o.status.as_str())
-        .collect();
+    pub fn add(&mut self, other: &Self) {
+        self.edge_cents += other.edge_cents;
+        self.slippage_cents += other.slippage_cents;
+        self.fee_cents += other.fee_cents;
+        self.unfilled_cents += other.unfilled_cents;
+    }
+}
 
-    // Compute actual net profit from fill prices matched by ticker
-    let mut actual_cost_or_revenue: i64 = 0;
-    let mut actual_fees: i64 = 0;
+/// Attribute the net of a set of fills, matched to the opportunity's brackets by ticker.
+/// An incomplete arb is valued at its worst case: a LONG is paid only on its thinnest
+/// bracket, a SHORT owes on its fullest. An order against the arb's direction (an unwind
+/// closing a leg) takes its contracts off the bracket and its price off the premium, and
+/// isn't measured against the quote. None when a count is negative or an amount overflows.
+pub fn attribute_pnl(opp: &ArbOpportunity, filled_orders: &[(String, Order)]) -> Option<PnlAttribution> {
+    let quoted = |b: &BracketQuote| match opp.direction {
+        ArbDirection::Long => b.yes_ask_cents,
+        ArbDirection::Short => b.yes_bid_cents,
+    };
+    let size = Contracts(opp.position_size);
+    let quoted_premium = opp
+        .brackets
+        .iter()
+        .try_fold(Cents::ZERO, |total, b| total.checked_add(Cents(quoted(b)).checked_mul(size)?))?;
+    let full_payout = Cents(PAYOUT_CENTS).checked_mul(size)?;
+    let edge = match opp.direction {
+        ArbDirection::Long => full_payout.checked_sub(quoted_premium)?,
+        ArbDirection::Short => quoted_premium.checked_sub(full_payout)?,
+    };
 
+    // Long: the premium paid. Short: the premium received.
+    let (mut premium, mut fees, mut slippage) = (Cents::ZERO, Cents::ZERO, Cents::ZERO);
+    let mut counts: HashMap<&str, i64> = HashMap::new();
     for (ticker, order) in filled_orders {
         let actual_price = order.yes_price.unwrap_or(0);
-        let count = order.fill_count.or(order.count).unwrap_or(0) as u32;
-        let fee = taker_fee_cents(count, actual_price);
-
-        match opp.direction {
-            ArbDirection::Long => {
-                // Cost = price * count
-                actual_cost_or_revenue += actual_price * count as i64;
-            }
-            ArbDirection::Short => {
-                // Revenue = price * count
-                actual_cost_or_revenue += actual_price * count as i64;
-            }
+        let count = Contracts::from_i64(order.filled_count())?;
+        fees = fees.checked_add(Cents(fill_fee_cents(ticker, order, count.get(), actual_price)))?;
+        // An order against the arb's direction (an unwind selling back) closes contracts
+        let buys_yes = matches!((order.action.as_str(), order.side.as_str()), ("buy", "yes") | ("sell", "no"));
+        if buys_yes != (opp.direction == ArbDirection::Long) {
+            premium = premium.checked_sub(Cents(actual_price).checked_mul(count)?)?;
+            *counts.entry(ticker.as_str()).or_default() -= count.get() as i64;
+            continue;
         }
-        actual_fees += fee;
-
-        // Find expected price from brackets
-        let expected_price = opp
-            .brackets
-            .iter()
-            .find(|b| b.ticker == *ticker)
-            .map(|b| match opp.direction {
-                ArbDirection::Long => b.yes_ask_cents,
-                ArbDirection::Short => b.yes_bid_cents,
-            })
-            .unwrap_or(0);
+        premium = premium.checked_add(Cents(actual_price).checked_mul(count)?)?;
+        *counts.entry(ticker.as_str()).or_default() += count.get() as i64;
 
+        let expected_price = opp.brackets.iter().find(|b| b.ticker == *ticker).map_or(actual_price, quoted);
         if actual_price != expected_price {
             tracing::debug!(
                 ticker = %ticker,
@@ -134,41 +351,1146 @@ pub fn log_reconciliation(
                 "Price slippage detected"
             );
         }
+        let better = match opp.direction {
+            ArbDirection::Long => expected_price - actual_price,
+            ArbDirection::Short => actual_price - expected_price,
+        };
+        slippage = slippage.checked_add(Cents(better).checked_mul(count)?)?;
     }
 
-    // Use fill_count from first order as representative count, or fall back
-    let position_size = filled_orders
-        .first()
-        .and_then(|(_, o)| o.fill_count.or(o.count))
-        .unwrap_or(0);
-
-    let actual_net = match opp.direction {
-        ArbDirection::Long => {
-            // Payout = 100 * position_size (one bracket pays), cost = actual_cost_or_revenue
-            100 * position_size - actual_cost_or_revenue - actual_fees
-        }
-        ArbDirection::Short => {
-            // Revenue = actual_cost_or_revenue, liability = 100 * position_size
-            actual_cost_or_revenue - 100 * position_size - actual_fees
-        }
+    let filled = opp.brackets.iter().map(|b| counts.get(b.ticker.as_str()).copied().unwrap_or(0));
+    let paid_on = match opp.direction {
+        ArbDirection::Long => filled.min(),
+        ArbDirection::Short => filled.max(),
+    };
+    let payout = Cents(PAYOUT_CENTS).checked_mul(Contracts::from_i64(paid_on.unwrap_or(0))?)?;
+    let net = match opp.direction {
+        ArbDirection::Long => payout.checked_sub(premium)?.checked_sub(fees)?,
+        ArbDirection::Short => premium.checked_sub(payout)?.checked_sub(fees)?,
     };
+    let unfilled = net.checked_sub(edge)?.checked_sub(slippage)?.checked_add(fees)?;
+    Some(PnlAttribution {
+        edge_cents: edge.0,
+        slippage_cents: slippage.0,
+        fee_cents: -fees.0,
+        unfilled_cents: unfilled.0,
+    })
+}
+
+/// Net profit realized by a set of fills (see `attribute_pnl`).
+pub fn realized_net_cents(opp: &ArbOpportunity, filled_orders: &[(String, Order)]) -> Option<i64> {
+    attribute_pnl(opp, filled_orders).map(|a| a.net_cents())
+}
+
+/// Log reconciliation data for filled orders, matching them to brackets by ticker, with
+/// the realized net attributed (`attribute_pnl`). `incomplete` is true when the arb was
+/// only partially filled.
+pub fn log_reconciliation(
+    opp: &ArbOpportunity,
+    filled_orders: &[(String, Order)],
+    incomplete: bool,
+) -> Result<(), StorageError> {
+    let ts = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+
+    let order_ids: Vec<&str> = filled_orders
+        .iter()
+        .map(|(_, o)| o.order_id.as_str())
+        .collect();
+
+    let statuses: Vec<&str> = filled_orders
+        .iter()
+        .map(|(_, o)| o.status.as_str())
+        .collect();
 
     let expected_net = opp.net_profit_cents;
-    let slippage = actual_net - expected_net;
+    let attribution = attribute_pnl(opp, filled_orders);
+    let actual_net = attribution.map(|a| a.net_cents());
+    let slippage = actual_net.and_then(|a| a.checked_sub(expected_net));
+    let or_dash = |c: Option<i64>| c.map_or_else(|| "-".to_string(), dollars);
+    let part = |f: fn(&PnlAttribution) -> i64| or_dash(attribution.as_ref().map(f));
 
     let note = if incomplete { " (INCOMPLETE)" } else { "" };
 
     let line = format!(
-        "| {} | {} | {} | {} | {} | ${:.2} | ${:.2} | ${:.2}{} |",
+        "| {} | {} | {} | {} | {} | {} | {} | {}{} | {} | {} | {} | {} | {} | {} |",
         ts,
         opp.event_ticker,
         opp.direction,
         order_ids.join(", "),
         statuses.join(", "),
-        expected_net as f64 / 100.0,
-        actual_net as f64 / 100.0,
-        slippage as f64 / 100.0,
+        dollars(expected_net),
+        or_dash(actual_net),
+        or_dash(slippage),
         note,
+        part(|a| a.edge_cents),
+        part(|a| a.slippage_cents),
+        part(|a| a.fee_cents),
+        part(|a| a.unfilled_cents),
+        opp.uuid,
+        instance_name(),
     );
     append_line("data/reconciliation.md", &line)
 }
+
+/// Log a mirrored execution next to the live one it replayed.
+pub fn log_mirror(
+    opp: &ArbOpportunity,
+    mirror: &ExecutionResult,
+    live: &ExecutionResult,
+) -> Result<(), StorageError> {
+    let ts = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    let counts = |r: &ExecutionResult| {
+        format!(
+            "{}/{}/{}/{}",
+            r.filled.len(),
+            r.resting.len(),
+            r.other.len(),
+            r.api_failures.len()
+        )
+    };
+    let line = format!(
+        "| {} | {} | {} | {} | {} | {} | {} |",
+        ts,
+        opp.id,
+        opp.event_ticker,
+        opp.direction,
+        counts(live),
+        counts(mirror),
+        instance_name(),
+    );
+    append_line("data/mirror.md", &line)
+}
+
+/// Log a `[canary]` execution: the size it was placed at and its leg outcomes
+/// (filled/resting/other/failed).
+pub fn log_canary(opp: &ArbOpportunity, size: u32, result: &ExecutionResult) -> Result<(), StorageError> {
+    let ts = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    let line = format!(
+        "| {} | {} | {} | {} | {} | {}/{}/{}/{} | {} | {} |",
+        ts,
+        opp.id,
+        opp.event_ticker,
+        opp.direction,
+        size,
+        result.filled.len(),
+        result.resting.len(),
+        result.other.len(),
+        result.api_failures.len(),
+        dollars(opp.net_profit_cents),
+        instance_name(),
+    );
+    append_line("data/canary.md", &line)
+}
+
+/// Log a `balance_snapshot` run: available cash and arbs still open.
+pub fn log_balance(balance_cents: i64, open_arbs: u32) -> Result<(), StorageError> {
+    let ts = Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    log_row(
+        "balances",
+        || format!("| {} | {} | {} | {} |", ts, dollars(balance_cents), open_arbs, instance_name()),
+        || json_record(json!({ "balance_cents": balance_cents, "open_arbs": open_arbs })),
+    )
+}
+
+/// Logs `rotate_logs` rolls over. `executions.jsonl` and `paper.jsonl` are replayed at
+/// startup, so they are never rotated.
+const ROTATED_LOGS: &[&str] = &[
+    "scans",
+    "opportunities",
+    "shadow_opportunities",
+    "trades",
+    "reconciliation",
+    "canary",
+    "mirror",
+    "balances",
+];
+
+/// Rename every log in `dir` larger than `max_bytes` to `{name}.{UTC timestamp}.{ext}`,
+/// so rows go on in a fresh file. Returns the rotated-out files' new names.
+pub fn rotate_logs(dir: &Path, max_bytes: u64, now: DateTime<Utc>) -> Result<Vec<String>> {
+    let stamp = now.format("%Y%m%dT%H%M%SZ");
+    let mut rotated = Vec::new();
+    for name in ROTATED_LOGS {
+        for ext in ["md", "jsonl"] {
+            let path = dir.join(format!("{}.{}", name, ext));
+            let len = match std::fs::metadata(&path) {
+                Ok(meta) => meta.len(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+            };
+            if len <= max_bytes {
+                continue;
+            }
+            let archived = format!("{}.{}.{}", name, stamp, ext);
+            std::fs::rename(&path, dir.join(&archived))
+                .with_context(|| format!("Failed to rotate {}", path.display()))?;
+            rotated.push(archived);
+        }
+    }
+    Ok(rotated)
+}
+
+// --- Execution / order state store ---
+
+/// Full outcome of one `execute_arb` call, keyed by opportunity ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    /// Instance that placed the orders; records from before instances existed read as "default".
+    #[serde(default = "default_instance")]
+    pub instance: String,
+    pub opportunity_id: String,
+    /// The opportunity's UUID; None for records from before UUIDs, and for adopted orphans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
+    pub recorded_at: DateTime<Utc>,
+    pub result: ExecutionResult,
+    /// Bracket labels ("3.4% to 3.6%") by ticker, so the record reads without looking
+    /// tickers up. Empty for records from before labels, and for adopted orphans.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+fn default_instance() -> String { "default".to_string() }
+
+/// Last known state of a single order placed by the bot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderStateRecord {
+    pub opportunity_id: String,
+    pub order_id: String,
+    pub ticker: String,
+    pub status: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Where a detected opportunity stands. Every opportunity starts `detected` (its row in
+/// the opportunities log); later states are recorded in the execution store:
+/// detected → risk_blocked | executed → filled | partial | aborted → settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpportunityState {
+    Detected,
+    /// A pre-flight check refused it; nothing was placed.
+    RiskBlocked,
+    /// Its orders were sent.
+    Executed,
+    /// Every leg filled.
+    Filled,
+    /// Some legs filled; the rest were cancelled and the fills unwound or held.
+    Partial,
+    /// Nothing filled: every leg failed, or placement ran past `max_execution_ms`.
+    Aborted,
+    /// Every market it holds a leg in has a result.
+    Settled,
+}
+
+impl OpportunityState {
+    /// Whether an opportunity may move from `self` to `next`.
+    pub fn can_become(self, next: Self) -> bool {
+        use OpportunityState::*;
+        matches!(
+            (self, next),
+            (Detected, RiskBlocked | Executed) | (Executed, Filled | Partial | Aborted) | (Filled | Partial, Settled)
+        )
+    }
+}
+
+impl fmt::Display for OpportunityState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OpportunityState::Detected => "detected",
+            OpportunityState::RiskBlocked => "risk_blocked",
+            OpportunityState::Executed => "executed",
+            OpportunityState::Filled => "filled",
+            OpportunityState::Partial => "partial",
+            OpportunityState::Aborted => "aborted",
+            OpportunityState::Settled => "settled",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A state change of one opportunity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateRecord {
+    pub instance: String,
+    pub opportunity_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
+    pub state: OpportunityState,
+    /// Why it was blocked or aborted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// Net position in one market, derived from fills recorded in the execution store.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocalPosition {
+    pub ticker: String,
+    /// Signed like the exchange: positive = YES contracts, negative = NO contracts.
+    pub position: i64,
+    /// Cost of the position in cents (YES price for YES fills, 100 - YES price for NO fills).
+    pub cost_cents: i64,
+}
+
+impl LocalPosition {
+    /// Apply a fill of `signed` contracts costing `cost_cents` on the side it adds. A fill
+    /// against the position closes contracts at their average cost; what's left over opens
+    /// the other side at the fill's price.
+    fn add_fill(&mut self, signed: i64, cost_cents: i64) {
+        if self.position == 0 || self.position.signum() == signed.signum() {
+            self.position += signed;
+            self.cost_cents += cost_cents;
+            return;
+        }
+        let closed = signed.abs().min(self.position.abs());
+        self.cost_cents -= self.cost_cents * closed / self.position.abs();
+        self.position += signed;
+        let opened = signed.abs() - closed;
+        if opened > 0 {
+            self.cost_cents = cost_cents * opened / signed.abs();
+        }
+    }
+
+    /// Average entry price in cents, on the side the position is held.
+    pub fn avg_price_cents(&self) -> Option<f64> {
+        (self.position != 0).then(|| self.cost_cents as f64 / self.position.abs() as f64)
+    }
+}
+
+/// How a market's local position compares with the exchange's: `ok`, `LOCAL ONLY`,
+/// `EXCHANGE ONLY`, `QTY MISMATCH`, or `AVG MISMATCH` (half a cent or more apart).
+pub fn position_check(local: Option<&LocalPosition>, exchange: Option<&MarketPosition>) -> &'static str {
+    let local_qty = local.map_or(0, |p| p.position);
+    let exch_qty = exchange.map_or(0, |p| p.position);
+    match (local_qty, exch_qty) {
+        (_, 0) => "LOCAL ONLY",
+        (0, _) => "EXCHANGE ONLY",
+        (a, b) if a != b => "QTY MISMATCH",
+        _ => match (local.and_then(LocalPosition::avg_price_cents), exchange.and_then(exchange_avg_cents)) {
+            (Some(a), Some(b)) if (a - b).abs() >= 0.5 => "AVG MISMATCH",
+            _ => "ok",
+        },
+    }
+}
+
+/// Average entry price of an exchange position in cents, from its exposure.
+pub fn exchange_avg_cents(position: &MarketPosition) -> Option<f64> {
+    (position.position != 0).then(|| position.market_exposure as f64 / position.position.abs() as f64)
+}
+
+/// Closing state of a run, one line of `data/shutdowns.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub instance: String,
+    pub started_at: DateTime<Utc>,
+    pub stopped_at: DateTime<Utc>,
+    pub uptime_secs: i64,
+    /// What stopped the run (the signal received).
+    pub reason: String,
+    pub dry_run: bool,
+    pub cycles: u64,
+    /// Opportunities detected and arbs executed over the whole run.
+    pub opportunities: u64,
+    pub executions: u64,
+    /// The trading day's P&L at exit.
+    pub daily_pnl_cents: i64,
+    pub open_arbs: u32,
+    /// Orders still resting after the exit cancels.
+    pub resting_orders: usize,
+}
+
+pub fn log_shutdown(report: &ShutdownReport) -> Result<(), StorageError> {
+    let line = serde_json::to_string(report)
+        .map_err(|source| StorageError::Serialize { what: "shutdown report", source })?;
+    append_line("data/shutdowns.jsonl", &line)
+}
+
+/// One line of `data/executions.jsonl`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum StoreLine {
+    Execution(ExecutionRecord),
+    OrderState(OrderStateRecord),
+    State(StateRecord),
+}
+
+/// Append-only execution log with in-memory indexes by opportunity ID and order ID.
+/// Replayed from disk at startup so lookups survive restarts and crashes.
+#[derive(Debug, Default)]
+pub struct ExecutionStore {
+    executions: HashMap<String, ExecutionRecord>,
+    orders: HashMap<String, OrderStateRecord>,
+    /// Opportunities past `detected`, by ID.
+    states: HashMap<String, OpportunityState>,
+    /// Opportunity IDs by UUID, for finding the opportunity behind a client order ID.
+    uuids: HashMap<Uuid, String>,
+}
+
+impl ExecutionStore {
+    /// Replay `data/executions.jsonl`. A missing file yields an empty store;
+    /// unparseable lines (e.g. a torn final write) are skipped with a warning.
+    pub fn load() -> Result<Self, StorageError> {
+        Self::load_from(Path::new(EXECUTIONS_PATH))
+    }
+
+    /// Replay an execution log at another path (e.g. another instance's `data/`).
+    pub fn load_from(path: &Path) -> Result<Self, StorageError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => return Err(StorageError::Read { path: path.display().to_string(), source }),
+        };
+        Ok(Self::replay(&content))
+    }
+
+    fn replay(content: &str) -> Self {
+        let mut store = Self::default();
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<StoreLine>(line) {
+                Ok(entry) => store.apply(entry),
+                Err(e) => warn!(line = i + 1, error = %e, "Skipping unparseable execution record"),
+            }
+        }
+        store
+    }
+
+    fn apply(&mut self, entry: StoreLine) {
+        match entry {
+            StoreLine::Execution(rec) => {
+                for (ticker, order) in rec.result.orders().chain(&rec.result.unwind) {
+                    self.orders.insert(
+                        order.order_id.clone(),
+                        OrderStateRecord {
+                            opportunity_id: rec.opportunity_id.clone(),
+                            order_id: order.order_id.clone(),
+                            ticker: ticker.clone(),
+                            status: order.status.clone(),
+                            updated_at: rec.recorded_at,
+                        },
+                    );
+                }
+                if let Some(uuid) = rec.uuid {
+                    self.uuids.insert(uuid, rec.opportunity_id.clone());
+                }
+                self.executions.insert(rec.opportunity_id.clone(), rec);
+            }
+            StoreLine::OrderState(state) => {
+                self.orders.insert(state.order_id.clone(), state);
+            }
+            StoreLine::State(rec) => {
+                if let Some(uuid) = rec.uuid {
+                    self.uuids.insert(uuid, rec.opportunity_id.clone());
+                }
+                self.states.insert(rec.opportunity_id, rec.state);
+            }
+        }
+    }
+
+    fn persist(&mut self, entry: StoreLine) -> Result<(), StorageError> {
+        let line = serde_json::to_string(&entry)
+            .map_err(|source| StorageError::Serialize { what: "execution record", source })?;
+        append_line(EXECUTIONS_PATH, &line)?;
+        self.apply(entry);
+        Ok(())
+    }
+
+    /// Persist every bucket of an execution result.
+    pub fn record(
+        &mut self,
+        opportunity_id: &str,
+        uuid: Option<Uuid>,
+        result: &ExecutionResult,
+        labels: BTreeMap<String, String>,
+    ) -> Result<(), StorageError> {
+        self.persist(StoreLine::Execution(ExecutionRecord {
+            instance: instance_name().to_string(),
+            opportunity_id: opportunity_id.to_string(),
+            uuid,
+            recorded_at: Utc::now(),
+            result: result.clone(),
+            labels,
+        }))
+    }
+
+    /// Record a status change (e.g. "canceled") for an order we placed earlier.
+    pub fn update_order_status(&mut self, order_id: &str, status: &str) -> Result<(), StorageError> {
+        let Some(prev) = self.orders.get(order_id) else {
+            return Err(StorageError::UnknownOrder(order_id.to_string()));
+        };
+        let state = OrderStateRecord {
+            status: status.to_string(),
+            updated_at: Utc::now(),
+            ..prev.clone()
+        };
+        self.persist(StoreLine::OrderState(state))
+    }
+
+    /// Move an opportunity to `state`, with why for a block or abort. Refuses a move its
+    /// current state doesn't allow (see `OpportunityState::can_become`).
+    pub fn transition(
+        &mut self,
+        opportunity_id: &str,
+        uuid: Option<Uuid>,
+        state: OpportunityState,
+        reason: Option<&str>,
+    ) -> Result<(), StorageError> {
+        let current = self.state(opportunity_id);
+        if !current.can_become(state) {
+            return Err(StorageError::IllegalTransition { id: opportunity_id.to_string(), from: current, to: state });
+        }
+        self.persist(StoreLine::State(StateRecord {
+            instance: instance_name().to_string(),
+            opportunity_id: opportunity_id.to_string(),
+            uuid,
+            state,
+            reason: reason.map(str::to_string),
+            at: Utc::now(),
+        }))
+    }
+
+    /// Where an opportunity stands; `detected` if nothing later was recorded.
+    pub fn state(&self, opportunity_id: &str) -> OpportunityState {
+        self.states.get(opportunity_id).copied().unwrap_or(OpportunityState::Detected)
+    }
+
+    /// Execution outcome for an opportunity, if it was executed.
+    pub fn get(&self, opportunity_id: &str) -> Option<&ExecutionRecord> {
+        self.executions.get(opportunity_id)
+    }
+
+    /// Every opportunity recorded with a UUID, as (UUID, opportunity ID). An opportunity's
+    /// `executed` state is recorded before its orders go out, so every leg's opportunity is here.
+    pub fn uuids(&self) -> impl Iterator<Item = (&Uuid, &str)> {
+        self.uuids.iter().map(|(uuid, id)| (uuid, id.as_str()))
+    }
+
+    /// Every recorded execution, in no particular order.
+    pub fn executions(&self) -> impl Iterator<Item = &ExecutionRecord> {
+        self.executions.values()
+    }
+
+    /// Last known state of an order.
+    pub fn order(&self, order_id: &str) -> Option<&OrderStateRecord> {
+        self.orders.get(order_id)
+    }
+
+    /// An order as it was placed, from the execution that recorded it.
+    pub fn placed_order(&self, order_id: &str) -> Option<&Order> {
+        let state = self.orders.get(order_id)?;
+        let rec = self.executions.get(&state.opportunity_id)?;
+        rec.result.orders().map(|(_, o)| o).find(|o| o.order_id == order_id)
+    }
+
+    /// Net positions from every recorded fill, unwind orders included, sorted by ticker.
+    /// Markets that net to zero are omitted. Fills are applied in the order they were
+    /// recorded, at what the exchange charged when it said (else the limit price). Fills are
+    /// taken as of the execution record; later fills on orders that were left resting are
+    /// not seen here.
+    pub fn positions(&self) -> Vec<LocalPosition> {
+        let mut records: Vec<&ExecutionRecord> = self.executions.values().collect();
+        records.sort_by_key(|rec| rec.recorded_at);
+        let mut by_ticker: HashMap<&str, LocalPosition> = HashMap::new();
+        for rec in records {
+            for (ticker, order) in rec.result.orders().chain(&rec.result.unwind) {
+                let qty = order.filled_count();
+                if qty == 0 {
+                    continue;
+                }
+                // Total price on the order's own side, then on the YES side
+                let limit = match order.side.as_str() {
+                    "no" => order.no_price.unwrap_or_else(|| price::complement(order.yes_price.unwrap_or(0))),
+                    _ => order.yes_price.unwrap_or(0),
+                };
+                let charged = match (order.taker_fill_cost, order.maker_fill_cost) {
+                    (None, None) => qty * limit,
+                    (taker, maker) => taker.unwrap_or(0) + maker.unwrap_or(0),
+                };
+                let yes_cost = if order.side == "no" { qty * PAYOUT_CENTS - charged } else { charged };
+                // Buying YES or selling NO adds YES exposure; the reverse adds NO exposure.
+                let (signed, cost) = match (order.action.as_str(), order.side.as_str()) {
+                    ("buy", "yes") | ("sell", "no") => (qty, yes_cost),
+                    _ => (-qty, qty * PAYOUT_CENTS - yes_cost),
+                };
+                by_ticker
+                    .entry(ticker)
+                    .or_insert_with(|| LocalPosition { ticker: ticker.clone(), position: 0, cost_cents: 0 })
+                    .add_fill(signed, cost);
+            }
+        }
+        let mut positions: Vec<_> = by_ticker.into_values().filter(|p| p.position != 0).collect();
+        positions.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+        positions
+    }
+
+    /// Events with at least one order still resting.
+    pub fn open_events(&self) -> HashSet<String> {
+        self.open_orders()
+            .into_iter()
+            .filter_map(|o| self.executions.get(&o.opportunity_id))
+            .map(|rec| rec.result.event_ticker.clone())
+            .collect()
+    }
+
+    /// Orders whose last recorded state is still resting on the exchange.
+    pub fn open_orders(&self) -> Vec<&OrderStateRecord> {
+        let mut open: Vec<_> = self
+            .orders
+            .values()
+            .filter(|o| o.status == "resting")
+            .collect();
+        open.sort_by_key(|o| o.updated_at);
+        open
+    }
+}
+
+// --- Journal export ---
+
+/// How a markdown cell is typed on export.
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Text,
+    /// A row timestamp, written as RFC3339.
+    Time,
+    Int,
+    /// A dollar amount, written as integer cents.
+    Cents,
+    /// A percentage, written as a float without the `%`.
+    Pct,
+    /// `YES`/`NO`, written as a boolean.
+    Flag,
+    /// Whether the cell carries reconciliation's `(INCOMPLETE)` note.
+    Incomplete,
+}
+
+/// Which cell a column is read from. Older rows may lack trailing columns; `Last`
+/// follows the instance name to the end of a row whatever its length, and `BeforeLast`
+/// is a cell added ahead of it, missing when the instance lands there instead.
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    At(usize),
+    BeforeLast(usize),
+    Last,
+}
+
+/// An exported column: its name, type, and the cell it is read from.
+type Column = (&'static str, Kind, Source);
+
+const OPPORTUNITY_COLUMNS: &[Column] = &[
+    ("ts", Kind::Time, Source::At(0)),
+    ("opportunity_id", Kind::Text, Source::At(1)),
+    ("event_ticker", Kind::Text, Source::At(2)),
+    ("direction", Kind::Text, Source::At(3)),
+    ("brackets", Kind::Int, Source::At(4)),
+    ("sum_cents", Kind::Cents, Source::At(5)),
+    ("fees_cents", Kind::Cents, Source::At(6)),
+    ("net_profit_cents", Kind::Cents, Source::At(7)),
+    ("gross_roi_pct", Kind::Pct, Source::At(8)),
+    ("net_roi_pct", Kind::Pct, Source::At(9)),
+    ("slippage_adj_roi_pct", Kind::Pct, Source::At(10)),
+    ("executed", Kind::Flag, Source::At(11)),
+    ("uuid", Kind::Text, Source::BeforeLast(12)),
+    ("instance", Kind::Text, Source::Last),
+];
+
+const TRADE_COLUMNS: &[Column] = &[
+    ("ts", Kind::Time, Source::At(0)),
+    ("event_ticker", Kind::Text, Source::At(1)),
+    ("market", Kind::Text, Source::At(2)),
+    ("side", Kind::Text, Source::At(3)),
+    ("price_cents", Kind::Cents, Source::At(4)),
+    ("size", Kind::Int, Source::At(5)),
+    ("fee_cents", Kind::Cents, Source::At(6)),
+    ("order_id", Kind::Text, Source::At(7)),
+    ("status", Kind::Text, Source::At(8)),
+    ("opportunity_uuid", Kind::Text, Source::BeforeLast(9)),
+    ("instance", Kind::Text, Source::Last),
+];
+
+const RECONCILIATION_COLUMNS: &[Column] = &[
+    ("ts", Kind::Time, Source::At(0)),
+    ("event_ticker", Kind::Text, Source::At(1)),
+    ("direction", Kind::Text, Source::At(2)),
+    ("order_ids", Kind::Text, Source::At(3)),
+    ("statuses", Kind::Text, Source::At(4)),
+    ("expected_net_cents", Kind::Cents, Source::At(5)),
+    ("actual_net_cents", Kind::Cents, Source::At(6)),
+    ("slippage_cents", Kind::Cents, Source::At(7)),
+    ("incomplete", Kind::Incomplete, Source::At(7)),
+    ("edge_cents", Kind::Cents, Source::At(8)),
+    ("price_slippage_cents", Kind::Cents, Source::At(9)),
+    ("fee_cents", Kind::Cents, Source::At(10)),
+    ("unfilled_cents", Kind::Cents, Source::At(11)),
+    ("opportunity_uuid", Kind::Text, Source::BeforeLast(12)),
+    ("instance", Kind::Text, Source::Last),
+];
+
+/// The journal tables `export` writes, by the markdown log each is read from.
+const EXPORT_TABLES: &[(&str, &[Column])] = &[
+    ("opportunities", OPPORTUNITY_COLUMNS),
+    ("trades", TRADE_COLUMNS),
+    ("reconciliation", RECONCILIATION_COLUMNS),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Text(Option<String>),
+    Int(Option<i64>),
+    Float(Option<f64>),
+    Bool(Option<bool>),
+}
+
+impl Field {
+    fn parse(kind: Kind, cell: Option<&str>) -> Self {
+        match kind {
+            Kind::Text => Field::Text(cell.filter(|c| !c.is_empty()).map(str::to_string)),
+            Kind::Time => Field::Text(
+                cell.and_then(|c| c.parse::<DateTime<Utc>>().ok()).map(|t| t.to_rfc3339()),
+            ),
+            Kind::Int => Field::Int(cell.and_then(|c| c.parse().ok())),
+            Kind::Cents => Field::Int(cell.and_then(parse_dollars)),
+            Kind::Pct => Field::Float(cell.and_then(|c| c.strip_suffix('%')?.parse().ok())),
+            Kind::Flag => Field::Bool(cell.and_then(|c| match c {
+                "YES" => Some(true),
+                "NO" => Some(false),
+                _ => None,
+            })),
+            Kind::Incomplete => Field::Bool(cell.map(|c| c.contains("INCOMPLETE"))),
+        }
+    }
+
+    /// The CSV cell: empty when missing.
+    fn csv(&self) -> String {
+        match self {
+            Field::Text(v) => v.clone().unwrap_or_default(),
+            Field::Int(v) => v.map(|v| v.to_string()).unwrap_or_default(),
+            Field::Float(v) => v.map(|v| v.to_string()).unwrap_or_default(),
+            Field::Bool(v) => v.map(|v| v.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+/// One table as written by `export`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedTable {
+    pub name: &'static str,
+    pub rows: usize,
+}
+
+/// Export the opportunity, trade, and reconciliation logs in `dir` (a bot's `data/`
+/// directory) over `period` to `{name}.csv` and `{name}.parquet` under `out`. Columns
+/// are typed: money as integer cents, ROI as float percent, flags as booleans, and
+/// timestamps as RFC3339 strings. Reads the markdown tables, rotated-out files included,
+/// like `report`; a missing log exports as an empty table.
+pub fn export(dir: &Path, period: Period, out: &Path) -> Result<Vec<ExportedTable>> {
+    std::fs::create_dir_all(out).with_context(|| format!("Failed to create {}", out.display()))?;
+    let mut exported = Vec::new();
+    for &(name, columns) in EXPORT_TABLES {
+        let rows: Vec<Vec<Field>> = read_log_rows(dir, name)?
+            .into_iter()
+            .filter(|row| {
+                let ts = row.first().and_then(|c| c.parse::<DateTime<Utc>>().ok());
+                ts.is_some_and(|ts| period.contains(ts))
+            })
+            .map(|row| {
+                columns
+                    .iter()
+                    .map(|&(_, kind, source)| {
+                        let cell = match source {
+                            Source::At(i) => row.get(i),
+                            Source::BeforeLast(i) => row.get(i).filter(|_| i + 1 < row.len()),
+                            Source::Last => row.last(),
+                        };
+                        Field::parse(kind, cell.map(String::as_str))
+                    })
+                    .collect()
+            })
+            .collect();
+        write_csv(&out.join(format!("{}.csv", name)), columns, &rows)?;
+        write_parquet(&out.join(format!("{}.parquet", name)), columns, &rows)?;
+        exported.push(ExportedTable { name, rows: rows.len() });
+    }
+    Ok(exported)
+}
+
+fn write_csv(path: &Path, columns: &[Column], rows: &[Vec<Field>]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    writer.write_record(columns.iter().map(|(name, _, _)| name))?;
+    for row in rows {
+        writer.write_record(row.iter().map(Field::csv))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_parquet(path: &Path, columns: &[Column], rows: &[Vec<Field>]) -> Result<()> {
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+    for (i, &(name, kind, _)) in columns.iter().enumerate() {
+        let cells = rows.iter().map(|row| &row[i]);
+        let (data_type, array): (DataType, ArrayRef) = match kind {
+            Kind::Text | Kind::Time => (
+                DataType::Utf8,
+                Arc::new(StringArray::from_iter(cells.map(|f| match f {
+                    Field::Text(v) => v.clone(),
+                    _ => None,
+                }))),
+            ),
+            Kind::Int | Kind::Cents => (
+                DataType::Int64,
+                Arc::new(Int64Array::from_iter(cells.map(|f| match f {
+                    Field::Int(v) => *v,
+                    _ => None,
+                }))),
+            ),
+            Kind::Pct => (
+                DataType::Float64,
+                Arc::new(Float64Array::from_iter(cells.map(|f| match f {
+                    Field::Float(v) => *v,
+                    _ => None,
+                }))),
+            ),
+            Kind::Flag | Kind::Incomplete => (
+                DataType::Boolean,
+                Arc::new(BooleanArray::from_iter(cells.map(|f| match f {
+                    Field::Bool(v) => *v,
+                    _ => None,
+                }))),
+            ),
+        };
+        fields.push(ArrowField::new(name, data_type, true));
+        arrays.push(array);
+    }
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+    let file = std::fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn order(id: &str, status: &str) -> Order {
+        Order {
+            order_id: id.to_string(),
+            ticker: "T".to_string(),
+            status: status.to_string(),
+            action: "buy".to_string(),
+            side: "yes".to_string(),
+            order_type: "limit".to_string(),
+            yes_price: Some(30),
+            no_price: None,
+            count: Some(5),
+            remaining_count: None,
+            fill_count: None,
+            initial_count: None,
+            taker_fill_cost: None,
+            maker_fill_cost: None,
+            client_order_id: None,
+        }
+    }
+
+    #[test]
+    fn test_opportunity_record_keeps_bracket_detail() {
+        let opp = ArbOpportunity {
+            id: "EV-LONG-0".into(),
+            uuid: Uuid::nil(),
+            event_ticker: "EV".into(),
+            event_title: "Event".into(),
+            direction: ArbDirection::Long,
+            brackets: vec![BracketQuote {
+                ticker: "EV-B1".into(),
+                title: "B1".into(),
+                label: Some("3.4–3.6".into()),
+                yes_ask_cents: 30,
+                depth_at_no: 12,
+                ask_levels: vec![(30, 5), (31, 7)],
+                ..Default::default()
+            }],
+            position_size: 5,
+            sum_cents: 30,
+            total_fees_cents: 2,
+            gross_profit_cents: 10,
+            net_profit_cents: 8,
+            expected_slippage_cents: 0,
+            gross_roi_pct: "12.5".parse().unwrap(),
+            net_roi_pct: "9.75".parse().unwrap(),
+            slippage_adj_roi_pct: "9.75".parse().unwrap(),
+            settles_at: None,
+        };
+        let record = opportunity_record(&opp, "executed", true);
+        assert_eq!(record["schema_version"], SCHEMA_VERSION);
+        assert_eq!(record["direction"], "LONG");
+        assert_eq!(record["net_roi_pct"], 9.75);
+        assert_eq!(record["executed"], true);
+        assert_eq!(record["brackets"][0]["label"], "3.4–3.6");
+        assert_eq!(record["brackets"][0]["ask_levels"], json!([[30, 5], [31, 7]]));
+    }
+
+    #[test]
+    fn test_replay_tracks_latest_order_state() {
+        let result = ExecutionResult {
+            event_ticker: "EV".into(),
+            direction: ArbDirection::Long,
+            filled: vec![("A".into(), order("o1", "executed"))],
+            resting: vec![("B".into(), order("o2", "resting"))],
+            other: vec![],
+            api_failures: vec!["C".into()],
+            failure_kinds: Default::default(),
+            aborted: false,
+            unwind: Vec::new(),
+        };
+        let uuid = Uuid::parse_str("0b5d3c2e-8f61-4a7e-9d2c-5e4f3a1b6c7d").unwrap();
+        let exec = StoreLine::Execution(ExecutionRecord {
+            instance: "test".into(),
+            opportunity_id: "EV-LONG-1".into(),
+            uuid: Some(uuid),
+            recorded_at: Utc::now(),
+            result,
+            labels: BTreeMap::from([("A".into(), "40-41°".into())]),
+        });
+        let mut content = serde_json::to_string(&exec).unwrap();
+        content.push('\n');
+
+        let store = ExecutionStore::replay(&content);
+        assert_eq!(store.get("EV-LONG-1").unwrap().result.api_failures, vec!["C"]);
+        assert_eq!(store.get("EV-LONG-1").unwrap().labels["A"], "40-41°");
+        assert_eq!(store.get("EV-LONG-1").unwrap().uuid, Some(uuid));
+        assert_eq!(store.order("o2").unwrap().ticker, "B");
+        assert_eq!(store.open_orders().len(), 1);
+
+        // A later cancel supersedes the resting state; a torn trailing line is ignored
+        let cancel = StoreLine::OrderState(OrderStateRecord {
+            status: "canceled".into(),
+            ..store.order("o2").unwrap().clone()
+        });
+        content.push_str(&serde_json::to_string(&cancel).unwrap());
+        content.push_str("\n{\"kind\":\"execu");
+
+        let store = ExecutionStore::replay(&content);
+        assert_eq!(store.order("o2").unwrap().status, "canceled");
+        assert!(store.open_orders().is_empty());
+        assert_eq!(store.order("o1").unwrap().opportunity_id, "EV-LONG-1");
+        assert_eq!(store.state("EV-LONG-1"), OpportunityState::Detected);
+    }
+
+    #[test]
+    fn test_opportunity_states_replay_and_transitions() {
+        let line = |state: OpportunityState, reason: Option<&str>| {
+            let rec = StateRecord {
+                instance: "test".into(),
+                opportunity_id: "EV-LONG-1".into(),
+                uuid: None,
+                state,
+                reason: reason.map(str::to_string),
+                at: Utc::now(),
+            };
+            serde_json::to_string(&StoreLine::State(rec)).unwrap() + "\n"
+        };
+        let content = line(OpportunityState::Executed, None) + &line(OpportunityState::Partial, None);
+        assert!(content.starts_with(
+            "{\"kind\":\"state\",\"instance\":\"test\",\"opportunity_id\":\"EV-LONG-1\",\"state\":\"executed\""
+        ));
+        let store = ExecutionStore::replay(&content);
+        assert_eq!(store.state("EV-LONG-1"), OpportunityState::Partial);
+        assert_eq!(store.state("EV-SHORT-2"), OpportunityState::Detected);
+
+        use OpportunityState::*;
+        assert!(Partial.can_become(Settled));
+        assert!(Detected.can_become(RiskBlocked));
+        for (from, to) in [(Detected, Filled), (RiskBlocked, Executed), (Aborted, Settled), (Settled, Settled)] {
+            assert!(!from.can_become(to), "{} -> {}", from, to);
+        }
+    }
+
+    #[test]
+    fn test_positions_from_fills() {
+        let mut short_leg = order("o3", "resting");
+        short_leg.action = "sell".into();
+        short_leg.yes_price = Some(40);
+        short_leg.initial_count = Some(5);
+        short_leg.remaining_count = Some(3);
+        let result = ExecutionResult {
+            event_ticker: "EV".into(),
+            direction: ArbDirection::Long,
+            filled: vec![("A".into(), order("o1", "executed"))],
+            resting: vec![("B".into(), order("o2", "resting")), ("C".into(), short_leg)],
+            other: vec![],
+            api_failures: vec![],
+            failure_kinds: Default::default(),
+            aborted: false,
+            unwind: Vec::new(),
+        };
+        let mut store = ExecutionStore::default();
+        store.apply(StoreLine::Execution(ExecutionRecord {
+            instance: "test".into(),
+            opportunity_id: "EV-LONG-1".into(),
+            uuid: None,
+            recorded_at: Utc::now(),
+            result,
+            labels: BTreeMap::new(),
+        }));
+
+        let positions = store.positions();
+        assert_eq!(
+            positions,
+            vec![
+                LocalPosition { ticker: "A".into(), position: 5, cost_cents: 150 },
+                // 2 of 5 sold at 40c YES = 2 NO contracts at 60c
+                LocalPosition { ticker: "C".into(), position: -2, cost_cents: 120 },
+            ]
+        );
+        assert_eq!(positions[1].avg_price_cents(), Some(60.0));
+
+        // Later: 2 of A sold (improved to 36c), 3 of C bought back at 45c on YES, 4 of D
+        // bought at a 50c limit for 180
+        let fill = |id: &str, action: &str, yes_price: i64, count: i64, cost: i64| {
+            let mut o = order(id, "executed");
+            (o.action, o.yes_price, o.count) = (action.into(), Some(yes_price), Some(count));
+            o.taker_fill_cost = Some(cost);
+            o
+        };
+        let mut later = store.get("EV-LONG-1").unwrap().clone();
+        later.opportunity_id = "EV-LONG-2".into();
+        later.recorded_at += chrono::Duration::seconds(1);
+        later.result.filled =
+            vec![("A".into(), fill("o4", "sell", 35, 2, 72)), ("D".into(), fill("o6", "buy", 50, 4, 180))];
+        later.result.resting = vec![("C".into(), fill("o5", "buy", 45, 3, 135))];
+        store.apply(StoreLine::Execution(later));
+        assert_eq!(
+            store.positions(),
+            vec![
+                // Selling closes at the average cost: 3 left at 30c
+                LocalPosition { ticker: "A".into(), position: 3, cost_cents: 90 },
+                // Buying 3 YES closes the 2 NO and opens 1 YES at 45c
+                LocalPosition { ticker: "C".into(), position: 1, cost_cents: 45 },
+                LocalPosition { ticker: "D".into(), position: 4, cost_cents: 180 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_attribution_sums_to_realized_net() {
+        let quote = |ticker: &str| BracketQuote { ticker: ticker.into(), yes_ask_cents: 30, ..Default::default() };
+        let opp = ArbOpportunity {
+            id: "EV-LONG-0".into(),
+            uuid: Uuid::nil(),
+            event_ticker: "EV".into(),
+            event_title: "Event".into(),
+            direction: ArbDirection::Long,
+            brackets: vec![quote("A"), quote("B"), quote("C")],
+            position_size: 5,
+            sum_cents: 90,
+            total_fees_cents: 24,
+            gross_profit_cents: 50,
+            net_profit_cents: 26,
+            expected_slippage_cents: 0,
+            gross_roi_pct: Decimal::ZERO,
+            net_roi_pct: Decimal::ZERO,
+            slippage_adj_roi_pct: Decimal::ZERO,
+            settles_at: None,
+        };
+        let mut improved = order("o1", "executed");
+        improved.yes_price = Some(29);
+        let mut fills = vec![("A".to_string(), improved), ("B".into(), order("o2", "executed"))];
+        fills.push(("C".into(), order("o3", "executed")));
+
+        // 500 payout - 445 premium - 24 fees; 1c better on A's 5 contracts
+        let full = attribute_pnl(&opp, &fills).unwrap();
+        assert_eq!(full, PnlAttribution { edge_cents: 50, slippage_cents: 5, fee_cents: -24, unfilled_cents: 0 });
+        assert_eq!(realized_net_cents(&opp, &fills), Some(31));
+
+        // C never filled: nothing is owed if it wins, so the premium and fees are the worst case
+        let partial = attribute_pnl(&opp, &fills[..2]).unwrap();
+        assert_eq!((partial.fee_cents, partial.unfilled_cents), (-16, -350));
+        assert_eq!(partial.net_cents(), -311);
+
+        // Selling A and B back at 28 closes them: 295 paid, 280 back, 16 + 16 in fees
+        let sell_back = |id: &str| {
+            let mut o = order(id, "executed");
+            (o.action, o.yes_price) = ("sell".into(), Some(28));
+            o
+        };
+        let mut closed = fills[..2].to_vec();
+        closed.extend([("A".to_string(), sell_back("u1")), ("B".to_string(), sell_back("u2"))]);
+        let unwound = attribute_pnl(&opp, &closed).unwrap();
+        assert_eq!((unwound.slippage_cents, unwound.fee_cents, unwound.net_cents()), (5, -32, -47));
+    }
+
+    #[test]
+    fn test_export_types_columns() {
+        use arrow_array::Array;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let dir = std::env::temp_dir().join(format!("arb-export-test-{}", std::process::id()));
+        let out = dir.join("export");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("reconciliation.md"), "\
+            | Time | Event | Dir | Orders | Statuses | Expected | Actual | Slippage | Edge | Px | Fees | Unfilled | Opp | Inst |\n\
+            | 2026-09-30T23:59:00Z | EV-0 | LONG | a | executed | $0.12 | $0.10 | $-0.02 | weather |\n\
+            | 2026-10-01T00:02:00Z | EV-1 | LONG | a, b | executed, executed | $0.12 | $0.10 | $-0.02 | weather |\n\
+            | 2026-10-02T00:02:00Z | EV-3 | SHORT | d | executed | $0.20 | $-1.50 | $-1.70 (INCOMPLETE) | $0.25 | $0.01 | $-0.02 | $-1.74 | 0b5d3c2e-8f61-4a7e-9d2c-5e4f3a1b6c7d | weather |\n").unwrap();
+        std::fs::write(dir.join("opportunities.md"), "\
+            | 2026-10-01T00:01:00Z | id1 | EV-1 | LONG | 3 | $0.95 | $0.03 | $0.12 | 5.3% | 2.5% | 2.5% | YES | weather |\n\
+            | 2026-10-01T00:03:00Z | id2 | EV-2 | LONG | 3 | $0.95 | $0.03 | $0.12 | 5.3% | 2.5% | 2.5% | NO | 0b5d3c2e-8f61-4a7e-9d2c-5e4f3a1b6c7d | weather |\n").unwrap();
+
+        let utc = crate::clock::ExchangeClock::new(chrono_tz::UTC);
+        let october = Period { since: chrono::NaiveDate::from_ymd_opt(2026, 10, 1), until: None, clock: utc };
+        let tables = export(&dir, october, &out).unwrap();
+        let rows: Vec<_> = tables.iter().map(|t| (t.name, t.rows)).collect();
+        assert_eq!(rows, vec![("opportunities", 2), ("trades", 0), ("reconciliation", 2)]);
+
+        let csv = std::fs::read_to_string(out.join("reconciliation.csv")).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "ts,event_ticker,direction,order_ids,statuses,expected_net_cents,actual_net_cents,\
+            slippage_cents,incomplete,edge_cents,price_slippage_cents,fee_cents,unfilled_cents,opportunity_uuid,instance");
+        // Rows from before attribution leave those columns empty
+        assert_eq!(lines[1], "2026-10-01T00:02:00+00:00,EV-1,LONG,\"a, b\",\"executed, executed\",12,10,-2,false,,,,,,weather");
+        assert_eq!(lines[2], "2026-10-02T00:02:00+00:00,EV-3,SHORT,d,executed,20,-150,-170,true,25,1,-2,-174,0b5d3c2e-8f61-4a7e-9d2c-5e4f3a1b6c7d,weather");
+        let opps = std::fs::read_to_string(out.join("opportunities.csv")).unwrap();
+        // Rows from before opportunity UUIDs have none
+        assert!(opps.contains("\n2026-10-01T00:01:00+00:00,id1,EV-1,LONG,3,95,3,12,5.3,2.5,2.5,true,,weather\n"));
+        assert!(opps.ends_with(",false,0b5d3c2e-8f61-4a7e-9d2c-5e4f3a1b6c7d,weather\n"));
+
+        let file = std::fs::File::open(out.join("reconciliation.parquet")).unwrap();
+        let batch = ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap().next().unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let actual = batch.column_by_name("actual_net_cents").unwrap();
+        let actual = actual.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!((actual.value(0), actual.value(1)), (10, -150));
+        let edge = batch.column_by_name("edge_cents").unwrap();
+        assert!(edge.is_null(0));
+        assert_eq!(batch.schema().field_with_name("incomplete").unwrap().data_type(), &DataType::Boolean);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rotated_logs_are_still_read() {
+        let dir = std::env::temp_dir().join(format!("arb-rotate-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let scan = |day: u32| format!("| 2026-10-{:02}T00:00:00Z | 5 | 10 | 0 | 0 | weather |\n", day);
+        std::fs::write(dir.join("scans.md"), scan(1) + &scan(2)).unwrap();
+        std::fs::write(dir.join("trades.md"), "").unwrap();
+        std::fs::write(dir.join("executions.jsonl"), "{}\n".repeat(100)).unwrap();
+
+        let now: DateTime<Utc> = "2026-10-03T00:15:00Z".parse().unwrap();
+        assert_eq!(rotate_logs(&dir, 60, now).unwrap(), vec!["scans.20261003T001500Z.md"]);
+        assert!(!dir.join("scans.md").exists());
+        assert!(dir.join("executions.jsonl").exists(), "the execution store is never rotated");
+
+        append_line(dir.join("scans.md").to_str().unwrap(), scan(3).trim_end()).unwrap();
+        let days: Vec<String> = read_log_rows(&dir, "scans").unwrap().into_iter().map(|r| r[0].clone()).collect();
+        assert_eq!(days, ["2026-10-01T00:00:00Z", "2026-10-02T00:00:00Z", "2026-10-03T00:00:00Z"]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,7 +1,28 @@
-use anyhow::Result;
-use tracing::{debug, warn};
+use anyhow::{bail, Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
 
-/// Send an alert message via Telegram Bot API.
+use crate::halt;
+use crate::money::dollars;
+use crate::scheduler::{Control, ControlSender, Job, JobControl};
+use crate::status::{StatusReceiver, StatusSnapshot};
+
+/// `[telegram]` — remote control over the alert chat.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct TelegramConfig {
+    /// Long-poll the bot for commands from `TELEGRAM_CHAT_ID` (/status, /pause, /dryrun, ...).
+    #[serde(default)]
+    pub commands: bool,
+}
+
+/// Seconds a `getUpdates` call waits for a message before returning empty.
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+/// Send an alert message via Telegram Bot API, prefixed with the instance name.
 /// Reads TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID from the environment.
 /// If either is missing, silently returns Ok (opt-in alerting).
 pub async fn send_alert(message: &str) -> Result<()> {
@@ -19,15 +40,20 @@ pub async fn send_alert(message: &str) -> Result<()> {
             return Ok(());
         }
     };
+    send_message(&reqwest::Client::new(), &token, &chat_id, message).await;
+    Ok(())
+}
 
+/// Post `message` to `chat_id`, logging (not returning) failures.
+async fn send_message(http: &reqwest::Client, token: &str, chat_id: &str, message: &str) {
     let url = format!("https://api.telegram.org/bot{}/sendMessage", token);
     let body = serde_json::json!({
         "chat_id": chat_id,
-        "text": message,
+        "text": format!("[{}] {}", crate::config::instance_name(), message),
         "parse_mode": "Markdown",
     });
 
-    let resp = reqwest::Client::new().post(&url).json(&body).send().await;
+    let resp = http.post(&url).json(&body).send().await;
     match resp {
         Ok(r) if r.status().is_success() => {
             debug!("Telegram alert sent");
@@ -38,9 +64,247 @@ pub async fn send_alert(message: &str) -> Result<()> {
             warn!("Telegram API returned {}: {}", status, body);
         }
         Err(e) => {
-            warn!("Telegram alert failed: {}", e);
+            // The URL carries the bot token: keep it out of the log
+            warn!("Telegram alert failed: {}", e.without_url());
         }
     }
+}
 
-    Ok(())
+/// A command sent to the bot in the alert chat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BotCommand {
+    Status,
+    /// Pause or resume a job (`scan` when none is named).
+    Pause(Job),
+    Resume(Job),
+    Pnl,
+    Limits,
+    DryRun(bool),
+    /// Create the halt file: no new orders until it is deleted.
+    Halt,
+    Help,
+}
+
+/// Parse a message as a command. `/status@my_bot` is accepted as in group chats; anything
+/// that isn't a command is None, a malformed command an error to reply with.
+pub fn parse_command(text: &str) -> Option<Result<BotCommand, String>> {
+    let mut words = text.split_whitespace();
+    let command = words.next()?.strip_prefix('/')?;
+    let command = command.split('@').next().unwrap_or(command);
+    let arg = words.next();
+    let job = |arg: Option<&str>| match arg {
+        None => Ok(Job::Scan),
+        Some(name) => name.parse::<Job>().map_err(|e| e.to_string()),
+    };
+    Some(match command {
+        "status" => Ok(BotCommand::Status),
+        "pause" => job(arg).map(BotCommand::Pause),
+        "resume" => job(arg).map(BotCommand::Resume),
+        "pnl" => Ok(BotCommand::Pnl),
+        "limits" => Ok(BotCommand::Limits),
+        "dryrun" => match arg {
+            Some("on") => Ok(BotCommand::DryRun(true)),
+            Some("off") => Ok(BotCommand::DryRun(false)),
+            _ => Err("usage: /dryrun on|off".to_string()),
+        },
+        "halt" => Ok(BotCommand::Halt),
+        "help" | "start" => Ok(BotCommand::Help),
+        other => Err(format!("unknown command /{}, try /help", other)),
+    })
+}
+
+const HELP: &str = "/status — cycles, last scan, orders, paused jobs\n/pnl — today's P&L\n/limits — risk counters\n\
+                    /pause [job], /resume [job] — default scan\n/dryrun on|off — stop or resume placing orders\n\
+                    /halt — stop placing orders until the halt file is deleted";
+
+/// Reply to a read-only command from the latest snapshot.
+pub fn describe(command: BotCommand, s: &StatusSnapshot) -> String {
+    let risk = &s.risk;
+    match command {
+        BotCommand::Status => {
+            // Job names in backticks: a bare underscore breaks Markdown parsing
+            let paused: Vec<String> =
+                s.jobs.iter().filter(|j| j.paused).map(|j| format!("`{}`", j.job.name())).collect();
+            let last = match &s.last_cycle {
+                Some(c) => format!(
+                    "{} events, {} opportunities, {} trades in {}ms",
+                    c.events, c.opportunities, c.trades, c.duration_ms
+                ),
+                None => "none yet".to_string(),
+            };
+            format!(
+                "*STATUS* ({})\nUp since {}\nCycles: {}\nLast cycle: {}\nResting orders: {}\nPaused: {}",
+                if s.dry_run { "dry run" } else { "live" },
+                s.started_at.format("%Y-%m-%d %H:%M UTC"),
+                s.cycles,
+                last,
+                s.open_orders,
+                if paused.is_empty() { "none".to_string() } else { paused.join(", ") },
+            )
+        }
+        BotCommand::Pnl => {
+            let paper = &s.paper;
+            let paper = if paper.trades == 0 {
+                String::new()
+            } else {
+                format!(
+                    "\nPaper: {} trades ({} partial, {} open), realized {}, locked {}",
+                    paper.trades,
+                    paper.partial,
+                    paper.open,
+                    dollars(paper.realized_pnl_cents),
+                    dollars(paper.locked_net_cents),
+                )
+            };
+            format!(
+                "*P&L* {}\nToday: {}\nOpen arbs: {}\nSince start: {} opportunities, {} trades{}",
+                risk.trading_day,
+                dollars(risk.daily_pnl_cents),
+                risk.open_arbs,
+                s.total_opportunities,
+                s.total_trades,
+                paper,
+            )
+        }
+        BotCommand::Limits => format!(
+            "*LIMITS* {}\nOpen arbs: {}/{}\nDaily P&L: {} (halt at -{})\nEntry orders: {}/{}\n\
+             Cancels: {}, unwinds: {}\nResets: {}{}{}",
+            risk.trading_day,
+            risk.open_arbs,
+            risk.max_open_arbs,
+            dollars(risk.daily_pnl_cents),
+            dollars(risk.max_daily_loss_cents),
+            risk.daily_orders,
+            risk.max_daily_orders,
+            risk.daily_cancels,
+            risk.daily_unwinds,
+            risk.resets_at.format("%Y-%m-%d %H:%M UTC"),
+            risk.kill_switch.as_ref().map(|k| format!("\n*KILL SWITCH*: {}", k)).unwrap_or_default(),
+            risk.halted.as_ref().map(|h| format!("\n*HALTED*: {}", h)).unwrap_or_default(),
+        ),
+        _ => HELP.to_string(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Updates {
+    ok: bool,
+    #[serde(default)]
+    result: Vec<Update>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    #[serde(default)]
+    message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    chat: Chat,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+async fn get_updates(http: &reqwest::Client, token: &str, offset: i64, timeout_secs: u64) -> Result<Vec<Update>> {
+    let url = format!(
+        "https://api.telegram.org/bot{}/getUpdates?offset={}&timeout={}&allowed_updates=[\"message\"]",
+        token, offset, timeout_secs
+    );
+    // Errors are stripped of the URL, which carries the bot token, before anyone logs them
+    let resp = http.get(&url).send().await.map_err(reqwest::Error::without_url)?;
+    let updates: Updates =
+        resp.json().await.map_err(reqwest::Error::without_url).context("Failed to parse getUpdates")?;
+    if !updates.ok {
+        bail!("getUpdates returned ok=false");
+    }
+    Ok(updates.result)
+}
+
+/// Long-poll the bot for commands until the process exits. Only messages from
+/// `TELEGRAM_CHAT_ID` are acted on. Commands sent while the bot was down are skipped, so
+/// a stale `/dryrun off` can't take effect hours later. `/dryrun off` is refused unless the
+/// bot was started live (`live_allowed`). `/halt` creates `halt_file`; only deleting it on
+/// the host resumes trading.
+pub async fn run_commands(
+    status: StatusReceiver,
+    control: ControlSender,
+    live_allowed: bool,
+    halt_file: PathBuf,
+) -> Result<()> {
+    let token = std::env::var("TELEGRAM_BOT_TOKEN").context("TELEGRAM_BOT_TOKEN not set")?;
+    let chat_id = std::env::var("TELEGRAM_CHAT_ID").context("TELEGRAM_CHAT_ID not set")?;
+    let http = reqwest::Client::builder().timeout(Duration::from_secs(POLL_TIMEOUT_SECS + 10)).build()?;
+
+    // Offset -1 returns only the latest update; acknowledging it discards the backlog
+    let backlog = get_updates(&http, &token, -1, 0).await.context("Failed to reach the Telegram bot")?;
+    let mut offset = backlog.last().map_or(0, |u| u.update_id + 1);
+    info!("Telegram commands enabled");
+
+    loop {
+        let updates = match get_updates(&http, &token, offset, POLL_TIMEOUT_SECS).await {
+            Ok(updates) => updates,
+            Err(e) => {
+                warn!(error = %e, "Telegram poll failed, retrying");
+                sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        for update in updates {
+            offset = offset.max(update.update_id + 1);
+            let Some(message) = update.message else { continue };
+            if message.chat.id.to_string() != chat_id {
+                warn!(chat = message.chat.id, "Ignoring Telegram message from another chat");
+                continue;
+            }
+            let Some(parsed) = message.text.as_deref().and_then(parse_command) else { continue };
+            info!(command = ?parsed, "Telegram command");
+            let reply = match parsed {
+                Err(e) => e,
+                Ok(BotCommand::DryRun(false)) if !live_allowed => {
+                    "Refused: started with DRY_RUN=true, restart live to place orders".to_string()
+                }
+                Ok(BotCommand::Pause(job) | BotCommand::Resume(job)) => {
+                    let paused = matches!(parsed, Ok(BotCommand::Pause(_)));
+                    match control.send(Control::Job(JobControl { job, paused })) {
+                        Ok(()) => format!("`{}` {}", job.name(), if paused { "paused" } else { "resumed" }),
+                        Err(_) => "Scheduler stopped".to_string(),
+                    }
+                }
+                Ok(BotCommand::DryRun(on)) => match control.send(Control::DryRun(on)) {
+                    Ok(()) => format!("Dry run {} from the next job", if on { "on" } else { "off" }),
+                    Err(_) => "Scheduler stopped".to_string(),
+                },
+                Ok(BotCommand::Halt) => match halt::create(&halt_file, "halted from Telegram") {
+                    Ok(()) => format!("*HALTED*: no new orders. Delete `{}` to resume", halt_file.display()),
+                    Err(e) => format!("Halt failed: {:#}", e),
+                },
+                Ok(command) => describe(command, &status.borrow()),
+            };
+            send_message(&http, &token, &chat_id, &reply).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse_command("/status@arb_bot"), Some(Ok(BotCommand::Status)));
+        assert_eq!(parse_command("/pause"), Some(Ok(BotCommand::Pause(Job::Scan))));
+        assert_eq!(parse_command("/resume settlement"), Some(Ok(BotCommand::Resume(Job::Settlement))));
+        assert_eq!(parse_command("/dryrun off"), Some(Ok(BotCommand::DryRun(false))));
+        assert_eq!(parse_command("/halt"), Some(Ok(BotCommand::Halt)));
+        assert!(matches!(parse_command("/dryrun maybe"), Some(Err(_))));
+        assert!(matches!(parse_command("/pause nothing"), Some(Err(_))));
+        assert_eq!(parse_command("hello"), None);
+    }
 }
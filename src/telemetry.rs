@@ -0,0 +1,112 @@
+// Sampling for debug events that fire once per orderbook, event, or request. At debug
+// level a busy scan writes thousands of these a minute, so `[telemetry]` keeps 1 in
+// `sample_every` of each kind and caps each kind at `max_per_sec`. Every line that does go
+// out carries `dropped`, how many of its kind were left out since the previous one.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// `[telemetry]` — how much of the high-volume debug output is written.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct TelemetryConfig {
+    /// Log 1 in this many of each high-volume debug event (1 = every one).
+    #[serde(default = "default_sample_every")]
+    pub sample_every: u64,
+    /// At most this many lines per second of each kind (0 = no cap).
+    #[serde(default)]
+    pub max_per_sec: u32,
+}
+
+fn default_sample_every() -> u64 { 1 }
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self { sample_every: default_sample_every(), max_per_sec: 0 }
+    }
+}
+
+static TELEMETRY: OnceLock<TelemetryConfig> = OnceLock::new();
+
+/// Install the configured sampling for every `Sampled`. First call wins.
+pub fn init(config: &TelemetryConfig) {
+    TELEMETRY.get_or_init(|| config.clone());
+}
+
+/// One kind of high-volume debug event, declared as a `static` beside its `debug!`.
+pub struct Sampled {
+    seen: AtomicU64,
+    dropped: AtomicU64,
+    /// Start of the current one-second window and lines logged in it.
+    window: Mutex<Option<(Instant, u32)>>,
+}
+
+impl Sampled {
+    pub const fn new() -> Self {
+        Self { seen: AtomicU64::new(0), dropped: AtomicU64::new(0), window: Mutex::new(None) }
+    }
+
+    /// Whether to log this occurrence: Some(occurrences dropped since the last one logged),
+    /// or None to leave it out.
+    pub fn sample(&self) -> Option<u64> {
+        self.sample_at(TELEMETRY.get_or_init(TelemetryConfig::default), Instant::now())
+    }
+
+    fn sample_at(&self, config: &TelemetryConfig, now: Instant) -> Option<u64> {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed);
+        if n.is_multiple_of(config.sample_every.max(1)) && self.within_rate(config.max_per_sec, now) {
+            Some(self.dropped.swap(0, Ordering::Relaxed))
+        } else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+
+    fn within_rate(&self, max_per_sec: u32, now: Instant) -> bool {
+        if max_per_sec == 0 {
+            return true;
+        }
+        let mut window = self.window.lock().unwrap();
+        match window.as_mut() {
+            Some((start, logged)) if now.duration_since(*start) < Duration::from_secs(1) => {
+                *logged += 1;
+                *logged <= max_per_sec
+            }
+            _ => {
+                *window = Some((now, 1));
+                true
+            }
+        }
+    }
+}
+
+impl Default for Sampled {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampling_and_rate_cap_count_what_they_drop() {
+        let start = Instant::now();
+        let every_third = TelemetryConfig { sample_every: 3, max_per_sec: 0 };
+        let site = Sampled::new();
+        let kept: Vec<Option<u64>> = (0..7).map(|_| site.sample_at(&every_third, start)).collect();
+        assert_eq!(kept, [Some(0), None, None, Some(2), None, None, Some(2)]);
+
+        // Two a second: the third in the same second is dropped, a new second starts over
+        let capped = TelemetryConfig { sample_every: 1, max_per_sec: 2 };
+        let site = Sampled::new();
+        assert_eq!(site.sample_at(&capped, start), Some(0));
+        assert_eq!(site.sample_at(&capped, start), Some(0));
+        assert_eq!(site.sample_at(&capped, start + Duration::from_millis(500)), None);
+        assert_eq!(site.sample_at(&capped, start + Duration::from_millis(999)), None);
+        assert_eq!(site.sample_at(&capped, start + Duration::from_secs(1)), Some(2));
+    }
+}
@@ -0,0 +1,97 @@
+// What the detector needs from an exchange, so the same bracket scan can run against
+// Kalshi and other venues with mutually exclusive outcome sets (Polymarket).
+
+use anyhow::Result;
+use chrono::Utc;
+use std::future::Future;
+use tracing::{debug, warn};
+
+use crate::config::{RiskConfig, ScannerConfig};
+use crate::detector::{arb_candidate_markets, evaluate_arb, quote_from_orderbook, DetectParams, Evaluation};
+use crate::kalshi::client::KalshiClient;
+use crate::kalshi::types::{CreateOrderRequest, Event, Order, Orderbook};
+
+/// An exchange, in Kalshi's terms: events holding markets, each with a YES/NO bid book.
+/// Venues translate their own shapes into these types.
+pub trait Venue: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Open events with their markets. `group` narrows the listing the way the venue
+    /// groups events: a series ticker on Kalshi, a tag slug on Polymarket.
+    fn list_events(&self, group: &str) -> impl Future<Output = Result<Vec<Event>>> + Send;
+
+    fn get_orderbook(&self, market: &str) -> impl Future<Output = Result<Orderbook>> + Send;
+
+    fn place_order(&self, req: &CreateOrderRequest) -> impl Future<Output = Result<Order>> + Send;
+
+    fn cancel_order(&self, order_id: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl Venue for KalshiClient {
+    fn name(&self) -> &'static str {
+        "kalshi"
+    }
+
+    async fn list_events(&self, group: &str) -> Result<Vec<Event>> {
+        Ok(self.get_events(group).await?)
+    }
+
+    async fn get_orderbook(&self, market: &str) -> Result<Orderbook> {
+        Ok(KalshiClient::get_orderbook(self, market).await?)
+    }
+
+    async fn place_order(&self, req: &CreateOrderRequest) -> Result<Order> {
+        Ok(self.create_order(req).await?)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        Ok(KalshiClient::cancel_order(self, order_id).await?)
+    }
+}
+
+/// Run the bracket detector over one group of a venue's events: every mutually exclusive
+/// event within the scanner's bracket bounds is quoted in full and evaluated both ways.
+/// Returns every evaluation, passing or not.
+pub async fn detect<V: Venue>(
+    venue: &V,
+    group: &str,
+    scanner: &ScannerConfig,
+    risk: &RiskConfig,
+) -> Result<Vec<Evaluation>> {
+    let params = DetectParams::from_config(risk);
+    let mut evaluations = Vec::new();
+    for event in venue.list_events(group).await? {
+        if !event.mutually_exclusive {
+            continue;
+        }
+        let Some(markets) = arb_candidate_markets(&event.markets, Utc::now()) else {
+            continue;
+        };
+        if markets.len() < scanner.min_brackets || markets.len() > scanner.max_brackets {
+            continue;
+        }
+        let mut quotes = Vec::new();
+        for market in &markets {
+            let book = match venue.get_orderbook(&market.ticker).await {
+                Ok(book) => book,
+                Err(e) => {
+                    warn!(venue = venue.name(), market = %market.ticker, error = %e, "Failed to fetch orderbook");
+                    break;
+                }
+            };
+            match quote_from_orderbook(&market.ticker, &market.title, &book, risk.min_level_quantity as i64) {
+                Some(mut quote) => {
+                    quote.label = market.bracket_label();
+                    quotes.push(quote);
+                }
+                None => break,
+            }
+        }
+        if quotes.len() != markets.len() {
+            debug!(venue = venue.name(), event = %event.event_ticker, "Not every bracket quoted, skipping");
+            continue;
+        }
+        evaluations.extend(evaluate_arb(&event.event_ticker, &event.title, &quotes, &params));
+    }
+    Ok(evaluations)
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+
+use crate::status::CycleStats;
+
+/// Startup cycles scanned as dry runs whatever `DRY_RUN` says: they warm the series
+/// cache and skip memo, exercise parsing against live data, and time a full cycle
+/// before the first order goes out. Only successful cycles count. Jobs that place or
+/// prompt orders outside the scan (exits, relations) hold off until it is over too.
+pub struct Warmup {
+    remaining: u32,
+    cycle_ms: Vec<u64>,
+}
+
+impl Warmup {
+    pub fn new(cycles: u32) -> Self {
+        Self { remaining: cycles, cycle_ms: Vec::new() }
+    }
+
+    pub fn active(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Warmup cycles still to run.
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Count a finished warmup cycle; a failed one doesn't count. Returns the average
+    /// cycle time once the last one is done.
+    pub fn record(&mut self, cycle: &Result<CycleStats>) -> Option<u64> {
+        let stats = cycle.as_ref().ok().filter(|_| self.active())?;
+        self.remaining -= 1;
+        self.cycle_ms.push(stats.duration_ms);
+        (self.remaining == 0).then(|| self.baseline_ms())
+    }
+
+    fn baseline_ms(&self) -> u64 {
+        self.cycle_ms.iter().sum::<u64>() / self.cycle_ms.len().max(1) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn cycle(duration_ms: u64) -> Result<CycleStats> {
+        Ok(CycleStats {
+            started_at: Utc::now(),
+            duration_ms,
+            series: 3,
+            series_deferred: 0,
+            events: 10,
+            opportunities: 0,
+            trades: 0,
+            canary_trades: 0,
+            shadow_opportunities: 0,
+            shadow_net_cents: 0,
+        })
+    }
+
+    #[test]
+    fn test_only_successful_cycles_count() {
+        let mut warmup = Warmup::new(2);
+        assert!(warmup.active());
+        assert_eq!(warmup.record(&cycle(1_000)), None);
+        assert_eq!(warmup.record(&Err(anyhow::anyhow!("series list unavailable"))), None);
+        assert_eq!(warmup.remaining(), 1);
+        assert!(warmup.active());
+
+        // The baseline averages the successful cycles only
+        assert_eq!(warmup.record(&cycle(3_000)), Some(2_000));
+        assert!(!warmup.active());
+        // Later cycles are live and not counted
+        assert_eq!(warmup.record(&cycle(9_000)), None);
+        assert_eq!(warmup.remaining(), 0);
+    }
+
+    #[test]
+    fn test_no_warmup_cycles_is_live_from_the_start() {
+        let mut warmup = Warmup::new(0);
+        assert!(!warmup.active());
+        assert_eq!(warmup.record(&cycle(1_000)), None);
+    }
+}
@@ -0,0 +1,106 @@
+// Fast re-scan of the markets where the bot has live risk: its resting orders and the legs
+// of arbs that never completed. The scan cycle reaches them once per interval at best,
+// and they're the books that matter most, so the `watch` job re-checks them every few
+// seconds, and scan cycles run it between series when it comes due mid-cycle.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use tokio::time::{Duration, Instant};
+
+use crate::storage::OrderStateRecord;
+
+/// Markets re-quoted per pass, those with resting orders first.
+pub const MAX_MARKETS: usize = 20;
+
+/// A market with live risk and its latest quote, published in `/status`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchedMarket {
+    pub ticker: String,
+    /// Orders of ours resting in it.
+    pub resting: usize,
+    /// Holds a leg of an arb that never completed.
+    pub unhedged: bool,
+    pub yes_bid_cents: Option<i64>,
+    pub yes_ask_cents: Option<i64>,
+}
+
+/// The markets to watch, resting orders first, then by ticker, at most `MAX_MARKETS`.
+pub fn hot_markets(resting: &[&OrderStateRecord], unhedged: &HashSet<String>) -> Vec<WatchedMarket> {
+    let mut markets: BTreeMap<&str, (usize, bool)> = BTreeMap::new();
+    for order in resting {
+        markets.entry(order.ticker.as_str()).or_default().0 += 1;
+    }
+    for ticker in unhedged {
+        markets.entry(ticker.as_str()).or_default().1 = true;
+    }
+    let mut markets: Vec<WatchedMarket> = markets
+        .into_iter()
+        .map(|(ticker, (resting, unhedged))| WatchedMarket {
+            ticker: ticker.to_string(),
+            resting,
+            unhedged,
+            yes_bid_cents: None,
+            yes_ask_cents: None,
+        })
+        .collect();
+    markets.sort_by_key(|m| m.resting == 0);
+    markets.truncate(MAX_MARKETS);
+    markets
+}
+
+/// Paces the passes a scan cycle makes on the `watch` job's behalf.
+#[derive(Debug)]
+pub struct RiskWatch {
+    /// None when the job isn't on a fixed interval (scan cycles then leave it alone).
+    interval: Option<Duration>,
+    last_run: Option<Instant>,
+}
+
+impl RiskWatch {
+    pub fn new(interval: Option<Duration>) -> Self {
+        Self { interval, last_run: None }
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        match (self.interval, self.last_run) {
+            (Some(interval), Some(last)) => now.duration_since(last) >= interval,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    pub fn ran(&mut self, now: Instant) {
+        self.last_run = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_hot_markets_put_resting_orders_first() {
+        let order = |ticker: &str| OrderStateRecord {
+            opportunity_id: "EV-LONG-1".into(),
+            order_id: format!("o-{}", ticker),
+            ticker: ticker.into(),
+            status: "resting".into(),
+            updated_at: Utc::now(),
+        };
+        let (b, c) = (order("B"), order("C"));
+        let unhedged = HashSet::from(["A".to_string(), "C".to_string()]);
+        let markets = hot_markets(&[&c, &b, &c], &unhedged);
+        let summary: Vec<(&str, usize, bool)> =
+            markets.iter().map(|m| (m.ticker.as_str(), m.resting, m.unhedged)).collect();
+        assert_eq!(summary, [("B", 1, false), ("C", 2, true), ("A", 0, true)]);
+
+        let mut watch = RiskWatch::new(Some(Duration::from_secs(5)));
+        let start = Instant::now();
+        assert!(watch.due(start));
+        watch.ran(start);
+        assert!(!watch.due(start + Duration::from_secs(4)));
+        assert!(watch.due(start + Duration::from_secs(5)));
+        assert!(!RiskWatch::new(None).due(start));
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// `watchlist.toml` — which series and events the scanner looks at. Re-read every
+/// cycle, separately from `config.toml`, so it can be edited while the bot runs.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Watchlist {
+    /// Series to scan; replaces `[scanner] series_filter` when non-empty.
+    #[serde(default)]
+    pub series: Vec<String>,
+    /// Series never scanned, whatever the filters say.
+    #[serde(default)]
+    pub blocked_series: Vec<String>,
+    /// Events never traded (exact event tickers).
+    #[serde(default)]
+    pub blocked_events: Vec<String>,
+}
+
+impl Watchlist {
+    pub fn allows_series(&self, ticker: &str, config_filter: &[String]) -> bool {
+        let filter = if self.series.is_empty() { config_filter } else { &self.series };
+        (filter.is_empty() || filter.iter().any(|s| s == ticker)) && !self.blocked_series.iter().any(|s| s == ticker)
+    }
+
+    pub fn allows_event(&self, event_ticker: &str) -> bool {
+        !self.blocked_events.iter().any(|e| e == event_ticker)
+    }
+}
+
+/// The watchlist file and the last version of it that parsed.
+pub struct WatchlistFile {
+    path: PathBuf,
+    content: Option<String>,
+    current: Watchlist,
+}
+
+impl WatchlistFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, content: None, current: Watchlist::default() }
+    }
+
+    /// Re-read the file. An edit that doesn't parse is reported and ignored, keeping the
+    /// last good list; a missing file means no watchlist (config filters only).
+    pub fn refresh(&mut self) -> &Watchlist {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                warn!(path = %self.path.display(), error = %e, "Failed to read watchlist, keeping the last one");
+                return &self.current;
+            }
+        };
+        if self.content.as_deref() == Some(content.as_str()) {
+            return &self.current;
+        }
+        match toml::from_str::<Watchlist>(&content) {
+            Ok(list) => {
+                info!(
+                    series = list.series.len(),
+                    blocked_series = list.blocked_series.len(),
+                    blocked_events = list.blocked_events.len(),
+                    "Watchlist loaded"
+                );
+                self.current = list;
+            }
+            Err(e) => warn!(path = %self.path.display(), error = %e, "Invalid watchlist, keeping the last good one"),
+        }
+        // Remember bad content too, so it's reported once rather than every cycle
+        self.content = Some(content);
+        &self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_keeps_last_good_list() {
+        let path = std::env::temp_dir().join(format!("arb-watchlist-{}.toml", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let mut file = WatchlistFile::new(path.clone());
+        let config_filter = vec!["KXHIGHNY".to_string(), "KXRAIN".to_string()];
+        assert!(file.refresh().allows_series("KXRAIN", &config_filter));
+
+        std::fs::write(&path, "blocked_series = [\"KXRAIN\"]\nblocked_events = [\"KXHIGHNY-26OCT17\"]\n").unwrap();
+        let list = file.refresh().clone();
+        assert!(!list.allows_series("KXRAIN", &config_filter));
+        assert!(list.allows_series("KXHIGHNY", &config_filter));
+        assert!(!list.allows_series("KXSNOW", &config_filter));
+        assert!(!list.allows_event("KXHIGHNY-26OCT17"));
+
+        // A typo doesn't wipe the list
+        std::fs::write(&path, "blocked_seires = [\"KXHIGHNY\"]\n").unwrap();
+        assert_eq!(file.refresh(), &list);
+
+        std::fs::write(&path, "series = [\"KXSNOW\"]\n").unwrap();
+        assert!(file.refresh().allows_series("KXSNOW", &config_filter));
+        assert!(!file.refresh().allows_series("KXHIGHNY", &config_filter));
+        std::fs::remove_file(&path).ok();
+    }
+}
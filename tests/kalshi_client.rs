@@ -0,0 +1,563 @@
+//! Client and executor against a mock exchange. Each test starts its own wiremock server
+//! and points a `KalshiClient` at it, so nothing here reaches Kalshi.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use rsa::pkcs1::{EncodeRsaPrivateKey, LineEnding};
+use rsa::RsaPrivateKey;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use wiremock::matchers::{body_partial_json, header_exists, method, path, query_param, query_param_is_missing};
+use uuid::Uuid;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use bracket_arb::config::{KalshiConfig, ScannerConfig};
+use bracket_arb::detector;
+use bracket_arb::executor;
+use bracket_arb::kalshi::auth::KalshiAuth;
+use bracket_arb::kalshi::client::KalshiClient;
+use bracket_arb::kalshi::error::{ApiErrorKind, KalshiError};
+use bracket_arb::kalshi::types::{ArbDirection, ArbOpportunity, BracketQuote};
+
+/// Generous enough that only the test that delays a reply on purpose runs into it.
+const BUDGET: Duration = Duration::from_secs(10);
+
+/// One throwaway key for the whole run; generating it is the slowest part of a test.
+fn test_pem() -> &'static str {
+    static PEM: OnceLock<String> = OnceLock::new();
+    PEM.get_or_init(|| {
+        let key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+        key.to_pkcs1_pem(LineEnding::LF).unwrap().to_string()
+    })
+}
+
+fn client(server: &MockServer) -> KalshiClient {
+    let auth = KalshiAuth::from_pem(test_pem(), "test-key".into()).unwrap();
+    KalshiClient::with_base_url(auth, &server.uri()).unwrap()
+}
+
+/// Exchange reply for an order on `ticker` with `filled` of `count` contracts done.
+fn order(id: &str, ticker: &str, status: &str, count: i64, filled: i64) -> Value {
+    json!({
+        "order": {
+            "order_id": id,
+            "ticker": ticker,
+            "status": status,
+            "action": "buy",
+            "side": "yes",
+            "type": "limit",
+            "yes_price": 30,
+            "no_price": 70,
+            "count": count,
+            "remaining_count": count - filled,
+            "fill_count": filled,
+            "initial_count": count,
+        }
+    })
+}
+
+/// No Retry-After wait so retries don't slow the suite.
+fn rate_limited() -> ResponseTemplate {
+    ResponseTemplate::new(429).insert_header("retry-after", "0").set_body_string("too many requests")
+}
+
+fn long_opportunity(tickers: &[&str], size: u32) -> ArbOpportunity {
+    ArbOpportunity {
+        id: "EV-LONG-0".into(),
+        uuid: Uuid::nil(),
+        event_ticker: "EV".into(),
+        event_title: "Event".into(),
+        direction: ArbDirection::Long,
+        brackets: tickers
+            .iter()
+            .map(|t| BracketQuote {
+                ticker: t.to_string(),
+                title: format!("{} title", t),
+                yes_ask_cents: 30,
+                yes_bid_cents: 28,
+                depth_at_no: 100,
+                depth_at_yes: 100,
+                ..Default::default()
+            })
+            .collect(),
+        position_size: size,
+        sum_cents: 30 * tickers.len() as i64,
+        total_fees_cents: 0,
+        gross_profit_cents: 10,
+        net_profit_cents: 10,
+        expected_slippage_cents: 0,
+        gross_roi_pct: Decimal::ZERO,
+        net_roi_pct: Decimal::ZERO,
+        slippage_adj_roi_pct: Decimal::ZERO,
+        settles_at: None,
+    }
+}
+
+/// Client order ID of `long_opportunity`'s leg in `ticker`.
+fn leg_id(ticker: &str) -> String {
+    executor::leg_client_order_id(&Uuid::nil(), ticker)
+}
+
+#[tokio::test]
+async fn test_reads_series_events_and_orderbook() {
+    let server = MockServer::start().await;
+    // Two pages of series, signed on every request
+    Mock::given(method("GET"))
+        .and(path("/series"))
+        .and(query_param_is_missing("cursor"))
+        .and(header_exists("KALSHI-ACCESS-SIGNATURE"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "series": [{ "ticker": "KXA", "title": "A" }],
+            "cursor": "page2",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/series"))
+        .and(query_param("cursor", "page2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "series": [{ "ticker": "KXB", "title": "B", "status": "active" }],
+            "cursor": "",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/events"))
+        .and(query_param("series_ticker", "KXA"))
+        .and(query_param("with_nested_markets", "true"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "events": [{
+                "event_ticker": "KXA-1",
+                "title": "Event 1",
+                "mutually_exclusive": true,
+                "markets": [
+                    { "ticker": "KXA-1-B1", "title": "B1", "status": "active", "result": "" },
+                    { "ticker": "KXA-1-B2", "title": "B2", "status": "active", "result": "" },
+                ],
+            }],
+            "cursor": null,
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/markets/KXA-1-B1/orderbook"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("fixtures/orderbook_both_sides.json")),
+        )
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/markets/trades"))
+        .and(query_param("ticker", "KXA-1-B1"))
+        .and(query_param("min_ts", "1800000000"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "trades": [
+                {
+                    "trade_id": "t1", "ticker": "KXA-1-B1", "count": 4, "yes_price": 30,
+                    "created_time": "2027-01-15T08:05:00Z",
+                },
+                { "trade_id": "t2", "ticker": "KXA-1-B1", "count": 7, "created_time": "2027-01-15T08:06:00Z" },
+            ],
+            "cursor": "",
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let series = client.list_series().await.unwrap();
+    assert_eq!(series.iter().map(|s| s.ticker.as_str()).collect::<Vec<_>>(), ["KXA", "KXB"]);
+
+    let events = client.get_events("KXA").await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].markets.len(), 2);
+
+    let book = client.get_orderbook("KXA-1-B1").await.unwrap();
+    assert_eq!((book.yes.len(), book.no.len()), (5, 2));
+
+    let since = chrono::DateTime::from_timestamp(1_800_000_000, 0).unwrap();
+    assert_eq!(client.recent_volume("KXA-1-B1", since, 20).await.unwrap(), 11);
+}
+
+#[tokio::test]
+async fn test_volume_gate_stops_paging_once_enough_traded() {
+    let server = MockServer::start().await;
+    let trades = |ticker: &str, count: i64, cursor: &str| {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "trades": [{ "trade_id": "t", "ticker": ticker, "count": count, "created_time": "2027-01-15T08:05:00Z" }],
+            "cursor": cursor,
+        }))
+    };
+    // B1's first page already clears the floor: its second is never asked for
+    Mock::given(method("GET"))
+        .and(path("/markets/trades"))
+        .and(query_param("ticker", "KXA-1-B1"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(trades("KXA-1-B1", 25, "more"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/markets/trades"))
+        .and(query_param("cursor", "more"))
+        .respond_with(trades("KXA-1-B1", 25, ""))
+        .expect(0)
+        .mount(&server)
+        .await;
+    // B2 needs both of its pages, and still falls short
+    Mock::given(method("GET"))
+        .and(path("/markets/trades"))
+        .and(query_param("ticker", "KXA-1-B2"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(trades("KXA-1-B2", 8, "b2-page2"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/markets/trades"))
+        .and(query_param("cursor", "b2-page2"))
+        .respond_with(trades("KXA-1-B2", 4, ""))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let quotes = long_opportunity(&["KXA-1-B1", "KXA-1-B2"], 5).brackets;
+    let mut scanner: ScannerConfig = toml::from_str("interval_secs = 60\nmin_recent_volume = 20").unwrap();
+    assert_eq!(detector::low_volume_bracket(&client, &quotes, &scanner).await, Some(("KXA-1-B2".into(), 12)));
+
+    // Off without a floor: nothing is fetched
+    scanner.min_recent_volume = None;
+    assert_eq!(detector::low_volume_bracket(&client, &quotes, &scanner).await, None);
+}
+
+#[tokio::test]
+async fn test_rate_limited_reads_and_orders_are_retried() {
+    let server = MockServer::start().await;
+    // The first read is throttled, the retry goes through
+    Mock::given(method("GET"))
+        .and(path("/portfolio/balance"))
+        .respond_with(rate_limited())
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/portfolio/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "balance": 12_345 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // Orders stay throttled through every retry (two by default for writes)
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .respond_with(rate_limited())
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    assert_eq!(client.get_balance().await.unwrap(), 12_345);
+
+    let opp = long_opportunity(&["EV-B1"], 5);
+    let result = executor::execute_arb_unlogged(&client, &opp, 5, BUDGET).await.unwrap();
+    assert_eq!(result.api_failures, ["EV-B1"]);
+    assert!(result.is_total_failure());
+}
+
+#[tokio::test]
+async fn test_partial_fill_is_classified_and_cancellable() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B1", "action": "buy", "side": "yes", "yes_price": 30 })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(order("ord-1", "EV-B1", "executed", 5, 5)))
+        .mount(&server)
+        .await;
+    // Only 2 of 5 filled; the rest rests on the book
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B2" })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(order("ord-2", "EV-B2", "resting", 5, 2)))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B3" })))
+        .respond_with(ResponseTemplate::new(400).set_body_string("market closed"))
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/portfolio/orders/ord-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(order("ord-2", "EV-B2", "canceled", 5, 2)))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/portfolio/orders/ord-9"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let opp = long_opportunity(&["EV-B1", "EV-B2", "EV-B3"], 5);
+    let result = executor::execute_arb_unlogged(&client, &opp, 5, BUDGET).await.unwrap();
+
+    assert!(!result.is_fully_filled() && !result.is_total_failure());
+    assert_eq!(result.filled.len(), 1);
+    assert_eq!(result.resting.len(), 1);
+    assert_eq!(result.resting[0].1.filled_count(), 2);
+    assert_eq!(result.api_failures, ["EV-B3"]);
+
+    let outcomes = client.cancel_orders(&["ord-2".into(), "ord-9".into()]).await;
+    assert_eq!(outcomes.canceled, ["ord-2"]);
+    assert_eq!(outcomes.failed.len(), 1);
+    assert!(outcomes.failed[0].1.to_string().contains("404"), "{}", outcomes.failed[0].1);
+}
+
+#[tokio::test]
+async fn test_series_fetch_resumes_from_last_cursor() {
+    let server = MockServer::start().await;
+    // The first page is only ever fetched once: the retry picks up at page 2
+    Mock::given(method("GET"))
+        .and(path("/series"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "series": [{ "ticker": "KXA", "title": "A" }],
+            "cursor": "page2",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/series"))
+        .and(query_param("cursor", "page2"))
+        .respond_with(ResponseTemplate::new(502).set_body_string("bad gateway"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/series"))
+        .and(query_param("cursor", "page2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "series": [{ "ticker": "KXB", "title": "B" }],
+            "cursor": null,
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let err = client.list_series().await.unwrap_err();
+    assert!(matches!(err, KalshiError::Page { page: 2, .. }), "{}", err);
+
+    let series = client.list_series().await.unwrap();
+    assert_eq!(series.iter().map(|s| s.ticker.as_str()).collect::<Vec<_>>(), ["KXA", "KXB"]);
+}
+
+#[tokio::test]
+async fn test_resting_leg_that_fills_is_reclassified() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B1" })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(order("ord-1", "EV-B1", "executed", 5, 5)))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B2" })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(order("ord-2", "EV-B2", "resting", 5, 0)))
+        .mount(&server)
+        .await;
+    // Still resting on the first check, filled on the second
+    Mock::given(method("GET"))
+        .and(path("/portfolio/orders/ord-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(order("ord-2", "EV-B2", "resting", 5, 3)))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/portfolio/orders/ord-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(order("ord-2", "EV-B2", "executed", 5, 5)))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let opp = long_opportunity(&["EV-B1", "EV-B2"], 5);
+    let placed = executor::execute_arb_unlogged(&client, &opp, 5, BUDGET).await.unwrap();
+    assert_eq!(placed.resting.len(), 1);
+
+    let result = executor::await_fills(&client, placed, Duration::from_secs(5)).await;
+    assert!(result.is_fully_filled(), "{:?}", result);
+    assert_eq!(result.filled.len(), 2);
+}
+
+#[tokio::test]
+async fn test_leg_past_execution_budget_is_looked_up() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B1", "client_order_id": leg_id("EV-B1") })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(order("ord-1", "EV-B1", "executed", 5, 5)))
+        .mount(&server)
+        .await;
+    // The second leg lands on the exchange, but its reply is stuck well past the budget
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B2" })))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .set_body_json(order("ord-2", "EV-B2", "resting", 5, 0))
+                .set_delay(Duration::from_secs(30)),
+        )
+        .mount(&server)
+        .await;
+    let mut resting = order("ord-2", "EV-B2", "resting", 5, 0)["order"].clone();
+    resting["client_order_id"] = json!(leg_id("EV-B2"));
+    Mock::given(method("GET"))
+        .and(path("/portfolio/orders"))
+        .and(query_param("ticker", "EV-B2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "orders": [resting], "cursor": null })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // Nothing answers for the third leg: it was never placed
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B3" })))
+        .respond_with(ResponseTemplate::new(201).set_delay(Duration::from_secs(30)))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/portfolio/orders"))
+        .and(query_param("ticker", "EV-B3"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "orders": [], "cursor": null })))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let opp = long_opportunity(&["EV-B1", "EV-B2", "EV-B3"], 5);
+    let started = std::time::Instant::now();
+    let result = executor::execute_arb_unlogged(&client, &opp, 5, Duration::from_millis(300)).await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(10), "{:?}", started.elapsed());
+    assert_eq!(result.filled.len(), 1);
+    assert_eq!(result.resting[0].1.order_id, "ord-2");
+    assert_eq!(result.api_failures, ["EV-B3"]);
+    // Past the reply budget but with no max_execution: judged as usual, not aborted
+    assert!(!result.aborted);
+}
+
+#[tokio::test]
+async fn test_failed_post_that_landed_is_not_ordered_twice() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B1" })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(order("ord-1", "EV-B1", "executed", 5, 5)))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // The exchange takes the second leg but answers with a 5xx
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B2" })))
+        .respond_with(ResponseTemplate::new(502).set_body_string("bad gateway"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    for (id, ticker) in [("ord-1", "EV-B1"), ("ord-2", "EV-B2")] {
+        let mut landed = order(id, ticker, "executed", 5, 5)["order"].clone();
+        landed["client_order_id"] = json!(leg_id(ticker));
+        Mock::given(method("GET"))
+            .and(path("/portfolio/orders"))
+            .and(query_param("ticker", ticker))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "orders": [landed], "cursor": null })))
+            .mount(&server)
+            .await;
+    }
+
+    let client = client(&server);
+    let opp = long_opportunity(&["EV-B1", "EV-B2"], 5);
+    let result = executor::execute_arb_unlogged(&client, &opp, 5, BUDGET).await.unwrap();
+    assert!(result.is_fully_filled(), "{:?}", result);
+    assert!(result.api_failures.is_empty());
+
+    // Placing the same opportunity again sends nothing: its client order IDs are refused
+    // locally and the legs already on the exchange are reported instead
+    let again = executor::execute_arb_unlogged(&client, &opp, 5, BUDGET).await.unwrap();
+    assert!(again.is_fully_filled(), "{:?}", again);
+    assert_eq!(again.filled.len(), 2);
+}
+
+#[tokio::test]
+async fn test_refused_legs_are_classified_and_server_errors_retried() {
+    let server = MockServer::start().await;
+    // First attempt at EV-B1 hits a 503 and never reaches the book; the resend goes through
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B1" })))
+        .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B1" })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(order("ord-1", "EV-B1", "executed", 5, 5)))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/portfolio/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "orders": [], "cursor": null })))
+        .mount(&server)
+        .await;
+    // EV-B2 is refused for balance: not retried
+    Mock::given(method("POST"))
+        .and(path("/portfolio/orders"))
+        .and(body_partial_json(json!({ "ticker": "EV-B2" })))
+        .respond_with(ResponseTemplate::new(400).set_body_json(
+            json!({ "error": { "code": "insufficient_balance", "message": "Insufficient balance" } }),
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let opp = long_opportunity(&["EV-B1", "EV-B2"], 5);
+    let result = executor::execute_arb_unlogged(&client, &opp, 5, BUDGET).await.unwrap();
+    assert_eq!(result.filled.len(), 1, "{:?}", result);
+    assert_eq!(result.api_failures, ["EV-B2"]);
+    assert_eq!(result.failure_kinds.get("EV-B2"), Some(&ApiErrorKind::InsufficientBalance));
+}
+
+#[tokio::test]
+async fn test_observer_reads_unsigned_and_refuses_writes() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/markets/KXA-1-B1/orderbook"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("fixtures/orderbook_both_sides.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    // No write may reach the exchange, whichever call tries
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(201)).expect(0).mount(&server).await;
+    Mock::given(method("DELETE")).respond_with(ResponseTemplate::new(200)).expect(0).mount(&server).await;
+
+    let client = KalshiClient::observer(None, &KalshiConfig::with_base_url(&server.uri()), 0).unwrap();
+    assert!(client.is_read_only());
+    client.get_orderbook("KXA-1-B1").await.unwrap();
+    let request = &server.received_requests().await.unwrap()[0];
+    assert!(!request.headers.contains_key("KALSHI-ACCESS-SIGNATURE"));
+
+    let opp = long_opportunity(&["EV-B1", "EV-B2"], 5);
+    let result = executor::execute_arb_unlogged(&client, &opp, 5, BUDGET).await.unwrap();
+    assert_eq!(result.api_failures, ["EV-B1", "EV-B2"]);
+    let err = client.cancel_order("ord-1").await.unwrap_err();
+    assert!(matches!(err, KalshiError::Refused { .. }) && err.to_string().contains("read-only"), "{}", err);
+}
@@ -0,0 +1,231 @@
+//! The whole binary against the simulated exchange: the scan loop finds an arb, one leg
+//! only partly fills, and the bot cancels the remainder and unwinds. Also the unwind and
+//! execution replay, which run the executor against in-process simulated exchanges.
+
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use rsa::pkcs1::{EncodeRsaPrivateKey, LineEnding};
+use rsa::RsaPrivateKey;
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use bracket_arb::backtest::{Snapshot, SnapshotBracket};
+use bracket_arb::config::RiskConfig;
+use bracket_arb::executor::{self, ExecutionResult};
+use bracket_arb::kalshi::auth::KalshiAuth;
+use bracket_arb::kalshi::client::KalshiClient;
+use bracket_arb::kalshi::types::{ArbDirection, ArbOpportunity, BracketQuote, Order, Orderbook, PriceLevel};
+use bracket_arb::replay::{self, ReplayPolicy};
+use bracket_arb::routing::RoutePolicy;
+use bracket_arb::sim_exchange::{Scenario, SimExchange, SimOrder};
+use bracket_arb::storage::{self, ExecutionRecord};
+
+const SCENARIO: &str = include_str!("fixtures/sim_scenario.toml");
+
+/// Stops the bot even when an assertion fails first.
+struct Bot(Child);
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+#[tokio::test]
+async fn test_scan_loop_trades_and_unwinds_against_sim() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let sim = SimExchange::new(toml::from_str::<Scenario>(SCENARIO).unwrap());
+    tokio::spawn(sim.clone().serve(listener));
+
+    let dir = std::env::temp_dir().join(format!("arb-sim-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+    std::fs::write(dir.join("key.pem"), key.to_pkcs1_pem(LineEnding::LF).unwrap().as_bytes()).unwrap();
+    let config = format!(
+        "[instance]\nname = \"sim\"\n\n\
+         [scanner]\ninterval_secs = 60\nseries_filter = [\"KXSIM\"]\nwarmup_cycles = 0\n\n\
+         [risk]\nmin_net_profit_cents = 1\nmin_roi_pct = 0.1\nposition_size = 5\nmax_open_positions = 5\n\n\
+         [kalshi]\nbase_url = \"http://{}/trade-api/v2\"\nrsa_key_path = \"key.pem\"\n\n\
+         [status]\n[schedule]\n[scheduler]\n",
+        addr
+    );
+    std::fs::write(dir.join("config.toml"), config).unwrap();
+
+    let bot = Bot(
+        Command::new(env!("CARGO_BIN_EXE_bracket-arb"))
+            .current_dir(&dir)
+            .env("KALSHI_API_KEY_ID", "sim-key")
+            .env_remove("DRY_RUN")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    // Three legs, then the IOC unwind for the partly filled one
+    let deadline = Instant::now() + Duration::from_secs(60);
+    let orders: Vec<SimOrder> = loop {
+        let orders = sim.orders();
+        if orders.len() >= 4 || Instant::now() > deadline {
+            break orders;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    };
+    drop(bot);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(orders.len() >= 4, "{:?}", orders);
+    let legs = &orders[..3];
+    assert!(legs.iter().all(|o| o.action == "buy" && o.side == "yes" && o.yes_price == Some(30)), "{:?}", legs);
+    let partial = legs.iter().find(|o| o.ticker == "KXSIM-26OCT17-B2").unwrap();
+    assert_eq!((partial.status.as_str(), partial.filled), ("canceled", 2));
+    // The unwind buys the missing contracts on the short leg
+    assert_eq!((orders[3].ticker.as_str(), orders[3].count), ("KXSIM-26OCT17-B2", 3));
+}
+
+#[tokio::test]
+async fn test_unwind_records_what_it_closed() {
+    // A and B fill at 30, C is rejected and now asks 60: the edge is gone. A can be sold back
+    // into its 28 bid; B has no bid at all
+    let scenario = r#"
+        match_book = true
+        [[events]]
+        event_ticker = "KXUNW-26OCT17"
+        series_ticker = "KXUNW"
+        title = "Unwind"
+        [[events.markets]]
+        ticker = "KXUNW-26OCT17-A"
+        title = "A"
+        yes = [[28, 10]]
+        no = [[70, 10]]
+        [[events.markets]]
+        ticker = "KXUNW-26OCT17-B"
+        title = "B"
+        no = [[70, 10]]
+        [[events.markets]]
+        ticker = "KXUNW-26OCT17-C"
+        title = "C"
+        no = [[40, 10]]
+        reject = "market closed"
+    "#;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let sim = SimExchange::new(toml::from_str::<Scenario>(scenario).unwrap());
+    tokio::spawn(sim.clone().serve(listener));
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap();
+    let auth = KalshiAuth::from_pem(&key.to_pkcs1_pem(LineEnding::LF).unwrap(), "sim-key".into()).unwrap();
+    let client = KalshiClient::with_base_url(auth, &format!("http://{}/trade-api/v2", addr)).unwrap();
+
+    let bracket = |ticker: &str| BracketQuote {
+        ticker: format!("KXUNW-26OCT17-{}", ticker),
+        title: ticker.into(),
+        yes_ask_cents: 30,
+        yes_bid_cents: 28,
+        depth_at_no: 10,
+        depth_at_yes: 10,
+        ..Default::default()
+    };
+    let opp = ArbOpportunity {
+        id: "KXUNW-26OCT17-LONG-1".into(),
+        uuid: Uuid::nil(),
+        event_ticker: "KXUNW-26OCT17".into(),
+        event_title: "Unwind".into(),
+        direction: ArbDirection::Long,
+        brackets: vec![bracket("A"), bracket("B"), bracket("C")],
+        position_size: 5,
+        sum_cents: 90,
+        total_fees_cents: 24,
+        gross_profit_cents: 50,
+        net_profit_cents: 26,
+        expected_slippage_cents: 0,
+        gross_roi_pct: Default::default(),
+        net_roi_pct: Default::default(),
+        slippage_adj_roi_pct: Default::default(),
+        settles_at: None,
+    };
+    let result = executor::execute_arb_unlogged(&client, &opp, 5, Duration::from_secs(10)).await.unwrap();
+    assert_eq!((result.filled.len(), result.api_failures.len()), (2, 1), "{:?}", result);
+
+    let (settled, plan) = executor::unwind_mixed(&client, &opp, &result).await;
+    assert_eq!(plan.unquoted(), ["KXUNW-26OCT17-B"]);
+    let [(ticker, sold)] = settled.unwind.as_slice() else { panic!("{:?}", settled.unwind) };
+    assert_eq!((ticker.as_str(), sold.action.as_str(), sold.filled_count()), ("KXUNW-26OCT17-A", "sell", 5));
+    let client_order_id = sim.orders().last().unwrap().client_order_id.clone();
+    assert_eq!(client_order_id, Some(executor::unwind_client_order_id(&opp.uuid, "KXUNW-26OCT17-A")));
+
+    // What the execution realized counts the sale: 300 paid, 140 back, 8c fees on each order.
+    // B is still held, valued at its worst case
+    let fills = settled.fills();
+    assert_eq!(fills.len(), 3);
+    assert_eq!(storage::realized_net_cents(&opp, &fills), Some(-184));
+    assert_eq!(storage::realized_net_cents(&opp, &result.filled), Some(-316));
+    // Recorded, the unwind survives a reload
+    let line: ExecutionResult = serde_json::from_str(&serde_json::to_string(&settled).unwrap()).unwrap();
+    assert_eq!(line.unwind.len(), 1);
+}
+
+#[tokio::test]
+async fn test_replay_exec_compares_policies_on_recorded_books() {
+    let time = chrono::Utc::now() - chrono::Duration::minutes(5);
+    let levels = |raw: &[(i64, i64)]| raw.iter().map(|&(price, quantity)| PriceLevel { price, quantity }).collect();
+    // YES asks: 30c x 10 on B1 and B3; B2 has only 3 at 30c, then 10 at 31c
+    let bracket = |ticker: &str, no: &[(i64, i64)]| SnapshotBracket {
+        ticker: ticker.into(),
+        title: ticker.into(),
+        orderbook: Orderbook { yes: levels(&[(20, 10)]), no: levels(no) },
+    };
+    let snapshot = Snapshot {
+        time,
+        event_ticker: "KXSIM-26OCT17".into(),
+        title: "Sim".into(),
+        brackets: vec![
+            bracket("KXSIM-26OCT17-B1", &[(70, 10)]),
+            bracket("KXSIM-26OCT17-B2", &[(70, 3), (69, 10)]),
+            bracket("KXSIM-26OCT17-B3", &[(70, 10)]),
+        ],
+    };
+    let resting: Order = serde_json::from_value(serde_json::json!({
+        "order_id": "o-2", "ticker": "KXSIM-26OCT17-B2", "status": "resting", "action": "buy", "side": "yes",
+        "type": "limit", "yes_price": 30, "count": 5, "remaining_count": 2,
+    }))
+    .unwrap();
+    let record = ExecutionRecord {
+        instance: "default".into(),
+        opportunity_id: format!("KXSIM-26OCT17-LONG-{}", (time + chrono::Duration::seconds(1)).timestamp_millis()),
+        uuid: None,
+        recorded_at: time + chrono::Duration::seconds(2),
+        result: ExecutionResult {
+            event_ticker: "KXSIM-26OCT17".into(),
+            direction: ArbDirection::Long,
+            filled: Vec::new(),
+            resting: vec![("KXSIM-26OCT17-B2".into(), resting)],
+            other: Vec::new(),
+            api_failures: Vec::new(),
+            failure_kinds: Default::default(),
+            aborted: false,
+            unwind: Vec::new(),
+        },
+        labels: Default::default(),
+    };
+    let risk: RiskConfig =
+        toml::from_str("min_net_profit_cents = 1\nmin_roi_pct = 0.1\nposition_size = 1\nmax_open_positions = 5").unwrap();
+
+    let replay = replay::run(&record, &[snapshot], &risk).await.unwrap();
+    assert_eq!(replay.opportunity.position_size, 5);
+    let outcome = |policy: ReplayPolicy| replay.outcomes.iter().find(|o| o.policy == policy).unwrap().clone();
+    // At the quote, B2 fills 3 of 5 and the other legs are left 2 over
+    let at_quote = outcome(ReplayPolicy::Routed(RoutePolicy::YesLimit));
+    assert_eq!((at_quote.complete_sets, at_quote.unhedged), (3, 4));
+    // Sequential stops at B2, with B1 already filled
+    let sequential = outcome(ReplayPolicy::Sequential);
+    assert_eq!((sequential.complete_sets, sequential.unhedged, sequential.net_cents), (0, 8, 0));
+    // A 1c buffer reaches B2's second level and completes every set
+    let best = replay.best().unwrap();
+    assert_eq!(best.policy, ReplayPolicy::Routed(RoutePolicy::Buffered { buffer_cents: 1 }));
+    assert_eq!((best.complete_sets, best.unhedged), (5, 0));
+    assert!(best.net_cents > at_quote.net_cents);
+}